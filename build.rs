@@ -57,7 +57,6 @@ SOFTWARE.
 // File generated by build.rs, do not modify directly
 
 use assert_cmd::Command;
-use escargot;
 use std::time::Duration;
 
 const TIMEOUT_DURATION: Duration = Duration::from_secs(10);
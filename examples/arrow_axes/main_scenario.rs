@@ -0,0 +1,78 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use wgpu_igniter::LaunchContext;
+use wgpu_igniter::RenderLoopHandler;
+use wgpu_igniter::cgmath::Point3;
+use wgpu_igniter::plugins::orbit_controls::OrbitControlsPlugin;
+use wgpu_igniter::primitives::Shareable;
+use wgpu_igniter::primitives::arrow;
+
+const NORMALS_SHADER: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/examples/cube_normals/cube_normals.wgsl"
+));
+
+const AXIS_LENGTH: f32 = 3.0;
+const SHAFT_RADIUS: f32 = 0.05;
+const HEAD_SIZE: f32 = 0.4;
+
+pub struct MainScenario;
+
+impl MainScenario {
+    pub fn new(
+        LaunchContext {
+            draw_context,
+            plugin_registry,
+        }: LaunchContext,
+    ) -> Self {
+        let shader_module = draw_context.create_shader_module(NORMALS_SHADER);
+        let mut orbit_controls =
+            OrbitControlsPlugin::new(draw_context, Point3::new(0.0, 0.0, 0.0), 10.0);
+        orbit_controls.rotate(0.6, 0.4);
+        let origin = Point3::new(0.0, 0.0, 0.0);
+        // World axes, plus the negative y axis which is antiparallel to the arrow mesh
+        for tip in [
+            Point3::new(AXIS_LENGTH, 0.0, 0.0),
+            Point3::new(0.0, AXIS_LENGTH, 0.0),
+            Point3::new(0.0, 0.0, AXIS_LENGTH),
+            Point3::new(0.0, -AXIS_LENGTH / 2.0, 0.0),
+        ] {
+            let axis = arrow::create_arrow(
+                draw_context,
+                &shader_module,
+                &shader_module,
+                orbit_controls.scene.scene_uniforms(),
+                (origin, tip),
+                SHAFT_RADIUS,
+                HEAD_SIZE,
+            );
+            orbit_controls.scene.add(axis.into_shareable());
+        }
+        plugin_registry.register(orbit_controls);
+        Self
+    }
+}
+
+impl RenderLoopHandler for MainScenario {}
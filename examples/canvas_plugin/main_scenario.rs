@@ -36,7 +36,7 @@ impl MainScenario {
         }: LaunchContext,
     ) -> Self {
         let canvas = CanvasPlugin::new(
-            &draw_context,
+            draw_context,
             &draw_context.create_shader_module(FRAGMENT_SHADER),
             &[],
         )
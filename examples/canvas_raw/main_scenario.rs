@@ -68,7 +68,7 @@ impl RenderLoopHandler for MainScenario {
         render_pass: &mut wgpu::RenderPass<'static>,
     ) {
         self.time_uniform
-            .write_uniform(time_info.init_start.elapsed().as_secs_f32());
+            .write_uniform(time_info.animation_time.as_secs_f32());
         self.canvas.render(render_pass);
     }
 }
@@ -0,0 +1,104 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use std::rc::Rc;
+use wgpu_igniter::cameras::{Camera, InteractiveCamera};
+use wgpu_igniter::cgmath;
+use wgpu_igniter::plugins::PluginRegistry;
+use wgpu_igniter::plugins::scene_3d::{Scene3D, Scene3DPlugin};
+use wgpu_igniter::primitives::{Object3D, Shareable, Transforms, color, cube};
+use wgpu_igniter::{DrawContext, LaunchContext, RenderLoopHandler, TimeInfo};
+
+const DEFAULT_SHADER: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/src/shaders/default.wgsl"
+));
+
+const ROTATION_DEG_PER_S: f32 = 45.0;
+const CUBE_HALF_SIZE: f32 = 0.5;
+
+pub struct MainScenario {
+    pub cube: Rc<std::cell::RefCell<Object3D>>,
+}
+
+impl MainScenario {
+    pub fn new(
+        LaunchContext {
+            draw_context,
+            plugin_registry,
+        }: LaunchContext,
+    ) -> Self {
+        let shader_module = draw_context.create_shader_module(DEFAULT_SHADER);
+        let camera = InteractiveCamera::new(Camera::default());
+        let mut scene = Scene3D::new(draw_context);
+        let cube = cube::create_cube_with_colors(
+            draw_context,
+            &shader_module,
+            &shader_module,
+            scene.scene_uniforms(),
+            &Default::default(),
+        )
+        .into_shareable();
+        scene.add(cube.clone());
+        let scene_elements = Scene3DPlugin { camera, scene };
+        plugin_registry.register(scene_elements);
+        Self { cube }
+    }
+}
+
+impl RenderLoopHandler for MainScenario {
+    fn on_update(
+        &mut self,
+        _plugin_registry: &mut PluginRegistry,
+        draw_context: &mut DrawContext,
+        time_info: &TimeInfo,
+    ) {
+        let total_seconds = time_info.animation_time.as_secs_f32();
+        let transform =
+            cgmath::Matrix4::from_angle_y(cgmath::Deg(ROTATION_DEG_PER_S * total_seconds))
+                * cgmath::Matrix4::from_angle_x(cgmath::Deg(30.0));
+        self.cube.borrow_mut().set_transform(transform);
+
+        // World-space box enclosing the corners of the rotated cube
+        let mut min = [f32::MAX; 3];
+        let mut max = [f32::MIN; 3];
+        for index in 0..8 {
+            let sign = |bit: usize| if index & bit == 0 { -1.0 } else { 1.0 };
+            let corner = transform
+                * cgmath::Vector4::new(
+                    sign(1) * CUBE_HALF_SIZE,
+                    sign(2) * CUBE_HALF_SIZE,
+                    sign(4) * CUBE_HALF_SIZE,
+                    1.0,
+                );
+            for (axis, value) in [corner.x, corner.y, corner.z].into_iter().enumerate() {
+                min[axis] = min[axis].min(value);
+                max[axis] = max[axis].max(value);
+            }
+        }
+        let mut debug_draw = draw_context.debug_draw();
+        debug_draw.aabb(min, max, color::COLOR_YELLOW);
+        debug_draw.point([0.0, 0.0, 0.0], 0.2, color::COLOR_WHITE);
+    }
+}
@@ -0,0 +1,99 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use std::f32::consts::TAU;
+use wgpu_igniter::cameras::Camera;
+use wgpu_igniter::cgmath::{Deg, EuclideanSpace, Matrix4, MetricSpace, Point3, Rad, Vector3};
+use wgpu_igniter::plugins::PluginRegistry;
+use wgpu_igniter::plugins::scene_3d::Scene3DPlugin;
+use wgpu_igniter::primitives::{Shareable, Transforms, cube};
+use wgpu_igniter::{DrawContext, LaunchContext, RenderLoopHandler, TimeInfo};
+
+const DEFAULT_SHADER: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/src/shaders/default.wgsl"
+));
+
+const EFFECT_PERIOD_S: f32 = 6.0;
+const MIN_FOVY: Deg<f32> = Deg(15.0);
+const MAX_FOVY: Deg<f32> = Deg(90.0);
+// Cubes lined up behind the subject, whose apparent size changes with the effect
+const BACKGROUND_ROWS: i32 = 8;
+const BACKGROUND_SPACING: f32 = 3.0;
+
+pub struct MainScenario;
+
+impl MainScenario {
+    pub fn new(
+        LaunchContext {
+            draw_context,
+            plugin_registry,
+        }: LaunchContext,
+    ) -> Self {
+        let shader_module = draw_context.create_shader_module(DEFAULT_SHADER);
+        let camera = Camera::new_looking_at(Point3::new(0.0, 0.5, 6.0), Point3::origin());
+        let mut scene_plugin = Scene3DPlugin::new(draw_context, camera);
+        let scene = &mut scene_plugin.scene;
+        let mut add_cube = |translation: Vector3<f32>| {
+            let mut cube = cube::create_cube_with_colors(
+                draw_context,
+                &shader_module,
+                &shader_module,
+                scene.scene_uniforms(),
+                &Default::default(),
+            );
+            cube.set_transform(Matrix4::from_translation(translation));
+            scene.add(cube.into_shareable());
+        };
+        add_cube(Vector3::new(0.0, 0.0, 0.0));
+        for row in 1..=BACKGROUND_ROWS {
+            #[allow(clippy::cast_precision_loss)]
+            let z = -(row as f32) * BACKGROUND_SPACING;
+            add_cube(Vector3::new(-2.0, 0.0, z));
+            add_cube(Vector3::new(2.0, 0.0, z));
+        }
+        plugin_registry.register(scene_plugin);
+        Self
+    }
+}
+
+impl RenderLoopHandler for MainScenario {
+    fn on_update(
+        &mut self,
+        plugin_registry: &mut PluginRegistry,
+        _draw_context: &mut DrawContext,
+        time_info: &TimeInfo,
+    ) {
+        let Some(scene_plugin) = plugin_registry.get_mut::<Scene3DPlugin>() else {
+            return;
+        };
+        let camera = &mut scene_plugin.camera.controled_camera;
+        // Back and forth between both fields of view, the subject cube keeping its size
+        let phase = TAU * time_info.animation_time.as_secs_f32() / EFFECT_PERIOD_S;
+        let ratio = 0.5 - 0.5 * phase.cos();
+        let fovy = Rad::from(MIN_FOVY).0 + (Rad::from(MAX_FOVY).0 - Rad::from(MIN_FOVY).0) * ratio;
+        let subject_distance = camera.eye_position().distance(Point3::origin());
+        camera.dolly_zoom(fovy, subject_distance);
+    }
+}
@@ -0,0 +1,34 @@
+// Concatenated after EnvironmentMap::SHADER_SNIPPET
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+};
+
+struct FragmentInput {
+    @location(0) normal: vec3<f32>,
+    @builtin(position) position: vec4<f32>,
+};
+
+const ALBEDO = vec3<f32>(0.8, 0.8, 0.8);
+
+@group(0) @binding(0)
+var<uniform> camera: mat4x4<f32>;
+@group(1) @binding(0)
+var<uniform> transform: mat4x4<f32>;
+@group(1) @binding(1)
+var<uniform> normalmat: mat3x3<f32>;
+
+@vertex
+fn vtx_main(vtx_in: VertexInput) -> FragmentInput {
+    var out: FragmentInput;
+    out.normal = vtx_in.normal;
+    out.position = camera * transform * vec4<f32>(vtx_in.position, 1.0);
+    return out;
+}
+
+@fragment
+fn frg_main(frg_in: FragmentInput) -> @location(0) vec4<f32> {
+    let world_normal = normalmat * frg_in.normal;
+    return vec4<f32>(ibl_diffuse(world_normal, ALBEDO), 1.0);
+}
@@ -0,0 +1,127 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use wgpu_igniter::cameras::{Camera, InteractiveCamera};
+use wgpu_igniter::plugins::PluginRegistry;
+use wgpu_igniter::plugins::scene_3d::{Scene3D, Scene3DPlugin};
+use wgpu_igniter::primitives::cube::{self, CubeOptions};
+use wgpu_igniter::primitives::{Object3D, Shareable, Transforms};
+use wgpu_igniter::support::environment::EnvironmentMap;
+use wgpu_igniter::{Dimensions, DrawContext, LaunchContext, RenderLoopHandler, TimeInfo};
+
+const SHADER: &str = include_str!("cube_environment.wgsl");
+
+const ROTATION_DEG_PER_S: f32 = 30.0;
+const ENVIRONMENT_WIDTH: u32 = 256;
+const ENVIRONMENT_HEIGHT: u32 = 128;
+
+// Warm sky above a blue horizon and a dark green ground, with a bright sun. A Radiance HDR
+// file can be loaded instead with EnvironmentMap::from_hdr and the hdr feature.
+#[allow(clippy::cast_precision_loss)]
+fn procedural_sky() -> Vec<[f32; 4]> {
+    let sun = (
+        0.3 * ENVIRONMENT_WIDTH as f32,
+        0.2 * ENVIRONMENT_HEIGHT as f32,
+    );
+    (0..ENVIRONMENT_HEIGHT)
+        .flat_map(|y| (0..ENVIRONMENT_WIDTH).map(move |x| (x as f32, y as f32)))
+        .map(|(x, y)| {
+            // 1 at the zenith, -1 at the nadir
+            let elevation = 1.0 - 2.0 * y / ENVIRONMENT_HEIGHT as f32;
+            let [r, g, b] = if elevation > 0.0 {
+                let horizon = [0.6, 0.7, 0.9];
+                let zenith = [0.2, 0.35, 0.8];
+                std::array::from_fn(|i| horizon[i] + (zenith[i] - horizon[i]) * elevation)
+            } else {
+                [0.05, 0.12, 0.04]
+            };
+            let sun_intensity = if (x - sun.0).hypot(y - sun.1) < 6.0 {
+                40.0
+            } else {
+                0.0
+            };
+            [
+                r + sun_intensity,
+                g + 0.9 * sun_intensity,
+                b + 0.7 * sun_intensity,
+                1.0,
+            ]
+        })
+        .collect()
+}
+
+pub struct MainScenario {
+    cube: Rc<RefCell<Object3D>>,
+}
+
+impl MainScenario {
+    pub fn new(
+        LaunchContext {
+            draw_context,
+            plugin_registry,
+        }: LaunchContext,
+    ) -> Self {
+        let camera = InteractiveCamera::new(Camera::default());
+        let environment_map = EnvironmentMap::from_equirectangular(
+            draw_context,
+            Dimensions::new(ENVIRONMENT_WIDTH, ENVIRONMENT_HEIGHT),
+            &procedural_sky(),
+        )
+        .expect("Sky should have as many pixels as its dimensions");
+        let shader_module = draw_context
+            .create_shader_module(&format!("{}\n{SHADER}", EnvironmentMap::SHADER_SNIPPET));
+        let mut scene = Scene3D::new(draw_context);
+        let cube = cube::create_cube_with_normals(
+            draw_context,
+            &shader_module,
+            &shader_module,
+            scene.scene_uniforms(),
+            &CubeOptions {
+                environment_map: Some(&environment_map),
+                ..Default::default()
+            },
+        )
+        .into_shareable();
+        scene.add(cube.clone());
+        plugin_registry.register(Scene3DPlugin { camera, scene });
+        Self { cube }
+    }
+}
+
+impl RenderLoopHandler for MainScenario {
+    fn on_update(
+        &mut self,
+        _plugin_registry: &mut PluginRegistry,
+        _draw_context: &mut DrawContext,
+        time_info: &TimeInfo,
+    ) {
+        let delta_rotation = ROTATION_DEG_PER_S * time_info.processing_delta.as_secs_f32();
+        let transform = cgmath::Matrix4::from_angle_x(cgmath::Deg(delta_rotation))
+            * cgmath::Matrix4::from_angle_y(cgmath::Deg(delta_rotation));
+        self.cube.borrow_mut().apply_transform(transform);
+    }
+}
@@ -0,0 +1,94 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use std::time::Duration;
+use wgpu_igniter::cgmath::{Point3, Vector3};
+use wgpu_igniter::plugins::camera_path::CameraPathPlugin;
+use wgpu_igniter::plugins::scene_3d::Scene3D;
+use wgpu_igniter::primitives::curve::{Curve, CurveKind};
+use wgpu_igniter::primitives::{Shareable, cube};
+use wgpu_igniter::{LaunchContext, RenderLoopHandler};
+
+const INSTANCES_SHADER: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/examples/cube_instances/cube_instances.wgsl"
+));
+
+const CUBE_COUNT_PER_SIDE: u32 = 12;
+const CUBE_OFFSET: f32 = 3.0;
+const FLIGHT_DURATION: Duration = Duration::from_secs(20);
+
+// Swoops down between the rows of cubes, then climbs back to the start
+const FLIGHT_PATH: [[f32; 3]; 7] = [
+    [-20.0, 12.0, -20.0],
+    [-10.0, 4.0, -4.5],
+    [0.0, 1.0, 1.5],
+    [12.0, 1.5, 4.5],
+    [10.0, 6.0, -12.0],
+    [-6.0, 10.0, -22.0],
+    [-20.0, 12.0, -20.0],
+];
+
+pub struct MainScenario;
+
+impl MainScenario {
+    pub fn new(
+        LaunchContext {
+            draw_context,
+            plugin_registry,
+        }: LaunchContext,
+    ) -> Self {
+        draw_context.set_clear_color(Some(wgpu::Color::BLACK));
+        let shader_module = draw_context.create_shader_module(INSTANCES_SHADER);
+        let mut scene = Scene3D::new(draw_context);
+        let mut cubes = cube::create_cube_with_normals_instances(
+            draw_context,
+            &shader_module,
+            &shader_module,
+            scene.scene_uniforms(),
+            CUBE_COUNT_PER_SIDE * CUBE_COUNT_PER_SIDE,
+            &Default::default(),
+        );
+        #[allow(clippy::cast_precision_loss)]
+        cubes.update_instances(|idx, instance| {
+            let half_side = (CUBE_COUNT_PER_SIDE - 1) as f32 * CUBE_OFFSET / 2.0;
+            let x = (idx % CUBE_COUNT_PER_SIDE as usize) as f32;
+            let z = (idx / CUBE_COUNT_PER_SIDE as usize) as f32;
+            instance.set_translation(Vector3::new(
+                x.mul_add(CUBE_OFFSET, -half_side),
+                0.0,
+                z.mul_add(CUBE_OFFSET, -half_side),
+            ));
+        });
+        scene.add(cubes.into_shareable());
+        let path = Curve::new(&FLIGHT_PATH.map(Point3::from), CurveKind::CatmullRom)
+            .expect("Flight path should have control points");
+        let mut camera_path = CameraPathPlugin::new(draw_context, scene, path, FLIGHT_DURATION);
+        camera_path.set_looping(true);
+        plugin_registry.register(camera_path);
+        Self
+    }
+}
+
+impl RenderLoopHandler for MainScenario {}
@@ -0,0 +1,98 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use wgpu_igniter::cgmath::{Deg, Matrix4, Vector3};
+use wgpu_igniter::plugins::PluginRegistry;
+use wgpu_igniter::plugins::follow_camera::FollowCameraPlugin;
+use wgpu_igniter::plugins::ground_grid::GroundGridPlugin;
+use wgpu_igniter::plugins::scene_3d::Scene3D;
+use wgpu_igniter::primitives::{Object3D, Shareable, Transforms, cube};
+use wgpu_igniter::{DrawContext, LaunchContext, RenderLoopHandler, TimeInfo};
+
+const DEFAULT_SHADER: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/src/shaders/default.wgsl"
+));
+
+const TRACK_RADIUS: f32 = 6.0;
+const TRACK_DEG_PER_S: f32 = 60.0;
+const SPIN_DEG_PER_S: f32 = 180.0;
+
+pub struct MainScenario {
+    cube: Rc<RefCell<Object3D>>,
+}
+
+impl MainScenario {
+    pub fn new(
+        LaunchContext {
+            draw_context,
+            plugin_registry,
+        }: LaunchContext,
+    ) -> Self {
+        let shader_module = draw_context.create_shader_module(DEFAULT_SHADER);
+        let mut scene = Scene3D::new(draw_context);
+        let cube = cube::create_cube_with_colors(
+            draw_context,
+            &shader_module,
+            &shader_module,
+            scene.scene_uniforms(),
+            &Default::default(),
+        )
+        .into_shareable();
+        scene.add(cube.clone());
+        let mut ground_grid = GroundGridPlugin::new(draw_context, scene.scene_uniforms());
+        ground_grid.set_height(-0.5);
+        // Behind and above the cube, looking slightly ahead of it
+        let mut follow_camera = FollowCameraPlugin::new(
+            draw_context,
+            scene,
+            cube.clone(),
+            Vector3::new(0.0, 3.0, -8.0),
+        );
+        follow_camera.set_look_offset(Vector3::new(0.0, 0.5, 0.0));
+        follow_camera.set_max_lag(Some(4.0));
+        plugin_registry.register(follow_camera);
+        plugin_registry.register(ground_grid);
+        Self { cube }
+    }
+}
+
+impl RenderLoopHandler for MainScenario {
+    fn on_update(
+        &mut self,
+        _plugin_registry: &mut PluginRegistry,
+        _draw_context: &mut DrawContext,
+        time_info: &TimeInfo,
+    ) {
+        let seconds = time_info.animation_time.as_secs_f32();
+        let track_angle = Deg(TRACK_DEG_PER_S * seconds);
+        // Circles around the origin while spinning on itself
+        let transform = Matrix4::from_angle_y(track_angle)
+            * Matrix4::from_translation(Vector3::new(TRACK_RADIUS, 0.0, 0.0))
+            * Matrix4::from_angle_y(Deg(SPIN_DEG_PER_S * seconds));
+        self.cube.borrow_mut().set_transform(transform);
+    }
+}
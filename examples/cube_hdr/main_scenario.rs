@@ -0,0 +1,133 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use wgpu_igniter::cameras::Camera;
+use wgpu_igniter::plugins::PluginRegistry;
+use wgpu_igniter::plugins::scene_3d::Scene3D;
+use wgpu_igniter::primitives::cube::{self, CubeOptions};
+use wgpu_igniter::primitives::{Object3D, Shareable, Transforms};
+use wgpu_igniter::support::postprocess::{Tonemap, TonemapCurve};
+use wgpu_igniter::support::render_graph::{PassOutput, RenderGraph};
+use wgpu_igniter::support::render_texture::RenderTexture;
+use wgpu_igniter::{DrawContext, LaunchContext, RenderLoopHandler, TimeInfo};
+
+const DEFAULT_SHADER: &str = include_str!("cube_hdr.wgsl");
+
+const ROTATION_DEG_PER_S: f32 = 45.0;
+const EXPOSURE: f32 = 0.8;
+
+pub struct MainScenario {
+    cube: Rc<RefCell<Object3D>>,
+    camera: Camera,
+    scene: Scene3D,
+    hdr_target: RenderTexture,
+    tonemap: Tonemap,
+}
+
+impl MainScenario {
+    pub fn new(
+        LaunchContext {
+            draw_context,
+            plugin_registry: _,
+        }: LaunchContext,
+    ) -> Self {
+        let camera = Camera::default();
+        let shader_module = draw_context.create_shader_module(DEFAULT_SHADER);
+        let hdr_target = RenderTexture::new(
+            draw_context,
+            draw_context.surface_dimensions(),
+            RenderTexture::DEFAULT_HDR_FORMAT,
+        );
+        let mut scene = Scene3D::new(draw_context);
+        let cube = cube::create_cube_with_normals(
+            draw_context,
+            &shader_module,
+            &shader_module,
+            scene.scene_uniforms(),
+            &CubeOptions {
+                target_format: Some(hdr_target.format()),
+                ..Default::default()
+            },
+        )
+        .into_shareable();
+        scene.add(cube.clone());
+        let mut tonemap = Tonemap::new(draw_context, &hdr_target, TonemapCurve::Aces);
+        tonemap.set_exposure(EXPOSURE);
+        Self {
+            cube,
+            camera,
+            scene,
+            hdr_target,
+            tonemap,
+        }
+    }
+}
+
+impl RenderLoopHandler for MainScenario {
+    fn on_update(
+        &mut self,
+        _plugin_registry: &mut PluginRegistry,
+        draw_context: &mut DrawContext,
+        time_info: &TimeInfo,
+    ) {
+        let dimensions = draw_context.surface_dimensions();
+        if dimensions != self.hdr_target.dimensions() {
+            self.hdr_target =
+                RenderTexture::new(draw_context, dimensions, RenderTexture::DEFAULT_HDR_FORMAT);
+            self.tonemap.set_source(draw_context, &self.hdr_target);
+        }
+        let delta_rotation = ROTATION_DEG_PER_S * time_info.processing_delta.as_secs_f32();
+        let transform = cgmath::Matrix4::from_angle_z(cgmath::Deg(delta_rotation))
+            * cgmath::Matrix4::from_angle_y(cgmath::Deg(delta_rotation));
+        self.cube.borrow_mut().apply_transform(transform);
+    }
+    fn on_render(
+        &mut self,
+        _plugin_registry: &mut PluginRegistry,
+        draw_context: &DrawContext,
+        _time_info: &TimeInfo,
+        render_pass: &mut wgpu::RenderPass<'static>,
+    ) {
+        self.camera.resize_screen(self.hdr_target.dimensions());
+        self.scene.update(&self.camera);
+        let mut graph = RenderGraph::new();
+        graph
+            .add_texture("hdr", &self.hdr_target, wgpu::Color::BLACK)
+            .expect("Texture name should be unique")
+            .add_pass("scene", &[], PassOutput::Texture("hdr"), |hdr_pass| {
+                self.scene.render(hdr_pass);
+            })
+            .expect("Scene pass should write into a registered texture")
+            .add_pass("tonemap", &["hdr"], PassOutput::Target, |target_pass| {
+                self.tonemap.render(target_pass);
+            })
+            .expect("Tonemap pass should read a registered texture");
+        graph
+            .execute_in(draw_context, render_pass)
+            .expect("Render graph should not have cyclic dependencies");
+    }
+}
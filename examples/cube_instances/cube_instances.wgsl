@@ -29,12 +29,16 @@ var<uniform> camera_pos: vec3<f32>;
 var<storage, read> transforms: array<mat4x4<f32>>;
 @group(1) @binding(1)
 var<storage, read> normal_mat: array<mat3x3<f32>>;
+@group(1) @binding(2)
+var<uniform> group_transform: mat4x4<f32>;
+@group(1) @binding(3)
+var<uniform> group_normal_mat: mat3x3<f32>;
 
 @vertex
 fn vtx_main(vtx_in: VertexInput, inst_in: InstanceInput) -> FragmentInput {
     var out: FragmentInput;
-    var space_position = transforms[inst_in.index] * vec4<f32>(vtx_in.position, 1.0);
-    out.normal = normal_mat[inst_in.index] * vtx_in.normal;
+    var space_position = group_transform * transforms[inst_in.index] * vec4<f32>(vtx_in.position, 1.0);
+    out.normal = group_normal_mat * normal_mat[inst_in.index] * vtx_in.normal;
     out.space_position = space_position.xyz;
     out.clip_position = camera_mat * space_position;
     return out;
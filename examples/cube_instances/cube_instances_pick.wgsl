@@ -0,0 +1,18 @@
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> camera_mat: mat4x4<f32>;
+@group(1) @binding(0)
+var<storage, read> transforms: array<mat4x4<f32>>;
+@group(1) @binding(2)
+var<uniform> group_transform: mat4x4<f32>;
+
+@vertex
+fn vtx_pick(vtx_in: VertexInput, @builtin(instance_index) instance: u32) -> PickVertexOutput {
+    var out: PickVertexOutput;
+    out.clip_position = camera_mat * group_transform * transforms[instance] * vec4<f32>(vtx_in.position, 1.0);
+    out.instance = instance;
+    return out;
+}
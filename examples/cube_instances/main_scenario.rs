@@ -23,22 +23,28 @@ SOFTWARE.
 */
 
 use cgmath::Rotation3;
+use log::info;
 use std::cell::RefCell;
 use std::rc::Rc;
 use wgpu_igniter::cameras::{Camera, InteractiveCamera};
+use wgpu_igniter::picking::PickTarget;
 use wgpu_igniter::plugins::PluginRegistry;
 use wgpu_igniter::plugins::scene_3d::{Scene3D, Scene3DPlugin};
 use wgpu_igniter::primitives::cube::CubeOptions;
-use wgpu_igniter::primitives::{Object3DInstanceGroup, Shareable, cube};
-use wgpu_igniter::{DrawContext, LaunchContext, RenderLoopHandler, TimeInfo};
+use wgpu_igniter::primitives::{Object3DInstanceGroup, Shareable, Transforms, cube};
+use wgpu_igniter::{DrawContext, EventState, LaunchContext, RenderLoopHandler, TimeInfo};
+use winit::event::{ElementState, MouseButton, WindowEvent};
 
 const DEFAULT_SHADER: &str = include_str!("cube_instances.wgsl");
+const PICK_SHADER: &str = include_str!("cube_instances_pick.wgsl");
 const CUBE_WIDTH_COUNT: usize = 50;
 const CUBE_DEPTH_COUNT: usize = 50;
 const CUBE_OFFSET: f32 = 2.0;
+const GROUP_ROTATION_DEG_PER_S: f32 = 5.0;
 
 pub struct MainScenario {
     pub cube: Rc<RefCell<Object3DInstanceGroup>>,
+    pending_pick: bool,
 }
 
 impl MainScenario {
@@ -51,6 +57,8 @@ impl MainScenario {
         draw_context.set_clear_color(Some(wgpu::Color::BLACK));
         let camera = InteractiveCamera::new(Camera::default());
         let shader_module = draw_context.create_shader_module(DEFAULT_SHADER);
+        let pick_module =
+            draw_context.create_shader_module(&[PICK_SHADER, PickTarget::SHADER_SNIPPET].concat());
         let mut scene = Scene3D::new(draw_context);
         let cube = {
             let mut cube_init = cube::create_cube_with_normals_instances(
@@ -59,7 +67,11 @@ impl MainScenario {
                 &shader_module,
                 scene.scene_uniforms(),
                 (CUBE_WIDTH_COUNT * CUBE_DEPTH_COUNT) as u32,
-                &CubeOptions::default(),
+                &CubeOptions {
+                    depth_prepass: true,
+                    pick_module: Some(&pick_module),
+                    ..Default::default()
+                },
             );
             cube_init.update_instances(|idx, instance| {
                 let x = (idx % CUBE_WIDTH_COUNT) as f32;
@@ -80,26 +92,55 @@ impl MainScenario {
         };
         scene.add(cube.clone());
         plugin_registry.register(Scene3DPlugin { camera, scene });
-        Self { cube }
+        Self {
+            cube,
+            pending_pick: false,
+        }
     }
 }
 
 impl RenderLoopHandler for MainScenario {
+    // Right click logs the index of the cube under the cursor
+    fn on_window_event(&mut self, event: &WindowEvent) -> EventState {
+        if let WindowEvent::MouseInput {
+            state: ElementState::Pressed,
+            button: MouseButton::Right,
+            ..
+        } = event
+        {
+            self.pending_pick = true;
+        }
+        EventState::default()
+    }
     fn on_update(
         &mut self,
-        _plugin_registry: &mut PluginRegistry,
-        _draw_context: &mut DrawContext,
+        plugin_registry: &mut PluginRegistry,
+        draw_context: &mut DrawContext,
         time_info: &TimeInfo,
     ) {
+        if let Some(cursor_ndc) = std::mem::take(&mut self.pending_pick)
+            .then(|| draw_context.cursor_ndc())
+            .flatten()
+        {
+            let scene_plugin = plugin_registry
+                .get_mut::<Scene3DPlugin>()
+                .expect("Scene plugin should be registered");
+            match scene_plugin.scene.pick_instance(draw_context, cursor_ndc) {
+                Some((_, cube_index)) => info!("Picked cube {cube_index}"),
+                None => info!("No cube picked"),
+            }
+        }
         let delta = time_info.processing_delta.as_secs_f32();
-        self.cube
-            .borrow_mut()
-            .update_instances(move |index, instance| {
-                let rotation = cgmath::Quaternion::from_axis_angle(
-                    cgmath::Vector3::new((index as f32).cos(), (index as f32).sin(), 0.),
-                    cgmath::Deg(10. * delta),
-                );
-                instance.apply_rotation(rotation);
-            });
+        let mut cube = self.cube.borrow_mut();
+        cube.apply_transform(cgmath::Matrix4::from_angle_y(cgmath::Deg(
+            GROUP_ROTATION_DEG_PER_S * delta,
+        )));
+        cube.update_instances(move |index, instance| {
+            let rotation = cgmath::Quaternion::from_axis_angle(
+                cgmath::Vector3::new((index as f32).cos(), (index as f32).sin(), 0.),
+                cgmath::Deg(10. * delta),
+            );
+            instance.apply_rotation(rotation);
+        });
     }
 }
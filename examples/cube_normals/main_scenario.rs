@@ -26,7 +26,8 @@ use std::rc::Rc;
 use wgpu_igniter::cameras::{Camera, InteractiveCamera};
 use wgpu_igniter::plugins::PluginRegistry;
 use wgpu_igniter::plugins::scene_3d::{Scene3D, Scene3DPlugin};
-use wgpu_igniter::primitives::{Object3D, Shareable, Transforms, cube};
+use wgpu_igniter::primitives::mesh::MeshOptions;
+use wgpu_igniter::primitives::{Object3D, Shareable, Transforms, capsule, cube};
 use wgpu_igniter::{DrawContext, LaunchContext, RenderLoopHandler, TimeInfo};
 
 const DEFAULT_SHADER: &str = include_str!("cube_normals.wgsl");
@@ -56,6 +57,21 @@ impl MainScenario {
         )
         .into_shareable();
         scene.add(cube.clone());
+        // Same lighting on a rounded shape, left of the cube
+        let mut capsule = capsule::create_capsule(
+            draw_context,
+            &shader_module,
+            &shader_module,
+            scene.scene_uniforms(),
+            (0.5, 1.0),
+            (8, 24),
+            &MeshOptions::default(),
+        );
+        capsule.set_transform(
+            cgmath::Matrix4::from_translation(cgmath::Vector3::new(2.5, 0.0, 0.0))
+                * cgmath::Matrix4::from_angle_z(cgmath::Deg(30.0)),
+        );
+        scene.add(capsule.into_shareable());
 
         plugin_registry.register(Scene3DPlugin { camera, scene });
         Self { cube }
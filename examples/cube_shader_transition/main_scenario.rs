@@ -37,11 +37,6 @@ const INTERPOLATED_SHADER: &str = include_str!(concat!(
     "/src/shaders/default.wgsl"
 ));
 
-const FLAT_SHADER: &str = include_str!(concat!(
-    env!("CARGO_MANIFEST_DIR"),
-    "/src/shaders/flat.wgsl"
-));
-
 const ROTATION_DEG_PER_S: f32 = 45.0;
 const SHADER_TRANSITION_PERIOD: Duration = Duration::from_secs(1);
 
@@ -59,7 +54,7 @@ impl MainScenario {
     ) -> Self {
         let camera = InteractiveCamera::new(Camera::default());
         let interpolated_shader_module = draw_context.create_shader_module(INTERPOLATED_SHADER);
-        let flat_shader_module = draw_context.create_shader_module(FLAT_SHADER);
+        let flat_shader_module = draw_context.create_shader_module(cube::FLAT_PREMULTIPLIED_SHADER);
 
         let mut scene = Scene3D::new(draw_context);
         let scene_uniforms = scene.scene_uniforms();
@@ -77,7 +72,11 @@ impl MainScenario {
             &flat_shader_module,
             &flat_shader_module,
             scene_uniforms,
-            &CubeOptions { with_alpha: true },
+            &CubeOptions {
+                with_alpha: true,
+                premultiplied_alpha: true,
+                ..Default::default()
+            },
         )
         .into_shareable();
 
@@ -111,7 +110,7 @@ impl RenderLoopHandler for MainScenario {
             cube_flat.apply_transform(transform);
             cube_flat.set_opacity(
                 0.5 + f32::sin(
-                    2. * time_info.init_start.elapsed().as_secs_f32()
+                    2. * time_info.animation_time.as_secs_f32()
                         / SHADER_TRANSITION_PERIOD.as_secs_f32(),
                 ) / 2_f32,
             );
@@ -0,0 +1,18 @@
+// Concatenated with PickTarget::SHADER_SNIPPET
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> camera_mat: mat4x4<f32>;
+@group(1) @binding(0)
+var<uniform> transform: mat4x4<f32>;
+
+@vertex
+fn vtx_pick(vtx_in: VertexInput) -> PickVertexOutput {
+    var out: PickVertexOutput;
+    out.clip_position = camera_mat * transform * vec4<f32>(vtx_in.position, 1.0);
+    out.instance = 0u;
+    return out;
+}
@@ -23,21 +23,33 @@ SOFTWARE.
 */
 
 use std::rc::Rc;
-use wgpu_igniter::cameras::{Camera, InteractiveCamera};
+use wgpu_igniter::cgmath;
+use wgpu_igniter::picking::PickTarget;
 use wgpu_igniter::plugins::PluginRegistry;
-use wgpu_igniter::plugins::scene_3d::{Scene3D, Scene3DPlugin};
+use wgpu_igniter::plugins::ground_grid::GroundGridPlugin;
+use wgpu_igniter::plugins::orbit_controls::OrbitControlsPlugin;
+use wgpu_igniter::primitives::cube::CubeOptions;
 use wgpu_igniter::primitives::{Object3D, Shareable, Transforms, cube};
 use wgpu_igniter::{DrawContext, LaunchContext, RenderLoopHandler, TimeInfo};
+use winit::dpi::PhysicalPosition;
+use winit::event::{ElementState, KeyEvent};
+use winit::keyboard::{KeyCode, PhysicalKey};
 
 const DEFAULT_SHADER: &str = include_str!(concat!(
     env!("CARGO_MANIFEST_DIR"),
     "/src/shaders/default.wgsl"
 ));
+const PICK_SHADER: &str = include_str!("cube_simple_pick.wgsl");
+const ORBIT_TARGET: cgmath::Point3<f32> = cgmath::Point3::new(0.0, 0.0, 1.0);
+const ORBIT_DISTANCE: f32 = 11.0;
 
 const ROTATION_DEG_PER_S: f32 = 45.0;
+const SCREENSHOT_FILE_NAME: &str = "wgpu_igniter_screenshots/cube_simple.png";
 
 pub struct MainScenario {
     pub cube: Rc<std::cell::RefCell<Object3D>>,
+    pending_focus: Option<[f32; 2]>,
+    pending_screenshot: bool,
 }
 
 impl MainScenario {
@@ -48,31 +60,85 @@ impl MainScenario {
         }: LaunchContext,
     ) -> Self {
         let shader_module = draw_context.create_shader_module(DEFAULT_SHADER);
-        let camera = InteractiveCamera::new(Camera::default());
-        let mut scene = Scene3D::new(draw_context);
+        let pick_module =
+            draw_context.create_shader_module(&[PICK_SHADER, PickTarget::SHADER_SNIPPET].concat());
+        // Drag to turn around the cube, scroll to zoom, double-click to focus on the cube
+        let mut orbit_controls =
+            OrbitControlsPlugin::new(draw_context, ORBIT_TARGET, ORBIT_DISTANCE);
         let cube = cube::create_cube_with_colors(
             draw_context,
             &shader_module,
             &shader_module,
-            scene.scene_uniforms(),
-            &Default::default(),
+            orbit_controls.scene.scene_uniforms(),
+            &CubeOptions {
+                pick_module: Some(&pick_module),
+                ..Default::default()
+            },
         )
         .into_shareable();
-        scene.add(cube.clone());
-        let scene_elements = Scene3DPlugin { camera, scene };
-        plugin_registry.register(scene_elements);
-        Self { cube }
+        orbit_controls.scene.add_transformable(cube.clone());
+        let mut ground_grid =
+            GroundGridPlugin::new(draw_context, orbit_controls.scene.scene_uniforms());
+        ground_grid.set_height(-1.5);
+        plugin_registry.register(orbit_controls);
+        // Registered after the scene, over which it is blended
+        plugin_registry.register(ground_grid);
+        Self {
+            cube,
+            pending_focus: None,
+            pending_screenshot: false,
+        }
     }
 }
 
 impl RenderLoopHandler for MainScenario {
+    // P saves the current view in the temporary directory
+    fn on_keyboard_event(&mut self, event: &KeyEvent) {
+        if event.physical_key == PhysicalKey::Code(KeyCode::KeyP)
+            && event.state == ElementState::Pressed
+            && !event.repeat
+        {
+            self.pending_screenshot = true;
+        }
+    }
+    fn on_double_click(&mut self, draw_context: &DrawContext, _: PhysicalPosition<f64>) {
+        self.pending_focus = draw_context.cursor_ndc();
+    }
     fn on_update(
         &mut self,
-        _plugin_registry: &mut PluginRegistry,
-        _draw_context: &mut DrawContext,
+        plugin_registry: &mut PluginRegistry,
+        draw_context: &mut DrawContext,
         time_info: &TimeInfo,
     ) {
-        let total_seconds = time_info.init_start.elapsed().as_secs_f32();
+        #[cfg(not(target_arch = "wasm32"))]
+        if std::mem::take(&mut self.pending_screenshot) {
+            let path = std::env::temp_dir().join(SCREENSHOT_FILE_NAME);
+            match draw_context.save_screenshot(&path) {
+                Ok(()) => log::info!("Screenshot written to {}", path.display()),
+                Err(error) => log::warn!("Screenshot failed: {error:#}"),
+            }
+        }
+        if let Some(cursor_ndc) = self.pending_focus.take() {
+            let orbit_controls = plugin_registry
+                .get_mut::<OrbitControlsPlugin>()
+                .expect("Orbit controls should be registered");
+            // Closer to the cube under the cursor, back to the initial view otherwise
+            let bounds = orbit_controls
+                .scene
+                .pick(draw_context, cursor_ndc)
+                .and_then(|index| orbit_controls.scene.world_bounds(index));
+            match bounds {
+                Some(bounds) => {
+                    orbit_controls.set_target(bounds.center());
+                    orbit_controls.set_distance(4.0 * bounds.radius());
+                }
+                None => {
+                    orbit_controls.set_target(ORBIT_TARGET);
+                    orbit_controls.set_distance(ORBIT_DISTANCE);
+                }
+            }
+        }
+        let total_seconds = time_info.animation_time.as_secs_f32();
         let new_rotation = ROTATION_DEG_PER_S * total_seconds;
         // Translation on z to be in the clipped space (between -w and w) and camera in front of the cube
         let z_translation: cgmath::Matrix4<f32> =
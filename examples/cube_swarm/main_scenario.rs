@@ -0,0 +1,97 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use cgmath::Rotation3;
+use std::cell::RefCell;
+use std::rc::Rc;
+use wgpu_igniter::cameras::{Camera, InteractiveCamera};
+use wgpu_igniter::plugins::PluginRegistry;
+use wgpu_igniter::plugins::scene_3d::{Scene3D, Scene3DPlugin};
+use wgpu_igniter::primitives::{Object3DInstanceGroup, Shareable, cube};
+use wgpu_igniter::{DrawContext, LaunchContext, RenderLoopHandler, TimeInfo};
+
+const DEFAULT_SHADER: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/examples/cube_instances/cube_instances.wgsl"
+));
+const INITIAL_COUNT: u32 = 10;
+const MAX_COUNT: u32 = 100;
+const SPAWN_PER_S: f32 = 10.0;
+const SWARM_RADIUS: f32 = 6.0;
+const ORBIT_DEG_PER_S: f32 = 20.0;
+
+pub struct MainScenario {
+    pub swarm: Rc<RefCell<Object3DInstanceGroup>>,
+}
+
+impl MainScenario {
+    pub fn new(
+        LaunchContext {
+            draw_context,
+            plugin_registry,
+        }: LaunchContext,
+    ) -> Self {
+        draw_context.set_clear_color(Some(wgpu::Color::BLACK));
+        let camera = InteractiveCamera::new(Camera::default());
+        let shader_module = draw_context.create_shader_module(DEFAULT_SHADER);
+        let mut scene = Scene3D::new(draw_context);
+        let swarm = cube::create_cube_with_normals_instances(
+            draw_context,
+            &shader_module,
+            &shader_module,
+            scene.scene_uniforms(),
+            INITIAL_COUNT,
+            &Default::default(),
+        )
+        .into_shareable();
+        scene.add(swarm.clone());
+        plugin_registry.register(Scene3DPlugin { camera, scene });
+        Self { swarm }
+    }
+}
+
+impl RenderLoopHandler for MainScenario {
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn on_update(
+        &mut self,
+        _plugin_registry: &mut PluginRegistry,
+        draw_context: &mut DrawContext,
+        time_info: &TimeInfo,
+    ) {
+        let elapsed = time_info.animation_time.as_secs_f32();
+        let count = (INITIAL_COUNT + (elapsed * SPAWN_PER_S) as u32).min(MAX_COUNT);
+        let mut swarm = self.swarm.borrow_mut();
+        if count != swarm.count() {
+            swarm.set_count(draw_context, count);
+        }
+        swarm.update_instances(move |index, instance| {
+            let index = index as f32;
+            let angle = cgmath::Deg(index.mul_add(137.5, ORBIT_DEG_PER_S * elapsed));
+            let height = (index / MAX_COUNT as f32).mul_add(4.0, -2.0);
+            let orbit = cgmath::Quaternion::from_angle_y(angle);
+            instance.set_translation(orbit * cgmath::Vector3::new(SWARM_RADIUS, height, 0.0));
+            instance.set_rotation(orbit);
+        });
+    }
+}
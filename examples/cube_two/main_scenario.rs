@@ -76,14 +76,10 @@ impl MainScenario {
         .into_shareable();
         cube_left
             .borrow_mut()
-            .set_transform(cgmath::Matrix4::from_translation(cgmath::Vector3::new(
-                -0.5, 0.0, 5.0,
-            )));
+            .set_translation(cgmath::Vector3::new(-0.5, 0.0, 5.0));
         cube_right
             .borrow_mut()
-            .set_transform(cgmath::Matrix4::from_translation(cgmath::Vector3::new(
-                0.5, 0.0, 0.0,
-            )));
+            .set_translation(cgmath::Vector3::new(0.5, 0.0, 0.0));
         scene.add(cube_left.clone());
         scene.add(cube_right.clone());
 
@@ -0,0 +1,6 @@
+// Fragment entry point of the wireframe overlay, drawn over the colored faces
+
+@fragment
+fn frg_wireframe() -> @location(0) vec4<f32> {
+    return vec4<f32>(0.0, 0.0, 0.0, 1.0);
+}
@@ -0,0 +1,42 @@
+/*
+MIT License
+
+Copyright (c) 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+struct TransformUniform {
+    m: mat4x4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> camera: TransformUniform;
+@group(1) @binding(0)
+var<uniform> transform: TransformUniform;
+
+@vertex
+fn vtx_main(@location(0) position: vec3<f32>) -> @builtin(position) vec4<f32> {
+    return camera.m * transform.m * vec4<f32>(position, 1.0);
+}
+
+@fragment
+fn frg_main() -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0, 0.8, 0.2, 1.0);
+}
@@ -0,0 +1,145 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use wgpu_igniter::cameras::Camera;
+use wgpu_igniter::cgmath::{EuclideanSpace, Matrix4, Point3, Vector3};
+use wgpu_igniter::plugins::PluginRegistry;
+use wgpu_igniter::plugins::scene_3d::Scene3DPlugin;
+use wgpu_igniter::primitives::bounds::Aabb;
+use wgpu_igniter::primitives::curve::{self, Curve, CurveKind};
+use wgpu_igniter::primitives::{Shareable, Transforms, color, cube, wirecube};
+use wgpu_igniter::{DrawContext, LaunchContext, RenderLoopHandler, TimeInfo};
+
+const DEFAULT_SHADER: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/src/shaders/default.wgsl"
+));
+const CURVE_SHADER: &str = include_str!("curve_line.wgsl");
+const FLAT_SHADER: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/src/shaders/flat.wgsl"
+));
+
+const LOOP_SECONDS: f32 = 12.0;
+const CURVE_SAMPLES: u32 = 128;
+const CUBE_SPACING: f32 = 2.5;
+
+// Loop around the cubes, the last point closing it on the first one
+const PATH: [[f32; 3]; 6] = [
+    [8.0, 2.0, 0.0],
+    [0.0, 5.0, 8.0],
+    [-8.0, 1.0, 2.0],
+    [-3.0, 3.0, -7.0],
+    [5.0, 6.0, -5.0],
+    [8.0, 2.0, 0.0],
+];
+
+pub struct MainScenario {
+    path: Curve,
+}
+
+impl MainScenario {
+    pub fn new(
+        LaunchContext {
+            draw_context,
+            plugin_registry,
+        }: LaunchContext,
+    ) -> Self {
+        let shader_module = draw_context.create_shader_module(DEFAULT_SHADER);
+        let curve_module = draw_context.create_shader_module(CURVE_SHADER);
+        let flat_module = draw_context.create_shader_module(FLAT_SHADER);
+        let control_points = PATH.map(Point3::from);
+        let mut scene_plugin = Scene3DPlugin::new(draw_context, Camera::default());
+        let scene = &mut scene_plugin.scene;
+        let mut scene_bounds: Option<Aabb> = None;
+        for x in -1..=1 {
+            for z in -1..=1 {
+                let mut cube = cube::create_cube_with_colors(
+                    draw_context,
+                    &shader_module,
+                    &shader_module,
+                    scene.scene_uniforms(),
+                    &Default::default(),
+                );
+                #[allow(clippy::cast_precision_loss)]
+                cube.set_transform(Matrix4::from_translation(Vector3::new(
+                    x as f32 * CUBE_SPACING,
+                    0.0,
+                    z as f32 * CUBE_SPACING,
+                )));
+                if let Some(bounds) = cube.world_bounds() {
+                    scene_bounds = Some(scene_bounds.map_or(bounds, |union| union.union(bounds)));
+                }
+                scene.add(cube.into_shareable());
+            }
+        }
+        // Edges of the box enclosing all the cubes
+        if let Some(bounds) = scene_bounds {
+            let mut bounds_box = wirecube::create_wire_cube(
+                draw_context,
+                &flat_module,
+                &flat_module,
+                scene.scene_uniforms(),
+                bounds.half_extents(),
+                color::COLOR_GREEN,
+            );
+            bounds_box.set_transform(Matrix4::from_translation(bounds.center().to_vec()));
+            scene.add(bounds_box.into_shareable());
+        }
+        let path_line = curve::create_curve(
+            draw_context,
+            &curve_module,
+            &curve_module,
+            scene.scene_uniforms(),
+            &control_points,
+            CURVE_SAMPLES,
+            CurveKind::CatmullRom,
+        )
+        .unwrap();
+        scene.add(path_line.into_shareable());
+        plugin_registry.register(scene_plugin);
+        Self {
+            path: Curve::new(&control_points, CurveKind::CatmullRom).unwrap(),
+        }
+    }
+}
+
+impl RenderLoopHandler for MainScenario {
+    fn on_update(
+        &mut self,
+        plugin_registry: &mut PluginRegistry,
+        _draw_context: &mut DrawContext,
+        time_info: &TimeInfo,
+    ) {
+        // Constant speed along the path, looking at the center of the cubes
+        let progress = (time_info.animation_time.as_secs_f32() / LOOP_SECONDS).fract();
+        let eye = self.path.evaluate_uniform(progress);
+        if let Some(scene_plugin) = plugin_registry.get_mut::<Scene3DPlugin>() {
+            scene_plugin
+                .camera
+                .controled_camera
+                .set_look_at(eye, Point3::new(0.0, 0.0, 0.0));
+        }
+    }
+}
@@ -99,9 +99,8 @@ impl RenderLoopHandler for MainScenario {
     ) {
         self.egui_support
             .set_pixels_per_point(self.gui_state.pixels_per_point);
-        self.time_uniform.write_uniform(
-            time_info.init_start.elapsed().as_secs_f32() * self.gui_state.anim_speed,
-        );
+        self.time_uniform
+            .write_uniform(time_info.animation_time.as_secs_f32() * self.gui_state.anim_speed);
 
         self.canvas.render(render_pass);
         self.egui_support.draw(|egui_context| {
@@ -47,13 +47,13 @@ impl MainScenario {
         }: LaunchContext,
     ) -> Self {
         let egui_plugin = EquiPlugin::new(draw_context);
-        let speed_uniform = Uniform::new(&draw_context, 1.0);
+        let speed_uniform = Uniform::new(draw_context, 1.0);
         let gui_state = GuiState {
             pixels_per_point: egui_plugin.get_pixels_per_point(),
             anim_speed: 1.0,
         };
         let canvas = CanvasPlugin::new(
-            &draw_context,
+            draw_context,
             &draw_context.create_shader_module(FRAGMENT_SHADER),
             &[BindingSlot {
                 bind_group: 1,
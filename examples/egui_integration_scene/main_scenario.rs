@@ -102,7 +102,7 @@ impl RenderLoopHandler for MainScenario {
         _draw_context: &mut DrawContext,
         time_info: &TimeInfo,
     ) {
-        let total_seconds = time_info.init_start.elapsed().as_secs_f32();
+        let total_seconds = time_info.animation_time.as_secs_f32();
         let new_rotation = ROTATION_DEG_PER_S * total_seconds;
         // Translation on z to be in the clipped space (between -w and w) and camera in front of the cube
         let z_translation: cgmath::Matrix4<f32> =
@@ -0,0 +1,56 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+mod main_scenario;
+
+use std::env;
+use std::time::Duration;
+
+use main_scenario::MainScenario;
+use pollster::FutureExt;
+use wgpu_igniter::{DrawContext, render_headless_frames};
+
+const WIDTH: u32 = 320;
+const HEIGHT: u32 = 240;
+const FRAME_COUNT: u32 = 30;
+const FRAME_DELTA: Duration = Duration::from_millis(16);
+const OUTPUT_FILE_NAME: &str = "wgpu_igniter_headless.png";
+
+// Renders without any window nor environment variable, and saves the last frame as a PNG file
+fn main() -> anyhow::Result<()> {
+    let mut draw_context = DrawContext::new_headless(WIDTH, HEIGHT).block_on()?;
+    let pixels = render_headless_frames(&mut draw_context, FRAME_COUNT, FRAME_DELTA, |c| {
+        Box::new(MainScenario::new(c))
+    })?;
+    let output_path = env::temp_dir().join(OUTPUT_FILE_NAME);
+    image::save_buffer(
+        &output_path,
+        &pixels,
+        WIDTH,
+        HEIGHT,
+        image::ExtendedColorType::Rgba8,
+    )?;
+    println!("Image saved to {}", output_path.display());
+    Ok(())
+}
@@ -26,9 +26,9 @@ mod main_scenario;
 mod scenario_cube;
 mod scenario_triangle;
 
-use main_scenario::MainScenario;
+use main_scenario::new_sequence;
 use wgpu_igniter::launch_app;
 
 fn main() {
-    launch_app(|c| Box::new(MainScenario::new(c)));
+    launch_app(|c| Box::new(new_sequence(c)));
 }
@@ -67,7 +67,10 @@ impl MainScenario {
 
 impl RenderLoopHandler for MainScenario {
     fn on_init(&mut self, _plugin_registry: &mut PluginRegistry, draw_context: &mut DrawContext) {
-        draw_context.set_clear_color(Some(wgpu::Color::GREEN));
+        draw_context.push_clear_color(Some(wgpu::Color::GREEN));
+    }
+    fn on_exit(&mut self, draw_context: &mut DrawContext) {
+        draw_context.pop_clear_color();
     }
     fn on_update(
         &mut self,
@@ -75,7 +78,7 @@ impl RenderLoopHandler for MainScenario {
         _draw_context: &mut DrawContext,
         time_info: &TimeInfo,
     ) {
-        let total_seconds = time_info.init_start.elapsed().as_secs_f32();
+        let total_seconds = time_info.animation_time.as_secs_f32();
         let new_rotation = ROTATION_DEG_PER_S * total_seconds;
         // Translation on z to be in the clipped space (between -w and w) and camera in front of the cube
         let z_translation: cgmath::Matrix4<f32> =
@@ -84,7 +84,10 @@ impl MainScenario {
 
 impl RenderLoopHandler for MainScenario {
     fn on_init(&mut self, _plugin_registry: &mut PluginRegistry, draw_context: &mut DrawContext) {
-        draw_context.set_clear_color(Some(wgpu::Color::BLUE));
+        draw_context.push_clear_color(Some(wgpu::Color::BLUE));
+    }
+    fn on_exit(&mut self, draw_context: &mut DrawContext) {
+        draw_context.pop_clear_color();
     }
     fn on_render(
         &mut self,
@@ -93,7 +96,7 @@ impl RenderLoopHandler for MainScenario {
         time_info: &TimeInfo,
         render_pass: &mut wgpu::RenderPass<'static>,
     ) {
-        let total_seconds = time_info.init_start.elapsed().as_secs_f32();
+        let total_seconds = time_info.animation_time.as_secs_f32();
         let new_rotation = ROTATION_DEG_PER_S * total_seconds;
         let transform: cgmath::Matrix4<f32> = cgmath::Matrix4::from_scale(0.5)
             * cgmath::Matrix4::from_angle_z(cgmath::Deg(new_rotation));
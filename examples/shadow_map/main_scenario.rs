@@ -0,0 +1,156 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use cgmath::{Point3, Vector3};
+use wgpu_igniter::cameras::{
+    Camera, CameraView, InteractiveCamera, OrthogonalCameraConfig, PerspectiveCameraConfig,
+};
+use wgpu_igniter::plugins::PluginRegistry;
+use wgpu_igniter::plugins::scene_3d::{Scene3D, Scene3DPlugin};
+use wgpu_igniter::primitives::cube::{self, CubeOptions};
+use wgpu_igniter::primitives::{Object3D, Shareable, Transforms};
+use wgpu_igniter::shadows::{ShadowCaster, ShadowMap};
+use wgpu_igniter::{DrawContext, LaunchContext, RenderLoopHandler, TimeInfo};
+
+const SHADER: &str = include_str!("shadow_map.wgsl");
+const SHADOW_SHADER: &str = include_str!("shadow_caster.wgsl");
+const SHADOW_MAP_SIZE: u32 = 2048;
+
+const ROTATION_DEG_PER_S: f32 = 45.0;
+
+pub struct MainScenario {
+    cube: Rc<RefCell<Object3D>>,
+    ground: Rc<RefCell<Object3D>>,
+    shadow_map: ShadowMap,
+}
+
+impl MainScenario {
+    pub fn new(
+        LaunchContext {
+            draw_context,
+            plugin_registry,
+        }: LaunchContext,
+    ) -> Self {
+        let camera = InteractiveCamera::new(Camera::new(
+            CameraView {
+                eye: Point3::new(0.0, 4.0, -8.0),
+                center: Point3::new(0.0, 0.0, 0.0),
+                up: Vector3::unit_y(),
+            },
+            Box::new(PerspectiveCameraConfig::default()),
+        ));
+        let light_camera = Camera::new(
+            CameraView {
+                eye: Point3::new(4.0, 8.0, -2.0),
+                center: Point3::new(0.0, 0.0, 0.0),
+                up: Vector3::unit_y(),
+            },
+            Box::new(OrthogonalCameraConfig {
+                width: 14.0,
+                height: 14.0,
+                near: 0.1,
+                far: 30.0,
+                zoom: 1.0,
+            }),
+        );
+        let mut shadow_map = ShadowMap::new(draw_context, SHADOW_MAP_SIZE);
+        shadow_map.set_light_camera(&light_camera);
+
+        let shader_module = draw_context.create_shader_module(&format!(
+            "{}\n{}",
+            ShadowMap::SHADER_SNIPPET,
+            SHADER
+        ));
+        let shadow_shader_module = draw_context.create_shader_module(&format!(
+            "{}\n{}",
+            ShadowMap::SHADER_SNIPPET,
+            SHADOW_SHADER
+        ));
+        let mut scene = Scene3D::new(draw_context);
+        let cube_options = CubeOptions {
+            shadow_caster: Some(ShadowCaster {
+                shadow_map: &shadow_map,
+                vtx_module: &shadow_shader_module,
+            }),
+            ..Default::default()
+        };
+        let cube = cube::create_cube_with_normals(
+            draw_context,
+            &shader_module,
+            &shader_module,
+            scene.scene_uniforms(),
+            &cube_options,
+        )
+        .into_shareable();
+        let mut ground = cube::create_cube_with_normals(
+            draw_context,
+            &shader_module,
+            &shader_module,
+            scene.scene_uniforms(),
+            &cube_options,
+        );
+        ground.set_scale(Vector3::new(8.0, 0.2, 8.0));
+        ground.set_translation(Vector3::new(0.0, -1.5, 0.0));
+        let ground = ground.into_shareable();
+        scene.add(cube.clone());
+        scene.add(ground.clone());
+
+        plugin_registry.register(Scene3DPlugin { camera, scene });
+        Self {
+            cube,
+            ground,
+            shadow_map,
+        }
+    }
+}
+
+impl RenderLoopHandler for MainScenario {
+    fn on_update(
+        &mut self,
+        _plugin_registry: &mut PluginRegistry,
+        _draw_context: &mut DrawContext,
+        time_info: &TimeInfo,
+    ) {
+        let delta_rotation = ROTATION_DEG_PER_S * time_info.processing_delta.as_secs_f32();
+        let transform = cgmath::Matrix4::from_angle_z(cgmath::Deg(delta_rotation))
+            * cgmath::Matrix4::from_angle_y(cgmath::Deg(delta_rotation));
+        self.cube.borrow_mut().apply_transform(transform);
+    }
+    fn on_render(
+        &mut self,
+        _plugin_registry: &mut PluginRegistry,
+        draw_context: &DrawContext,
+        _time_info: &TimeInfo,
+        _render_pass: &mut wgpu::RenderPass<'static>,
+    ) {
+        // Submitted before the main render pass, which samples the shadow map
+        self.shadow_map.render(draw_context, |render_pass| {
+            self.cube.borrow().as_ref().render_shadow(render_pass);
+            self.ground.borrow().as_ref().render_shadow(render_pass);
+        });
+    }
+}
@@ -0,0 +1,7 @@
+@group(1) @binding(0)
+var<uniform> transform: mat4x4<f32>;
+
+@vertex
+fn vtx_shadow(@location(0) position: vec3<f32>) -> @builtin(position) vec4<f32> {
+    return shadow_clip_position(transform * vec4<f32>(position, 1.0));
+}
@@ -0,0 +1,105 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use cgmath::{Matrix4, Point3, Vector3};
+use wgpu_igniter::cameras::{Camera, InteractiveCamera};
+use wgpu_igniter::plugins::scene_3d::{Scene3D, Scene3DPlugin};
+use wgpu_igniter::primitives::mesh::MeshOptions;
+use wgpu_igniter::primitives::pbr::{self, PbrMaterial, PbrShading};
+use wgpu_igniter::primitives::phong::PhongLight;
+use wgpu_igniter::primitives::{Shareable, Transforms, sphere};
+use wgpu_igniter::support::environment::EnvironmentMap;
+use wgpu_igniter::{Dimensions, LaunchContext, RenderLoopHandler};
+
+const SPHERE_COUNT: u16 = 5;
+const SPHERE_SPACING: f32 = 1.2;
+const SKY_WIDTH: u32 = 64;
+const SKY_HEIGHT: u32 = 32;
+
+// Blue sky above a brown ground, for the ambient light
+fn sky() -> Vec<[f32; 4]> {
+    (0..SKY_HEIGHT)
+        .flat_map(|y| {
+            let color = if y < SKY_HEIGHT / 2 {
+                [0.3, 0.4, 0.6, 1.0]
+            } else {
+                [0.15, 0.1, 0.05, 1.0]
+            };
+            (0..SKY_WIDTH).map(move |_| color)
+        })
+        .collect()
+}
+
+pub struct MainScenario;
+
+impl MainScenario {
+    pub fn new(
+        LaunchContext {
+            draw_context,
+            plugin_registry,
+        }: LaunchContext,
+    ) -> Self {
+        let camera = InteractiveCamera::new(Camera::default());
+        let mut scene = Scene3D::new(draw_context);
+        let environment_map = EnvironmentMap::from_equirectangular(
+            draw_context,
+            Dimensions::new(SKY_WIDTH, SKY_HEIGHT),
+            &sky(),
+        )
+        .expect("Sky should have as many pixels as its dimensions");
+        let light = PhongLight::Point {
+            position: Point3::new(-4.0, 4.0, -6.0),
+            color: [20.0, 20.0, 20.0],
+        };
+        let sphere_mesh = sphere::uv_sphere_mesh(32, 64);
+        // From a smooth sphere on the left, to a fully rough one on the right
+        for index in 0..SPHERE_COUNT {
+            let ratio = f32::from(index) / f32::from(SPHERE_COUNT - 1);
+            let shading = PbrShading::new(
+                draw_context,
+                &PbrMaterial {
+                    base_color: [0.8, 0.2, 0.1],
+                    metallic: 0.0,
+                    roughness: 0.05 + 0.95 * ratio,
+                },
+                &light,
+                Some(&environment_map),
+            );
+            let mut sphere = pbr::create_pbr_mesh(
+                draw_context,
+                scene.scene_uniforms(),
+                &sphere_mesh,
+                &shading,
+                &MeshOptions::default(),
+            );
+            let offset = (ratio - 0.5) * f32::from(SPHERE_COUNT - 1) * SPHERE_SPACING;
+            sphere.set_transform(Matrix4::from_translation(Vector3::new(-offset, 0.0, 0.0)));
+            scene.add(sphere.into_shareable());
+        }
+        plugin_registry.register(Scene3DPlugin { camera, scene });
+        Self
+    }
+}
+
+impl RenderLoopHandler for MainScenario {}
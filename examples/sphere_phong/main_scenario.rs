@@ -0,0 +1,98 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use cgmath::{Point3, Vector3};
+use wgpu_igniter::cameras::{Camera, InteractiveCamera};
+use wgpu_igniter::plugins::PluginRegistry;
+use wgpu_igniter::plugins::scene_3d::{Scene3D, Scene3DPlugin};
+use wgpu_igniter::primitives::mesh::MeshOptions;
+use wgpu_igniter::primitives::phong::{self, PhongLight, PhongMaterial, PhongShading};
+use wgpu_igniter::primitives::{Shareable, sphere};
+use wgpu_igniter::{DrawContext, LaunchContext, RenderLoopHandler, TimeInfo};
+
+const LIGHT_ORBIT_RADIUS: f32 = 6.0;
+const LIGHT_DEG_PER_S: f32 = 60.0;
+const SPHERE_SCALE: f32 = 4.0;
+
+pub struct MainScenario {
+    shading: PhongShading,
+    light_angle: cgmath::Deg<f32>,
+}
+
+impl MainScenario {
+    pub fn new(
+        LaunchContext {
+            draw_context,
+            plugin_registry,
+        }: LaunchContext,
+    ) -> Self {
+        let camera = InteractiveCamera::new(Camera::default());
+        let mut scene = Scene3D::new(draw_context);
+        let shading = PhongShading::new(
+            draw_context,
+            &PhongMaterial {
+                ambient: [0.05, 0.02, 0.02],
+                diffuse: [0.7, 0.1, 0.1],
+                specular: [1.0, 1.0, 1.0],
+                shininess: 64.0,
+            },
+            &PhongLight::default(),
+        );
+        let mut sphere = phong::create_phong_mesh(
+            draw_context,
+            scene.scene_uniforms(),
+            &sphere::uv_sphere_mesh(32, 64),
+            &shading,
+            &MeshOptions::default(),
+        );
+        sphere.set_scale(Vector3::new(SPHERE_SCALE, SPHERE_SCALE, SPHERE_SCALE));
+        scene.add(sphere.into_shareable());
+        plugin_registry.register(Scene3DPlugin { camera, scene });
+        Self {
+            shading,
+            light_angle: cgmath::Deg(0.0),
+        }
+    }
+}
+
+impl RenderLoopHandler for MainScenario {
+    fn on_update(
+        &mut self,
+        _plugin_registry: &mut PluginRegistry,
+        _draw_context: &mut DrawContext,
+        time_info: &TimeInfo,
+    ) {
+        self.light_angle += cgmath::Deg(LIGHT_DEG_PER_S * time_info.processing_delta.as_secs_f32());
+        let (sin, cos) = cgmath::Angle::sin_cos(self.light_angle);
+        // Orbits in front of the sphere, on the side of the camera
+        self.shading.set_light(&PhongLight::Point {
+            position: Point3::new(
+                LIGHT_ORBIT_RADIUS * cos,
+                LIGHT_ORBIT_RADIUS * sin,
+                -LIGHT_ORBIT_RADIUS,
+            ),
+            color: [1.0, 1.0, 1.0],
+        });
+    }
+}
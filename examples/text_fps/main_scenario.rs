@@ -0,0 +1,83 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use wgpu_igniter::plugins::PluginRegistry;
+use wgpu_igniter::support::text::TextRenderer;
+use wgpu_igniter::{DrawContext, LaunchContext, RenderLoopHandler, TimeInfo};
+
+const TEXT_SIZE: f32 = 24.0;
+const TEXT_MARGIN: f32 = 10.0;
+// Weight of the last frame in the displayed average, to keep the value readable
+const FPS_SMOOTHING: f32 = 0.05;
+
+pub struct MainScenario {
+    text: TextRenderer,
+    average_fps: f32,
+}
+
+impl MainScenario {
+    pub fn new(LaunchContext { draw_context, .. }: LaunchContext) -> Self {
+        Self {
+            text: TextRenderer::with_default_font(draw_context),
+            average_fps: 0.0,
+        }
+    }
+}
+
+impl RenderLoopHandler for MainScenario {
+    fn on_update(
+        &mut self,
+        _plugin_registry: &mut PluginRegistry,
+        _draw_context: &mut DrawContext,
+        time_info: &TimeInfo,
+    ) {
+        let delta = time_info.processing_delta.as_secs_f32();
+        if delta > 0.0 {
+            self.average_fps =
+                (1.0 / delta - self.average_fps).mul_add(FPS_SMOOTHING, self.average_fps);
+        }
+        self.text.queue_text(
+            &format!("FPS: {:.0}", self.average_fps),
+            [TEXT_MARGIN, TEXT_MARGIN],
+            TEXT_SIZE,
+            [1.0, 1.0, 0.0, 1.0],
+        );
+        self.text.queue_text(
+            "Text drawn without egui",
+            [TEXT_MARGIN, 2.0f32.mul_add(TEXT_SIZE, TEXT_MARGIN)],
+            TEXT_SIZE,
+            [1.0, 1.0, 1.0, 1.0],
+        );
+    }
+
+    fn on_render(
+        &mut self,
+        _plugin_registry: &mut PluginRegistry,
+        draw_context: &DrawContext,
+        _time_info: &TimeInfo,
+        render_pass: &mut wgpu_igniter::wgpu::RenderPass<'static>,
+    ) {
+        self.text.render(draw_context, render_pass);
+    }
+}
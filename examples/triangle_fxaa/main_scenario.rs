@@ -0,0 +1,142 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use log::info;
+use wgpu_igniter::plugins::PluginRegistry;
+use wgpu_igniter::primitives::triangle::{
+    TRIANGLE_COLOR, TRIANGLE_GEOMETRY, TRIANGLE_VERTEX_COUNT,
+};
+use wgpu_igniter::support::postprocess::{Fxaa, FxaaQuality};
+use wgpu_igniter::support::render_texture::RenderTexture;
+use wgpu_igniter::{
+    DrawContext, DrawModeParams, Drawable, DrawableBuilder, LaunchContext, RenderLoopHandler,
+    TimeInfo,
+};
+use winit::event::{ElementState, KeyEvent};
+use winit::keyboard::{Key, NamedKey};
+
+const DEFAULT_SHADER: &str = include_str!("./triangle_fxaa.wgsl");
+
+// The triangle is drawn without multisampling, so that FXAA is the only anti-aliasing
+const SAMPLE_COUNT: u32 = 1;
+
+pub struct MainScenario {
+    triangle: Drawable,
+    aliased_target: RenderTexture,
+    fxaa: Fxaa,
+    fxaa_quality: FxaaQuality,
+}
+
+impl MainScenario {
+    pub fn new(LaunchContext { draw_context, .. }: LaunchContext) -> Self {
+        let aliased_target = RenderTexture::with_sample_count(
+            draw_context,
+            draw_context.surface_dimensions(),
+            draw_context.surface_config.format,
+            SAMPLE_COUNT,
+        );
+        let shader_module = draw_context.create_shader_module(DEFAULT_SHADER);
+        let mut drawable_builder = DrawableBuilder::new(
+            draw_context,
+            &shader_module,
+            &shader_module,
+            DrawModeParams::Direct {
+                vertex_count: TRIANGLE_VERTEX_COUNT,
+            },
+        );
+        drawable_builder
+            .set_color_target_format(aliased_target.format())
+            .set_sample_count(SAMPLE_COUNT)
+            .add_attribute(
+                0,
+                wgpu::VertexStepMode::Vertex,
+                TRIANGLE_GEOMETRY,
+                wgpu::VertexFormat::Float32x3,
+            )
+            .expect("Location should be different than for another attribute.")
+            .add_attribute(
+                1,
+                wgpu::VertexStepMode::Vertex,
+                TRIANGLE_COLOR,
+                wgpu::VertexFormat::Float32x3,
+            )
+            .expect("Location should be different than for another attribute.");
+        let triangle = drawable_builder.build();
+        let fxaa_quality = FxaaQuality::High;
+        let fxaa = Fxaa::new(draw_context, &aliased_target, fxaa_quality);
+        Self {
+            triangle,
+            aliased_target,
+            fxaa,
+            fxaa_quality,
+        }
+    }
+}
+
+impl RenderLoopHandler for MainScenario {
+    // Space cycles through the FXAA quality presets
+    fn on_keyboard_event(&mut self, event: &KeyEvent) {
+        if event.state != ElementState::Pressed || event.logical_key != Key::Named(NamedKey::Space)
+        {
+            return;
+        }
+        self.fxaa_quality = match self.fxaa_quality {
+            FxaaQuality::Low => FxaaQuality::Medium,
+            FxaaQuality::Medium => FxaaQuality::High,
+            FxaaQuality::High => FxaaQuality::Low,
+        };
+        info!("FXAA quality: {:?}", self.fxaa_quality);
+        self.fxaa.set_quality(self.fxaa_quality);
+    }
+    fn on_update(
+        &mut self,
+        _plugin_registry: &mut PluginRegistry,
+        draw_context: &mut DrawContext,
+        _time_info: &TimeInfo,
+    ) {
+        let dimensions = draw_context.surface_dimensions();
+        if dimensions != self.aliased_target.dimensions() {
+            self.aliased_target = RenderTexture::with_sample_count(
+                draw_context,
+                dimensions,
+                draw_context.surface_config.format,
+                SAMPLE_COUNT,
+            );
+            self.fxaa.set_source(draw_context, &self.aliased_target);
+        }
+    }
+    fn on_render(
+        &mut self,
+        _plugin_registry: &mut PluginRegistry,
+        draw_context: &DrawContext,
+        _time_info: &TimeInfo,
+        render_pass: &mut wgpu::RenderPass<'static>,
+    ) {
+        self.aliased_target
+            .render(draw_context, wgpu::Color::BLACK, |triangle_pass| {
+                self.triangle.render(triangle_pass);
+            });
+        self.fxaa.render(render_pass);
+    }
+}
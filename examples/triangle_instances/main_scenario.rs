@@ -0,0 +1,124 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use wgpu_igniter::plugins::PluginRegistry;
+use wgpu_igniter::primitives::triangle::{
+    TRIANGLE_COLOR, TRIANGLE_GEOMETRY, TRIANGLE_VERTEX_COUNT,
+};
+use wgpu_igniter::{
+    DrawContext, DrawModeParams, Drawable, DrawableBuilder, LaunchContext, RenderLoopHandler,
+    StorageBuffer, TimeInfo,
+};
+
+const DEFAULT_SHADER: &str = include_str!("./triangle_instances.wgsl");
+
+const INSTANCE_COUNT: usize = 1000;
+const COLUMN_COUNT: usize = 40;
+const ROW_COUNT: usize = INSTANCE_COUNT / COLUMN_COUNT;
+const ROTATION_DEG_PER_S: f32 = 45.0;
+
+pub struct MainScenario {
+    triangles: Drawable,
+    transforms: StorageBuffer<[[f32; 4]; 4]>,
+}
+
+impl MainScenario {
+    pub fn new(LaunchContext { draw_context, .. }: LaunchContext) -> Self {
+        let shader_module = draw_context.create_shader_module(DEFAULT_SHADER);
+        let transforms = StorageBuffer::new_array(
+            draw_context,
+            &[cgmath::Matrix4::from_scale(0.0).into(); INSTANCE_COUNT],
+        );
+        let mut drawable_builder = DrawableBuilder::new(
+            draw_context,
+            &shader_module,
+            &shader_module,
+            DrawModeParams::Direct {
+                vertex_count: TRIANGLE_VERTEX_COUNT,
+            },
+        );
+        drawable_builder
+            .add_attribute(
+                0,
+                wgpu::VertexStepMode::Vertex,
+                TRIANGLE_GEOMETRY,
+                wgpu::VertexFormat::Float32x3,
+            )
+            .expect("Location should be different than for another attribute.")
+            .add_attribute(
+                1,
+                wgpu::VertexStepMode::Vertex,
+                TRIANGLE_COLOR,
+                wgpu::VertexFormat::Float32x3,
+            )
+            .expect("Location should be different than for another attribute.")
+            .set_instance_transforms(0, 0, &transforms)
+            .expect("Bind group or binding should be different from other uniforms.");
+        let triangles = drawable_builder.build();
+        Self {
+            triangles,
+            transforms,
+        }
+    }
+}
+
+impl RenderLoopHandler for MainScenario {
+    #[allow(clippy::cast_precision_loss)]
+    fn on_render(
+        &mut self,
+        _plugin_registry: &mut PluginRegistry,
+        draw_context: &DrawContext,
+        time_info: &TimeInfo,
+        render_pass: &mut wgpu::RenderPass<'static>,
+    ) {
+        let screen_ratio = draw_context.surface_ratio();
+        let scale_factor = if screen_ratio > 1.0 {
+            cgmath::Matrix4::from_nonuniform_scale(1.0 / screen_ratio, 1.0, 1.0)
+        } else {
+            cgmath::Matrix4::from_nonuniform_scale(1.0, screen_ratio, 1.0)
+        };
+        let total_seconds = time_info.animation_time.as_secs_f32();
+        let cell_width = 2.0 / COLUMN_COUNT as f32;
+        let cell_height = 2.0 / ROW_COUNT as f32;
+        let mut writer = self.transforms.start_write();
+        for index in 0..INSTANCE_COUNT {
+            let x = (index % COLUMN_COUNT) as f32 + 0.5;
+            let y = (index / COLUMN_COUNT) as f32 + 0.5;
+            // Each triangle spins at its own pace
+            let rotation = ROTATION_DEG_PER_S * total_seconds * (1.0 + (index % 7) as f32 / 7.0);
+            let transform = scale_factor
+                * cgmath::Matrix4::from_translation(cgmath::Vector3::new(
+                    x.mul_add(cell_width, -1.0),
+                    y.mul_add(cell_height, -1.0),
+                    0.0,
+                ))
+                * cgmath::Matrix4::from_scale(cell_width.min(cell_height) * 0.45)
+                * cgmath::Matrix4::from_angle_z(cgmath::Deg(rotation));
+            writer.set_value(index, transform.into());
+        }
+        writer.apply_write();
+
+        self.triangles.render(render_pass);
+    }
+}
@@ -96,7 +96,7 @@ impl RenderLoopHandler for MainScenario {
         } else {
             cgmath::Matrix4::from_nonuniform_scale(1.0, screen_ratio, 1.0)
         };
-        let total_seconds = time_info.init_start.elapsed().as_secs_f32();
+        let total_seconds = time_info.animation_time.as_secs_f32();
         let new_rotation = ROTATION_DEG_PER_S * total_seconds;
         let transform: cgmath::Matrix4<f32> = scale_factor
             * cgmath::Matrix4::from_scale(0.9)
@@ -22,16 +22,20 @@ OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 SOFTWARE.
 */
 
-use cgmath::{InnerSpace, Matrix3, Matrix4, PerspectiveFov, Rad, Vector3, vec3};
+use cgmath::{
+    InnerSpace, Matrix, Matrix3, Matrix4, PerspectiveFov, Quaternion, Rad, Rotation3, Vector3,
+    Vector4, vec3,
+};
 use cgmath::{Ortho, Point3};
 use log::warn;
 use std::collections::BTreeSet;
 use std::f32::consts::PI;
 use std::sync::LazyLock;
-use winit::event::{DeviceEvent, ElementState, KeyEvent};
+use winit::event::{DeviceEvent, ElementState, KeyEvent, MouseScrollDelta};
 use winit::keyboard::{KeyCode, PhysicalKey};
 
 use crate::Dimensions;
+use crate::primitives::bounds::Aabb;
 
 static SWITCH_Z_AXIS: LazyLock<Matrix4<f32>> =
     LazyLock::new(|| Matrix4::from_nonuniform_scale(1., 1., -1.));
@@ -39,6 +43,9 @@ static TO_WEBGPU_NDCS: LazyLock<Matrix4<f32>> = LazyLock::new(|| {
     Matrix4::from_translation(vec3(0., 0., 0.5)) * Matrix4::from_nonuniform_scale(1., 1., 0.5)
 });
 
+// The view matrix is left-handed: seen from the eye, +x is on the right, +y is up and the
+// target is forward, whichever the sign of its z. SWITCH_Z_AXIS then hands the view over to the
+// right-handed cgmath projections, so the flip never needs to be compensated by the user.
 pub struct CameraView {
     pub eye: Point3<f32>,
     pub center: Point3<f32>,
@@ -46,6 +53,27 @@ pub struct CameraView {
 }
 
 impl CameraView {
+    // The up vector is the world +y, or +z when looking straight up or down
+    #[must_use]
+    pub fn looking_at(eye: Point3<f32>, target: Point3<f32>) -> Self {
+        let (forward, center) = if (target - eye).magnitude2() > f32::EPSILON {
+            ((target - eye).normalize(), target)
+        } else {
+            warn!("Eye and target are at the same position, looking toward +z");
+            (Vector3::unit_z(), eye + Vector3::unit_z())
+        };
+        let world_up = if forward.y.abs() > 0.999 {
+            Vector3::unit_z()
+        } else {
+            Vector3::unit_y()
+        };
+        let right = world_up.cross(forward).normalize();
+        Self {
+            eye,
+            center,
+            up: forward.cross(right),
+        }
+    }
     #[must_use]
     pub fn calc_view_matrix(&self) -> Matrix4<f32> {
         Matrix4::look_at_lh(self.eye, self.center, self.up)
@@ -113,33 +141,212 @@ impl Default for CameraView {
     }
 }
 
+// Position and orientation quaternion, the view basis being derived from the quaternion: it
+// does not drift under continuous rotation like the eye/center/up vectors of CameraView. The
+// local axes follow the same convention, +x on the right, +y up and +z forward.
+pub struct FreeFlightView {
+    pub position: Point3<f32>,
+    orientation: Quaternion<f32>,
+}
+
+impl FreeFlightView {
+    #[must_use]
+    pub fn new(position: Point3<f32>, orientation: Quaternion<f32>) -> Self {
+        Self {
+            position,
+            orientation: orientation.normalize(),
+        }
+    }
+    #[must_use]
+    pub fn looking_at(eye: Point3<f32>, target: Point3<f32>) -> Self {
+        Self::from(&CameraView::looking_at(eye, target))
+    }
+    #[must_use]
+    pub const fn orientation(&self) -> Quaternion<f32> {
+        self.orientation
+    }
+    #[must_use]
+    pub fn forward(&self) -> Vector3<f32> {
+        self.orientation * Vector3::unit_z()
+    }
+    #[must_use]
+    pub fn up(&self) -> Vector3<f32> {
+        self.orientation * Vector3::unit_y()
+    }
+    #[must_use]
+    pub fn right(&self) -> Vector3<f32> {
+        self.orientation * Vector3::unit_x()
+    }
+    // One unit in front of the position
+    #[must_use]
+    pub fn center(&self) -> Point3<f32> {
+        self.position + self.forward()
+    }
+    #[must_use]
+    pub fn calc_view_matrix(&self) -> Matrix4<f32> {
+        Matrix4::look_at_lh(self.position, self.center(), self.up())
+    }
+    pub fn move_x(&mut self, val: f32) {
+        self.position += self.right() * val;
+    }
+    pub fn move_y(&mut self, val: f32) {
+        self.position += self.up() * val;
+    }
+    pub fn move_z(&mut self, val: f32) {
+        self.position += self.forward() * val;
+    }
+    pub fn roll(&mut self, val: f32) {
+        self.rotate_local(Vector3::unit_z(), val);
+    }
+    pub fn tilt(&mut self, val: f32) {
+        self.rotate_local(Vector3::unit_x(), val);
+    }
+    pub fn pan(&mut self, val: f32) {
+        self.rotate_local(Vector3::unit_y(), val);
+    }
+    fn rotate_local(&mut self, axis: Vector3<f32>, val: f32) {
+        self.orientation =
+            (self.orientation * Quaternion::from_axis_angle(axis, Rad(val))).normalize();
+    }
+}
+
+impl From<&CameraView> for FreeFlightView {
+    fn from(view: &CameraView) -> Self {
+        let forward = (view.center - view.eye).normalize();
+        let right = view.up.cross(forward).normalize();
+        let up = forward.cross(right);
+        Self::new(
+            view.eye,
+            Quaternion::from(Matrix3::from_cols(right, up, forward)),
+        )
+    }
+}
+
+// Eye/center/up view on which both representations can be driven by a Camera
+enum ViewKind {
+    LookAt(CameraView),
+    FreeFlight(FreeFlightView),
+}
+
+impl ViewKind {
+    fn calc_view_matrix(&self) -> Matrix4<f32> {
+        match self {
+            Self::LookAt(view) => view.calc_view_matrix(),
+            Self::FreeFlight(view) => view.calc_view_matrix(),
+        }
+    }
+    const fn eye(&self) -> Point3<f32> {
+        match self {
+            Self::LookAt(view) => view.eye,
+            Self::FreeFlight(view) => view.position,
+        }
+    }
+    fn forward(&self) -> Vector3<f32> {
+        match self {
+            Self::LookAt(view) => (view.center - view.eye).normalize(),
+            Self::FreeFlight(view) => view.forward(),
+        }
+    }
+    fn move_x(&mut self, val: f32) {
+        match self {
+            Self::LookAt(view) => view.move_x(val, false),
+            Self::FreeFlight(view) => view.move_x(val),
+        }
+    }
+    fn move_y(&mut self, val: f32) {
+        match self {
+            Self::LookAt(view) => view.move_y(val, false),
+            Self::FreeFlight(view) => view.move_y(val),
+        }
+    }
+    fn move_z(&mut self, val: f32) {
+        match self {
+            Self::LookAt(view) => view.move_z(val, false),
+            Self::FreeFlight(view) => view.move_z(val),
+        }
+    }
+    fn pan(&mut self, val: f32) {
+        match self {
+            Self::LookAt(view) => view.pan(val),
+            Self::FreeFlight(view) => view.pan(val),
+        }
+    }
+    fn tilt(&mut self, val: f32) {
+        match self {
+            Self::LookAt(view) => view.tilt(val),
+            Self::FreeFlight(view) => view.tilt(val),
+        }
+    }
+    fn roll(&mut self, val: f32) {
+        match self {
+            Self::LookAt(view) => view.roll(val),
+            Self::FreeFlight(view) => view.roll(val),
+        }
+    }
+}
+
 pub trait CameraProjection {
     fn calc_projection(&self) -> Matrix4<f32>;
     fn resize_screen(&mut self, dimensions: Dimensions);
+    fn near(&self) -> f32;
+    fn far(&self) -> f32;
+    fn set_near(&mut self, near: f32);
+    fn set_far(&mut self, far: f32);
+    fn zoom(&self) -> f32 {
+        1.0
+    }
+    fn set_zoom(&mut self, _zoom: f32) {}
+    // Vertical field of view in radians, None for projections without perspective
+    fn fovy(&self) -> Option<f32> {
+        None
+    }
+    fn set_fovy(&mut self, _fovy: f32) {}
 }
 
+// Width and height are in world units, the visible extent being divided by the zoom factor
 pub struct OrthogonalCameraConfig {
     pub width: f32,
     pub height: f32,
     pub near: f32,
     pub far: f32,
+    pub zoom: f32,
 }
 
 impl CameraProjection for OrthogonalCameraConfig {
     fn calc_projection(&self) -> Matrix4<f32> {
+        let half_width = self.width / (2.0 * self.zoom);
+        let half_height = self.height / (2.0 * self.zoom);
         Matrix4::from(Ortho {
-            left: -self.width / 2.0,
-            right: self.width / 2.0,
-            bottom: -self.height / 2.0,
-            top: self.height / 2.0,
+            left: -half_width,
+            right: half_width,
+            bottom: -half_height,
+            top: half_height,
             near: self.near,
             far: self.far,
         })
     }
     #[allow(clippy::cast_precision_loss)]
     fn resize_screen(&mut self, dimensions: Dimensions) {
-        self.width = dimensions.width as f32;
-        self.height = dimensions.height as f32;
+        // Keep the world height, only adapt the width to the new aspect ratio
+        self.width = self.height * dimensions.width as f32 / dimensions.height as f32;
+    }
+    fn near(&self) -> f32 {
+        self.near
+    }
+    fn far(&self) -> f32 {
+        self.far
+    }
+    fn set_near(&mut self, near: f32) {
+        self.near = near;
+    }
+    fn set_far(&mut self, far: f32) {
+        self.far = far;
+    }
+    fn zoom(&self) -> f32 {
+        self.zoom
+    }
+    fn set_zoom(&mut self, zoom: f32) {
+        self.zoom = zoom.max(f32::EPSILON);
     }
 }
 
@@ -150,6 +357,7 @@ impl Default for OrthogonalCameraConfig {
             height: 9.0 / 4.0,
             near: 0.,
             far: 1_000.0,
+            zoom: 1.0,
         }
     }
 }
@@ -185,11 +393,39 @@ impl CameraProjection for PerspectiveCameraConfig {
     fn resize_screen(&mut self, dimensions: Dimensions) {
         self.aspect = dimensions.width as f32 / dimensions.height as f32;
     }
+    fn near(&self) -> f32 {
+        self.near
+    }
+    fn far(&self) -> f32 {
+        self.far
+    }
+    // The far plane is pushed back if needed, so it stays behind the near plane
+    fn set_near(&mut self, near: f32) {
+        self.near = near.max(Self::MIN_NEAR);
+        self.far = self.far.max(self.near * Self::MIN_FAR_RATIO);
+    }
+    fn set_far(&mut self, far: f32) {
+        self.far = far.max(self.near * Self::MIN_FAR_RATIO);
+    }
+    fn fovy(&self) -> Option<f32> {
+        Some(self.fovy)
+    }
+    fn set_fovy(&mut self, fovy: f32) {
+        self.fovy = fovy.clamp(Self::MIN_FOVY, PI - Self::MIN_FOVY);
+    }
+}
+
+impl PerspectiveCameraConfig {
+    // Keeps the projection finite at both ends of the range
+    const MIN_FOVY: f32 = 0.001;
+    // The near plane cannot be on the eye, nor the far plane on the near one
+    const MIN_NEAR: f32 = 0.0001;
+    const MIN_FAR_RATIO: f32 = 1.01;
 }
 
 pub struct Camera {
     projection: Box<dyn CameraProjection>,
-    view: CameraView,
+    view: ViewKind,
     projection_cache: Matrix4<f32>,
     view_cache: Matrix4<f32>,
 }
@@ -202,10 +438,18 @@ impl Default for Camera {
         )
     }
 }
-// TODO Provide method to replace the project and the view directly
+// TODO Provide method to replace the view directly
 impl Camera {
     #[must_use]
     pub fn new(view: CameraView, projection: Box<dyn CameraProjection>) -> Self {
+        Self::with_view(ViewKind::LookAt(view), projection)
+    }
+    // Numerically stable under long interactive rotations, see FreeFlightView
+    #[must_use]
+    pub fn new_free_flight(view: FreeFlightView, projection: Box<dyn CameraProjection>) -> Self {
+        Self::with_view(ViewKind::FreeFlight(view), projection)
+    }
+    fn with_view(view: ViewKind, projection: Box<dyn CameraProjection>) -> Self {
         let view_cache = view.calc_view_matrix();
         let projection_cache = projection.calc_projection();
         Self {
@@ -215,12 +459,30 @@ impl Camera {
             view_cache,
         }
     }
+    // Perspective camera with the default projection, see CameraView for the axes convention
+    #[must_use]
+    pub fn new_looking_at(eye: Point3<f32>, target: Point3<f32>) -> Self {
+        Self::new(
+            CameraView::looking_at(eye, target),
+            Box::new(PerspectiveCameraConfig::default()),
+        )
+    }
+    // Replaces the view, e.g. for controllers computing the eye position themselves
+    pub fn set_look_at(&mut self, eye: Point3<f32>, target: Point3<f32>) {
+        self.view = ViewKind::LookAt(CameraView::looking_at(eye, target));
+        self.update_view_cache();
+    }
     fn update_view_cache(&mut self) {
         self.view_cache = self.view.calc_view_matrix();
     }
     fn update_projection_cache(&mut self) {
         self.projection_cache = self.projection.calc_projection();
     }
+    // The new projection is not resized, resize_screen should be called if needed
+    pub fn set_projection(&mut self, projection: Box<dyn CameraProjection>) {
+        self.projection = projection;
+        self.update_projection_cache();
+    }
     pub fn resize_screen(&mut self, dimensions: Dimensions) {
         self.projection.resize_screen(dimensions);
         self.update_projection_cache();
@@ -230,19 +492,96 @@ impl Camera {
         (*TO_WEBGPU_NDCS) * self.projection_cache * (*SWITCH_Z_AXIS) * self.view_cache
     }
     #[must_use]
+    pub fn frustum(&self) -> Frustum {
+        Frustum::from_matrix(self.get_camera_matrix())
+    }
+    #[must_use]
     pub fn eye_position(&self) -> Point3<f32> {
-        self.view.eye
+        self.view.eye()
+    }
+    // Pixel coordinates with the origin at the top left corner, None when behind the camera
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn world_to_screen(&self, world: Point3<f32>, viewport: Dimensions) -> Option<(f32, f32)> {
+        let clip = self.get_camera_matrix() * world.to_homogeneous();
+        if clip.w <= f32::EPSILON {
+            return None;
+        }
+        let ndc_x = clip.x / clip.w;
+        let ndc_y = clip.y / clip.w;
+        // NDC y points up while pixel rows go down
+        Some((
+            ndc_x.mul_add(0.5, 0.5) * viewport.width as f32,
+            ndc_y.mul_add(-0.5, 0.5) * viewport.height as f32,
+        ))
+    }
+    #[must_use]
+    pub fn near(&self) -> f32 {
+        self.projection.near()
+    }
+    #[must_use]
+    pub fn far(&self) -> f32 {
+        self.projection.far()
+    }
+    pub fn set_near(&mut self, near: f32) {
+        self.projection.set_near(near);
+        self.update_projection_cache();
+    }
+    pub fn set_far(&mut self, far: f32) {
+        self.projection.set_far(far);
+        self.update_projection_cache();
+    }
+    #[must_use]
+    pub fn zoom(&self) -> f32 {
+        self.projection.zoom()
+    }
+    // Only orthogonal projections react to the zoom factor
+    pub fn set_zoom(&mut self, zoom: f32) {
+        self.projection.set_zoom(zoom);
+        self.update_projection_cache();
+    }
+    #[must_use]
+    pub fn fovy(&self) -> Option<f32> {
+        self.projection.fovy()
+    }
+    // Only perspective projections have a field of view
+    pub fn set_fovy(&mut self, fovy: f32) {
+        self.projection.set_fovy(fovy);
+        self.update_projection_cache();
+    }
+    // Vertigo effect: changes the field of view while moving the eye along its forward axis, so
+    // the plane at subject_distance in front of the eye keeps the same size on screen. Its
+    // visible height 2 * distance * tan(fovy / 2) is preserved. Without perspective, nothing
+    // changes.
+    pub fn dolly_zoom(&mut self, target_fovy: f32, subject_distance: f32) {
+        let Some(fovy) = self.fovy() else {
+            return;
+        };
+        self.set_fovy(target_fovy);
+        let target_fovy = self.fovy().unwrap_or(fovy);
+        let distance = subject_distance * (fovy / 2.0).tan() / (target_fovy / 2.0).tan();
+        self.move_z(subject_distance - distance);
+    }
+    // Tightest planes around a bounding sphere of the scene, to make the most of depth precision
+    pub fn fit_near_far(&mut self, center: Point3<f32>, radius: f32, min_near: f32) {
+        let forward = self.view.forward();
+        let distance = (center - self.view.eye()).dot(forward);
+        let near = (distance - radius).max(min_near);
+        let far = (distance + radius).max(near + min_near);
+        self.projection.set_near(near);
+        self.projection.set_far(far);
+        self.update_projection_cache();
     }
     pub fn move_z(&mut self, val: f32) {
-        self.view.move_z(val, false);
+        self.view.move_z(val);
         self.update_view_cache();
     }
     pub fn move_x(&mut self, val: f32) {
-        self.view.move_x(val, false);
+        self.view.move_x(val);
         self.update_view_cache();
     }
     pub fn move_y(&mut self, val: f32) {
-        self.view.move_y(val, false);
+        self.view.move_y(val);
         self.update_view_cache();
     }
     pub fn pan(&mut self, val: f32) {
@@ -259,16 +598,52 @@ impl Camera {
     }
 }
 
+// Planes of the volume a camera matrix projects into the WebGPU clip space, depth going
+// from 0 to 1. Their normals point inside the volume.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frustum {
+    planes: [Vector4<f32>; 6],
+}
+
+impl Frustum {
+    #[must_use]
+    pub fn from_matrix(camera_matrix: Matrix4<f32>) -> Self {
+        let [x, y, z, w] = [0, 1, 2, 3].map(|index| camera_matrix.row(index));
+        Self {
+            planes: [w + x, w - x, w + y, w - y, z, w - z],
+        }
+    }
+    // Conservative: a box near a corner of the frustum can be reported as intersecting
+    #[must_use]
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        self.planes.iter().all(|plane| {
+            let pick = |normal: f32, min: f32, max: f32| if normal >= 0.0 { max } else { min };
+            let farthest = Vector4::new(
+                pick(plane.x, aabb.min.x, aabb.max.x),
+                pick(plane.y, aabb.min.y, aabb.max.y),
+                pick(plane.z, aabb.min.z, aabb.max.z),
+                1.0,
+            );
+            plane.dot(farthest) >= 0.0
+        })
+    }
+}
+
 pub struct InteractiveCamera {
     pub controled_camera: Camera,
     enabled_keys: BTreeSet<KeyCode>,
     key_speed: f32,
     rotation_speed: f32,
+    zoom_speed: f32,
 }
 
 impl InteractiveCamera {
     const DEFAULT_KEY_SPEED: f32 = 0.03;
     const DEFAULT_ROTATION_SPEED: f32 = 1.0 / 500.0;
+    // Distance moved along the forward axis for each line scrolled
+    const DEFAULT_ZOOM_SPEED: f32 = 0.5;
+    // Pixel deltas of trackpads are converted to lines, so both devices zoom alike
+    const PIXELS_PER_LINE: f64 = 40.0;
     const SPEED_MULTIPLICATOR: f32 = 10.0;
 
     #[must_use]
@@ -278,9 +653,14 @@ impl InteractiveCamera {
             enabled_keys: BTreeSet::new(),
             key_speed: Self::DEFAULT_KEY_SPEED,
             rotation_speed: Self::DEFAULT_ROTATION_SPEED,
+            zoom_speed: Self::DEFAULT_ZOOM_SPEED,
         }
     }
 
+    pub fn set_zoom_speed(&mut self, zoom_speed: f32) {
+        self.zoom_speed = zoom_speed;
+    }
+
     #[must_use]
     pub fn get_camera_matrix(&self) -> Matrix4<f32> {
         self.controled_camera.get_camera_matrix()
@@ -299,9 +679,15 @@ impl InteractiveCamera {
                 self.controled_camera
                     .tilt(delta.1 as f32 * self.rotation_speed);
             }
-            DeviceEvent::MouseWheel {
-                delta: _scroll_delta,
-            } => {}
+            DeviceEvent::MouseWheel { delta } => {
+                let lines = match delta {
+                    MouseScrollDelta::LineDelta(_, lines) => *lines,
+                    MouseScrollDelta::PixelDelta(position) => {
+                        (position.y / Self::PIXELS_PER_LINE) as f32
+                    }
+                };
+                self.controled_camera.move_z(lines * self.zoom_speed);
+            }
             _ => {}
         }
     }
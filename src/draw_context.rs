@@ -23,27 +23,55 @@ SOFTWARE.
 */
 
 use std::array;
-use std::collections::{BTreeMap, HashSet};
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell, RefMut};
+use std::collections::{BTreeMap, HashMap, HashSet};
+#[cfg(not(target_arch = "wasm32"))]
+use std::fs;
 use std::marker::PhantomData;
+use std::num::NonZeroU32;
+use std::ops::Range;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
+use std::path::PathBuf;
 use std::rc::Rc;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
 
 use anyhow::{Ok, anyhow, bail};
+
 use bytemuck::NoUninit;
-use log::debug;
+use log::{debug, warn};
+use web_time::Instant;
 use wgpu::util::{BufferInitDescriptor, DeviceExt};
 use wgpu::{
     DepthBiasState, PipelineCompilationOptions, PipelineLayoutDescriptor, StencilState,
     SurfaceConfiguration, Texture,
 };
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::event::WindowEvent;
 use winit::window::Window;
 
+use crate::picking::PickTarget;
+use crate::render_loop::{FrameBudget, FrameStats, RedrawHandle, TimeController};
+use crate::shadows::{ShadowCaster, ShadowMap};
+use crate::support::background::{Background, GradientRenderer};
+use crate::support::debug_draw::{DebugDraw, DebugDrawRenderer};
+use crate::support::render_texture::RenderTexture;
+use crate::support::staging_batch::StagingBatch;
+use crate::textures::{SampledDepth, Texture2D};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Dimensions {
     pub width: u32,
     pub height: u32,
 }
 
 impl Dimensions {
+    #[must_use]
+    pub fn new(width: u32, height: u32) -> Self {
+        Self { width, height }
+    }
     #[must_use]
     #[allow(clippy::cast_precision_loss)]
     pub fn surface_ratio(&self) -> f32 {
@@ -55,6 +83,83 @@ impl Dimensions {
     }
 }
 
+impl From<(u32, u32)> for Dimensions {
+    fn from((width, height): (u32, u32)) -> Self {
+        Self { width, height }
+    }
+}
+
+impl From<PhysicalSize<u32>> for Dimensions {
+    fn from(size: PhysicalSize<u32>) -> Self {
+        Self {
+            width: size.width,
+            height: size.height,
+        }
+    }
+}
+
+impl From<Dimensions> for (u32, u32) {
+    fn from(dimensions: Dimensions) -> Self {
+        (dimensions.width, dimensions.height)
+    }
+}
+
+// Area of the render target in pixels and the depth range the NDC depth is mapped to, e.g.
+// 0.0..0.1 to keep a gizmo in front of the scene without disabling the depth test
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub min_depth: f32,
+    pub max_depth: f32,
+}
+
+impl Viewport {
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn full(target: Dimensions) -> Self {
+        Self {
+            x: 0.0,
+            y: 0.0,
+            width: target.width as f32,
+            height: target.height as f32,
+            min_depth: 0.0,
+            max_depth: 1.0,
+        }
+    }
+    #[must_use]
+    pub fn with_rect(self, x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+            ..self
+        }
+    }
+    #[must_use]
+    pub fn with_depth_range(self, min_depth: f32, max_depth: f32) -> Self {
+        Self {
+            min_depth,
+            max_depth,
+            ..self
+        }
+    }
+    // Also used to restore the viewport of the target after a drawable with its own viewport
+    pub fn apply(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        render_pass.set_viewport(
+            self.x,
+            self.y,
+            self.width,
+            self.height,
+            self.min_depth,
+            self.max_depth,
+        );
+    }
+}
+
 enum DrawMode {
     Direct {
         vertex_count: u32,
@@ -72,11 +177,36 @@ pub enum DrawModeParams<'a> {
 }
 
 pub enum IndexData<'a> {
-    U32(&'a [u32]),
-    U16(&'a [u16]),
+    U32(Cow<'a, [u32]>),
+    U16(Cow<'a, [u16]>),
 }
 
 impl IndexData<'_> {
+    // Chooses the smallest index format able to address all the indices
+    #[must_use]
+    pub fn from_u32_auto(indices: &[u32]) -> IndexData<'static> {
+        if indices.iter().all(|&index| u16::try_from(index).is_ok()) {
+            IndexData::u16_from_u32(indices)
+        } else {
+            IndexData::U32(Cow::Owned(indices.to_vec()))
+        }
+    }
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn u16_from_u32(indices: &[u32]) -> IndexData<'static> {
+        IndexData::U16(
+            indices
+                .iter()
+                .map(|&index| {
+                    debug_assert!(
+                        u16::try_from(index).is_ok(),
+                        "Index {index} does not fit in u16"
+                    );
+                    index as u16
+                })
+                .collect(),
+        )
+    }
     #[must_use]
     pub fn format(&self) -> wgpu::IndexFormat {
         match self {
@@ -92,6 +222,13 @@ impl IndexData<'_> {
         }
     }
     #[must_use]
+    pub fn to_u32(&self) -> Vec<u32> {
+        match self {
+            IndexData::U32(data) => data.to_vec(),
+            IndexData::U16(data) => data.iter().map(|&index| u32::from(index)).collect(),
+        }
+    }
+    #[must_use]
     pub fn data(&self) -> &[u8] {
         match self {
             IndexData::U32(data) => bytemuck::cast_slice(data),
@@ -122,6 +259,7 @@ impl_uniform!(f32, u32, i32);
 impl_uniform!([f32; 2], [f32; 3], [f32; 4]);
 impl_uniform!([u32; 2], [u32; 3], [u32; 4]);
 impl_uniform!([i32; 2], [i32; 3], [i32; 4]);
+impl_uniform!([[f32; 4]; 2], [[f32; 4]; 3]);
 impl_uniform!([[f32; 4]; 4], [[u32; 4]; 4], [[i32; 4]; 4]);
 
 impl UnitformType for [[f32; 3]; 3] {
@@ -134,7 +272,8 @@ impl UnitformType for [[f32; 3]; 3] {
 pub struct Uniform<T> {
     value: T,
     buffer: wgpu::Buffer,
-    queue: Rc<wgpu::Queue>,
+    queue: Arc<wgpu::Queue>,
+    staging_batch: Arc<StagingBatch>,
 }
 
 impl<T: UnitformType> Uniform<T> {
@@ -146,11 +285,12 @@ impl<T: UnitformType> Uniform<T> {
                 contents: bytemuck::cast_slice(&[value.apply_alignment()]),
                 usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::UNIFORM,
             });
-        let queue = Rc::clone(&context.queue);
+        let queue = Arc::clone(&context.queue);
         Self {
             value,
             buffer,
             queue,
+            staging_batch: Arc::clone(&context.staging_batch),
         }
     }
     pub fn read_uniform(&self) -> &T {
@@ -158,7 +298,8 @@ impl<T: UnitformType> Uniform<T> {
     }
     pub fn write_uniform(&mut self, data: T) {
         self.value = data;
-        self.queue.write_buffer(
+        self.staging_batch.write_buffer(
+            &self.queue,
             &self.buffer,
             0 as wgpu::BufferAddress,
             bytemuck::cast_slice(&[self.value.apply_alignment()]),
@@ -166,6 +307,92 @@ impl<T: UnitformType> Uniform<T> {
     }
 }
 
+// Uniform written every frame: each frame in flight gets its own slot of the buffer, so
+// writing the value of a frame does not race with the GPU still reading a previous one
+pub struct FrameUniform<T> {
+    value: T,
+    buffer: wgpu::Buffer,
+    queue: Arc<wgpu::Queue>,
+    frame_index: Rc<Cell<u64>>,
+    slot_stride: u32,
+    dynamic_offset: Arc<AtomicU32>,
+    staging_batch: Arc<StagingBatch>,
+}
+
+impl<T: UnitformType> FrameUniform<T> {
+    pub const FRAMES_IN_FLIGHT: u32 = 3;
+
+    pub fn new(context: &DrawContext, value: T) -> Self {
+        let slot_size =
+            u32::try_from(size_of::<T::AlignedType>()).expect("Uniform size should fit in u32");
+        let slot_stride =
+            slot_size.next_multiple_of(context.device.limits().min_uniform_buffer_offset_alignment);
+        let buffer = context.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Frame Uniform Buffer"),
+            size: wgpu::BufferAddress::from(slot_stride * Self::FRAMES_IN_FLIGHT),
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::UNIFORM,
+            mapped_at_creation: false,
+        });
+        let frame_uniform = Self {
+            value,
+            buffer,
+            queue: Arc::clone(&context.queue),
+            frame_index: Rc::clone(&context.frame_index),
+            slot_stride,
+            dynamic_offset: Arc::new(AtomicU32::new(0)),
+            staging_batch: Arc::clone(&context.staging_batch),
+        };
+        frame_uniform.write_current_slot();
+        frame_uniform
+    }
+    pub fn read_uniform(&self) -> &T {
+        &self.value
+    }
+    #[must_use]
+    pub fn current_slot(&self) -> u32 {
+        self.dynamic_offset.load(Ordering::Relaxed) / self.slot_stride
+    }
+    pub fn write_uniform(&mut self, data: T) {
+        self.value = data;
+        self.write_current_slot();
+    }
+    #[allow(clippy::cast_possible_truncation)]
+    fn write_current_slot(&self) {
+        let slot = (self.frame_index.get() % u64::from(Self::FRAMES_IN_FLIGHT)) as u32;
+        let offset = slot * self.slot_stride;
+        self.staging_batch.write_buffer(
+            &self.queue,
+            &self.buffer,
+            wgpu::BufferAddress::from(offset),
+            bytemuck::cast_slice(&[self.value.apply_alignment()]),
+        );
+        self.dynamic_offset.store(offset, Ordering::Relaxed);
+    }
+}
+
+impl<T> AsBindingResource for FrameUniform<T>
+where
+    T: UnitformType,
+{
+    fn binding_resource(&self) -> wgpu::BindingResource<'_> {
+        wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+            buffer: &self.buffer,
+            offset: 0,
+            size: wgpu::BufferSize::new(size_of::<T::AlignedType>() as wgpu::BufferAddress),
+        })
+    }
+    fn binding_type(&self) -> wgpu::BindingType {
+        wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Uniform,
+            has_dynamic_offset: true,
+            min_binding_size: None,
+        }
+    }
+    fn dynamic_offset(&self) -> Option<Arc<AtomicU32>> {
+        Some(Arc::clone(&self.dynamic_offset))
+    }
+}
+
 pub struct BindingSlot<'a> {
     pub bind_group: u32,
     pub binding: u32,
@@ -173,15 +400,23 @@ pub struct BindingSlot<'a> {
 }
 
 pub trait AsBindingResource {
-    fn binding_resource(&self) -> wgpu::BindingResource;
+    fn binding_resource(&self) -> wgpu::BindingResource<'_>;
     fn binding_type(&self) -> wgpu::BindingType;
+    // Offset to apply when setting the bind group, for bindings with a dynamic offset
+    fn dynamic_offset(&self) -> Option<Arc<AtomicU32>> {
+        None
+    }
+    // Number of elements for bindings of arrays of resources
+    fn count(&self) -> Option<NonZeroU32> {
+        None
+    }
 }
 
 impl<T> AsBindingResource for Uniform<T>
 where
     T: UnitformType,
 {
-    fn binding_resource(&self) -> wgpu::BindingResource {
+    fn binding_resource(&self) -> wgpu::BindingResource<'_> {
         self.buffer.as_entire_binding()
     }
     fn binding_type(&self) -> wgpu::BindingType {
@@ -193,29 +428,39 @@ where
     }
 }
 
+// Values are stored with the padding the WGSL layout rules require, e.g. a mat3x3 column
+// takes the room of a vec4. The padding is never visible through the public API.
 pub trait StorageBufferType: NoUninit {
     type AlignedType: NoUninit;
     fn apply_alignment(&self) -> Self::AlignedType;
+    fn remove_alignment(aligned: &Self::AlignedType) -> Self;
 }
 impl StorageBufferType for [[f32; 3]; 3] {
     type AlignedType = [[f32; 4]; 3];
     fn apply_alignment(&self) -> Self::AlignedType {
         array::from_fn(|i| [self[i][0], self[i][1], self[i][2], 0.])
     }
+    fn remove_alignment(aligned: &Self::AlignedType) -> Self {
+        array::from_fn(|i| [aligned[i][0], aligned[i][1], aligned[i][2]])
+    }
 }
 impl StorageBufferType for [[f32; 4]; 4] {
     type AlignedType = [[f32; 4]; 4];
     fn apply_alignment(&self) -> Self::AlignedType {
         *self
     }
+    fn remove_alignment(aligned: &Self::AlignedType) -> Self {
+        *aligned
+    }
 }
 
 #[derive(Clone)]
 pub struct StorageBuffer<T: StorageBufferType> {
     pub(crate) count: usize,
     pub(crate) remote_buffer: Arc<wgpu::Buffer>,
-    pub local_buffer: Vec<T::AlignedType>, // FIXME Should I avoid it being public?
-    queue: Rc<wgpu::Queue>,
+    local_buffer: Vec<T::AlignedType>,
+    queue: Arc<wgpu::Queue>,
+    staging_batch: Arc<StagingBatch>,
 }
 
 impl<T: StorageBufferType> StorageBuffer<T> {
@@ -225,7 +470,8 @@ impl<T: StorageBufferType> StorageBuffer<T> {
             .map(StorageBufferType::apply_alignment)
             .collect();
         Self {
-            queue: Rc::clone(&context.queue),
+            queue: Arc::clone(&context.queue),
+            staging_batch: Arc::clone(&context.staging_batch),
             count: data_init.len(),
             remote_buffer: Arc::new(context.device.create_buffer_init(&BufferInitDescriptor {
                 label: Some("Storage Buffer"),
@@ -236,9 +482,78 @@ impl<T: StorageBufferType> StorageBuffer<T> {
         }
     }
 
+    #[must_use]
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<T> {
+        self.local_buffer
+            .get(index)
+            .map(StorageBufferType::remove_alignment)
+    }
+
+    // Only uploads the changed value, use start_write to change many of them
+    pub fn set(&mut self, index: usize, value: T) {
+        let aligned = value.apply_alignment();
+        self.local_buffer[index] = aligned;
+        self.staging_batch.write_buffer(
+            &self.queue,
+            &self.remote_buffer,
+            (index * size_of::<T::AlignedType>()) as wgpu::BufferAddress,
+            bytemuck::bytes_of(&aligned),
+        );
+    }
+
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        usize::try_from(self.remote_buffer.size()).expect("Buffer size should fit in usize")
+            / size_of::<T::AlignedType>()
+    }
+
+    // Returns true if the GPU buffer was reallocated, in which case the bind groups
+    // using it must be rebuilt
+    pub fn resize(&mut self, context: &DrawContext, new_count: usize, value: T) -> bool {
+        let old_count = self.count;
+        self.local_buffer.resize(new_count, value.apply_alignment());
+        self.count = new_count;
+        if new_count <= self.capacity() {
+            // The GPU buffer still holds the values dropped by a previous shrink
+            if new_count > old_count {
+                self.staging_batch.write_buffer(
+                    &self.queue,
+                    &self.remote_buffer,
+                    (old_count * size_of::<T::AlignedType>()) as wgpu::BufferAddress,
+                    bytemuck::cast_slice(&self.local_buffer[old_count..]),
+                );
+            }
+            return false;
+        }
+        let capacity = new_count.max(2 * self.capacity());
+        let mut contents = self.local_buffer.clone();
+        contents.resize(capacity, value.apply_alignment());
+        self.remote_buffer = Arc::new(context.device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("Storage Buffer"),
+            contents: bytemuck::cast_slice(&contents),
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::STORAGE,
+        }));
+        true
+    }
+
     pub fn start_write(&mut self) -> StorageBufferWriteGuard<'_, T> {
         StorageBufferWriteGuard {
-            queue: Rc::clone(&self.queue),
+            queue: Arc::clone(&self.queue),
             storage_buffer: self,
         }
     }
@@ -248,11 +563,9 @@ impl<T> AsBindingResource for StorageBuffer<T>
 where
     T: StorageBufferType,
 {
-    #[must_use]
-    fn binding_resource(&self) -> wgpu::BindingResource {
+    fn binding_resource(&self) -> wgpu::BindingResource<'_> {
         self.remote_buffer.as_entire_binding()
     }
-    #[must_use]
     fn binding_type(&self) -> wgpu::BindingType {
         wgpu::BindingType::Buffer {
             ty: wgpu::BufferBindingType::Storage { read_only: true },
@@ -262,9 +575,160 @@ where
     }
 }
 
+pub struct TextureBinding {
+    view: wgpu::TextureView,
+    sample_type: wgpu::TextureSampleType,
+    view_dimension: wgpu::TextureViewDimension,
+    multisampled: bool,
+}
+
+impl TextureBinding {
+    // Only the depth of a combined depth stencil texture is bound, as a view has a single aspect
+    #[must_use]
+    pub fn new(texture: &wgpu::Texture, sample_type: wgpu::TextureSampleType) -> Self {
+        Self {
+            view: texture.create_view(&wgpu::TextureViewDescriptor {
+                aspect: if texture.format().is_combined_depth_stencil_format() {
+                    wgpu::TextureAspect::DepthOnly
+                } else {
+                    wgpu::TextureAspect::All
+                },
+                ..Default::default()
+            }),
+            sample_type,
+            view_dimension: wgpu::TextureViewDimension::D2,
+            multisampled: texture.sample_count() > 1,
+        }
+    }
+
+    // Binds the 6 layers of the texture as a cubemap
+    #[must_use]
+    pub fn new_cube(texture: &wgpu::Texture, sample_type: wgpu::TextureSampleType) -> Self {
+        Self {
+            view: texture.create_view(&wgpu::TextureViewDescriptor {
+                dimension: Some(wgpu::TextureViewDimension::Cube),
+                ..Default::default()
+            }),
+            sample_type,
+            view_dimension: wgpu::TextureViewDimension::Cube,
+            multisampled: false,
+        }
+    }
+}
+
+impl AsBindingResource for TextureBinding {
+    fn binding_resource(&self) -> wgpu::BindingResource<'_> {
+        wgpu::BindingResource::TextureView(&self.view)
+    }
+    fn binding_type(&self) -> wgpu::BindingType {
+        wgpu::BindingType::Texture {
+            sample_type: self.sample_type,
+            view_dimension: self.view_dimension,
+            multisampled: self.multisampled,
+        }
+    }
+}
+
+pub struct Sampler {
+    sampler: wgpu::Sampler,
+    binding_type: wgpu::SamplerBindingType,
+}
+
+impl Sampler {
+    #[must_use]
+    pub fn new(context: &DrawContext, descriptor: &wgpu::SamplerDescriptor) -> Self {
+        let is_filtering = [
+            descriptor.mag_filter,
+            descriptor.min_filter,
+            descriptor.mipmap_filter,
+        ]
+        .contains(&wgpu::FilterMode::Linear);
+        let binding_type = if descriptor.compare.is_some() {
+            wgpu::SamplerBindingType::Comparison
+        } else if is_filtering {
+            wgpu::SamplerBindingType::Filtering
+        } else {
+            wgpu::SamplerBindingType::NonFiltering
+        };
+        Self {
+            sampler: context.device.create_sampler(descriptor),
+            binding_type,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SamplerConfig {
+    pub mag_filter: wgpu::FilterMode,
+    pub min_filter: wgpu::FilterMode,
+    pub mipmap_filter: wgpu::FilterMode,
+    pub address_mode_u: wgpu::AddressMode,
+    pub address_mode_v: wgpu::AddressMode,
+    pub address_mode_w: wgpu::AddressMode,
+    // Between 1 and 16, values above 1 require linear filtering everywhere
+    pub anisotropy_clamp: u16,
+}
+
+impl Default for SamplerConfig {
+    fn default() -> Self {
+        Self {
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            anisotropy_clamp: 1,
+        }
+    }
+}
+
+impl SamplerConfig {
+    // Checked before reaching wgpu, which would otherwise raise an uncaptured validation error
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if !(1..=16).contains(&self.anisotropy_clamp) {
+            bail!(
+                "Anisotropy clamp should be between 1 and 16, got {}",
+                self.anisotropy_clamp
+            );
+        }
+        let is_linear = [self.mag_filter, self.min_filter, self.mipmap_filter]
+            .iter()
+            .all(|filter| *filter == wgpu::FilterMode::Linear);
+        if self.anisotropy_clamp > 1 && !is_linear {
+            bail!("Anisotropic filtering requires linear mag, min and mipmap filters");
+        }
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn descriptor(&self) -> wgpu::SamplerDescriptor<'static> {
+        wgpu::SamplerDescriptor {
+            label: Some("Sampler"),
+            address_mode_u: self.address_mode_u,
+            address_mode_v: self.address_mode_v,
+            address_mode_w: self.address_mode_w,
+            mag_filter: self.mag_filter,
+            min_filter: self.min_filter,
+            mipmap_filter: self.mipmap_filter,
+            anisotropy_clamp: self.anisotropy_clamp,
+            ..Default::default()
+        }
+    }
+}
+
+impl AsBindingResource for Sampler {
+    fn binding_resource(&self) -> wgpu::BindingResource<'_> {
+        wgpu::BindingResource::Sampler(&self.sampler)
+    }
+    fn binding_type(&self) -> wgpu::BindingType {
+        wgpu::BindingType::Sampler(self.binding_type)
+    }
+}
+
 pub struct StorageBufferWriteGuard<'a, T: StorageBufferType> {
-    queue: Rc<wgpu::Queue>,
-    pub storage_buffer: &'a mut StorageBuffer<T>, // FIXME Should I avoid it being public?
+    queue: Arc<wgpu::Queue>,
+    storage_buffer: &'a mut StorageBuffer<T>,
 }
 
 impl<T: StorageBufferType> StorageBufferWriteGuard<'_, T> {
@@ -275,17 +739,50 @@ impl<T: StorageBufferType> StorageBufferWriteGuard<'_, T> {
     pub fn count(&self) -> usize {
         self.storage_buffer.count
     }
+    #[must_use]
+    pub fn get_value(&self, index: usize) -> Option<T> {
+        self.storage_buffer.get(index)
+    }
     pub fn set_value(&mut self, index: usize, value: T) {
         self.storage_buffer.local_buffer[index] = value.apply_alignment();
     }
-    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T::AlignedType> {
-        self.storage_buffer.local_buffer.iter_mut()
+    pub fn update_each<F>(&mut self, mut f: F)
+    where
+        F: FnMut(usize, &mut T),
+    {
+        for (index, aligned) in self.storage_buffer.local_buffer.iter_mut().enumerate() {
+            let mut value = T::remove_alignment(aligned);
+            f(index, &mut value);
+            *aligned = value.apply_alignment();
+        }
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn par_update_each<F>(&mut self, f: F)
+    where
+        F: Fn(usize, &mut T) + Send + Sync,
+        T::AlignedType: Send,
+    {
+        use rayon::iter::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
+        self.storage_buffer
+            .local_buffer
+            .par_iter_mut()
+            .enumerate()
+            .for_each(|(index, aligned)| {
+                let mut value = T::remove_alignment(aligned);
+                f(index, &mut value);
+                *aligned = value.apply_alignment();
+            });
+    }
+    // Padded values, for the instance groups updating several buffers in the same pass
+    pub(crate) fn aligned_values_mut(&mut self) -> &mut [T::AlignedType] {
+        &mut self.storage_buffer.local_buffer
     }
 }
 
 impl<T: StorageBufferType> Drop for StorageBufferWriteGuard<'_, T> {
     fn drop(&mut self) {
-        self.queue.write_buffer(
+        self.storage_buffer.staging_batch.write_buffer(
+            &self.queue,
             &self.storage_buffer.remote_buffer,
             0 as wgpu::BufferAddress,
             bytemuck::cast_slice(&self.storage_buffer.local_buffer),
@@ -321,6 +818,96 @@ impl<T: InstancesAttributeType> InstancesAttribute<T> {
     }
 }
 
+// Shader modules, pipeline settings and bindings shared by several objects, e.g. the camera.
+// Pipelines built from the material are cached, so the drawables with the same vertex layout
+// and bindings share them.
+pub struct Material<'a> {
+    vtx_shader_module: &'a wgpu::ShaderModule,
+    frg_shader_module: &'a wgpu::ShaderModule,
+    blend_option: Option<wgpu::BlendState>,
+    cull_mode: Option<wgpu::Face>,
+    depth_bias: Option<wgpu::DepthBiasState>,
+    depth_prepass: bool,
+    binding_slots: Vec<BindingSlot<'a>>,
+    pipelines: RefCell<HashMap<PipelineKey, CachedPipelines>>,
+}
+
+type CachedPipelines = (wgpu::RenderPipeline, Option<wgpu::RenderPipeline>);
+
+#[derive(PartialEq, Eq, Hash)]
+struct PipelineKey {
+    vertex_buffers: Vec<(
+        wgpu::BufferAddress,
+        wgpu::VertexStepMode,
+        Vec<wgpu::VertexAttribute>,
+    )>,
+    bind_group_layouts: Vec<Vec<wgpu::BindGroupLayoutEntry>>,
+    color_target_format: wgpu::TextureFormat,
+    sample_count: u32,
+    blend_option: Option<wgpu::BlendState>,
+    cull_mode: Option<wgpu::Face>,
+    fill_depth_bias: wgpu::DepthBiasState,
+    depth_prepass: bool,
+    depth_write: bool,
+    stencil_state: wgpu::StencilState,
+    topology: wgpu::PrimitiveTopology,
+    polygon_mode: wgpu::PolygonMode,
+    entry_points: (Option<String>, Option<String>),
+}
+
+impl<'a> Material<'a> {
+    #[must_use]
+    pub fn new(
+        vtx_shader_module: &'a wgpu::ShaderModule,
+        frg_shader_module: &'a wgpu::ShaderModule,
+    ) -> Self {
+        Self {
+            vtx_shader_module,
+            frg_shader_module,
+            blend_option: None,
+            cull_mode: Some(wgpu::Face::Back),
+            depth_bias: None,
+            depth_prepass: false,
+            binding_slots: Vec::new(),
+            pipelines: RefCell::new(HashMap::new()),
+        }
+    }
+    pub fn set_blend_option(&mut self, blend_option: wgpu::BlendState) -> &mut Self {
+        self.blend_option = Some(blend_option);
+        self
+    }
+    pub fn set_cull_mode(&mut self, cull_mode: Option<wgpu::Face>) -> &mut Self {
+        self.cull_mode = cull_mode;
+        self
+    }
+    pub fn set_depth_bias(&mut self, depth_bias: wgpu::DepthBiasState) -> &mut Self {
+        self.depth_bias = Some(depth_bias);
+        self
+    }
+    pub fn set_depth_prepass(&mut self, enabled: bool) -> &mut Self {
+        self.depth_prepass = enabled;
+        self
+    }
+    pub fn add_binding_slot(&mut self, binding_slot: BindingSlot<'a>) -> &mut Self {
+        self.binding_slots.push(binding_slot);
+        self
+    }
+    #[must_use]
+    pub fn cached_pipeline_count(&self) -> usize {
+        self.pipelines.borrow().len()
+    }
+    fn cached_pipelines<F>(&self, key: PipelineKey, create_pipelines: F) -> CachedPipelines
+    where
+        F: FnOnce() -> CachedPipelines,
+    {
+        self.pipelines
+            .borrow_mut()
+            .entry(key)
+            .or_insert_with(create_pipelines)
+            .clone()
+    }
+}
+
 pub struct DrawableBuilder<'a> {
     context: &'a DrawContext,
     vtx_shader_module: &'a wgpu::ShaderModule,
@@ -332,17 +919,45 @@ pub struct DrawableBuilder<'a> {
     layouts: Vec<wgpu::VertexBufferLayout<'a>>,
     instance_count: u32,
     blend_option: Option<wgpu::BlendState>,
+    depth_prepass: bool,
+    shadow_caster: Option<ShadowCaster<'a>>,
+    pick_module: Option<&'a wgpu::ShaderModule>,
+    color_target_format: Option<wgpu::TextureFormat>,
+    sample_count: Option<u32>,
+    cull_mode: Option<wgpu::Face>,
+    depth_bias: Option<wgpu::DepthBiasState>,
+    depth_write: bool,
+    stencil_state: wgpu::StencilState,
+    topology: wgpu::PrimitiveTopology,
+    polygon_mode: wgpu::PolygonMode,
+    wireframe_module: Option<&'a wgpu::ShaderModule>,
+    // Kept to derive the edges of the wireframe overlay
+    triangle_indices: Vec<u32>,
+    dynamic_offsets: BTreeMap<u32, BTreeMap<u32, Arc<AtomicU32>>>,
     binding_groups:
         Vec<Option<BTreeMap<u32, (wgpu::BindingResource<'a>, wgpu::BindGroupLayoutEntry)>>>,
+    material: Option<&'a Material<'a>>,
+    vtx_entry_point: Option<&'a str>,
+    frg_entry_point: Option<&'a str>,
 }
 
 impl<'a> DrawableBuilder<'a> {
+    // Pushes the filled triangles slightly away, in depth buffer units
+    const WIREFRAME_FILL_DEPTH_BIAS: wgpu::DepthBiasState = wgpu::DepthBiasState {
+        constant: 2,
+        slope_scale: 1.0,
+        clamp: 0.0,
+    };
     pub fn new(
         context: &'a DrawContext,
         vtx_shader_module: &'a wgpu::ShaderModule,
         frg_shader_module: &'a wgpu::ShaderModule,
         draw_params: DrawModeParams,
     ) -> Self {
+        let triangle_indices = match &draw_params {
+            DrawModeParams::Direct { vertex_count } => (0..*vertex_count).collect(),
+            DrawModeParams::Indexed { index_data } => index_data.to_u32(),
+        };
         let draw_mode = match draw_params {
             DrawModeParams::Direct { vertex_count } => DrawMode::Direct { vertex_count },
             DrawModeParams::Indexed { index_data } => {
@@ -352,7 +967,7 @@ impl<'a> DrawableBuilder<'a> {
                         .create_buffer_init(&wgpu::util::BufferInitDescriptor {
                             label: Some("Index Buffer"),
                             contents: index_data.data(),
-                            usage: wgpu::BufferUsages::INDEX,
+                            usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST,
                         });
                 DrawMode::Indexed {
                     format: index_data.format(),
@@ -373,7 +988,47 @@ impl<'a> DrawableBuilder<'a> {
             instance_count: 1,
             draw_mode,
             blend_option: None,
+            depth_prepass: false,
+            shadow_caster: None,
+            pick_module: None,
+            color_target_format: None,
+            sample_count: None,
+            cull_mode: Some(wgpu::Face::Back),
+            depth_bias: None,
+            depth_write: true,
+            stencil_state: wgpu::StencilState::default(),
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            wireframe_module: None,
+            triangle_indices,
+            dynamic_offsets: BTreeMap::new(),
+            material: None,
+            vtx_entry_point: None,
+            frg_entry_point: None,
+        }
+    }
+    // Shaders, settings and bindings come from the material, whose pipelines are reused by the
+    // drawables with the same vertex layout and bindings
+    pub fn from_material(
+        context: &'a DrawContext,
+        material: &'a Material<'a>,
+        draw_params: DrawModeParams,
+    ) -> anyhow::Result<Self> {
+        let mut builder = Self::new(
+            context,
+            material.vtx_shader_module,
+            material.frg_shader_module,
+            draw_params,
+        );
+        builder.blend_option = material.blend_option;
+        builder.cull_mode = material.cull_mode;
+        builder.depth_bias = material.depth_bias;
+        builder.depth_prepass = material.depth_prepass;
+        for binding_slot in &material.binding_slots {
+            builder.add_binding_slot(binding_slot)?;
         }
+        builder.material = Some(material);
+        Ok(builder)
     }
     pub fn set_instance_count(&mut self, value: u32) -> &mut Self {
         self.instance_count = value;
@@ -383,20 +1038,121 @@ impl<'a> DrawableBuilder<'a> {
         self.blend_option = Some(blend_option);
         self
     }
-    pub fn add_binding_slot(
-        &mut self,
-        binding_slot: &BindingSlot<'a>,
-    ) -> Result<&mut Self, anyhow::Error> {
-        let bind_group_layout_entry = wgpu::BindGroupLayoutEntry {
-            binding: binding_slot.binding,
-            visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
-            ty: binding_slot.resource.binding_type(),
-            count: None,
+    pub fn set_depth_prepass(&mut self, enabled: bool) -> &mut Self {
+        self.depth_prepass = enabled;
+        self
+    }
+    // Needed when the module has several @vertex functions, otherwise the only one is used
+    pub fn set_vertex_entry(&mut self, entry_point: &'a str) -> &mut Self {
+        self.vtx_entry_point = Some(entry_point);
+        self
+    }
+    // Needed when the module has several @fragment functions, otherwise the only one is used
+    pub fn set_fragment_entry(&mut self, entry_point: &'a str) -> &mut Self {
+        self.frg_entry_point = Some(entry_point);
+        self
+    }
+    // By default, the drawable renders into the surface format
+    pub fn set_color_target_format(&mut self, format: wgpu::TextureFormat) -> &mut Self {
+        self.color_target_format = Some(format);
+        self
+    }
+    // By default, the drawable follows the multisample configuration of the context
+    pub fn set_sample_count(&mut self, sample_count: u32) -> &mut Self {
+        self.sample_count = Some(sample_count);
+        self
+    }
+    // Back faces are culled by default, None renders both sides
+    pub fn set_cull_mode(&mut self, cull_mode: Option<wgpu::Face>) -> &mut Self {
+        self.cull_mode = cull_mode;
+        self
+    }
+    // Useful for imported meshes whose winding is not consistent
+    pub fn set_double_sided(&mut self, double_sided: bool) -> &mut Self {
+        self.cull_mode = if double_sided {
+            None
+        } else {
+            Some(wgpu::Face::Back)
         };
-        let bind_group = binding_slot.bind_group as usize;
-        if bind_group >= self.binding_groups.len() {
-            self.binding_groups.resize(bind_group + 1, None);
-        }
+        self
+    }
+    pub fn set_depth_bias(&mut self, depth_bias: wgpu::DepthBiasState) -> &mut Self {
+        self.depth_bias = Some(depth_bias);
+        self
+    }
+    // Still depth tested, e.g. for transparent overlays drawn after the opaque geometry
+    pub fn set_depth_write(&mut self, enabled: bool) -> &mut Self {
+        self.depth_write = enabled;
+        self
+    }
+    // Requires a depth format with a stencil aspect, see DrawContextConfig. The reference
+    // value is given when rendering, with Drawable::render_with_stencil_reference.
+    pub fn set_stencil_state(
+        &mut self,
+        stencil_state: wgpu::StencilState,
+    ) -> anyhow::Result<&mut Self> {
+        let depth_format = self.context.depth_format();
+        if stencil_state.is_enabled() && !depth_format.has_stencil_aspect() {
+            bail!("Depth format {depth_format:?} has no stencil aspect");
+        }
+        self.stencil_state = stencil_state;
+        Ok(self)
+    }
+    // Only applies to the main pipeline. Line and Point need the POLYGON_MODE_LINE and
+    // POLYGON_MODE_POINT features, which the context enables when the adapter has them.
+    pub fn set_polygon_mode(
+        &mut self,
+        polygon_mode: wgpu::PolygonMode,
+    ) -> anyhow::Result<&mut Self> {
+        let required_feature = match polygon_mode {
+            wgpu::PolygonMode::Fill => wgpu::Features::empty(),
+            wgpu::PolygonMode::Line => wgpu::Features::POLYGON_MODE_LINE,
+            wgpu::PolygonMode::Point => wgpu::Features::POLYGON_MODE_POINT,
+        };
+        if !self.context.device.features().contains(required_feature) {
+            bail!(
+                "Polygon mode {polygon_mode:?} requires the {required_feature:?} feature, not supported by the device"
+            );
+        }
+        self.polygon_mode = polygon_mode;
+        Ok(self)
+    }
+    // Only applies to the main pipeline, the shadow, picking and wireframe passes expecting
+    // triangles
+    pub fn set_topology(&mut self, topology: wgpu::PrimitiveTopology) -> &mut Self {
+        self.topology = topology;
+        self
+    }
+    // Draws the edges of the triangles on top of the filled geometry, with the fragment entry
+    // point of the given module. Unless a depth bias is set, the filled triangles are pushed
+    // back so the lines do not z-fight with them.
+    pub fn set_wireframe_overlay(&mut self, frg_module: &'a wgpu::ShaderModule) -> &mut Self {
+        self.wireframe_module = Some(frg_module);
+        self
+    }
+    pub fn set_shadow_caster(&mut self, shadow_caster: ShadowCaster<'a>) -> &mut Self {
+        self.shadow_caster = Some(shadow_caster);
+        self
+    }
+    // Module with the vertex entry point of the picking pass, see PickTarget::SHADER_SNIPPET
+    pub fn set_pick_module(&mut self, pick_module: &'a wgpu::ShaderModule) -> &mut Self {
+        self.pick_module = Some(pick_module);
+        self
+    }
+    pub fn add_binding_slot(
+        &mut self,
+        binding_slot: &BindingSlot<'a>,
+    ) -> Result<&mut Self, anyhow::Error> {
+        let bind_group_layout_entry = wgpu::BindGroupLayoutEntry {
+            binding: binding_slot.binding,
+            visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+            ty: binding_slot.resource.binding_type(),
+            count: binding_slot.resource.count(),
+        };
+        let bind_group = binding_slot.bind_group as usize;
+        if bind_group >= self.binding_groups.len() {
+            self.binding_groups.resize(bind_group + 1, None);
+        }
         let to_store = (
             binding_slot.resource.binding_resource(),
             bind_group_layout_entry,
@@ -408,9 +1164,31 @@ impl<'a> DrawableBuilder<'a> {
             bindings.insert(binding_slot.binding, to_store);
             self.binding_groups[bind_group] = Some(bindings);
         }
+        if let Some(dynamic_offset) = binding_slot.resource.dynamic_offset() {
+            self.dynamic_offsets
+                .entry(binding_slot.bind_group)
+                .or_default()
+                .insert(binding_slot.binding, dynamic_offset);
+        }
         // TODO Ensure group and binding are not already used
         Ok(self)
     }
+    // One transform per instance, indexed in the shader with @builtin(instance_index), and as
+    // many instances drawn as there are transforms
+    pub fn set_instance_transforms(
+        &mut self,
+        bind_group: u32,
+        binding: u32,
+        transforms: &'a StorageBuffer<[[f32; 4]; 4]>,
+    ) -> Result<&mut Self, anyhow::Error> {
+        self.add_binding_slot(&BindingSlot {
+            bind_group,
+            binding,
+            resource: transforms,
+        })?;
+        self.instance_count = u32::try_from(transforms.count())?;
+        Ok(self)
+    }
     pub fn add_attribute<T>(
         &mut self,
         shader_location: u32,
@@ -481,6 +1259,7 @@ impl<'a> DrawableBuilder<'a> {
     pub fn build(self) -> Drawable {
         let mut bind_groups = BTreeMap::<u32, wgpu::BindGroup>::new();
         let mut bind_group_layouts = Vec::new();
+        let mut all_layout_entries = Vec::new();
         for (group_id, group) in self.binding_groups.into_iter().enumerate() {
             let group_id = u32::try_from(group_id).expect("Value should fit in u32");
             let mut bind_group_layout_entries = Vec::new();
@@ -511,28 +1290,13 @@ impl<'a> DrawableBuilder<'a> {
                 });
             bind_group_layouts.push(bind_group_layout);
             bind_groups.insert(group_id, bind_group);
+            all_layout_entries.push(bind_group_layout_entries);
         }
 
         let mut vertex_buffer_layouts = self.layouts;
         for (layout, attribute) in vertex_buffer_layouts.iter_mut().zip(self.attributes.iter()) {
             layout.attributes = attribute;
         }
-        let vertex_state = wgpu::VertexState {
-            module: self.vtx_shader_module,
-            entry_point: None,
-            buffers: &vertex_buffer_layouts,
-            compilation_options: PipelineCompilationOptions::default(),
-        };
-        let fragment_state = wgpu::FragmentState {
-            module: self.frg_shader_module,
-            entry_point: None,
-            targets: &[Some(wgpu::ColorTargetState {
-                format: self.context.surface_config.format,
-                blend: self.blend_option,
-                write_mask: wgpu::ColorWrites::ALL,
-            })],
-            compilation_options: PipelineCompilationOptions::default(),
-        };
         let pipeline_layout =
             self.context
                 .device
@@ -541,37 +1305,199 @@ impl<'a> DrawableBuilder<'a> {
                     bind_group_layouts: &bind_group_layouts.iter().collect::<Vec<_>>(), // Not sure if right order here
                     push_constant_ranges: &[],
                 });
-        let pipeline =
+        let color_target_format = self
+            .color_target_format
+            .unwrap_or(self.context.surface_config.format);
+        let sample_count = self
+            .sample_count
+            .unwrap_or_else(|| self.context.multisample_config.get_multisample_count());
+        let fill_depth_bias = match (self.depth_bias, self.wireframe_module) {
+            (Some(depth_bias), _) => depth_bias,
+            (None, Some(_)) => Self::WIREFRAME_FILL_DEPTH_BIAS,
+            (None, None) => DepthBiasState::default(),
+        };
+        let create_pipeline = |label, depth_write_enabled, depth_compare, write_mask| {
             self.context
                 .device
                 .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
                     cache: None,
-                    label: Some("Render Pipeline"),
+                    label: Some(label),
                     layout: Some(&pipeline_layout),
-                    vertex: vertex_state,
-                    fragment: Some(fragment_state),
+                    vertex: wgpu::VertexState {
+                        module: self.vtx_shader_module,
+                        entry_point: self.vtx_entry_point,
+                        buffers: &vertex_buffer_layouts,
+                        compilation_options: PipelineCompilationOptions::default(),
+                    },
+                    fragment: Some(wgpu::FragmentState {
+                        module: self.frg_shader_module,
+                        entry_point: self.frg_entry_point,
+                        targets: &[Some(wgpu::ColorTargetState {
+                            format: color_target_format,
+                            blend: self.blend_option,
+                            write_mask,
+                        })],
+                        compilation_options: PipelineCompilationOptions::default(),
+                    }),
                     primitive: wgpu::PrimitiveState {
-                        topology: wgpu::PrimitiveTopology::TriangleList,
+                        topology: self.topology,
                         strip_index_format: None,
                         front_face: wgpu::FrontFace::Ccw,
-                        cull_mode: Some(wgpu::Face::Back),
+                        cull_mode: self.cull_mode,
                         unclipped_depth: false,
-                        polygon_mode: wgpu::PolygonMode::Fill, // wgpu::PolygonMode::Line
+                        polygon_mode: self.polygon_mode,
                         conservative: false,
                     },
                     depth_stencil: Some(wgpu::DepthStencilState {
-                        format: wgpu::TextureFormat::Depth32Float,
-                        depth_write_enabled: true,
-                        depth_compare: wgpu::CompareFunction::LessEqual,
-                        stencil: StencilState::default(),
-                        bias: DepthBiasState::default(),
+                        format: self.context.depth_format(),
+                        depth_write_enabled,
+                        depth_compare,
+                        stencil: self.stencil_state.clone(),
+                        bias: fill_depth_bias,
                     }),
                     multisample: wgpu::MultisampleState {
-                        count: self.context.multisample_config.get_multisample_count(),
+                        count: sample_count,
                         ..Default::default()
                     },
                     multiview: None,
-                });
+                })
+        };
+        // With a depth pre-pass, the main pipeline only shades the fragments left by the pre-pass
+        let create_pipelines = || {
+            if self.depth_prepass {
+                (
+                    create_pipeline(
+                        "Render Pipeline",
+                        false,
+                        wgpu::CompareFunction::Equal,
+                        wgpu::ColorWrites::ALL,
+                    ),
+                    Some(create_pipeline(
+                        "Depth Prepass Pipeline",
+                        true,
+                        wgpu::CompareFunction::LessEqual,
+                        wgpu::ColorWrites::empty(),
+                    )),
+                )
+            } else {
+                (
+                    create_pipeline(
+                        "Render Pipeline",
+                        self.depth_write,
+                        wgpu::CompareFunction::LessEqual,
+                        wgpu::ColorWrites::ALL,
+                    ),
+                    None,
+                )
+            }
+        };
+        let (pipeline, depth_prepass_pipeline) = match self.material {
+            Some(material) => material.cached_pipelines(
+                PipelineKey {
+                    vertex_buffers: vertex_buffer_layouts
+                        .iter()
+                        .map(|layout| {
+                            (
+                                layout.array_stride,
+                                layout.step_mode,
+                                layout.attributes.to_vec(),
+                            )
+                        })
+                        .collect(),
+                    bind_group_layouts: all_layout_entries,
+                    color_target_format,
+                    sample_count,
+                    blend_option: self.blend_option,
+                    cull_mode: self.cull_mode,
+                    fill_depth_bias,
+                    depth_prepass: self.depth_prepass,
+                    depth_write: self.depth_write,
+                    stencil_state: self.stencil_state.clone(),
+                    topology: self.topology,
+                    polygon_mode: self.polygon_mode,
+                    entry_points: (
+                        self.vtx_entry_point.map(str::to_string),
+                        self.frg_entry_point.map(str::to_string),
+                    ),
+                },
+                create_pipelines,
+            ),
+            None => create_pipelines(),
+        };
+        let shadow_pass = self.shadow_caster.map(|shadow_caster| {
+            Self::build_shadow_pass(
+                self.context,
+                &shadow_caster,
+                &bind_group_layouts,
+                &vertex_buffer_layouts,
+                self.cull_mode,
+            )
+        });
+        let pick_pass = self.pick_module.map(|pick_module| {
+            Self::build_pick_pass(
+                self.context,
+                pick_module,
+                &bind_group_layouts,
+                &vertex_buffer_layouts,
+                self.cull_mode,
+            )
+        });
+        let wireframe_pass = self.wireframe_module.map(|wireframe_module| {
+            let edges = crate::primitives::mesh::triangle_edges(&self.triangle_indices);
+            let pipeline =
+                self.context
+                    .device
+                    .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                        cache: None,
+                        label: Some("Wireframe Pipeline"),
+                        layout: Some(&pipeline_layout),
+                        vertex: wgpu::VertexState {
+                            module: self.vtx_shader_module,
+                            entry_point: self.vtx_entry_point,
+                            buffers: &vertex_buffer_layouts,
+                            compilation_options: PipelineCompilationOptions::default(),
+                        },
+                        fragment: Some(wgpu::FragmentState {
+                            module: wireframe_module,
+                            entry_point: None,
+                            targets: &[Some(wgpu::ColorTargetState {
+                                format: color_target_format,
+                                blend: self.blend_option,
+                                write_mask: wgpu::ColorWrites::ALL,
+                            })],
+                            compilation_options: PipelineCompilationOptions::default(),
+                        }),
+                        primitive: wgpu::PrimitiveState {
+                            topology: wgpu::PrimitiveTopology::LineList,
+                            ..Default::default()
+                        },
+                        depth_stencil: Some(wgpu::DepthStencilState {
+                            format: self.context.depth_format(),
+                            depth_write_enabled: false,
+                            depth_compare: wgpu::CompareFunction::LessEqual,
+                            stencil: StencilState::default(),
+                            bias: DepthBiasState::default(),
+                        }),
+                        multisample: wgpu::MultisampleState {
+                            count: sample_count,
+                            ..Default::default()
+                        },
+                        multiview: None,
+                    });
+            let index_buffer =
+                self.context
+                    .device
+                    .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                        label: Some("Wireframe Index Buffer"),
+                        contents: bytemuck::cast_slice(&edges),
+                        usage: wgpu::BufferUsages::INDEX,
+                    });
+            WireframePass {
+                pipeline,
+                index_buffer,
+                index_count: u32::try_from(edges.len()).expect("Value should fit in u32"),
+            }
+        });
         let blend_color_opacity = wgpu::Color::WHITE;
 
         Drawable {
@@ -579,10 +1505,219 @@ impl<'a> DrawableBuilder<'a> {
             buffers: self.buffers,
             instance_count: self.instance_count,
             pipeline,
+            depth_prepass_pipeline,
+            shadow_pass,
+            pick_pass,
+            wireframe_pass,
+            bind_group_layouts,
             bind_groups,
+            dynamic_offsets: self
+                .dynamic_offsets
+                .into_iter()
+                .map(|(group_id, offsets)| (group_id, offsets.into_values().collect()))
+                .collect(),
             blend_color_opacity,
+            viewport: None,
+            sample_count,
         }
     }
+    fn build_shadow_pass(
+        context: &DrawContext,
+        shadow_caster: &ShadowCaster<'_>,
+        bind_group_layouts: &[wgpu::BindGroupLayout],
+        vertex_buffer_layouts: &[wgpu::VertexBufferLayout<'_>],
+        cull_mode: Option<wgpu::Face>,
+    ) -> ShadowPass {
+        let device = &context.device;
+        let shadow_map = shadow_caster.shadow_map;
+        let light_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Shadow Light Bind Group Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: ShadowMap::BINDING_LIGHT_VIEW_PROJ,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                ty: shadow_map.light_view_proj().binding_type(),
+                count: None,
+            }],
+        });
+        let light_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Shadow Light Bind Group"),
+            layout: &light_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: ShadowMap::BINDING_LIGHT_VIEW_PROJ,
+                resource: shadow_map.light_view_proj().binding_resource(),
+            }],
+        });
+        let (pipeline_layout, empty_bind_group) = Self::create_auxiliary_pipeline_layout(
+            context,
+            bind_group_layouts,
+            DrawContext::BIND_GROUP_INDEX_SHADOW,
+            &light_layout,
+        );
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            cache: None,
+            label: Some("Shadow Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: shadow_caster.vtx_module,
+                entry_point: None,
+                buffers: vertex_buffer_layouts,
+                compilation_options: PipelineCompilationOptions::default(),
+            },
+            fragment: None,
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode,
+                ..Default::default()
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: ShadowMap::DEPTH_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: StencilState::default(),
+                bias: ShadowMap::DEPTH_BIAS,
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+        ShadowPass {
+            pipeline,
+            light_bind_group,
+            empty_bind_group,
+        }
+    }
+
+    // Pipeline layout reusing the drawable groups before extra_group, groups the drawable does
+    // not use still need a layout and a bind group
+    fn create_auxiliary_pipeline_layout(
+        context: &DrawContext,
+        bind_group_layouts: &[wgpu::BindGroupLayout],
+        extra_group: u32,
+        extra_layout: &wgpu::BindGroupLayout,
+    ) -> (wgpu::PipelineLayout, wgpu::BindGroup) {
+        let device = &context.device;
+        let empty_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: None,
+            entries: &[],
+        });
+        let empty_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &empty_layout,
+            entries: &[],
+        });
+        let layouts = (0..=extra_group as usize)
+            .map(|group| {
+                if group == extra_group as usize {
+                    extra_layout
+                } else {
+                    bind_group_layouts.get(group).unwrap_or(&empty_layout)
+                }
+            })
+            .collect::<Vec<_>>();
+        let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: Some("Auxiliary Pipeline Layout"),
+            bind_group_layouts: &layouts,
+            push_constant_ranges: &[],
+        });
+        (pipeline_layout, empty_bind_group)
+    }
+
+    fn build_pick_pass(
+        context: &DrawContext,
+        pick_module: &wgpu::ShaderModule,
+        bind_group_layouts: &[wgpu::BindGroupLayout],
+        vertex_buffer_layouts: &[wgpu::VertexBufferLayout<'_>],
+        cull_mode: Option<wgpu::Face>,
+    ) -> PickPass {
+        let device = &context.device;
+        let object_id = Uniform::new(context, 0u32);
+        let object_id_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Pick Object Id Bind Group Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: PickTarget::BINDING_OBJECT_ID,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                ty: object_id.binding_type(),
+                count: None,
+            }],
+        });
+        let object_id_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Pick Object Id Bind Group"),
+            layout: &object_id_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: PickTarget::BINDING_OBJECT_ID,
+                resource: object_id.binding_resource(),
+            }],
+        });
+        let (pipeline_layout, empty_bind_group) = Self::create_auxiliary_pipeline_layout(
+            context,
+            bind_group_layouts,
+            DrawContext::BIND_GROUP_INDEX_PICKING,
+            &object_id_layout,
+        );
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            cache: None,
+            label: Some("Pick Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: pick_module,
+                entry_point: None,
+                buffers: vertex_buffer_layouts,
+                compilation_options: PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: pick_module,
+                entry_point: None,
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: PickTarget::FORMAT,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode,
+                ..Default::default()
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: PickTarget::DEPTH_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: StencilState::default(),
+                bias: DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+        PickPass {
+            pipeline,
+            object_id: Mutex::new(object_id),
+            object_id_bind_group,
+            empty_bind_group,
+        }
+    }
+}
+
+struct ShadowPass {
+    pipeline: wgpu::RenderPipeline,
+    light_bind_group: wgpu::BindGroup,
+    empty_bind_group: wgpu::BindGroup,
+}
+
+// Same vertices and bind groups as the filled geometry, drawn as a line list of its edges
+struct WireframePass {
+    pipeline: wgpu::RenderPipeline,
+    index_buffer: wgpu::Buffer,
+    index_count: u32,
+}
+
+struct PickPass {
+    pipeline: wgpu::RenderPipeline,
+    // Written right before drawing, the scene assigns the ids when picking
+    object_id: Mutex<Uniform<u32>>,
+    object_id_bind_group: wgpu::BindGroup,
+    empty_bind_group: wgpu::BindGroup,
 }
 
 pub struct Drawable {
@@ -590,8 +1725,17 @@ pub struct Drawable {
     buffers: Vec<Arc<wgpu::Buffer>>,
     pub(crate) instance_count: u32,
     pipeline: wgpu::RenderPipeline,
+    depth_prepass_pipeline: Option<wgpu::RenderPipeline>,
+    shadow_pass: Option<ShadowPass>,
+    pick_pass: Option<PickPass>,
+    wireframe_pass: Option<WireframePass>,
     blend_color_opacity: wgpu::Color,
+    bind_group_layouts: Vec<wgpu::BindGroupLayout>,
     bind_groups: BTreeMap<u32, wgpu::BindGroup>,
+    // Sorted by binding, as expected by wgpu
+    dynamic_offsets: BTreeMap<u32, Vec<Arc<AtomicU32>>>,
+    viewport: Option<Viewport>,
+    sample_count: u32,
 }
 
 impl Drawable {
@@ -605,19 +1749,325 @@ impl Drawable {
         }
     }
 
+    // Vertices processed per instance, i.e. the index count of indexed geometry
+    #[must_use]
+    pub fn vertex_count(&self) -> u32 {
+        match self.draw_mode {
+            DrawMode::Direct { vertex_count } => vertex_count,
+            DrawMode::Indexed { index_count, .. } => index_count,
+        }
+    }
+
+    #[must_use]
+    pub fn instance_count(&self) -> u32 {
+        self.instance_count
+    }
+
+    // Baked into the pipeline at build time, the drawable must be built again
+    // after DrawContext::set_multisample changes the sample count
+    #[must_use]
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    #[must_use]
+    pub fn is_compatible_with(&self, context: &DrawContext) -> bool {
+        self.sample_count == context.multisample_config.get_multisample_count()
+    }
+
+    // None keeps the viewport of the render pass, the full target with the default depth range
+    // unless changed; a viewport stays set after the draw, the caller restores the one of the
+    // target before the next drawables, e.g. Viewport::full(context.surface_dimensions())
+    pub fn set_viewport(&mut self, viewport: Option<Viewport>) {
+        self.viewport = viewport;
+    }
+
+    #[must_use]
+    pub fn viewport(&self) -> Option<Viewport> {
+        self.viewport
+    }
+
+    pub fn set_instance_count(&mut self, instance_count: u32) {
+        self.instance_count = instance_count;
+    }
+
+    // Number of indices the index buffer can hold, None if the drawable is not indexed
+    #[must_use]
+    pub fn index_capacity(&self) -> Option<u32> {
+        match &self.draw_mode {
+            DrawMode::Direct { .. } => None,
+            DrawMode::Indexed {
+                format,
+                index_buffer,
+                ..
+            } => Some(
+                u32::try_from(index_buffer.size() / format.byte_size() as u64)
+                    .expect("Value should fit in u32"),
+            ),
+        }
+    }
+
+    // Draws only the first indices of the index buffer, without reallocating it
+    pub fn set_index_count(&mut self, count: u32) -> anyhow::Result<()> {
+        let Some(capacity) = self.index_capacity() else {
+            bail!("Index count can only be set on an indexed drawable");
+        };
+        if count > capacity {
+            bail!("Index count {count} exceeds the index buffer capacity {capacity}");
+        }
+        if let DrawMode::Indexed { index_count, .. } = &mut self.draw_mode {
+            *index_count = count;
+        }
+        Ok(())
+    }
+
+    // Replaces the first indices and draws only them. The format must be the one given at
+    // build, and the wireframe overlay keeps the edges of the initial indices.
+    pub fn update_indices(
+        &mut self,
+        queue: &wgpu::Queue,
+        index_data: &IndexData<'_>,
+    ) -> anyhow::Result<()> {
+        let DrawMode::Indexed {
+            format,
+            index_buffer,
+            ..
+        } = &self.draw_mode
+        else {
+            bail!("Indices can only be updated on an indexed drawable");
+        };
+        if index_data.format() != *format {
+            bail!(
+                "Index format {:?} differs from the format {format:?} of the index buffer",
+                index_data.format()
+            );
+        }
+        let mut contents = index_data.data().to_vec();
+        // Buffer writes must be a multiple of 4 bytes, an odd count of u16 needs padding
+        let aligned_len = (contents.len() as u64).next_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT);
+        contents.resize(
+            usize::try_from(aligned_len).expect("Value should fit in usize"),
+            0,
+        );
+        if contents.len() as u64 > index_buffer.size() {
+            bail!(
+                "{} indices exceed the index buffer capacity {}",
+                index_data.size(),
+                self.index_capacity().unwrap_or_default()
+            );
+        }
+        queue.write_buffer(index_buffer, 0, &contents);
+        self.set_index_count(index_data.size())
+    }
+
+    // To call when a resource of the bind group was replaced, e.g. a reallocated buffer.
+    // All the bindings of the group must be given again, with the same types as during build.
+    pub fn rebuild_bind_group(
+        &mut self,
+        context: &DrawContext,
+        bind_group: u32,
+        binding_slots: &[BindingSlot<'_>],
+    ) -> anyhow::Result<()> {
+        let Some(layout) = self.bind_group_layouts.get(bind_group as usize) else {
+            bail!("Bind group {} is not used by this drawable", bind_group);
+        };
+        if let Some(slot) = binding_slots
+            .iter()
+            .find(|slot| slot.bind_group != bind_group)
+        {
+            bail!(
+                "Binding slot targets group {} instead of {}",
+                slot.bind_group,
+                bind_group
+            );
+        }
+        let entries = binding_slots
+            .iter()
+            .map(|slot| wgpu::BindGroupEntry {
+                binding: slot.binding,
+                resource: slot.resource.binding_resource(),
+            })
+            .collect::<Vec<_>>();
+        let new_bind_group = context
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: None,
+                layout,
+                entries: &entries,
+            });
+        self.bind_groups.insert(bind_group, new_bind_group);
+        Ok(())
+    }
+
     pub fn render(&self, render_pass: &mut wgpu::RenderPass<'_>) {
-        render_pass.set_pipeline(&self.pipeline);
+        self.draw(render_pass, 0..self.instance_count);
+    }
+
+    // The reference is compared and written by the stencil state of the drawable, and kept by
+    // the render pass for the next draws
+    pub fn render_with_stencil_reference(
+        &self,
+        render_pass: &mut wgpu::RenderPass<'_>,
+        reference: u32,
+    ) {
+        render_pass.set_stencil_reference(reference);
+        self.render(render_pass);
+    }
+
+    // The query index must be lower than DrawContext::occlusion_query_count
+    pub fn render_with_occlusion_query(
+        &self,
+        render_pass: &mut wgpu::RenderPass<'_>,
+        query_index: u32,
+    ) {
+        render_pass.begin_occlusion_query(query_index);
+        self.render(render_pass);
+        render_pass.end_occlusion_query();
+    }
+
+    pub fn render_instances(
+        &self,
+        render_pass: &mut wgpu::RenderPass<'_>,
+        instances: Range<u32>,
+    ) -> anyhow::Result<()> {
+        if instances.end > self.instance_count {
+            bail!(
+                "Instance range {:?} exceeds instance count {}",
+                instances,
+                self.instance_count
+            );
+        }
+        if instances.is_empty() {
+            return Ok(());
+        }
+        self.draw(render_pass, instances);
+        Ok(())
+    }
+
+    pub fn render_depth_prepass(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        if let Some(depth_prepass_pipeline) = &self.depth_prepass_pipeline {
+            self.draw_with_pipeline(render_pass, depth_prepass_pipeline, 0..self.instance_count);
+        }
+    }
+
+    pub fn render_shadow(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        let Some(shadow_pass) = &self.shadow_pass else {
+            return;
+        };
+        render_pass.set_pipeline(&shadow_pass.pipeline);
+        self.set_auxiliary_bind_groups(
+            render_pass,
+            DrawContext::BIND_GROUP_INDEX_SHADOW,
+            &shadow_pass.light_bind_group,
+            &shadow_pass.empty_bind_group,
+        );
+        self.draw_geometry(render_pass, 0..self.instance_count);
+    }
+
+    #[must_use]
+    pub fn is_pickable(&self) -> bool {
+        self.pick_pass.is_some()
+    }
+
+    // Renders object_id and the instance indices into a PickTarget pass
+    pub fn render_pick(&self, render_pass: &mut wgpu::RenderPass<'_>, object_id: u32) {
+        let Some(pick_pass) = &self.pick_pass else {
+            return;
+        };
+        pick_pass
+            .object_id
+            .lock()
+            .expect("Pick object id lock should not be poisoned")
+            .write_uniform(object_id);
+        render_pass.set_pipeline(&pick_pass.pipeline);
+        self.set_auxiliary_bind_groups(
+            render_pass,
+            DrawContext::BIND_GROUP_INDEX_PICKING,
+            &pick_pass.object_id_bind_group,
+            &pick_pass.empty_bind_group,
+        );
+        self.draw_geometry(render_pass, 0..self.instance_count);
+    }
+
+    fn set_auxiliary_bind_groups(
+        &self,
+        render_pass: &mut wgpu::RenderPass<'_>,
+        extra_group: u32,
+        extra_bind_group: &wgpu::BindGroup,
+        empty_bind_group: &wgpu::BindGroup,
+    ) {
+        for group_id in 0..extra_group {
+            if self.bind_groups.contains_key(&group_id) {
+                self.set_bind_group(render_pass, group_id);
+            } else {
+                render_pass.set_bind_group(group_id, empty_bind_group, &[]);
+            }
+        }
+        render_pass.set_bind_group(extra_group, extra_bind_group, &[]);
+    }
+
+    #[must_use]
+    pub fn has_depth_prepass(&self) -> bool {
+        self.depth_prepass_pipeline.is_some()
+    }
+
+    fn draw(&self, render_pass: &mut wgpu::RenderPass<'_>, instances: Range<u32>) {
+        if let Some(viewport) = &self.viewport {
+            viewport.apply(render_pass);
+        }
+        self.draw_with_pipeline(render_pass, &self.pipeline, instances.clone());
+        if let Some(wireframe_pass) = &self.wireframe_pass {
+            // The bind groups and vertex buffers are still set from the filled geometry
+            render_pass.set_pipeline(&wireframe_pass.pipeline);
+            render_pass.set_index_buffer(
+                wireframe_pass.index_buffer.slice(..),
+                wgpu::IndexFormat::Uint32,
+            );
+            render_pass.draw_indexed(0..wireframe_pass.index_count, 0, instances);
+        }
+    }
+
+    #[must_use]
+    pub fn has_wireframe_overlay(&self) -> bool {
+        self.wireframe_pass.is_some()
+    }
+
+    fn draw_with_pipeline(
+        &self,
+        render_pass: &mut wgpu::RenderPass<'_>,
+        pipeline: &wgpu::RenderPipeline,
+        instances: Range<u32>,
+    ) {
+        render_pass.set_pipeline(pipeline);
         render_pass.set_blend_constant(self.blend_color_opacity);
-        for (group_id, bind_group) in &self.bind_groups {
-            render_pass.set_bind_group(*group_id, bind_group, &[]);
+        for group_id in self.bind_groups.keys() {
+            self.set_bind_group(render_pass, *group_id);
         }
+        self.draw_geometry(render_pass, instances);
+    }
+
+    fn set_bind_group(&self, render_pass: &mut wgpu::RenderPass<'_>, group_id: u32) {
+        let offsets = self
+            .dynamic_offsets
+            .get(&group_id)
+            .map(|offsets| {
+                offsets
+                    .iter()
+                    .map(|offset| offset.load(Ordering::Relaxed))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        render_pass.set_bind_group(group_id, &self.bind_groups[&group_id], &offsets);
+    }
+
+    fn draw_geometry(&self, render_pass: &mut wgpu::RenderPass<'_>, instances: Range<u32>) {
         for (slot, vertex_buffer) in self.buffers.iter().enumerate() {
             let slot = u32::try_from(slot).expect("Value should fit in u32");
             render_pass.set_vertex_buffer(slot, vertex_buffer.slice(..));
         }
         match &self.draw_mode {
             DrawMode::Direct { vertex_count } => {
-                render_pass.draw(0..*vertex_count, 0..self.instance_count);
+                render_pass.draw(0..*vertex_count, instances);
             }
             DrawMode::Indexed {
                 format,
@@ -625,7 +2075,7 @@ impl Drawable {
                 index_buffer,
             } => {
                 render_pass.set_index_buffer(index_buffer.slice(..), *format);
-                render_pass.draw_indexed(0..*index_count, 0, 0..self.instance_count);
+                render_pass.draw_indexed(0..*index_count, 0, instances);
             }
         }
     }
@@ -637,12 +2087,50 @@ impl AsRef<Self> for Drawable {
     }
 }
 
+// Options fixed when creating the draw context
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DrawContextConfig {
+    // One of Depth24Plus, Depth24PlusStencil8 or Depth32Float, shared by the depth
+    // attachment and the pipelines of the drawables
+    pub depth_format: wgpu::TextureFormat,
+}
+
+impl Default for DrawContextConfig {
+    fn default() -> Self {
+        Self {
+            depth_format: wgpu::TextureFormat::Depth32Float,
+        }
+    }
+}
+
+impl DrawContextConfig {
+    pub const SUPPORTED_DEPTH_FORMATS: [wgpu::TextureFormat; 3] = [
+        wgpu::TextureFormat::Depth24Plus,
+        wgpu::TextureFormat::Depth24PlusStencil8,
+        wgpu::TextureFormat::Depth32Float,
+    ];
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if !Self::SUPPORTED_DEPTH_FORMATS.contains(&self.depth_format) {
+            bail!("Unsupported depth format {:?}", self.depth_format);
+        }
+        Ok(())
+    }
+}
+
 pub struct MultiSampleConfig {
     multisample_enabled: bool,
     multisample_count: u32,
 }
 
 impl MultiSampleConfig {
+    #[must_use]
+    pub fn new(multisample_enabled: bool, multisample_count: u32) -> Self {
+        Self {
+            multisample_enabled,
+            multisample_count,
+        }
+    }
     #[must_use]
     pub fn get_multisample_count(&self) -> u32 {
         if self.multisample_enabled {
@@ -657,11 +2145,41 @@ impl MultiSampleConfig {
     }
 }
 
+// The GL backend cannot create multisampled textures other than render attachments
+fn depth_usage(
+    backend: wgpu::Backend,
+    multisample_config: &MultiSampleConfig,
+) -> wgpu::TextureUsages {
+    match (multisample_config.is_multisample_enabled(), backend) {
+        (true, wgpu::Backend::Gl) => wgpu::TextureUsages::RENDER_ATTACHMENT,
+        _ => wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+    }
+}
+
+// Cleared along with the depth, None without a stencil aspect so the passes stay valid
+pub(crate) fn stencil_operations(
+    depth_format: wgpu::TextureFormat,
+    depth_load: wgpu::LoadOp<f32>,
+) -> Option<wgpu::Operations<u32>> {
+    depth_format
+        .has_stencil_aspect()
+        .then_some(wgpu::Operations {
+            load: match depth_load {
+                wgpu::LoadOp::Clear(_) => wgpu::LoadOp::Clear(0),
+                wgpu::LoadOp::Load => wgpu::LoadOp::Load,
+            },
+            store: wgpu::StoreOp::Store,
+        })
+}
+
 trait DeviceLocalExt {
+    // Shares the sample count of the color attachment, as required by the passes using both
     fn create_depth_texture(
         &self,
         surface_config: &wgpu::SurfaceConfiguration,
         multisample_config: &MultiSampleConfig,
+        format: wgpu::TextureFormat,
+        backend: wgpu::Backend,
     ) -> wgpu::Texture;
     fn create_multisample_texture(
         &self,
@@ -675,6 +2193,8 @@ impl DeviceLocalExt for wgpu::Device {
         &self,
         surface_config: &SurfaceConfiguration,
         multisample_config: &MultiSampleConfig,
+        format: wgpu::TextureFormat,
+        backend: wgpu::Backend,
     ) -> Texture {
         self.create_texture(&wgpu::TextureDescriptor {
             label: Some("Depth Texture"),
@@ -687,8 +2207,8 @@ impl DeviceLocalExt for wgpu::Device {
             sample_count: multisample_config.get_multisample_count(),
             dimension: wgpu::TextureDimension::D2,
             view_formats: &[],
-            format: wgpu::TextureFormat::Depth32Float,
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format,
+            usage: depth_usage(backend, multisample_config),
         })
     }
 
@@ -738,6 +2258,32 @@ impl DrawTarget {
             }
         }
     }
+    // Surfaces are also copied from, for screenshots, when supported
+    fn usage(&self, adapter: &wgpu::Adapter) -> wgpu::TextureUsages {
+        match self {
+            Self::Texture(_) => wgpu::TextureUsages::RENDER_ATTACHMENT,
+            Self::Surface(surface) => {
+                let copy_usage =
+                    surface.get_capabilities(adapter).usages & wgpu::TextureUsages::COPY_SRC;
+                wgpu::TextureUsages::RENDER_ATTACHMENT | copy_usage
+            }
+        }
+    }
+    // sRGB if the surface supports it
+    fn preferred_format(&self, adapter: &wgpu::Adapter) -> wgpu::TextureFormat {
+        match self {
+            Self::Texture(_) => wgpu::TextureFormat::Rgba8UnormSrgb,
+            Self::Surface(surface) => {
+                let surface_caps = surface.get_capabilities(adapter);
+                surface_caps
+                    .formats
+                    .iter()
+                    .find(|f| f.is_srgb())
+                    .copied()
+                    .unwrap_or(surface_caps.formats[0])
+            }
+        }
+    }
     fn create_texture(device: &wgpu::Device, width: u32, height: u32) -> wgpu::Texture {
         device.create_texture(&wgpu::TextureDescriptor {
             label: Some("Draw Target Texture"),
@@ -756,65 +2302,231 @@ impl DrawTarget {
     }
 }
 
-pub struct DrawContext {
-    multisample_texture: Option<wgpu::Texture>,
-    draw_target: DrawTarget,
-    clear_color: Option<wgpu::Color>,
-    pub window: Option<Arc<Window>>,
-    pub multisample_config: MultiSampleConfig,
-    pub depth_texture: wgpu::Texture,
-    pub queue: Rc<wgpu::Queue>,
-    pub device: wgpu::Device,
-    pub surface_config: wgpu::SurfaceConfiguration,
+type FrameCompleteCallback = Box<dyn FnOnce(u64) + Send>;
+#[cfg(not(target_arch = "wasm32"))]
+pub type ParallelPass<'a> = Box<dyn Fn(&mut wgpu::RenderPass<'_>) + Send + Sync + 'a>;
+
+// Only the first pass of a frame clears its targets
+const fn pass_load_ops(
+    index: usize,
+    clear_load_op: wgpu::LoadOp<wgpu::Color>,
+) -> (wgpu::LoadOp<wgpu::Color>, wgpu::LoadOp<f32>) {
+    if index == 0 {
+        (clear_load_op, wgpu::LoadOp::Clear(1.0))
+    } else {
+        (wgpu::LoadOp::Load, wgpu::LoadOp::Load)
+    }
 }
 
-impl DrawContext {
-    const DEFAULT_WIDTH: u32 = 500;
-    const DEFAULT_HEIGHT: u32 = 500;
-    const DEFAULT_MULTISAMPLE_ENABLED: bool = true;
+// Views of the frame being rendered, the surface texture being presented once submitted
+struct FrameTargets {
+    surface_texture: Option<(wgpu::SurfaceTexture, Instant)>,
+    displayed_view: wgpu::TextureView,
+    multisample_view: Option<wgpu::TextureView>,
+    depth_view: wgpu::TextureView,
+    depth_format: wgpu::TextureFormat,
+    // Submitted before the scene, when the background is a gradient
+    background_commands: Option<wgpu::CommandBuffer>,
+}
+
+impl FrameTargets {
+    // Rendered into the multisample texture when enabled, resolved into the displayed one
+    fn color_attachment(
+        &self,
+        load: wgpu::LoadOp<wgpu::Color>,
+    ) -> wgpu::RenderPassColorAttachment<'_> {
+        let (view, resolve_target) = self
+            .multisample_view
+            .as_ref()
+            .map_or((&self.displayed_view, None), |multisample_view| {
+                (multisample_view, Some(&self.displayed_view))
+            });
+        wgpu::RenderPassColorAttachment {
+            view,
+            resolve_target,
+            ops: wgpu::Operations {
+                load,
+                store: wgpu::StoreOp::Store,
+            },
+        }
+    }
+    fn depth_attachment(
+        &self,
+        load: wgpu::LoadOp<f32>,
+    ) -> wgpu::RenderPassDepthStencilAttachment<'_> {
+        wgpu::RenderPassDepthStencilAttachment {
+            view: &self.depth_view,
+            depth_ops: Some(wgpu::Operations {
+                load,
+                store: wgpu::StoreOp::Store,
+            }),
+            stencil_ops: stencil_operations(self.depth_format, load),
+        }
+    }
+}
+
+// Resolved after each frame, so the results of the last one can be read back
+struct OcclusionQueries {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    count: u32,
+}
+
+// 8 bit formats only, BGRA pixels being swizzled to RGBA
+#[cfg(not(target_arch = "wasm32"))]
+fn write_png(path: &Path, mut pixels: Vec<u8>, texture: &wgpu::Texture) -> anyhow::Result<()> {
+    match texture.format() {
+        wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => {}
+        wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => {
+            for pixel in pixels.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+        format => bail!("Screenshots of {format:?} textures are not supported"),
+    }
+    image::save_buffer(
+        path,
+        &pixels,
+        texture.width(),
+        texture.height(),
+        image::ExtendedColorType::Rgba8,
+    )
+    .map_err(|error| anyhow!("Cannot write {}: {error}", path.display()))
+}
+
+fn create_instance() -> wgpu::Instance {
+    wgpu::Instance::new(&wgpu::InstanceDescriptor {
+        backends: wgpu::Backends::all(),
+        ..Default::default()
+    })
+}
+
+async fn request_adapter(
+    instance: &wgpu::Instance,
+    compatible_surface: Option<&wgpu::Surface<'_>>,
+) -> anyhow::Result<wgpu::Adapter> {
+    // The software adapter, when the platform has one, lets CI machines without GPU run
+    for force_fallback_adapter in [false, true] {
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::default(),
+                force_fallback_adapter,
+                compatible_surface,
+            })
+            .await;
+        if let Some(adapter) = adapter {
+            return Ok(adapter);
+        }
+    }
+    Err(anyhow!("Could not create WebGPU adapter"))
+}
+
+// Formats, present modes and alpha modes supported by the surface of the window, to choose a
+// configuration before creating the DrawContext. The instance and adapter are only created for
+// the query. Without a window, the adapter is checked and the capabilities are empty.
+pub async fn query_surface_capabilities(
+    window: Option<Arc<Window>>,
+) -> anyhow::Result<wgpu::SurfaceCapabilities> {
+    let instance = create_instance();
+    let surface = window
+        .map(|window| instance.create_surface(window))
+        .transpose()?;
+    let adapter = request_adapter(&instance, surface.as_ref()).await?;
+    Ok(
+        surface.map_or_else(wgpu::SurfaceCapabilities::default, |surface| {
+            surface.get_capabilities(&adapter)
+        }),
+    )
+}
+
+pub struct DrawContext {
+    multisample_texture: Option<wgpu::Texture>,
+    draw_target: DrawTarget,
+    background: Background,
+    background_stack: Vec<Background>,
+    frame_index: Rc<Cell<u64>>,
+    frame_complete_callbacks: RefCell<Vec<FrameCompleteCallback>>,
+    sampled_depth: RefCell<Option<SampledDepth>>,
+    debug_draw: RefCell<DebugDraw>,
+    time_controller: TimeController,
+    frame_budget: FrameBudget,
+    frame_stats: Cell<FrameStats>,
+    debug_draw_renderer: RefCell<Option<DebugDrawRenderer>>,
+    gradient_renderer: RefCell<Option<GradientRenderer>>,
+    occlusion_queries: Option<OcclusionQueries>,
+    staging_batch: Arc<StagingBatch>,
+    rendering_enabled: bool,
+    low_latency: bool,
+    last_submission: RefCell<Option<wgpu::SubmissionIndex>>,
+    pending_screenshot: RefCell<Option<PathBuf>>,
+    depth_format: wgpu::TextureFormat,
+    redraw_handle: RedrawHandle,
+    backend: wgpu::Backend,
+    cursor_position: Option<PhysicalPosition<f64>>,
+    scale_factor: f64,
+    instance: wgpu::Instance,
+    adapter: wgpu::Adapter,
+    pub window: Option<Arc<Window>>,
+    pub multisample_config: MultiSampleConfig,
+    pub depth_texture: wgpu::Texture,
+    pub queue: Arc<wgpu::Queue>,
+    pub device: wgpu::Device,
+    pub surface_config: wgpu::SurfaceConfiguration,
+}
+
+impl DrawContext {
+    const DEFAULT_WIDTH: u32 = 500;
+    const DEFAULT_HEIGHT: u32 = 500;
+    const DEFAULT_MULTISAMPLE_ENABLED: bool = true;
     const DEFAULT_MULTISAMPLE_COUNT: u32 = 4;
+    // Enabled when the adapter supports them, bindings relying on them check the device
+    const OPTIONAL_FEATURES: wgpu::Features = wgpu::Features::TEXTURE_BINDING_ARRAY
+        .union(wgpu::Features::SAMPLED_TEXTURE_AND_STORAGE_BUFFER_ARRAY_NON_UNIFORM_INDEXING)
+        .union(wgpu::Features::POLYGON_MODE_LINE)
+        .union(wgpu::Features::POLYGON_MODE_POINT);
     const DEFAULT_CLEAR_COLOR: wgpu::Color = wgpu::Color {
         r: 0.0,
         g: 0.5,
         b: 0.5,
         a: 1.0,
     };
+    // Bind group conventions shared by the primitives and their shaders
     pub const BIND_GROUP_INDEX_CAMERA: u32 = 0;
+    pub const BIND_GROUP_INDEX_MODEL: u32 = 1;
+    pub const BIND_GROUP_INDEX_MATERIAL: u32 = 2;
+    pub const BIND_GROUP_INDEX_SHADOW: u32 = 3;
+    // Only used by the picking pipeline, so it does not conflict with the shadow group
+    pub const BIND_GROUP_INDEX_PICKING: u32 = 3;
 
     // FIXME winit window has size of 0 at startup for web browser, so also passing dimensions to draw context
     pub async fn new(
         window: Option<Arc<Window>>,
         dimensions: Option<Dimensions>,
     ) -> anyhow::Result<Self> {
-        let (width, height) = dimensions.map_or_else(
-            || {
-                window
-                    .as_ref()
-                    .map_or((Self::DEFAULT_WIDTH, Self::DEFAULT_HEIGHT), |w| {
-                        (w.inner_size().width, w.inner_size().height)
-                    })
-            },
-            |d| (d.width, d.height),
-        );
+        Self::new_with_config(window, dimensions, DrawContextConfig::default()).await
+    }
+
+    pub async fn new_with_config(
+        window: Option<Arc<Window>>,
+        dimensions: Option<Dimensions>,
+        config: DrawContextConfig,
+    ) -> anyhow::Result<Self> {
+        config.validate()?;
+        let depth_format = config.depth_format;
+        let (width, height) = dimensions
+            .or_else(|| window.as_ref().map(|w| w.inner_size().into()))
+            .unwrap_or_else(|| Dimensions::new(Self::DEFAULT_WIDTH, Self::DEFAULT_HEIGHT))
+            .into();
+        let scale_factor = window.as_ref().map_or(1.0, |w| w.scale_factor());
         let multisample_config = MultiSampleConfig {
             multisample_enabled: Self::DEFAULT_MULTISAMPLE_ENABLED,
             multisample_count: Self::DEFAULT_MULTISAMPLE_COUNT,
         };
-        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
-            backends: wgpu::Backends::all(),
-            ..Default::default()
-        });
+        let instance = create_instance();
         let surface = window
             .as_ref()
             .map(|w| instance.create_surface(Arc::clone(w)).unwrap());
-        let adapter = instance
-            .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::default(),
-                force_fallback_adapter: false,
-                compatible_surface: surface.as_ref(),
-            })
-            .await
-            .ok_or_else(|| anyhow!("Could not create WebGPU adapter"))?;
+        let adapter = request_adapter(&instance, surface.as_ref()).await?;
         debug!("{:?}", adapter);
         debug!("{:?}", adapter.features());
         let required_limits = if cfg!(target_arch = "wasm32") {
@@ -826,7 +2538,7 @@ impl DrawContext {
             .request_device(
                 &wgpu::DeviceDescriptor {
                     label: Some("Device Descriptor"),
-                    required_features: wgpu::Features::empty(),
+                    required_features: adapter.features() & Self::OPTIONAL_FEATURES,
                     required_limits,
                     memory_hints: wgpu::MemoryHints::Performance,
                 },
@@ -837,20 +2549,10 @@ impl DrawContext {
             || DrawTarget::new_texture_target(&device, width, height),
             DrawTarget::Surface,
         );
-        let surface_format = if let DrawTarget::Surface(s) = &draw_target {
-            let surface_caps = s.get_capabilities(&adapter);
-            surface_caps
-                .formats
-                .iter()
-                .find(|f| f.is_srgb())
-                .copied()
-                .unwrap_or(surface_caps.formats[0])
-        } else {
-            wgpu::TextureFormat::Rgba8UnormSrgb
-        };
+        let surface_format = draw_target.preferred_format(&adapter);
         let surface_config = wgpu::SurfaceConfiguration {
             desired_maximum_frame_latency: 2,
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            usage: draw_target.usage(&adapter),
             format: surface_format,
             width,
             height,
@@ -859,9 +2561,17 @@ impl DrawContext {
             present_mode: wgpu::PresentMode::Fifo,
         };
         draw_target.configure(&device, &surface_config);
-        let depth_texture = device.create_depth_texture(&surface_config, &multisample_config);
+        let backend = adapter.get_info().backend;
+        let depth_texture = device.create_depth_texture(
+            &surface_config,
+            &multisample_config,
+            depth_format,
+            backend,
+        );
         let multisample_texture =
             device.create_multisample_texture(&surface_config, &multisample_config);
+        let redraw_handle = RedrawHandle::new(window.clone());
+        let staging_batch = Arc::new(StagingBatch::new(device.clone()));
 
         Ok(Self {
             window,
@@ -869,15 +2579,72 @@ impl DrawContext {
             multisample_texture,
             draw_target,
             device,
-            queue: Rc::new(queue),
+            queue: Arc::new(queue),
             surface_config,
             depth_texture,
-            clear_color: Some(Self::DEFAULT_CLEAR_COLOR),
+            background: Background::Solid(Self::DEFAULT_CLEAR_COLOR),
+            background_stack: Vec::new(),
+            frame_index: Rc::new(Cell::new(0)),
+            frame_complete_callbacks: RefCell::new(Vec::new()),
+            sampled_depth: RefCell::new(None),
+            debug_draw: RefCell::new(DebugDraw::default()),
+            time_controller: TimeController::default(),
+            frame_budget: FrameBudget::default(),
+            frame_stats: Cell::new(FrameStats::default()),
+            debug_draw_renderer: RefCell::new(None),
+            gradient_renderer: RefCell::new(None),
+            occlusion_queries: None,
+            staging_batch,
+            rendering_enabled: true,
+            low_latency: false,
+            last_submission: RefCell::new(None),
+            pending_screenshot: RefCell::new(None),
+            depth_format,
+            redraw_handle,
+            backend,
+            cursor_position: None,
+            scale_factor,
+            instance,
+            adapter,
         })
     }
 
+    // Draws into a texture, whose pixels can be read back with read_target_pixels
+    pub async fn new_headless(width: u32, height: u32) -> anyhow::Result<Self> {
+        Self::new(None, Some(Dimensions::new(width, height))).await
+    }
+
     pub fn set_clear_color(&mut self, color: Option<wgpu::Color>) {
-        self.clear_color = color;
+        self.background = color.into();
+    }
+
+    // None when the background is not a solid color
+    #[must_use]
+    pub fn clear_color(&self) -> Option<wgpu::Color> {
+        self.background.clear_color()
+    }
+
+    pub fn set_background(&mut self, background: Background) {
+        self.background = background;
+    }
+
+    #[must_use]
+    pub fn background(&self) -> Background {
+        self.background
+    }
+
+    // Saves the current background, to be restored with pop_clear_color
+    pub fn push_clear_color(&mut self, color: Option<wgpu::Color>) {
+        self.background_stack.push(self.background);
+        self.background = color.into();
+    }
+
+    pub fn pop_clear_color(&mut self) {
+        if let Some(background) = self.background_stack.pop() {
+            self.background = background;
+        } else {
+            warn!("No clear color to restore, keeping the current one");
+        }
     }
 
     pub fn create_shader_module(&self, wgsl_shader: &str) -> wgpu::ShaderModule {
@@ -888,17 +2655,142 @@ impl DrawContext {
             })
     }
 
+    pub fn create_sampler(&self, config: &SamplerConfig) -> anyhow::Result<Sampler> {
+        config.validate()?;
+        Ok(Sampler::new(self, &config.descriptor()))
+    }
+
+    // Depth textures are not filterable, so only nearest filtering is accepted
+    pub fn create_depth_sampler(&self, config: &SamplerConfig) -> anyhow::Result<Sampler> {
+        config.validate()?;
+        if [config.mag_filter, config.min_filter, config.mipmap_filter]
+            .contains(&wgpu::FilterMode::Linear)
+        {
+            bail!("A sampler for a depth texture should not use linear filtering");
+        }
+        Ok(Sampler::new(self, &config.descriptor()))
+    }
+
+    // Convenience for Texture2D::new, with a view spanning all the generated mip levels
+    pub fn create_texture_with_mips(
+        &self,
+        dimensions: Dimensions,
+        rgba: &[u8],
+        generate_mips: bool,
+    ) -> anyhow::Result<Texture2D> {
+        Texture2D::new(self, dimensions, rgba, generate_mips)
+    }
+
     pub fn resize(&mut self, width: u32, height: u32) {
         self.surface_config.width = width;
         self.surface_config.height = height;
         self.draw_target
             .configure(&self.device, &self.surface_config);
-        self.depth_texture = self
-            .device
-            .create_depth_texture(&self.surface_config, &self.multisample_config);
+        self.depth_texture = self.device.create_depth_texture(
+            &self.surface_config,
+            &self.multisample_config,
+            self.depth_format,
+            self.backend,
+        );
         self.multisample_texture = self
             .device
             .create_multisample_texture(&self.surface_config, &self.multisample_config);
+        self.sampled_depth.replace(None);
+    }
+
+    // Unsupported modes fall back to Fifo, which all surfaces support. Without a surface, the
+    // mode is only kept in the configuration. Returns the mode in use.
+    #[allow(clippy::must_use_candidate)]
+    pub fn set_present_mode(&mut self, present_mode: wgpu::PresentMode) -> wgpu::PresentMode {
+        let present_mode = match &self.draw_target {
+            DrawTarget::Surface(surface)
+                if !surface
+                    .get_capabilities(&self.adapter)
+                    .present_modes
+                    .contains(&present_mode) =>
+            {
+                warn!("Present mode {present_mode:?} is not supported, falling back to Fifo");
+                wgpu::PresentMode::Fifo
+            }
+            _ => present_mode,
+        };
+        self.surface_config.present_mode = present_mode;
+        if let DrawTarget::Surface(surface) = &self.draw_target {
+            surface.configure(&self.device, &self.surface_config);
+        }
+        present_mode
+    }
+
+    #[must_use]
+    pub fn present_mode(&self) -> wgpu::PresentMode {
+        self.surface_config.present_mode
+    }
+
+    // Recreates the multisample and depth attachments with the new sample count.
+    // Drawables built before keep the previous count in their pipelines and must be
+    // built again, see Drawable::is_compatible_with.
+    pub fn set_multisample(&mut self, enabled: bool, count: u32) -> anyhow::Result<()> {
+        if enabled {
+            if count < 2 || !count.is_power_of_two() {
+                bail!("Multisample count {count} is not a power of two greater than 1");
+            }
+            // Without the adapter specific format features, the device only allows 1 and 4
+            // samples, whatever the adapter reports
+            let adapter_specific = self
+                .device
+                .features()
+                .contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES);
+            for format in [self.surface_config.format, self.depth_format] {
+                let supported = if adapter_specific {
+                    let features = self.adapter.get_texture_format_features(format);
+                    features.flags.sample_count_supported(count)
+                } else {
+                    count == 4
+                };
+                if !supported {
+                    bail!("Multisample count {count} is not supported for {format:?}");
+                }
+            }
+        }
+        self.multisample_config = MultiSampleConfig::new(enabled, count);
+        self.resize(self.surface_config.width, self.surface_config.height);
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn depth_format(&self) -> wgpu::TextureFormat {
+        self.depth_format
+    }
+
+    #[must_use]
+    pub fn is_depth_sampling_supported(&self) -> bool {
+        depth_usage(self.backend, &self.multisample_config)
+            .contains(wgpu::TextureUsages::TEXTURE_BINDING)
+    }
+
+    // Depth of the last frame given to resolve_depth, to bind with a non-filtering sampler.
+    // The binding must be created again after a resize.
+    pub fn depth_view_for_sampling(&self) -> anyhow::Result<TextureBinding> {
+        if !self.is_depth_sampling_supported() {
+            bail!("The multisampled depth texture cannot be sampled with this backend");
+        }
+        Ok(self
+            .sampled_depth
+            .borrow_mut()
+            .get_or_insert_with(|| SampledDepth::new(self))
+            .binding())
+    }
+
+    // To call after rendering the scene and before the passes sampling its depth
+    pub fn resolve_depth(&self) -> anyhow::Result<()> {
+        if !self.is_depth_sampling_supported() {
+            bail!("The multisampled depth texture cannot be sampled with this backend");
+        }
+        self.sampled_depth
+            .borrow_mut()
+            .get_or_insert_with(|| SampledDepth::new(self))
+            .update(self);
+        Ok(())
     }
 
     #[allow(clippy::cast_precision_loss)]
@@ -911,81 +2803,827 @@ impl DrawContext {
     }
 
     pub fn surface_dimensions(&self) -> Dimensions {
-        Dimensions {
-            width: self.surface_config.width,
-            height: self.surface_config.height,
+        Dimensions::new(self.surface_config.width, self.surface_config.height)
+    }
+
+    // Ratio between physical pixels and logical points, as reported by the window
+    #[must_use]
+    pub fn scale_factor(&self) -> f64 {
+        self.scale_factor
+    }
+
+    pub fn set_scale_factor(&mut self, scale_factor: f64) {
+        self.scale_factor = scale_factor;
+    }
+
+    // Surface of an additional window, drawn with the device and the color format of the main target
+    pub fn create_window_surface(
+        &self,
+        window: Arc<Window>,
+    ) -> anyhow::Result<(wgpu::Surface<'static>, wgpu::SurfaceConfiguration)> {
+        let size = window.inner_size();
+        let surface = self.instance.create_surface(window)?;
+        let surface_caps = surface.get_capabilities(&self.adapter);
+        if !surface_caps.formats.contains(&self.surface_config.format) {
+            bail!(
+                "The window surface does not support the {:?} format",
+                self.surface_config.format
+            );
+        }
+        let surface_config = wgpu::SurfaceConfiguration {
+            width: size.width.max(1),
+            height: size.height.max(1),
+            ..self.surface_config.clone()
+        };
+        surface.configure(&self.device, &surface_config);
+        Ok((surface, surface_config))
+    }
+
+    // Surface dimensions in logical points, which is what egui works with
+    #[must_use]
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss
+    )]
+    pub fn logical_dimensions(&self) -> Dimensions {
+        let dimensions = self.surface_dimensions();
+        Dimensions::new(
+            (f64::from(dimensions.width) / self.scale_factor).round() as u32,
+            (f64::from(dimensions.height) / self.scale_factor).round() as u32,
+        )
+    }
+
+    // Returns the new position when the cursor moved inside the window
+    pub fn track_cursor_event(&mut self, event: &WindowEvent) -> Option<PhysicalPosition<f64>> {
+        match event {
+            WindowEvent::CursorMoved { position, .. } => {
+                self.cursor_position = Some(*position);
+                self.cursor_position
+            }
+            WindowEvent::CursorLeft { .. } => {
+                self.cursor_position = None;
+                None
+            }
+            _ => None,
         }
     }
 
+    // In window coordinates, None when the cursor is outside of the window
+    #[must_use]
+    pub fn cursor_position(&self) -> Option<PhysicalPosition<f64>> {
+        self.cursor_position
+    }
+
+    // In normalized device coordinates, as expected by Scene3D::pick
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn cursor_ndc(&self) -> Option<[f32; 2]> {
+        let position = self.cursor_position?;
+        let Dimensions { width, height } = self.surface_dimensions();
+        if width == 0 || height == 0 {
+            return None;
+        }
+        Some([
+            (2.0 * position.x / f64::from(width) - 1.0) as f32,
+            (1.0 - 2.0 * position.y / f64::from(height)) as f32,
+        ])
+    }
+
     pub fn render_scene<C>(&self, callback: C) -> anyhow::Result<()>
     where
         C: FnOnce(wgpu::RenderPass<'_>),
     {
-        let depth_texture_view = self
-            .depth_texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
+        self.render_frame(|_| {}, callback)
+    }
 
-        let surface_texture = if let DrawTarget::Surface(surface) = &self.draw_target {
-            Some(surface.get_current_texture()?)
-        } else {
-            None
+    // The commands recorded by prepare, e.g. offscreen passes, are submitted along with the
+    // main render pass
+    pub fn render_frame<P, C>(&self, prepare: P, callback: C) -> anyhow::Result<()>
+    where
+        P: FnOnce(&mut wgpu::CommandEncoder),
+        C: FnOnce(wgpu::RenderPass<'_>),
+    {
+        let Some(frame_targets) = self.begin_frame()? else {
+            return Ok(());
         };
-        let displayed_view = match &self.draw_target {
-            DrawTarget::Texture(texture) => {
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Command Encoder"),
+            });
+        prepare(&mut encoder);
+        let render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Render pass"),
+            timestamp_writes: None,
+            occlusion_query_set: self.occlusion_queries.as_ref().map(|q| &q.query_set),
+            color_attachments: &[Some(frame_targets.color_attachment(self.clear_load_op()))],
+            depth_stencil_attachment: Some(
+                frame_targets.depth_attachment(wgpu::LoadOp::Clear(1.0)),
+            ),
+        });
+        callback(render_pass);
+        self.record_frame_overlays(&mut encoder, &frame_targets);
+        self.end_frame(vec![encoder.finish()], frame_targets);
+        Ok(())
+    }
+
+    // Several passes in the same frame: the first one clears color and depth, the next ones
+    // (overlays, gizmos) load them, so their geometry is depth tested against the scene.
+    pub fn render_scene_passes(
+        &self,
+        passes: &mut [&mut dyn FnMut(&mut wgpu::RenderPass<'_>)],
+    ) -> anyhow::Result<()> {
+        let Some(frame_targets) = self.begin_frame()? else {
+            return Ok(());
+        };
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Command Encoder"),
+            });
+        let clear_load_op = self.clear_load_op();
+        for (index, pass) in passes.iter_mut().enumerate() {
+            let (color_load, depth_load) = pass_load_ops(index, clear_load_op);
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Render pass"),
+                timestamp_writes: None,
+                occlusion_query_set: self.occlusion_queries.as_ref().map(|q| &q.query_set),
+                color_attachments: &[Some(frame_targets.color_attachment(color_load))],
+                depth_stencil_attachment: Some(frame_targets.depth_attachment(depth_load)),
+            });
+            pass(&mut render_pass);
+        }
+        self.record_frame_overlays(&mut encoder, &frame_targets);
+        self.end_frame(vec![encoder.finish()], frame_targets);
+        Ok(())
+    }
+
+    // Each pass is recorded on a Rayon task into its own command encoder, the command buffers
+    // being submitted together in the order of the passes. The first pass clears the targets,
+    // the next ones load what the previous ones drew.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn render_scene_parallel(&self, passes: Vec<ParallelPass<'_>>) -> anyhow::Result<()> {
+        use rayon::iter::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
+
+        if passes.is_empty() {
+            return self.render_scene(|_| {});
+        }
+        let Some(frame_targets) = self.begin_frame()? else {
+            return Ok(());
+        };
+        let device = &self.device;
+        let occlusion_query_set = self.occlusion_queries.as_ref().map(|q| &q.query_set);
+        let clear_load_op = self.clear_load_op();
+        let mut command_buffers = passes
+            .into_par_iter()
+            .enumerate()
+            .map(|(index, pass)| {
+                let (color_load, depth_load) = pass_load_ops(index, clear_load_op);
+                let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("Parallel Command Encoder"),
+                });
+                let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("Parallel render pass"),
+                    timestamp_writes: None,
+                    occlusion_query_set,
+                    color_attachments: &[Some(frame_targets.color_attachment(color_load))],
+                    depth_stencil_attachment: Some(frame_targets.depth_attachment(depth_load)),
+                });
+                pass(&mut render_pass);
+                drop(render_pass);
+                encoder.finish()
+            })
+            .collect::<Vec<_>>();
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Command Encoder"),
+            });
+        self.record_frame_overlays(&mut encoder, &frame_targets);
+        command_buffers.push(encoder.finish());
+        self.end_frame(command_buffers, frame_targets);
+        Ok(())
+    }
+
+    // A gradient is already drawn by the background pass, which is loaded by the scene
+    fn clear_load_op(&self) -> wgpu::LoadOp<wgpu::Color> {
+        self.clear_color()
+            .map_or(wgpu::LoadOp::Load, wgpu::LoadOp::Clear)
+    }
+
+    // None when the frame must be skipped, the batched writes being submitted anyway so they
+    // do not pile up
+    fn begin_frame(&self) -> anyhow::Result<Option<FrameTargets>> {
+        if !self.rendering_enabled {
+            self.flush_staging_batch();
+            return Ok(None);
+        }
+        self.validate_sample_counts()?;
+        self.wait_for_last_submission();
+        let surface_texture = match &self.draw_target {
+            DrawTarget::Surface(surface) => {
+                let Some(surface_texture) = self.acquire_surface_texture(surface)? else {
+                    self.flush_staging_batch();
+                    return Ok(None);
+                };
+                Some((surface_texture, Instant::now()))
+            }
+            DrawTarget::Texture(_) => None,
+        };
+        let displayed_view = match (&self.draw_target, &surface_texture) {
+            (DrawTarget::Texture(texture), _) => {
                 texture.create_view(&wgpu::TextureViewDescriptor::default())
             }
-            DrawTarget::Surface(_) => surface_texture
-                .as_ref()
-                .expect("When surface is used, this optional should not be empty")
+            (DrawTarget::Surface(_), Some((surface_texture, _))) => surface_texture
                 .texture
                 .create_view(&wgpu::TextureViewDescriptor::default()),
+            (DrawTarget::Surface(_), None) => {
+                unreachable!("When surface is used, the surface texture is acquired")
+            }
         };
-        let (pass_view, pass_resolve_target) = if self.multisample_config.is_multisample_enabled() {
-            let multisample_texture = self
-                .multisample_texture
+        let multisample_view = self.multisample_config.is_multisample_enabled().then(|| {
+            self.multisample_texture
                 .as_ref()
-                .expect("When multisample_enabled is at true, this optional should not be empty");
-            let multisample_view =
-                multisample_texture.create_view(&wgpu::TextureViewDescriptor::default());
-            (multisample_view, Some(&displayed_view))
-        } else {
-            (displayed_view, None)
+                .expect("When multisample_enabled is at true, this optional should not be empty")
+                .create_view(&wgpu::TextureViewDescriptor::default())
+        });
+        let mut frame_targets = FrameTargets {
+            surface_texture,
+            displayed_view,
+            multisample_view,
+            depth_view: self
+                .depth_texture
+                .create_view(&wgpu::TextureViewDescriptor::default()),
+            depth_format: self.depth_format,
+            background_commands: None,
+        };
+        if let Background::Gradient(top, bottom) = self.background {
+            frame_targets.background_commands =
+                Some(self.record_gradient(&frame_targets, top, bottom));
+        }
+        Ok(Some(frame_targets))
+    }
+
+    // The multisample configuration only reaches the attachments when they are recreated by resize
+    fn validate_sample_counts(&self) -> anyhow::Result<()> {
+        let expected = self.multisample_config.get_multisample_count();
+        let color = self
+            .multisample_texture
+            .as_ref()
+            .map_or(1, wgpu::Texture::sample_count);
+        let depth = self.depth_texture.sample_count();
+        if color != expected || depth != expected {
+            bail!(
+                "Color and depth attachments have {color} and {depth} samples while the multisample configuration expects {expected}, resize should be called after changing it"
+            );
+        }
+        Ok(())
+    }
+
+    fn record_gradient(
+        &self,
+        frame_targets: &FrameTargets,
+        top: wgpu::Color,
+        bottom: wgpu::Color,
+    ) -> wgpu::CommandBuffer {
+        let sample_count = self.multisample_config.get_multisample_count();
+        let mut renderer = self.gradient_renderer.borrow_mut();
+        let renderer = match &mut *renderer {
+            Some(renderer) if renderer.sample_count() == sample_count => renderer,
+            renderer => renderer.insert(GradientRenderer::new(self)),
         };
         let mut encoder = self
             .device
             .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                label: Some("Command Encoder"),
+                label: Some("Background Command Encoder"),
             });
-        let load_op = self
-            .clear_color
-            .map_or(wgpu::LoadOp::Load, wgpu::LoadOp::Clear);
-        let render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-            label: Some("Render pass"),
+        renderer.render(
+            self,
+            &mut encoder,
+            (top, bottom),
+            frame_targets.color_attachment(wgpu::LoadOp::Clear(bottom)),
+        );
+        encoder.finish()
+    }
+
+    // Recorded after the scene, in the last command buffer of the frame
+    fn record_frame_overlays(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        frame_targets: &FrameTargets,
+    ) {
+        self.render_debug_draw(
+            encoder,
+            frame_targets.color_attachment(wgpu::LoadOp::Load),
+            &frame_targets.depth_view,
+        );
+        if let Some(queries) = &self.occlusion_queries {
+            encoder.resolve_query_set(
+                &queries.query_set,
+                0..queries.count,
+                &queries.resolve_buffer,
+                0,
+            );
+        }
+    }
+
+    fn end_frame(&self, command_buffers: Vec<wgpu::CommandBuffer>, frame_targets: FrameTargets) {
+        let submission = self.submit(
+            frame_targets
+                .background_commands
+                .into_iter()
+                .chain(command_buffers),
+        );
+        if self.low_latency {
+            self.last_submission.replace(Some(submission));
+        }
+        let frame_index = self.frame_index.get();
+        for callback in self.frame_complete_callbacks.take() {
+            self.queue
+                .on_submitted_work_done(move || callback(frame_index));
+        }
+        self.update_frame_stats(FrameStats::record_rendered_frame);
+        if let Some((surface_texture, acquired_at)) = frame_targets.surface_texture {
+            #[cfg(not(target_arch = "wasm32"))]
+            if let Some(path) = self.pending_screenshot.take()
+                && let Err(error) = self.save_surface_screenshot(&surface_texture.texture, &path)
+            {
+                warn!("Screenshot {} failed: {error:#}", path.display());
+            }
+            surface_texture.present();
+            self.update_frame_stats(|stats| stats.record_presented_frame(acquired_at.elapsed()));
+        }
+        self.frame_index.set(frame_index + 1);
+    }
+
+    // When disabled, the render methods return without recording or submitting anything, e.g.
+    // to test the update logic of a scenario where the adapter cannot render
+    pub fn set_rendering_enabled(&mut self, enabled: bool) {
+        self.rendering_enabled = enabled;
+    }
+    #[must_use]
+    pub fn is_rendering_enabled(&self) -> bool {
+        self.rendering_enabled
+    }
+
+    // When enabled, a frame only starts once the GPU finished the previous one, so at most one
+    // frame is in flight: the input read for a frame is displayed sooner, at the cost of the
+    // CPU and GPU no longer working in parallel. Polling does not block on the web.
+    pub fn set_low_latency(&mut self, enabled: bool) {
+        self.low_latency = enabled;
+        if !enabled {
+            self.last_submission.take();
+        }
+    }
+    #[must_use]
+    pub fn is_low_latency(&self) -> bool {
+        self.low_latency
+    }
+
+    fn wait_for_last_submission(&self) {
+        if let Some(submission) = self.last_submission.take() {
+            self.device
+                .poll(wgpu::Maintain::WaitForSubmissionIndex(submission));
+        }
+    }
+
+    // Handle kept by a scenario or a plugin to request the next frame from its event callbacks
+    #[must_use]
+    pub fn redraw_handle(&self) -> RedrawHandle {
+        self.redraw_handle.clone()
+    }
+    pub fn request_redraw(&self) {
+        self.redraw_handle.request_redraw();
+    }
+
+    // When enabled, the uniform and storage buffer writes are uploaded together with the next
+    // submission, see submit
+    pub fn set_staging_batch_enabled(&mut self, enabled: bool) {
+        self.staging_batch.set_enabled(enabled);
+    }
+    #[must_use]
+    pub fn staging_batch(&self) -> &StagingBatch {
+        &self.staging_batch
+    }
+    // To use instead of queue.submit, so the batched writes are copied before the commands
+    // reading them
+    pub fn submit<I>(&self, command_buffers: I) -> wgpu::SubmissionIndex
+    where
+        I: IntoIterator<Item = wgpu::CommandBuffer>,
+    {
+        self.queue.submit(
+            self.staging_batch
+                .flush()
+                .into_iter()
+                .chain(command_buffers),
+        )
+    }
+    fn flush_staging_batch(&self) {
+        if let Some(command_buffer) = self.staging_batch.flush() {
+            self.queue.submit(Some(command_buffer));
+        }
+    }
+
+    // Queries available to Drawable::render_with_occlusion_query in the main render pass, 0
+    // disabling them
+    pub fn set_occlusion_query_count(&mut self, count: u32) -> anyhow::Result<()> {
+        if count > wgpu::QUERY_SET_MAX_QUERIES {
+            bail!(
+                "At most {} occlusion queries are supported",
+                wgpu::QUERY_SET_MAX_QUERIES
+            );
+        }
+        self.occlusion_queries = (count > 0).then(|| OcclusionQueries {
+            query_set: self.device.create_query_set(&wgpu::QuerySetDescriptor {
+                label: Some("Occlusion Query Set"),
+                ty: wgpu::QueryType::Occlusion,
+                count,
+            }),
+            resolve_buffer: self.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Occlusion Resolve Buffer"),
+                size: u64::from(count) * u64::from(wgpu::QUERY_SIZE),
+                usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+                mapped_at_creation: false,
+            }),
+            count,
+        });
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn occlusion_query_count(&self) -> u32 {
+        self.occlusion_queries.as_ref().map_or(0, |q| q.count)
+    }
+
+    // Visible sample counts of the last rendered frame, one per query. Some backends only
+    // report whether any sample passed, as 0 or 1.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn occlusion_results(&self) -> anyhow::Result<Vec<u64>> {
+        let Some(queries) = &self.occlusion_queries else {
+            bail!("Occlusion queries are not enabled");
+        };
+        let readback_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Occlusion Readback Buffer"),
+            size: queries.resolve_buffer.size(),
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Occlusion Readback Encoder"),
+            });
+        encoder.copy_buffer_to_buffer(
+            &queries.resolve_buffer,
+            0,
+            &readback_buffer,
+            0,
+            queries.resolve_buffer.size(),
+        );
+        self.submit(std::iter::once(encoder.finish()));
+        let buffer_slice = readback_buffer.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        self.device.poll(wgpu::Maintain::Wait);
+        receiver.recv()??;
+        let results = bytemuck::pod_collect_to_vec::<u8, u64>(&buffer_slice.get_mapped_range());
+        readback_buffer.unmap();
+        Ok(results)
+    }
+
+    #[must_use]
+    pub fn time_controller(&self) -> &TimeController {
+        &self.time_controller
+    }
+
+    pub fn time_controller_mut(&mut self) -> &mut TimeController {
+        &mut self.time_controller
+    }
+
+    // None when the frame must be skipped, a lost or outdated surface being reconfigured
+    // for the next one
+    fn acquire_surface_texture(
+        &self,
+        surface: &wgpu::Surface,
+    ) -> anyhow::Result<Option<wgpu::SurfaceTexture>> {
+        match surface.get_current_texture() {
+            Result::Ok(surface_texture) => Ok(Some(surface_texture)),
+            Err(
+                error @ (wgpu::SurfaceError::Lost
+                | wgpu::SurfaceError::Outdated
+                | wgpu::SurfaceError::Timeout),
+            ) => {
+                warn!("Frame skipped: {error}");
+                if error != wgpu::SurfaceError::Timeout {
+                    surface.configure(&self.device, &self.surface_config);
+                }
+                self.update_frame_stats(|stats| stats.record_surface_error(&error));
+                Ok(None)
+            }
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    #[must_use]
+    pub fn frame_stats(&self) -> FrameStats {
+        self.frame_stats.get()
+    }
+
+    fn update_frame_stats(&self, update: impl FnOnce(&mut FrameStats)) {
+        let mut frame_stats = self.frame_stats.get();
+        update(&mut frame_stats);
+        self.frame_stats.set(frame_stats);
+    }
+
+    #[must_use]
+    pub fn frame_budget(&self) -> &FrameBudget {
+        &self.frame_budget
+    }
+
+    pub fn frame_budget_mut(&mut self) -> &mut FrameBudget {
+        &mut self.frame_budget
+    }
+
+    // Renders into several color targets at once, each one with its own load operation, e.g.
+    // to clear an ID buffer to 0 and the color to the sky. The targets share the depth of the
+    // first one, cleared with it, so they must have the same dimensions and sample count.
+    pub fn render_scene_mrt<C>(
+        &self,
+        targets: &[(&RenderTexture, wgpu::LoadOp<wgpu::Color>)],
+        callback: C,
+    ) -> anyhow::Result<()>
+    where
+        C: FnOnce(&mut wgpu::RenderPass<'_>),
+    {
+        let Some(((first_target, first_load), _)) = targets.split_first() else {
+            bail!("At least one render target is expected");
+        };
+        if targets.iter().any(|(target, _)| {
+            target.dimensions() != first_target.dimensions()
+                || target.sample_count() != first_target.sample_count()
+        }) {
+            bail!("Render targets should have the same dimensions and sample count");
+        }
+        let views: Vec<_> = targets
+            .iter()
+            .map(|(target, _)| target.color_views())
+            .collect();
+        let color_attachments: Vec<_> = views
+            .iter()
+            .zip(targets)
+            .map(|((texture_view, multisample_view), (_, load))| {
+                let (view, resolve_target) = multisample_view
+                    .as_ref()
+                    .map_or((texture_view, None), |multisample_view| {
+                        (multisample_view, Some(texture_view))
+                    });
+                Some(wgpu::RenderPassColorAttachment {
+                    view,
+                    resolve_target,
+                    ops: wgpu::Operations {
+                        load: *load,
+                        store: wgpu::StoreOp::Store,
+                    },
+                })
+            })
+            .collect();
+        let depth_view = first_target.depth_view();
+        let depth_load = match first_load {
+            wgpu::LoadOp::Clear(_) => wgpu::LoadOp::Clear(1.0),
+            wgpu::LoadOp::Load => wgpu::LoadOp::Load,
+        };
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("MRT Command Encoder"),
+            });
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("MRT Render Pass"),
             timestamp_writes: None,
             occlusion_query_set: None,
-            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                view: &pass_view,
-                resolve_target: pass_resolve_target,
-                ops: wgpu::Operations {
-                    load: load_op,
-                    store: wgpu::StoreOp::Store,
-                },
-            })],
+            color_attachments: &color_attachments,
             depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
-                view: &depth_texture_view,
+                view: &depth_view,
                 depth_ops: Some(wgpu::Operations {
-                    load: wgpu::LoadOp::Clear(1.0),
+                    load: depth_load,
                     store: wgpu::StoreOp::Store,
                 }),
-                stencil_ops: None,
+                stencil_ops: stencil_operations(self.depth_format, depth_load),
             }),
         });
-        callback(render_pass);
-        let command_buffers = std::iter::once(encoder.finish());
-        self.queue.submit(command_buffers);
-        if let Some(s) = surface_texture {
-            s.present();
-        }
+        callback(&mut render_pass);
+        drop(render_pass);
+        self.submit(Some(encoder.finish()));
         Ok(())
     }
+
+    // Lines queued here are drawn after the scene of the next rendered frame, then cleared
+    pub fn debug_draw(&self) -> RefMut<'_, DebugDraw> {
+        self.debug_draw.borrow_mut()
+    }
+
+    fn render_debug_draw(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        color_attachment: wgpu::RenderPassColorAttachment<'_>,
+        depth_view: &wgpu::TextureView,
+    ) {
+        let mut debug_draw = self.debug_draw.borrow_mut();
+        if debug_draw.is_empty() {
+            return;
+        }
+        let sample_count = self.multisample_config.get_multisample_count();
+        let mut renderer = self.debug_draw_renderer.borrow_mut();
+        let renderer = match &mut *renderer {
+            Some(renderer) if renderer.sample_count() == sample_count => renderer,
+            renderer => renderer.insert(DebugDrawRenderer::new(self)),
+        };
+        renderer.render(self, encoder, &debug_draw, color_attachment, depth_view);
+        debug_draw.clear();
+    }
+
+    // Called once with its frame index when the GPU has finished the work of the next rendered
+    // frame. On native, callbacks are dispatched when the device is polled, which every
+    // submission does for the previous frames.
+    pub fn on_frame_complete<F>(&self, callback: F)
+    where
+        F: FnOnce(u64) + Send + 'static,
+    {
+        self.frame_complete_callbacks
+            .borrow_mut()
+            .push(Box::new(callback));
+    }
+
+    #[must_use]
+    pub fn frame_index(&self) -> u64 {
+        self.frame_index.get()
+    }
+
+    // With multisampling, the render pass resolves into the draw target texture, so the
+    // copy below always reads a single-sampled texture
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn read_target_pixels(&self) -> anyhow::Result<Vec<u8>> {
+        let DrawTarget::Texture(texture) = &self.draw_target else {
+            bail!("Pixels can only be read back when drawing into a texture");
+        };
+        self.read_texture_pixels(texture)
+    }
+
+    // Same as read_target_pixels, along with the width and height of the image, the texture
+    // target always being RGBA8 so each pixel takes 4 bytes
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn read_target_image(&self) -> anyhow::Result<(Vec<u8>, Dimensions)> {
+        let DrawTarget::Texture(texture) = &self.draw_target else {
+            bail!("Pixels can only be read back when drawing into a texture");
+        };
+        let pixels = self.read_texture_pixels(texture)?;
+        Ok((pixels, Dimensions::new(texture.width(), texture.height())))
+    }
+
+    // Writes the draw target as a PNG file, creating the missing parent directories. The bytes
+    // are stored as rendered: an sRGB target already holds sRGB encoded values, so they are not
+    // converted again. A surface frame cannot be read once presented, so the next frame is copied
+    // into an intermediate texture before being presented, and written then, failures being
+    // logged.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save_screenshot(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        match &self.draw_target {
+            DrawTarget::Texture(texture) => {
+                let pixels = self.read_texture_pixels(texture)?;
+                write_png(path, pixels, texture)
+            }
+            DrawTarget::Surface(_) => {
+                if !self
+                    .surface_config
+                    .usage
+                    .contains(wgpu::TextureUsages::COPY_SRC)
+                {
+                    bail!("The surface cannot be copied from, screenshots are not supported");
+                }
+                self.pending_screenshot.replace(Some(path.to_path_buf()));
+                Ok(())
+            }
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save_surface_screenshot(
+        &self,
+        surface_texture: &wgpu::Texture,
+        path: &Path,
+    ) -> anyhow::Result<()> {
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Screenshot Texture"),
+            size: surface_texture.size(),
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: surface_texture.format(),
+            usage: wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Screenshot Encoder"),
+            });
+        encoder.copy_texture_to_texture(
+            surface_texture.as_image_copy(),
+            texture.as_image_copy(),
+            surface_texture.size(),
+        );
+        self.submit(std::iter::once(encoder.finish()));
+        let pixels = self.read_texture_pixels(&texture)?;
+        write_png(path, pixels, &texture)
+    }
+
+    // Texture must be single-sampled and created with the COPY_SRC usage
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn read_texture_pixels(&self, texture: &wgpu::Texture) -> anyhow::Result<Vec<u8>> {
+        let dimensions = Dimensions::new(texture.width(), texture.height());
+        self.read_texture_region(texture, (0, 0), dimensions)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn read_texture_region(
+        &self,
+        texture: &wgpu::Texture,
+        origin: (u32, u32),
+        dimensions: Dimensions,
+    ) -> anyhow::Result<Vec<u8>> {
+        if texture.sample_count() != 1 {
+            bail!("Texture must be resolved before being read back");
+        }
+        if origin.0 + dimensions.width > texture.width()
+            || origin.1 + dimensions.height > texture.height()
+        {
+            bail!("Region to read back exceeds the texture dimensions");
+        }
+        let (width, height) = (dimensions.width, dimensions.height);
+        let bytes_per_pixel = texture
+            .format()
+            .block_copy_size(None)
+            .ok_or_else(|| anyhow!("Texture format cannot be copied"))?;
+        let unpadded_bytes_per_row = width * bytes_per_pixel;
+        let padded_bytes_per_row = unpadded_bytes_per_row
+            .div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT)
+            * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let readback_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Readback Buffer"),
+            size: u64::from(padded_bytes_per_row) * u64::from(height),
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Readback Encoder"),
+            });
+        encoder.copy_texture_to_buffer(
+            wgpu::TexelCopyTextureInfo {
+                origin: wgpu::Origin3d {
+                    x: origin.0,
+                    y: origin.1,
+                    z: 0,
+                },
+                ..texture.as_image_copy()
+            },
+            wgpu::TexelCopyBufferInfo {
+                buffer: &readback_buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: Some(height),
+                },
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+        self.submit(std::iter::once(encoder.finish()));
+        let buffer_slice = readback_buffer.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        self.device.poll(wgpu::Maintain::Wait);
+        receiver.recv()??;
+        let padded_data = buffer_slice.get_mapped_range();
+        let pixels = padded_data
+            .chunks_exact(padded_bytes_per_row as usize)
+            .flat_map(|row| &row[..unpadded_bytes_per_row as usize])
+            .copied()
+            .collect();
+        drop(padded_data);
+        readback_buffer.unmap();
+        Ok(pixels)
+    }
 }
@@ -22,35 +22,156 @@ OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 SOFTWARE.
 */
 
-use log::info;
+use log::{info, warn};
 use std::env;
+use web_time::Duration;
 
 use crate::{
     LaunchContext,
-    draw_context::DrawContext,
-    render_loop::{RenderLoopBuilder, RenderLoopHandler},
+    draw_context::{Dimensions, DrawContext, DrawContextConfig},
+    render_loop::{DoubleClickDetector, RenderLoopBuilder, RenderLoopHandler},
     window::init_event_loop,
 };
 
-const GLOBAL_LOG_FILTER: log::LevelFilter = log::LevelFilter::Info;
 const ENV_HEADLESS: &str = "HEADLESS";
+const ENV_RUST_LOG: &str = "RUST_LOG";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LaunchConfig {
+    // Id of the HTML canvas element the app draws into, only used with WASM
+    pub canvas_id: String,
+    // Bounds of the inner size of the window, in physical pixels
+    pub min_inner_size: Option<Dimensions>,
+    pub max_inner_size: Option<Dimensions>,
+    // Width and height terms of a ratio the window keeps when resized, e.g. (16, 9)
+    pub aspect_ratio: Option<(u32, u32)>,
+    // Longest delay between the clicks of a double-click
+    pub double_click_interval: Duration,
+    // Options of the draw context created for the window
+    pub draw_context_config: DrawContextConfig,
+    // Level of the log records kept, unless overridden for their target below
+    pub log_level: log::LevelFilter,
+    // Levels per target, e.g. a crate or a module path, overriding log_level
+    pub log_target_levels: Vec<(String, log::LevelFilter)>,
+}
+
+impl LaunchConfig {
+    pub const DEFAULT_CANVAS_ID: &str = "target";
+    pub const DEFAULT_LOG_LEVEL: log::LevelFilter = log::LevelFilter::Info;
+
+    // Overrides the log levels with a RUST_LOG like spec, e.g. "warn,wgpu_igniter=debug".
+    // Directives that are not understood are ignored.
+    pub fn apply_log_spec(&mut self, spec: &str) {
+        for directive in spec.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            match directive.split_once('=') {
+                Some((target, level)) => {
+                    let Ok(level) = level.trim().parse() else {
+                        continue;
+                    };
+                    let target = target.trim().to_string();
+                    self.log_target_levels.retain(|(other, _)| *other != target);
+                    self.log_target_levels.push((target, level));
+                }
+                None => {
+                    if let Ok(level) = directive.parse() {
+                        self.log_level = level;
+                    } else {
+                        // A target alone enables all its records
+                        self.log_target_levels
+                            .retain(|(other, _)| other != directive);
+                        self.log_target_levels
+                            .push((directive.to_string(), log::LevelFilter::Trace));
+                    }
+                }
+            }
+        }
+    }
+
+    // Size requested from the window when it is resized to another one, the min and max bounds
+    // being also set on the window attributes. The height follows the aspect ratio, unless that
+    // goes out of bounds, then the width does.
+    #[must_use]
+    pub fn constrain_inner_size(&self, requested: Dimensions) -> Dimensions {
+        let clamp_width = |width: u32| self.clamp(width, |dimensions| dimensions.width);
+        let clamp_height = |height: u32| self.clamp(height, |dimensions| dimensions.height);
+        let width = clamp_width(requested.width);
+        let height = clamp_height(requested.height);
+        let Some((ratio_width, ratio_height)) = self.aspect_ratio else {
+            return Dimensions::new(width, height);
+        };
+        let scale = |value: u32, numerator: u32, denominator: u32| {
+            u32::try_from(
+                (u64::from(value) * u64::from(numerator) + u64::from(denominator) / 2)
+                    / u64::from(denominator.max(1)),
+            )
+            .unwrap_or(u32::MAX)
+        };
+        let ratio_height_of_width = scale(width, ratio_height, ratio_width);
+        if clamp_height(ratio_height_of_width) == ratio_height_of_width {
+            return Dimensions::new(width, ratio_height_of_width);
+        }
+        let height = clamp_height(ratio_height_of_width);
+        Dimensions::new(
+            clamp_width(scale(height, ratio_width, ratio_height)),
+            height,
+        )
+    }
+
+    fn clamp(&self, value: u32, component: impl Fn(Dimensions) -> u32) -> u32 {
+        let min = self.min_inner_size.map_or(0, &component);
+        let max = self.max_inner_size.map_or(u32::MAX, &component).max(min);
+        value.clamp(min, max)
+    }
+}
+
+impl Default for LaunchConfig {
+    fn default() -> Self {
+        Self {
+            canvas_id: Self::DEFAULT_CANVAS_ID.to_string(),
+            min_inner_size: None,
+            max_inner_size: None,
+            aspect_ratio: None,
+            double_click_interval: DoubleClickDetector::DEFAULT_INTERVAL,
+            draw_context_config: DrawContextConfig::default(),
+            log_level: Self::DEFAULT_LOG_LEVEL,
+            log_target_levels: vec![(
+                env!("CARGO_PKG_NAME").replace('-', "_"),
+                log::LevelFilter::Debug,
+            )],
+        }
+    }
+}
 
 pub fn launch_app<F>(builder: F)
 where
     F: Fn(LaunchContext) -> Box<dyn RenderLoopHandler> + 'static + Send,
 {
-    init_log();
+    launch_app_with_config(LaunchConfig::default(), builder);
+}
+
+pub fn launch_app_with_config<F>(config: LaunchConfig, builder: F)
+where
+    F: Fn(LaunchContext) -> Box<dyn RenderLoopHandler> + 'static + Send,
+{
+    init_log(&config);
     info!("Init app");
     let is_headless = env::var(ENV_HEADLESS).is_ok();
     if is_headless {
         info!("Running in headless mode");
         init_headless(Box::new(builder));
     } else {
-        init_event_loop(Box::new(builder));
+        init_event_loop(config, Box::new(builder));
     }
 }
 
-fn init_log() {
+// Installs the global logger with the levels of the config, overridden by RUST_LOG. If the
+// app already set up its own logger, it is kept and false is returned.
+#[allow(clippy::must_use_candidate)]
+pub fn init_log(config: &LaunchConfig) -> bool {
+    let mut config = config.clone();
+    if let Ok(spec) = env::var(ENV_RUST_LOG) {
+        config.apply_log_spec(&spec);
+    }
     let mut builder = fern::Dispatch::new();
     let level_formatter;
     #[cfg(target_arch = "wasm32")]
@@ -68,9 +189,11 @@ fn init_log() {
         level_formatter = move |level| colors.color(level);
         builder = builder.chain(std::io::stdout());
     }
-    builder
-        .level(GLOBAL_LOG_FILTER)
-        .level_for(env!("CARGO_PKG_NAME"), log::LevelFilter::Debug)
+    builder = builder.level(config.log_level);
+    for (target, level) in config.log_target_levels {
+        builder = builder.level_for(target, level);
+    }
+    let result = builder
         .format(move |out, message, record| {
             out.finish(format_args!(
                 "{}[{}][{}:{}] {}",
@@ -81,13 +204,26 @@ fn init_log() {
                 message
             ));
         })
-        .apply()
-        .unwrap();
+        .apply();
+    if let Err(err) = result {
+        warn!("Keeping the logger already installed: {err}");
+        return false;
+    }
+    true
 }
 
 #[cfg(not(target_arch = "wasm32"))]
 #[allow(clippy::needless_pass_by_value)]
 fn init_headless(builder: Box<RenderLoopBuilder>) {
+    run_headless(builder);
+}
+
+// Renders a single frame offscreen, without initializing the logs
+#[cfg(not(target_arch = "wasm32"))]
+pub fn run_headless<F>(builder: F)
+where
+    F: Fn(LaunchContext) -> Box<dyn RenderLoopHandler>,
+{
     use pollster::FutureExt;
 
     use crate::{TimeInfo, plugins::PluginRegistry};
@@ -109,6 +245,53 @@ fn init_headless(builder: Box<RenderLoopBuilder>) {
             );
         })
         .unwrap();
+    scene_handler.on_exit(draw_context);
+}
+
+// Renders frames of a scenario into the texture target of the context, independently of the
+// environment and without winit, e.g. to produce images on demand in a web service. The time
+// advances by frame_delta at each frame, and the RGBA pixels of the last frame are returned.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn render_headless_frames<F>(
+    draw_context: &mut DrawContext,
+    frame_count: u32,
+    frame_delta: web_time::Duration,
+    builder: F,
+) -> anyhow::Result<Vec<u8>>
+where
+    F: FnOnce(LaunchContext) -> Box<dyn RenderLoopHandler>,
+{
+    use crate::{TimeInfo, plugins::PluginRegistry};
+    use web_time::Instant;
+
+    if draw_context.window.is_some() {
+        anyhow::bail!("Headless frames can only be rendered without a window");
+    }
+    let plugin_registry = &mut PluginRegistry::default();
+    let mut scene_handler = builder(LaunchContext {
+        draw_context,
+        plugin_registry,
+    });
+    scene_handler.on_init(plugin_registry, draw_context);
+    let init_start = Instant::now();
+    for _ in 0..frame_count {
+        draw_context.time_controller_mut().advance(frame_delta);
+        let time_info =
+            TimeInfo::with_time_controller(init_start, frame_delta, draw_context.time_controller());
+        scene_handler.on_update(plugin_registry, draw_context, &time_info);
+        for listener in plugin_registry.iter_mut() {
+            listener.on_update(draw_context, &time_info);
+        }
+        draw_context.render_scene(|pass| {
+            let pass = &mut pass.forget_lifetime();
+            scene_handler.on_render(plugin_registry, draw_context, &time_info, pass);
+            for listener in plugin_registry.iter_mut() {
+                listener.on_render(draw_context, &time_info, pass);
+            }
+        })?;
+    }
+    scene_handler.on_exit(draw_context);
+    draw_context.read_target_pixels()
 }
 
 #[cfg(target_arch = "wasm32")]
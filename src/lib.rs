@@ -37,14 +37,22 @@ pub(crate) mod draw_context;
 pub(crate) mod render_loop;
 
 pub mod cameras;
+pub mod picking;
 pub mod plugins;
 pub mod primitives;
+pub mod shadows;
+pub mod support;
+pub mod textures;
 
 pub use draw_context::*;
-pub use launcher::launch_app;
+pub use launcher::{LaunchConfig, init_log, launch_app, launch_app_with_config};
+#[cfg(not(target_arch = "wasm32"))]
+pub use launcher::{render_headless_frames, run_headless};
 pub use render_loop::*;
 
+pub use cgmath;
 #[cfg(feature = "egui")]
 pub use egui;
 pub use wgpu;
 pub use winit::event::{DeviceEvent, KeyEvent, WindowEvent};
+pub use winit::event_loop::ControlFlow;
@@ -0,0 +1,90 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::draw_context::{Dimensions, DrawContext};
+use crate::support::render_texture::RenderTexture;
+
+// Integer render target receiving the object ids and instance indices of the drawables
+// built with a pick module, to find what is under the cursor
+pub struct PickTarget {
+    render_texture: RenderTexture,
+}
+
+impl PickTarget {
+    pub const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rg32Uint;
+    pub const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+    pub const BINDING_OBJECT_ID: u32 = 0;
+    pub const SHADER_SNIPPET: &str = include_str!("shaders/picking.wgsl");
+    // Cleared value, so object ids start at 1
+    pub const NO_OBJECT_ID: u32 = 0;
+
+    #[must_use]
+    pub fn new(context: &DrawContext, dimensions: Dimensions) -> Self {
+        Self {
+            render_texture: RenderTexture::with_sample_count(context, dimensions, Self::FORMAT, 1),
+        }
+    }
+
+    #[must_use]
+    pub fn dimensions(&self) -> Dimensions {
+        self.render_texture.dimensions()
+    }
+
+    pub fn render<C>(&self, context: &DrawContext, callback: C)
+    where
+        C: FnOnce(&mut wgpu::RenderPass<'_>),
+    {
+        self.render_texture
+            .render(context, wgpu::Color::TRANSPARENT, callback);
+    }
+
+    // Pixel under a position in normalized device coordinates, None when outside of the target
+    #[must_use]
+    pub fn ndc_to_pixel(&self, ndc: [f32; 2]) -> Option<(u32, u32)> {
+        if !(-1.0..=1.0).contains(&ndc[0]) || !(-1.0..=1.0).contains(&ndc[1]) {
+            return None;
+        }
+        let Dimensions { width, height } = self.dimensions();
+        let to_pixel = |coord: f32, size: u32| {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let pixel = (f64::from(coord) * f64::from(size)) as u32;
+            pixel.min(size - 1)
+        };
+        Some((
+            to_pixel(ndc[0].mul_add(0.5, 0.5), width),
+            to_pixel(ndc[1].mul_add(-0.5, 0.5), height),
+        ))
+    }
+
+    // Returns the object id and instance index rendered at the pixel
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn read_id(&self, context: &DrawContext, pixel: (u32, u32)) -> anyhow::Result<[u32; 2]> {
+        let bytes = context.read_texture_region(
+            self.render_texture.texture(),
+            pixel,
+            Dimensions::new(1, 1),
+        )?;
+        Ok(bytemuck::pod_read_unaligned(&bytes))
+    }
+}
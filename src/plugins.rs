@@ -25,13 +25,20 @@ SOFTWARE.
 use std::any::{Any, TypeId};
 
 use indexmap::IndexMap;
+use winit::dpi::PhysicalPosition;
 use winit::event::{DeviceEvent, KeyEvent, WindowEvent};
+use winit::event_loop::ActiveEventLoop;
+use winit::window::WindowId;
 
 use crate::{DrawContext, EventState, TimeInfo};
 
+pub mod camera_path;
 pub mod canvas;
 #[cfg(feature = "egui")]
 pub mod egui;
+pub mod follow_camera;
+pub mod ground_grid;
+pub mod orbit_controls;
 pub mod scene_3d;
 
 #[allow(unused_variables)]
@@ -40,9 +47,37 @@ pub trait Plugin: Any {
         EventState::default()
     }
     fn on_keyboard_event(&mut self, event: &KeyEvent) {}
+    fn on_cursor_moved(&mut self, position: PhysicalPosition<f64>) {}
+    // Press and release of the left button without dragging, e.g. to pick an object
+    fn on_click(
+        &mut self,
+        draw_context: &DrawContext,
+        position: PhysicalPosition<f64>,
+    ) -> EventState {
+        EventState::default()
+    }
+    // Second click close in time and position to the previous one, after its on_click
+    fn on_double_click(
+        &mut self,
+        draw_context: &DrawContext,
+        position: PhysicalPosition<f64>,
+    ) -> EventState {
+        EventState::default()
+    }
     fn on_window_event(&mut self, event: &WindowEvent) -> EventState {
         EventState::default()
     }
+    // Events of the windows a plugin opened itself, the main window events go to on_window_event
+    fn on_secondary_window_event(
+        &mut self,
+        draw_context: &DrawContext,
+        window_id: WindowId,
+        event: &WindowEvent,
+    ) -> EventState {
+        EventState::default()
+    }
+    // Called once per event loop iteration, where additional windows can be created
+    fn on_about_to_wait(&mut self, draw_context: &DrawContext, event_loop: &ActiveEventLoop) {}
     fn on_update(&mut self, draw_context: &DrawContext, time_info: &TimeInfo) {}
     fn on_render(
         &mut self,
@@ -0,0 +1,150 @@
+/*
+MIT License
+
+Copyright (c) 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::{
+    TimeInfo, cameras::Camera, draw_context::DrawContext, plugins::scene_3d::Scene3D,
+    primitives::curve::Curve,
+};
+use cgmath::Point3;
+use std::time::Duration;
+
+use super::Plugin;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CameraPathLook {
+    // Looks ahead along the path
+    Tangent,
+    Target(Point3<f32>),
+}
+
+// Cinematic camera travelling along a curve in a given duration. The curve is walked by arc
+// length, so the speed stays constant however its control points are spaced.
+pub struct CameraPathPlugin {
+    pub scene: Scene3D,
+    camera: Camera,
+    path: Curve,
+    duration: Duration,
+    elapsed: Duration,
+    look: CameraPathLook,
+    looping: bool,
+}
+
+impl CameraPathPlugin {
+    // The camera starts at the first control point, looking along the path
+    #[must_use]
+    pub fn new(context: &DrawContext, scene: Scene3D, path: Curve, duration: Duration) -> Self {
+        let mut camera = Camera::default();
+        camera.resize_screen(context.surface_dimensions());
+        let mut plugin = Self {
+            scene,
+            camera,
+            path,
+            duration,
+            elapsed: Duration::ZERO,
+            look: CameraPathLook::Tangent,
+            looping: false,
+        };
+        plugin.update_camera();
+        plugin
+    }
+
+    #[must_use]
+    pub fn camera(&self) -> &Camera {
+        &self.camera
+    }
+
+    #[must_use]
+    pub fn path(&self) -> &Curve {
+        &self.path
+    }
+
+    pub fn set_look(&mut self, look: CameraPathLook) {
+        self.look = look;
+        self.update_camera();
+    }
+
+    // Starts over once at the end instead of stopping there
+    pub fn set_looping(&mut self, looping: bool) {
+        self.looping = looping;
+    }
+
+    // Fraction of the path already travelled, in [0, 1]
+    #[must_use]
+    pub fn progress(&self) -> f32 {
+        if self.duration.is_zero() {
+            return 1.0;
+        }
+        (self.elapsed.as_secs_f32() / self.duration.as_secs_f32()).min(1.0)
+    }
+
+    pub fn set_progress(&mut self, progress: f32) {
+        self.elapsed = self.duration.mul_f32(progress.clamp(0.0, 1.0));
+        self.update_camera();
+    }
+
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        !self.looping && self.elapsed >= self.duration
+    }
+
+    pub fn advance(&mut self, delta: Duration) {
+        self.elapsed += delta;
+        if self.elapsed > self.duration {
+            self.elapsed = if self.looping && !self.duration.is_zero() {
+                Duration::from_nanos(
+                    u64::try_from(self.elapsed.as_nanos() % self.duration.as_nanos())
+                        .expect("Remainder should be lower than the duration"),
+                )
+            } else {
+                self.duration
+            };
+        }
+        self.update_camera();
+    }
+
+    fn update_camera(&mut self) {
+        let progress = self.progress();
+        let eye = self.path.evaluate_uniform(progress);
+        let target = match self.look {
+            CameraPathLook::Tangent => eye + self.path.tangent_uniform(progress),
+            CameraPathLook::Target(target) => target,
+        };
+        self.camera.set_look_at(eye, target);
+    }
+}
+
+impl Plugin for CameraPathPlugin {
+    fn on_update(&mut self, _: &DrawContext, time_info: &TimeInfo) {
+        self.advance(time_info.processing_delta);
+    }
+    fn on_render(
+        &mut self,
+        draw_context: &DrawContext,
+        _: &TimeInfo,
+        render_pass: &mut wgpu::RenderPass<'static>,
+    ) {
+        self.scene
+            .render_with_camera(draw_context, &mut self.camera, render_pass);
+    }
+}
@@ -1,11 +1,12 @@
 use anyhow::Result;
 use chrono::{Datelike, Timelike, Utc};
 use wgpu::ShaderModule;
+use winit::dpi::PhysicalPosition;
 use winit::event::DeviceEvent;
 
 use crate::{
-    BindingSlot, DrawContext, DrawModeParams, Drawable, DrawableBuilder, EventState, TimeInfo,
-    Uniform,
+    BindingSlot, DrawContext, DrawModeParams, Drawable, DrawableBuilder, EventState, FrameUniform,
+    TimeInfo, Uniform,
 };
 
 use super::Plugin;
@@ -20,23 +21,39 @@ ShaderToy variables:
 - [X] uniform float iTime;
 - [X] uniform float iTimeDelta;
 - [X] uniform float iFrame;
-- [X] uniform vec3 iResolution;
+- [X] uniform vec3 iResolution; (physical pixels by default, see ResolutionConvention)
 - [ ] uniform vec4 iMouse;
 - [X] uniform vec4 iDate;
 - [ ] uniform float iSampleRate;
 - [ ] uniform float iChannelTime[4];
 - [ ] uniform vec3 iChannelResolution[4];
 - [ ] uniform samplerXX iChanneli;
+
+Additional variables:
+
+- binding 6: pixels_per_point, the window scale factor, to convert between both conventions
 */
 
+// Unit in which iResolution is reported to the fragment shader
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ResolutionConvention {
+    // Framebuffer size, matching @builtin(position)
+    #[default]
+    Physical,
+    // Size in logical points, matching the egui overlay
+    Logical,
+}
+
 pub struct CanvasPlugin {
     canvas: Drawable,
-    u_time: Uniform<f32>,
-    u_time_delta: Uniform<f32>,
-    u_frame: Uniform<f32>,
-    u_resolution: Uniform<[f32; 3]>,
+    u_time: FrameUniform<f32>,
+    u_time_delta: FrameUniform<f32>,
+    u_frame: FrameUniform<f32>,
+    u_resolution: FrameUniform<[f32; 3]>,
     u_mouse: Uniform<[f32; 4]>,
-    u_date: Uniform<[f32; 4]>,
+    u_date: FrameUniform<[f32; 4]>,
+    u_pixels_per_point: FrameUniform<f32>,
+    resolution_convention: ResolutionConvention,
 }
 
 impl CanvasPlugin {
@@ -45,12 +62,13 @@ impl CanvasPlugin {
         fragment_shader: &ShaderModule,
         uniforms: &[BindingSlot],
     ) -> Result<Self> {
-        let u_time = Uniform::new(draw_context, 0f32);
-        let u_time_delta = Uniform::new(draw_context, 0f32);
-        let u_frame = Uniform::new(draw_context, 0f32);
-        let u_resolution = Uniform::new(draw_context, [0f32; 3]);
+        let u_time = FrameUniform::new(draw_context, 0f32);
+        let u_time_delta = FrameUniform::new(draw_context, 0f32);
+        let u_frame = FrameUniform::new(draw_context, 0f32);
+        let u_resolution = FrameUniform::new(draw_context, [0f32; 3]);
         let u_mouse = Uniform::new(draw_context, [0f32; 4]);
-        let u_date = Uniform::new(draw_context, [0f32; 4]);
+        let u_date = FrameUniform::new(draw_context, [0f32; 4]);
+        let u_pixels_per_point = FrameUniform::new(draw_context, 1f32);
         let shader_module = &draw_context.create_shader_module(CANVAS_STATIC_SHADER);
         let mut drawable_builder = DrawableBuilder::new(
             draw_context,
@@ -94,7 +112,13 @@ impl CanvasPlugin {
                 bind_group: 0,
                 resource: &u_date,
             })
-            .expect("Bind group 0 and binding 5 should not have been already taken.");
+            .expect("Bind group 0 and binding 5 should not have been already taken.")
+            .add_binding_slot(&BindingSlot {
+                binding: 6,
+                bind_group: 0,
+                resource: &u_pixels_per_point,
+            })
+            .expect("Bind group 0 and binding 6 should not have been already taken.");
         for uniform in uniforms {
             drawable_builder.add_binding_slot(&BindingSlot {
                 binding: uniform.binding,
@@ -111,24 +135,59 @@ impl CanvasPlugin {
             u_resolution,
             u_mouse,
             u_date,
+            u_pixels_per_point,
+            resolution_convention: ResolutionConvention::default(),
         })
     }
+
+    pub fn set_resolution_convention(&mut self, convention: ResolutionConvention) -> &mut Self {
+        self.resolution_convention = convention;
+        self
+    }
+
+    #[must_use]
+    pub fn resolution_convention(&self) -> ResolutionConvention {
+        self.resolution_convention
+    }
+
+    // Value of iResolution for the current surface, following the configured convention
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn resolution(&self, draw_context: &DrawContext) -> [f32; 3] {
+        let dimensions = match self.resolution_convention {
+            ResolutionConvention::Physical => draw_context.surface_dimensions(),
+            ResolutionConvention::Logical => draw_context.logical_dimensions(),
+        };
+        [
+            dimensions.width as f32,
+            dimensions.height as f32,
+            dimensions.surface_ratio(),
+        ]
+    }
 }
 
 impl Plugin for CanvasPlugin {
     fn on_mouse_event(&mut self, event: &DeviceEvent) -> EventState {
         // TODO Actually, behavior depends on if button is pressed ; probably requires better mouse event API
         if let DeviceEvent::MouseMotion { delta } = event {
+            let [_, _, x, y] = *self.u_mouse.read_uniform();
             #[allow(clippy::cast_possible_truncation)]
             self.u_mouse
-                .write_uniform([delta.0 as f32, delta.1 as f32, 0.0, 0.0]);
+                .write_uniform([delta.0 as f32, delta.1 as f32, x, y]);
         }
         EventState::default()
     }
 
+    // Motion delta in xy, cursor position in window coordinates in zw
+    fn on_cursor_moved(&mut self, position: PhysicalPosition<f64>) {
+        let [delta_x, delta_y, _, _] = *self.u_mouse.read_uniform();
+        #[allow(clippy::cast_possible_truncation)]
+        self.u_mouse
+            .write_uniform([delta_x, delta_y, position.x as f32, position.y as f32]);
+    }
+
     #[allow(clippy::cast_precision_loss)]
     fn on_update(&mut self, draw_context: &DrawContext, time_info: &TimeInfo) {
-        let dimensions = draw_context.surface_dimensions();
         let now = Utc::now();
         let year = now.year() as f32;
         let month = now.month() as f32;
@@ -137,16 +196,16 @@ impl Plugin for CanvasPlugin {
             now.num_seconds_from_midnight() as f32 + now.nanosecond() as f32 / 1_000_000_000.0;
 
         self.u_time
-            .write_uniform(time_info.init_start.elapsed().as_secs_f32());
+            .write_uniform(time_info.animation_time.as_secs_f32());
         self.u_time_delta
-            .write_uniform(time_info.processing_delta.as_secs_f32());
+            .write_uniform(time_info.animation_delta.as_secs_f32());
         self.u_frame
             .write_uniform(self.u_frame.read_uniform() + 1.0);
-        self.u_resolution.write_uniform([
-            dimensions.width as f32,
-            dimensions.height as f32,
-            dimensions.surface_ratio(),
-        ]);
+        self.u_resolution
+            .write_uniform(self.resolution(draw_context));
+        #[allow(clippy::cast_possible_truncation)]
+        self.u_pixels_per_point
+            .write_uniform(draw_context.scale_factor() as f32);
         self.u_date
             .write_uniform([year, month, day, seconds_since_midnight]);
     }
@@ -1,134 +1,369 @@
 use std::sync::Arc;
 
 use crate::{EventState, draw_context::DrawContext};
-use winit::window::Window;
+use log::{debug, warn};
+use viewport::{DetachedViewport, ViewportRequest};
+use web_time::Instant;
+use winit::event::WindowEvent;
+use winit::event_loop::ActiveEventLoop;
+use winit::window::{Window, WindowId};
 
 use super::Plugin;
 
+mod viewport;
+
+#[allow(clippy::large_enum_variant)]
 pub enum EquiPlugin {
-    NoWindow(egui::Context),
+    NoWindow(EguiPluginHeadless),
     WithWindow(EguiPluginWithWindow),
 }
 
 pub struct EguiPluginWithWindow {
     egui_state: egui_winit::State,
-    egui_renderer: egui_wgpu::Renderer,
+    egui_renderer: EguiRenderer,
     pixels_per_point: f32,
     window: Arc<Window>,
+    viewport_request: Option<ViewportRequest>,
+    detached_viewport: Option<DetachedViewport>,
+}
+
+// Without a window, the egui input is synthesized from the draw target size,
+// so the UI can still be rendered into the texture target.
+pub struct EguiPluginHeadless {
+    egui_context: egui::Context,
+    egui_renderer: EguiRenderer,
+    pixels_per_point: f32,
+    size_in_pixels: [u32; 2],
+    max_texture_side: usize,
+    init_start: Instant,
+    pass_started: bool,
+    viewport_request: Option<ViewportRequest>,
+}
+
+struct EguiRenderer {
+    renderer: egui_wgpu::Renderer,
+    sample_count: u32,
+    depth_format: wgpu::TextureFormat,
+}
+
+impl EguiRenderer {
+    fn new(draw_context: &DrawContext) -> Self {
+        let sample_count = draw_context.multisample_config.get_multisample_count();
+        let depth_format = draw_context.depth_texture.format();
+        Self {
+            renderer: Self::create_renderer(draw_context, sample_count, depth_format),
+            sample_count,
+            depth_format,
+        }
+    }
+
+    fn create_renderer(
+        draw_context: &DrawContext,
+        sample_count: u32,
+        depth_format: wgpu::TextureFormat,
+    ) -> egui_wgpu::Renderer {
+        egui_wgpu::Renderer::new(
+            &draw_context.device,
+            draw_context.surface_config.format,
+            Some(depth_format),
+            sample_count,
+            true,
+        )
+    }
+
+    /// Rebuild the egui renderer if the sample count or the depth format of the
+    /// draw context changed since it was created, as its pipeline must match the
+    /// render pass it draws into.
+    ///
+    /// The font atlas is uploaded again to the new renderer; textures registered
+    /// directly on the previous renderer are lost and must be registered again.
+    fn reconfigure(&mut self, egui_context: &egui::Context, draw_context: &DrawContext) {
+        let sample_count = draw_context.multisample_config.get_multisample_count();
+        let depth_format = draw_context.depth_texture.format();
+        if sample_count == self.sample_count && depth_format == self.depth_format {
+            return;
+        }
+        debug!("Rebuilding egui renderer for {sample_count} sample(s) and {depth_format:?} depth");
+        self.renderer = Self::create_renderer(draw_context, sample_count, depth_format);
+        self.sample_count = sample_count;
+        self.depth_format = depth_format;
+        upload_font_atlas(&mut self.renderer, egui_context, draw_context);
+    }
+
+    fn draw(
+        &mut self,
+        egui_context: &egui::Context,
+        shapes: Vec<egui::epaint::ClippedShape>,
+        draw_context: &DrawContext,
+        screen_descriptor: &egui_wgpu::ScreenDescriptor,
+        render_pass: &mut wgpu::RenderPass<'static>,
+    ) {
+        let (device, queue) = (&draw_context.device, &draw_context.queue);
+        let tris = egui_context.tessellate(shapes, screen_descriptor.pixels_per_point);
+        let mut encoder =
+            device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        self.renderer
+            .update_buffers(device, queue, &mut encoder, &tris, screen_descriptor);
+        self.renderer.render(render_pass, &tris, screen_descriptor);
+    }
+}
+
+// A new renderer has none of the textures egui already sent, the font atlas is needed to draw text
+fn upload_font_atlas(
+    renderer: &mut egui_wgpu::Renderer,
+    egui_context: &egui::Context,
+    draw_context: &DrawContext,
+) {
+    let font_image = egui_context.fonts(|fonts| fonts.texture_atlas().lock().image().clone());
+    renderer.update_texture(
+        &draw_context.device,
+        &draw_context.queue,
+        egui::TextureId::default(),
+        &egui::epaint::ImageDelta::full(font_image, egui::epaint::TextureAtlas::texture_options()),
+    );
+}
+
+// Texture deltas are produced by the pass that needed them, but shared by all the renderers
+fn update_textures(
+    renderers: &mut [&mut egui_wgpu::Renderer],
+    draw_context: &DrawContext,
+    textures_delta: &egui::TexturesDelta,
+) {
+    for renderer in renderers {
+        for (id, image_delta) in &textures_delta.set {
+            renderer.update_texture(&draw_context.device, &draw_context.queue, *id, image_delta);
+        }
+    }
+}
+
+fn free_textures(renderers: &mut [&mut egui_wgpu::Renderer], textures_delta: &egui::TexturesDelta) {
+    for renderer in renderers {
+        for id in &textures_delta.free {
+            renderer.free_texture(id);
+        }
+    }
 }
 
 impl EquiPlugin {
     pub fn new(draw_context: &DrawContext) -> Self {
         let Some(window) = draw_context.window.as_ref() else {
-            return Self::NoWindow(egui::Context::default());
+            return Self::NoWindow(EguiPluginHeadless {
+                egui_context: Self::new_egui_context(),
+                egui_renderer: EguiRenderer::new(draw_context),
+                #[allow(clippy::cast_possible_truncation)]
+                pixels_per_point: draw_context.scale_factor() as f32,
+                size_in_pixels: [
+                    draw_context.surface_config.width,
+                    draw_context.surface_config.height,
+                ],
+                max_texture_side: draw_context.device.limits().max_texture_dimension_2d as usize,
+                init_start: Instant::now(),
+                pass_started: false,
+                viewport_request: None,
+            });
         };
         let window = Arc::clone(window);
         let egui_state = egui_winit::State::new(
-            egui::Context::default(),
+            Self::new_egui_context(),
             egui::ViewportId::default(),
             &window,
             None,
             None,
             None,
         );
-        let egui_renderer = egui_wgpu::Renderer::new(
-            &draw_context.device,
-            draw_context.surface_config.format,
-            Some(draw_context.depth_texture.format()),
-            draw_context.multisample_config.get_multisample_count(),
-            true,
-        );
         Self::WithWindow(EguiPluginWithWindow {
             egui_state,
-            egui_renderer,
+            egui_renderer: EguiRenderer::new(draw_context),
             #[allow(clippy::cast_possible_truncation)]
             pixels_per_point: window.scale_factor() as f32,
             window,
+            viewport_request: None,
+            detached_viewport: None,
         })
     }
+    // Dark theme by default, whatever the system theme is
+    fn new_egui_context() -> egui::Context {
+        let egui_context = egui::Context::default();
+        egui_context.set_theme(egui::Theme::Dark);
+        egui_context
+    }
+    pub fn set_theme(&mut self, theme: egui::Theme) {
+        self.egui_context().set_theme(theme);
+    }
+    /// Replace the style of the current theme.
+    pub fn set_style(&mut self, style: egui::Style) {
+        self.egui_context().set_style(style);
+    }
     pub fn set_pixels_per_point(&mut self, pixels_per_point: f32) {
         match self {
             Self::WithWindow(egui_support) => {
                 egui_support.pixels_per_point = pixels_per_point;
             }
-            Self::NoWindow(_) => {}
+            Self::NoWindow(egui_support) => {
+                egui_support.pixels_per_point = pixels_per_point;
+            }
         }
     }
     #[must_use]
     pub fn get_pixels_per_point(&self) -> f32 {
         match self {
             Self::WithWindow(egui_support) => egui_support.pixels_per_point,
-            Self::NoWindow(_) => 1.0,
+            Self::NoWindow(egui_support) => egui_support.pixels_per_point,
         }
     }
     #[must_use]
     pub fn egui_context(&self) -> &egui::Context {
         match self {
-            Self::NoWindow(ctx) => ctx,
+            Self::NoWindow(egui_support) => &egui_support.egui_context,
             Self::WithWindow(egui_support) => egui_support.egui_state.egui_ctx(),
         }
     }
 
+    /// Show egui deferred viewports in their own window instead of embedding them.
+    ///
+    /// Windows are opened from the event loop, so the plugin must be registered in the
+    /// plugin registry. Only one detached viewport is supported at a time. Without a
+    /// window, the requested viewport is only tracked, as no other window can be opened.
+    pub fn set_detachable_viewports(&mut self, enabled: bool) {
+        self.egui_context().set_embed_viewports(!enabled);
+    }
+
+    /// Deferred viewport shown, or about to be shown, in its own window.
+    #[must_use]
+    pub fn detached_viewport(&self) -> Option<egui::ViewportId> {
+        match self {
+            Self::WithWindow(egui_support) => egui_support
+                .detached_viewport
+                .as_ref()
+                .map(|detached| detached.id)
+                .or_else(|| egui_support.viewport_request.as_ref().map(|r| r.id)),
+            Self::NoWindow(egui_support) => egui_support.viewport_request.as_ref().map(|r| r.id),
+        }
+    }
+
     pub fn draw<F>(&mut self, run_ui: F)
     where
         F: FnOnce(&egui::Context),
     {
-        let Self::WithWindow(egui_support) = self else {
-            return;
-        };
-        Self::begin_frame(egui_support);
-        let egui_context = egui_support.egui_state.egui_ctx();
-        run_ui(egui_context);
-    }
-
-    fn begin_frame(egui_support: &mut EguiPluginWithWindow) {
-        let raw_input = egui_support
-            .egui_state
-            .take_egui_input(&egui_support.window);
-        egui_support.egui_state.egui_ctx().begin_pass(raw_input);
+        match self {
+            Self::WithWindow(egui_support) => {
+                let raw_input = egui_support
+                    .egui_state
+                    .take_egui_input(&egui_support.window);
+                egui_support.egui_state.egui_ctx().begin_pass(raw_input);
+            }
+            Self::NoWindow(egui_support) => {
+                let raw_input = egui_support.raw_input();
+                egui_support.egui_context.begin_pass(raw_input);
+                egui_support.pass_started = true;
+            }
+        }
+        run_ui(self.egui_context());
     }
+}
 
-    fn end_frame_and_draw(
-        egui_support: &mut EguiPluginWithWindow,
-        device: &wgpu::Device,
-        queue: &wgpu::Queue,
+impl EguiPluginWithWindow {
+    fn render(
+        &mut self,
+        draw_context: &DrawContext,
         screen_descriptor: &egui_wgpu::ScreenDescriptor,
-        encoder: &mut wgpu::CommandEncoder,
         render_pass: &mut wgpu::RenderPass<'static>,
     ) {
-        // TODO We must call begin_frame before calling end_frame_and_draw, otherwise panic
-        egui_support
-            .egui_state
-            .egui_ctx()
-            .set_pixels_per_point(screen_descriptor.pixels_per_point);
-        let full_output = egui_support.egui_state.egui_ctx().end_pass();
-
-        egui_support
-            .egui_state
-            .handle_platform_output(&egui_support.window, full_output.platform_output);
-
-        let tris = egui_support.egui_state.egui_ctx().tessellate(
+        // TODO We must call draw before calling on_render, otherwise panic
+        let egui_context = self.egui_state.egui_ctx().clone();
+        self.egui_renderer.reconfigure(&egui_context, draw_context);
+        egui_context.set_pixels_per_point(screen_descriptor.pixels_per_point);
+        let mut full_output = egui_context.end_pass();
+        self.egui_state.handle_platform_output(
+            &self.window,
+            std::mem::take(&mut full_output.platform_output),
+        );
+        self.update_viewport_request(&full_output.viewport_output);
+        let mut detached_output = self
+            .detached_viewport
+            .as_mut()
+            .map(|detached| detached.run(&egui_context));
+
+        let textures_deltas = [
+            Some(std::mem::take(&mut full_output.textures_delta)),
+            detached_output
+                .as_mut()
+                .map(|output| std::mem::take(&mut output.textures_delta)),
+        ];
+        for textures_delta in textures_deltas.iter().flatten() {
+            update_textures(&mut self.renderers(), draw_context, textures_delta);
+        }
+        self.egui_renderer.draw(
+            &egui_context,
             full_output.shapes,
-            egui_support.egui_state.egui_ctx().pixels_per_point(),
+            draw_context,
+            screen_descriptor,
+            render_pass,
         );
-        for (id, image_delta) in &full_output.textures_delta.set {
-            egui_support
-                .egui_renderer
-                .update_texture(device, queue, *id, image_delta);
+        if let (Some(detached), Some(detached_output)) =
+            (self.detached_viewport.as_mut(), detached_output)
+        {
+            detached.draw(&egui_context, detached_output.shapes, draw_context);
         }
-        egui_support
-            .egui_renderer
-            .update_buffers(device, queue, encoder, &tris, screen_descriptor);
+        for textures_delta in textures_deltas.iter().flatten() {
+            free_textures(&mut self.renderers(), textures_delta);
+        }
+    }
 
-        egui_support
-            .egui_renderer
-            .render(render_pass, &tris, screen_descriptor);
-        for x in &full_output.textures_delta.free {
-            egui_support.egui_renderer.free_texture(x);
+    fn renderers(&mut self) -> Vec<&mut egui_wgpu::Renderer> {
+        let mut renderers = vec![&mut self.egui_renderer.renderer];
+        renderers.extend(
+            self.detached_viewport
+                .as_mut()
+                .map(DetachedViewport::renderer_mut),
+        );
+        renderers
+    }
+
+    // The detached window is closed as soon as the UI code stops showing its viewport
+    fn update_viewport_request(
+        &mut self,
+        viewport_output: &egui::viewport::ViewportIdMap<egui::viewport::ViewportOutput>,
+    ) {
+        let current = self.detached_viewport.as_ref().map(|detached| detached.id);
+        let request = ViewportRequest::from_output(viewport_output, current);
+        match (self.detached_viewport.as_mut(), request) {
+            (Some(detached), Some(request)) if detached.id == request.id => {
+                detached.update(request);
+                self.viewport_request = None;
+            }
+            (detached, request) => {
+                if detached.is_some() {
+                    debug!("Closing detached egui viewport");
+                    self.detached_viewport = None;
+                }
+                self.viewport_request = request;
+            }
         }
     }
 }
 
+impl EguiPluginHeadless {
+    fn raw_input(&self) -> egui::RawInput {
+        #[allow(clippy::cast_precision_loss)]
+        let size_in_points =
+            egui::vec2(self.size_in_pixels[0] as f32, self.size_in_pixels[1] as f32)
+                / self.pixels_per_point;
+        let mut raw_input = egui::RawInput {
+            screen_rect: Some(egui::Rect::from_min_size(egui::Pos2::ZERO, size_in_points)),
+            max_texture_side: Some(self.max_texture_side),
+            time: Some(self.init_start.elapsed().as_secs_f64()),
+            ..Default::default()
+        };
+        raw_input
+            .viewports
+            .entry(egui::ViewportId::ROOT)
+            .or_default()
+            .native_pixels_per_point = Some(self.pixels_per_point);
+        raw_input
+    }
+}
+
 impl Plugin for EquiPlugin {
     fn on_window_event(&mut self, event: &winit::event::WindowEvent) -> EventState {
         match self {
@@ -143,6 +378,38 @@ impl Plugin for EquiPlugin {
             Self::NoWindow(_) => EventState::default(),
         }
     }
+    fn on_secondary_window_event(
+        &mut self,
+        draw_context: &DrawContext,
+        window_id: WindowId,
+        event: &WindowEvent,
+    ) -> EventState {
+        let Self::WithWindow(egui_support) = self else {
+            return EventState::default();
+        };
+        match egui_support.detached_viewport.as_mut() {
+            Some(detached) if detached.window_id() == window_id => {
+                detached.on_window_event(draw_context, event)
+            }
+            _ => EventState::default(),
+        }
+    }
+    fn on_about_to_wait(&mut self, draw_context: &DrawContext, event_loop: &ActiveEventLoop) {
+        let Self::WithWindow(egui_support) = self else {
+            return;
+        };
+        let Some(request) = egui_support.viewport_request.take() else {
+            return;
+        };
+        let egui_context = egui_support.egui_state.egui_ctx();
+        match DetachedViewport::open(request, egui_context, draw_context, event_loop) {
+            Ok(detached) => egui_support.detached_viewport = Some(detached),
+            Err(err) => {
+                warn!("Cannot open a window for the egui viewport, it is embedded instead: {err}");
+                egui_context.set_embed_viewports(true);
+            }
+        }
+    }
     fn on_mouse_event(&mut self, _event: &winit::event::DeviceEvent) -> EventState {
         if self.egui_context().is_using_pointer() {
             return EventState { processed: true };
@@ -155,26 +422,46 @@ impl Plugin for EquiPlugin {
         _: &crate::TimeInfo,
         render_pass: &mut wgpu::RenderPass<'static>,
     ) {
-        let Self::WithWindow(egui_support) = self else {
-            return;
-        };
         let screen_descriptor = egui_wgpu::ScreenDescriptor {
             size_in_pixels: [
                 draw_context.surface_config.width,
                 draw_context.surface_config.height,
             ],
-            pixels_per_point: egui_support.pixels_per_point,
+            pixels_per_point: self.get_pixels_per_point(),
         };
-        let mut encoder = draw_context
-            .device
-            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
-        Self::end_frame_and_draw(
-            egui_support,
-            &draw_context.device,
-            &draw_context.queue,
-            &screen_descriptor,
-            &mut encoder,
-            render_pass,
-        );
+        match self {
+            Self::WithWindow(egui_support) => {
+                egui_support.render(draw_context, &screen_descriptor, render_pass);
+            }
+            Self::NoWindow(egui_support) => {
+                egui_support.size_in_pixels = screen_descriptor.size_in_pixels;
+                if !std::mem::take(&mut egui_support.pass_started) {
+                    return;
+                }
+                let egui_context = egui_support.egui_context.clone();
+                egui_support
+                    .egui_renderer
+                    .reconfigure(&egui_context, draw_context);
+                egui_context.set_pixels_per_point(screen_descriptor.pixels_per_point);
+                let full_output = egui_context.end_pass();
+                egui_support.viewport_request = ViewportRequest::from_output(
+                    &full_output.viewport_output,
+                    egui_support.viewport_request.as_ref().map(|r| r.id),
+                );
+                let renderers = &mut [&mut egui_support.egui_renderer.renderer];
+                update_textures(renderers, draw_context, &full_output.textures_delta);
+                egui_support.egui_renderer.draw(
+                    &egui_context,
+                    full_output.shapes,
+                    draw_context,
+                    &screen_descriptor,
+                    render_pass,
+                );
+                free_textures(
+                    &mut [&mut egui_support.egui_renderer.renderer],
+                    &full_output.textures_delta,
+                );
+            }
+        }
     }
 }
@@ -0,0 +1,230 @@
+/*
+MIT License
+
+Copyright (c) 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use std::sync::Arc;
+
+use egui::ViewportEvent;
+use egui::viewport::{
+    DeferredViewportUiCallback, ViewportBuilder, ViewportId, ViewportIdMap, ViewportOutput,
+};
+use log::warn;
+use winit::event::WindowEvent;
+use winit::event_loop::ActiveEventLoop;
+use winit::window::{Window, WindowId};
+
+use super::upload_font_atlas;
+use crate::{EventState, draw_context::DrawContext};
+
+// Deferred viewport egui asks to show in its own window
+pub(super) struct ViewportRequest {
+    pub(super) id: ViewportId,
+    builder: ViewportBuilder,
+    ui_callback: Arc<DeferredViewportUiCallback>,
+}
+
+impl ViewportRequest {
+    // Only one detached viewport is supported, the current one is kept if still requested
+    pub(super) fn from_output(
+        viewport_output: &ViewportIdMap<ViewportOutput>,
+        current: Option<ViewportId>,
+    ) -> Option<Self> {
+        let deferred: Vec<_> = viewport_output
+            .iter()
+            .filter_map(|(id, output)| output.viewport_ui_cb.as_ref().map(|cb| (*id, output, cb)))
+            .collect();
+        let (id, output, ui_callback) = deferred
+            .iter()
+            .find(|(id, ..)| Some(*id) == current)
+            .or_else(|| deferred.first())?;
+        if current != Some(*id) && deferred.len() > 1 {
+            warn!("Only one detached egui viewport is supported, the others are not shown");
+        }
+        Some(Self {
+            id: *id,
+            builder: output.builder.clone(),
+            ui_callback: Arc::clone(ui_callback),
+        })
+    }
+}
+
+pub(super) struct DetachedViewport {
+    pub(super) id: ViewportId,
+    window: Arc<Window>,
+    surface: wgpu::Surface<'static>,
+    surface_config: wgpu::SurfaceConfiguration,
+    egui_state: egui_winit::State,
+    renderer: egui_wgpu::Renderer,
+    ui_callback: Arc<DeferredViewportUiCallback>,
+    close_requested: bool,
+}
+
+impl DetachedViewport {
+    pub(super) fn open(
+        request: ViewportRequest,
+        egui_context: &egui::Context,
+        draw_context: &DrawContext,
+        event_loop: &ActiveEventLoop,
+    ) -> anyhow::Result<Self> {
+        let window = Arc::new(egui_winit::create_window(
+            egui_context,
+            event_loop,
+            &request.builder,
+        )?);
+        let (surface, surface_config) = draw_context.create_window_surface(Arc::clone(&window))?;
+        let egui_state = egui_winit::State::new(
+            egui_context.clone(),
+            request.id,
+            &window,
+            #[allow(clippy::cast_possible_truncation)]
+            Some(window.scale_factor() as f32),
+            None,
+            Some(draw_context.device.limits().max_texture_dimension_2d as usize),
+        );
+        let mut renderer =
+            egui_wgpu::Renderer::new(&draw_context.device, surface_config.format, None, 1, true);
+        upload_font_atlas(&mut renderer, egui_context, draw_context);
+        Ok(Self {
+            id: request.id,
+            window,
+            surface,
+            surface_config,
+            egui_state,
+            renderer,
+            ui_callback: request.ui_callback,
+            close_requested: false,
+        })
+    }
+
+    pub(super) fn window_id(&self) -> WindowId {
+        self.window.id()
+    }
+
+    // The UI callback is given again by egui each frame
+    pub(super) fn update(&mut self, request: ViewportRequest) {
+        self.ui_callback = request.ui_callback;
+    }
+
+    pub(super) fn renderer_mut(&mut self) -> &mut egui_wgpu::Renderer {
+        &mut self.renderer
+    }
+
+    pub(super) fn on_window_event(
+        &mut self,
+        draw_context: &DrawContext,
+        event: &WindowEvent,
+    ) -> EventState {
+        match event {
+            // Closing is left to the UI code, which stops showing the viewport when it wants to
+            WindowEvent::CloseRequested => {
+                self.close_requested = true;
+            }
+            WindowEvent::Resized(size) => {
+                self.surface_config.width = size.width.max(1);
+                self.surface_config.height = size.height.max(1);
+                self.surface
+                    .configure(&draw_context.device, &self.surface_config);
+            }
+            _ => {
+                let event_response = self.egui_state.on_window_event(&self.window, event);
+                if event_response.repaint {
+                    self.window.request_redraw();
+                }
+            }
+        }
+        EventState { processed: true }
+    }
+
+    pub(super) fn run(&mut self, egui_context: &egui::Context) -> egui::FullOutput {
+        let mut raw_input = self.egui_state.take_egui_input(&self.window);
+        if std::mem::take(&mut self.close_requested) {
+            raw_input
+                .viewports
+                .entry(self.id)
+                .or_default()
+                .events
+                .push(ViewportEvent::Close);
+        }
+        let ui_callback = Arc::clone(&self.ui_callback);
+        let mut full_output = egui_context.run(raw_input, |ctx| ui_callback(ctx));
+        self.egui_state.handle_platform_output(
+            &self.window,
+            std::mem::take(&mut full_output.platform_output),
+        );
+        full_output
+    }
+
+    pub(super) fn draw(
+        &mut self,
+        egui_context: &egui::Context,
+        shapes: Vec<egui::epaint::ClippedShape>,
+        draw_context: &DrawContext,
+    ) {
+        let frame = match self.surface.get_current_texture() {
+            Ok(frame) => frame,
+            Err(err) => {
+                warn!("Detached egui viewport cannot be drawn: {err}");
+                return;
+            }
+        };
+        let view = frame
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        let screen_descriptor = egui_wgpu::ScreenDescriptor {
+            size_in_pixels: [self.surface_config.width, self.surface_config.height],
+            pixels_per_point: egui_winit::pixels_per_point(egui_context, &self.window),
+        };
+        let tris = egui_context.tessellate(shapes, screen_descriptor.pixels_per_point);
+        let mut encoder = draw_context
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        let mut command_buffers = self.renderer.update_buffers(
+            &draw_context.device,
+            &draw_context.queue,
+            &mut encoder,
+            &tris,
+            &screen_descriptor,
+        );
+        {
+            let mut render_pass = encoder
+                .begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("Detached egui viewport"),
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view: &view,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                            store: wgpu::StoreOp::Store,
+                        },
+                    })],
+                    ..Default::default()
+                })
+                .forget_lifetime();
+            self.renderer
+                .render(&mut render_pass, &tris, &screen_descriptor);
+        }
+        command_buffers.push(encoder.finish());
+        draw_context.submit(command_buffers);
+        frame.present();
+    }
+}
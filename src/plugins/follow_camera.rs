@@ -0,0 +1,160 @@
+/*
+MIT License
+
+Copyright (c) 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::{
+    TimeInfo, cameras::Camera, draw_context::DrawContext, plugins::scene_3d::Scene3D,
+    primitives::Transforms,
+};
+use cgmath::{EuclideanSpace, InnerSpace, Point3, Transform, Vector3};
+use std::{cell::RefCell, rc::Rc};
+
+use super::Plugin;
+
+// Third person camera chasing the origin of a target transform, e.g. a cube moved by the
+// scenario. The eye and the look-at point ease toward their goals at each update, with an
+// exponential damping independent of the frame rate, and never lag further than max_lag.
+pub struct FollowCameraPlugin {
+    pub scene: Scene3D,
+    camera: Camera,
+    target: Rc<RefCell<dyn Transforms>>,
+    // World space offsets from the target position to the eye and to the look-at point
+    offset: Vector3<f32>,
+    look_offset: Vector3<f32>,
+    damping: f32,
+    max_lag: Option<f32>,
+    eye: Point3<f32>,
+    look_at: Point3<f32>,
+}
+
+impl FollowCameraPlugin {
+    // Fraction of the remaining distance covered in a second is 1 - exp(-damping)
+    pub const DEFAULT_DAMPING: f32 = 5.0;
+
+    // The scene is created first, its uniforms being needed to build the target. The camera
+    // starts at its goal position, without easing in.
+    #[must_use]
+    pub fn new(
+        context: &DrawContext,
+        scene: Scene3D,
+        target: Rc<RefCell<dyn Transforms>>,
+        offset: Vector3<f32>,
+    ) -> Self {
+        let mut camera = Camera::default();
+        camera.resize_screen(context.surface_dimensions());
+        let mut plugin = Self {
+            scene,
+            camera,
+            target,
+            offset,
+            look_offset: Vector3::new(0.0, 0.0, 0.0),
+            damping: Self::DEFAULT_DAMPING,
+            max_lag: None,
+            eye: Point3::origin(),
+            look_at: Point3::origin(),
+        };
+        plugin.snap();
+        plugin
+    }
+
+    #[must_use]
+    pub fn camera(&self) -> &Camera {
+        &self.camera
+    }
+
+    #[must_use]
+    pub fn target_position(&self) -> Point3<f32> {
+        self.target
+            .borrow()
+            .get_transform()
+            .transform_point(Point3::origin())
+    }
+
+    pub fn set_target(&mut self, target: Rc<RefCell<dyn Transforms>>) {
+        self.target = target;
+    }
+
+    pub fn set_offset(&mut self, offset: Vector3<f32>) {
+        self.offset = offset;
+    }
+
+    pub fn set_look_offset(&mut self, look_offset: Vector3<f32>) {
+        self.look_offset = look_offset;
+    }
+
+    // Higher values follow more tightly, 0 freezes the camera
+    pub fn set_damping(&mut self, damping: f32) {
+        self.damping = damping.max(0.0);
+    }
+
+    // Distance from its goal beyond which the camera is dragged along, for fast targets
+    pub fn set_max_lag(&mut self, max_lag: Option<f32>) {
+        self.max_lag = max_lag;
+    }
+
+    // Moves the camera to its goal at once, e.g. after teleporting the target
+    pub fn snap(&mut self) {
+        let (eye, look_at) = self.goals();
+        self.eye = eye;
+        self.look_at = look_at;
+        self.camera.set_look_at(self.eye, self.look_at);
+    }
+
+    pub fn follow(&mut self, delta_seconds: f32) {
+        let (eye_goal, look_at_goal) = self.goals();
+        let ratio = 1.0 - (-self.damping * delta_seconds).exp();
+        self.eye = self.ease(self.eye, eye_goal, ratio);
+        self.look_at = self.ease(self.look_at, look_at_goal, ratio);
+        self.camera.set_look_at(self.eye, self.look_at);
+    }
+
+    fn goals(&self) -> (Point3<f32>, Point3<f32>) {
+        let position = self.target_position();
+        (position + self.offset, position + self.look_offset)
+    }
+
+    fn ease(&self, current: Point3<f32>, goal: Point3<f32>, ratio: f32) -> Point3<f32> {
+        let eased = current + (goal - current) * ratio;
+        match self.max_lag {
+            Some(max_lag) if (goal - eased).magnitude() > max_lag => {
+                goal - (goal - eased).normalize_to(max_lag)
+            }
+            _ => eased,
+        }
+    }
+}
+
+impl Plugin for FollowCameraPlugin {
+    fn on_update(&mut self, _: &DrawContext, time_info: &TimeInfo) {
+        self.follow(time_info.processing_delta.as_secs_f32());
+    }
+    fn on_render(
+        &mut self,
+        draw_context: &DrawContext,
+        _: &TimeInfo,
+        render_pass: &mut wgpu::RenderPass<'static>,
+    ) {
+        self.scene
+            .render_with_camera(draw_context, &mut self.camera, render_pass);
+    }
+}
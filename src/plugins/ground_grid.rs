@@ -0,0 +1,135 @@
+/*
+MIT License
+
+Copyright (c) 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::{
+    BindingSlot, DrawContext, DrawModeParams, Drawable, DrawableBuilder, TimeInfo, Uniform,
+    plugins::scene_3d::Scene3DUniforms,
+};
+
+use super::Plugin;
+
+const GROUND_GRID_SHADER: &str = include_str!("./ground_grid.wgsl");
+
+// Grid on a horizontal plane, around the camera of the scene whose uniforms are given. The lines
+// fade out with the distance to the camera, showing the background behind. Register it after
+// the scene plugin: it is blended over the scene, and hidden by its geometry without hiding it.
+pub struct GroundGridPlugin {
+    grid: Drawable,
+    color: Uniform<[f32; 4]>,
+    params: Uniform<[f32; 4]>,
+}
+
+impl GroundGridPlugin {
+    pub const DEFAULT_COLOR: [f32; 4] = [0.5, 0.5, 0.5, 1.0];
+    pub const DEFAULT_CELL_SIZE: f32 = 1.0;
+    pub const DEFAULT_FADE_DISTANCE: f32 = 50.0;
+
+    pub fn new(context: &DrawContext, scene_uniforms: &Scene3DUniforms) -> Self {
+        let color = Uniform::new(context, Self::DEFAULT_COLOR);
+        let params = Uniform::new(
+            context,
+            [
+                Self::DEFAULT_CELL_SIZE,
+                Self::DEFAULT_FADE_DISTANCE,
+                0.0,
+                0.0,
+            ],
+        );
+        let shader_module = context.create_shader_module(GROUND_GRID_SHADER);
+        let mut drawable_builder = DrawableBuilder::new(
+            context,
+            &shader_module,
+            &shader_module,
+            DrawModeParams::Direct { vertex_count: 6 },
+        );
+        drawable_builder
+            .set_blend_option(wgpu::BlendState::ALPHA_BLENDING)
+            .set_depth_write(false)
+            .set_double_sided(true)
+            .add_binding_slot(&BindingSlot {
+                bind_group: DrawContext::BIND_GROUP_INDEX_CAMERA,
+                binding: Scene3DUniforms::BINDING_CAMERA_MAT,
+                resource: &scene_uniforms.camera_mat,
+            })
+            .expect("Camera bindings should be free on a new drawable")
+            .add_binding_slot(&BindingSlot {
+                bind_group: DrawContext::BIND_GROUP_INDEX_CAMERA,
+                binding: Scene3DUniforms::BINDING_CAMERA_POS,
+                resource: &scene_uniforms.camera_pos,
+            })
+            .expect("Camera bindings should be free on a new drawable")
+            .add_binding_slot(&BindingSlot {
+                bind_group: DrawContext::BIND_GROUP_INDEX_MODEL,
+                binding: 0,
+                resource: &color,
+            })
+            .expect("Grid bindings should be free on a new drawable")
+            .add_binding_slot(&BindingSlot {
+                bind_group: DrawContext::BIND_GROUP_INDEX_MODEL,
+                binding: 1,
+                resource: &params,
+            })
+            .expect("Grid bindings should be free on a new drawable");
+        Self {
+            grid: drawable_builder.build(),
+            color,
+            params,
+        }
+    }
+
+    // Straight RGBA, the alpha being the opacity of the lines close to the camera
+    pub fn set_color(&mut self, color: [f32; 4]) {
+        self.color.write_uniform(color);
+    }
+
+    pub fn set_cell_size(&mut self, cell_size: f32) {
+        self.update_params(|params| params[0] = cell_size.max(f32::EPSILON));
+    }
+
+    // Distance to the camera where the lines are no longer visible, e.g. the far plane
+    pub fn set_fade_distance(&mut self, fade_distance: f32) {
+        self.update_params(|params| params[1] = fade_distance);
+    }
+
+    pub fn set_height(&mut self, height: f32) {
+        self.update_params(|params| params[2] = height);
+    }
+
+    fn update_params(&mut self, update: impl FnOnce(&mut [f32; 4])) {
+        let mut params = *self.params.read_uniform();
+        update(&mut params);
+        self.params.write_uniform(params);
+    }
+}
+
+impl Plugin for GroundGridPlugin {
+    fn on_render(
+        &mut self,
+        _: &DrawContext,
+        _: &TimeInfo,
+        render_pass: &mut wgpu::RenderPass<'static>,
+    ) {
+        self.grid.render(render_pass);
+    }
+}
@@ -0,0 +1,68 @@
+/*
+MIT License
+
+Copyright (c) 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+@group(0) @binding(0)
+var<uniform> camera_mat: mat4x4<f32>;
+@group(0) @binding(1)
+var<uniform> camera_pos: vec3<f32>;
+@group(1) @binding(0)
+var<uniform> grid_color: vec4<f32>;
+// x: cell size, y: fade distance, z: height of the plane
+@group(1) @binding(1)
+var<uniform> grid_params: vec4<f32>;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) world: vec3<f32>,
+};
+
+@vertex
+fn vtx_main(@builtin(vertex_index) vertex: u32) -> VertexOutput {
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(-1.0, -1.0),
+        vec2<f32>(1.0, -1.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(-1.0, -1.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(-1.0, 1.0),
+    );
+    // The quad follows the camera, snapped to the cells so the lines do not move with it
+    let center = floor(camera_pos.xz / grid_params.x) * grid_params.x;
+    let xz = center + corners[vertex] * grid_params.y;
+    let world = vec3<f32>(xz.x, grid_params.z, xz.y);
+    var out: VertexOutput;
+    out.position = camera_mat * vec4<f32>(world, 1.0);
+    out.world = world;
+    return out;
+}
+
+@fragment
+fn frg_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // Distance to the closest line in pixels, from the screen space derivatives of the coordinates
+    let coord = in.world.xz / grid_params.x;
+    let to_line = abs(fract(coord - 0.5) - 0.5) / fwidth(coord);
+    let line = 1.0 - min(min(to_line.x, to_line.y), 1.0);
+    let fade = 1.0 - smoothstep(0.0, grid_params.y, distance(in.world, camera_pos));
+    return vec4<f32>(grid_color.rgb, grid_color.a * line * fade);
+}
@@ -0,0 +1,160 @@
+/*
+MIT License
+
+Copyright (c) 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::{
+    EventState, TimeInfo, cameras::Camera, draw_context::DrawContext, plugins::scene_3d::Scene3D,
+};
+use cgmath::Point3;
+use std::f32::consts::FRAC_PI_2;
+use winit::event::{DeviceEvent, MouseScrollDelta, WindowEvent};
+
+use super::Plugin;
+
+// Alternative to the fly camera of Scene3DPlugin: dragging the mouse turns the camera around
+// the target, and scrolling moves it closer or further. The scene is rendered the same way.
+pub struct OrbitControlsPlugin {
+    pub scene: Scene3D,
+    camera: Camera,
+    target: Point3<f32>,
+    distance: f32,
+    // Angles around the vertical axis and above the horizontal plane, in radians
+    yaw: f32,
+    pitch: f32,
+    rotation_speed: f32,
+    zoom_factor: f32,
+}
+
+impl OrbitControlsPlugin {
+    const DEFAULT_ROTATION_SPEED: f32 = 1.0 / 200.0;
+    // Distance ratio for each line scrolled
+    const DEFAULT_ZOOM_FACTOR: f32 = 1.1;
+    const PIXELS_PER_LINE: f64 = 40.0;
+    const MIN_DISTANCE: f32 = 0.01;
+    // Short of the poles, where the up vector of the view flips
+    const MAX_PITCH: f32 = FRAC_PI_2 - 0.01;
+
+    // Looks at the target from -z, as the default camera does
+    #[must_use]
+    pub fn new(context: &DrawContext, target: Point3<f32>, distance: f32) -> Self {
+        let mut camera = Camera::default();
+        camera.resize_screen(context.surface_dimensions());
+        let mut plugin = Self {
+            scene: Scene3D::new(context),
+            camera,
+            target,
+            distance: distance.max(Self::MIN_DISTANCE),
+            yaw: 0.0,
+            pitch: 0.0,
+            rotation_speed: Self::DEFAULT_ROTATION_SPEED,
+            zoom_factor: Self::DEFAULT_ZOOM_FACTOR,
+        };
+        plugin.update_camera();
+        plugin
+    }
+
+    #[must_use]
+    pub fn camera(&self) -> &Camera {
+        &self.camera
+    }
+
+    #[must_use]
+    pub fn target(&self) -> Point3<f32> {
+        self.target
+    }
+
+    pub fn set_target(&mut self, target: Point3<f32>) {
+        self.target = target;
+        self.update_camera();
+    }
+
+    #[must_use]
+    pub fn distance(&self) -> f32 {
+        self.distance
+    }
+
+    pub fn set_distance(&mut self, distance: f32) {
+        self.distance = distance.max(Self::MIN_DISTANCE);
+        self.update_camera();
+    }
+
+    // Radians turned per pixel dragged
+    pub fn set_rotation_speed(&mut self, rotation_speed: f32) {
+        self.rotation_speed = rotation_speed;
+    }
+
+    pub fn rotate(&mut self, delta_yaw: f32, delta_pitch: f32) {
+        self.yaw = (self.yaw + delta_yaw) % std::f32::consts::TAU;
+        self.pitch = (self.pitch + delta_pitch).clamp(-Self::MAX_PITCH, Self::MAX_PITCH);
+        self.update_camera();
+    }
+
+    // Positive lines move the camera toward the target
+    pub fn zoom(&mut self, lines: f32) {
+        self.set_distance(self.distance * self.zoom_factor.powf(-lines));
+    }
+
+    fn update_camera(&mut self) {
+        let (sin_yaw, cos_yaw) = self.yaw.sin_cos();
+        let (sin_pitch, cos_pitch) = self.pitch.sin_cos();
+        let offset = cgmath::vec3(cos_pitch * sin_yaw, sin_pitch, -cos_pitch * cos_yaw);
+        self.camera
+            .set_look_at(self.target + offset * self.distance, self.target);
+    }
+}
+
+impl Plugin for OrbitControlsPlugin {
+    // Mouse motions are only received while dragging
+    #[allow(clippy::cast_possible_truncation)]
+    fn on_mouse_event(&mut self, event: &DeviceEvent) -> EventState {
+        let DeviceEvent::MouseMotion { delta } = event else {
+            return EventState::default();
+        };
+        self.rotate(
+            -delta.0 as f32 * self.rotation_speed,
+            delta.1 as f32 * self.rotation_speed,
+        );
+        EventState { processed: true }
+    }
+    #[allow(clippy::cast_possible_truncation)]
+    fn on_window_event(&mut self, event: &WindowEvent) -> EventState {
+        let WindowEvent::MouseWheel { delta, .. } = event else {
+            return EventState::default();
+        };
+        let lines = match delta {
+            MouseScrollDelta::LineDelta(_, lines) => *lines,
+            MouseScrollDelta::PixelDelta(position) => (position.y / Self::PIXELS_PER_LINE) as f32,
+        };
+        self.zoom(lines);
+        EventState { processed: true }
+    }
+    fn on_render(
+        &mut self,
+        draw_context: &DrawContext,
+        _: &TimeInfo,
+        render_pass: &mut wgpu::RenderPass<'static>,
+    ) {
+        self.scene
+            .render_with_camera(draw_context, &mut self.camera, render_pass);
+    }
+}
@@ -24,11 +24,16 @@ SOFTWARE.
 
 use crate::{
     EventState, TimeInfo,
-    cameras::{Camera, InteractiveCamera},
-    draw_context::{DrawContext, Drawable, Uniform},
+    cameras::{Camera, Frustum, InteractiveCamera},
+    draw_context::{DrawContext, Drawable, FrameUniform},
+    picking::PickTarget,
+    primitives::{Transforms, bounds::Aabb},
 };
 use cgmath::{SquareMatrix, Zero};
-use std::{cell::RefCell, rc::Rc};
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
 use winit::event::{DeviceEvent, KeyEvent};
 
 use super::Plugin;
@@ -37,25 +42,41 @@ pub type DrawableWrapper = Rc<RefCell<dyn AsRef<Drawable>>>;
 
 #[allow(clippy::manual_non_exhaustive)]
 pub struct Scene3DUniforms {
-    pub camera_mat: Uniform<[[f32; 4]; 4]>,
-    pub camera_pos: Uniform<[f32; 3]>,
+    pub camera_mat: FrameUniform<[[f32; 4]; 4]>,
+    pub camera_pos: FrameUniform<[f32; 3]>,
     _private: (),
 }
 
+impl Scene3DUniforms {
+    pub const BINDING_CAMERA_MAT: u32 = 0;
+    pub const BINDING_CAMERA_POS: u32 = 1;
+}
+
 pub struct Scene3D {
     drawables: Vec<DrawableWrapper>,
+    // Same indices as the drawables, for the elements added with their transforms
+    transformables: Vec<Option<Rc<RefCell<dyn Transforms>>>>,
     scene_uniforms: Scene3DUniforms,
+    pick_target: Option<PickTarget>,
+    frustum: Option<Frustum>,
+    frustum_culling: bool,
+    last_draw_count: Cell<usize>,
 }
 
 impl Scene3D {
     pub fn new(context: &DrawContext) -> Self {
         Self {
             drawables: Vec::new(),
+            transformables: Vec::new(),
             scene_uniforms: Scene3DUniforms {
-                camera_mat: Uniform::new(context, cgmath::Matrix4::identity().into()),
-                camera_pos: Uniform::new(context, cgmath::Vector3::zero().into()),
+                camera_mat: FrameUniform::new(context, cgmath::Matrix4::identity().into()),
+                camera_pos: FrameUniform::new(context, cgmath::Vector3::zero().into()),
                 _private: (),
             },
+            pick_target: None,
+            frustum: None,
+            frustum_culling: false,
+            last_draw_count: Cell::new(0),
         }
     }
     #[must_use]
@@ -63,17 +84,74 @@ impl Scene3D {
         &self.scene_uniforms
     }
 
-    fn update(&mut self, camera: &Camera) {
+    pub fn update(&mut self, camera: &Camera) {
         self.scene_uniforms
             .camera_mat
             .write_uniform(camera.get_camera_matrix().into());
         self.scene_uniforms
             .camera_pos
             .write_uniform(camera.eye_position().into());
+        self.frustum = Some(camera.frustum());
     }
 
     pub fn add(&mut self, element: DrawableWrapper) {
         self.drawables.push(element);
+        self.transformables.push(None);
+    }
+
+    // Also flushed before rendering, for elements deferring their transform uploads
+    pub fn add_transformable<T: AsRef<Drawable> + Transforms + 'static>(
+        &mut self,
+        element: Rc<RefCell<T>>,
+    ) {
+        self.transformables.push(Some(Rc::clone(&element) as _));
+        self.drawables.push(element);
+    }
+
+    pub fn flush_transforms(&self) {
+        for transformable in self.transformables.iter().flatten() {
+            transformable.borrow_mut().flush();
+        }
+    }
+
+    // Skips the elements added with add_transformable whose world bounds are outside of the
+    // frustum of the camera given to update. Shadows are still rendered for all of them.
+    pub fn set_frustum_culling(&mut self, enabled: bool) {
+        self.frustum_culling = enabled;
+    }
+
+    #[must_use]
+    pub fn is_frustum_culling(&self) -> bool {
+        self.frustum_culling
+    }
+
+    // Drawables rendered by the last call to render
+    #[must_use]
+    pub fn last_draw_count(&self) -> usize {
+        self.last_draw_count.get()
+    }
+
+    fn is_culled(&self, index: usize) -> bool {
+        let (true, Some(frustum), Some(transformable)) = (
+            self.frustum_culling,
+            &self.frustum,
+            &self.transformables[index],
+        ) else {
+            return false;
+        };
+        transformable
+            .borrow()
+            .world_bounds()
+            .is_some_and(|bounds| !frustum.intersects_aabb(&bounds))
+    }
+
+    // World bounds of the element at the index returned by pick, if added with add_transformable
+    #[must_use]
+    pub fn world_bounds(&self, index: usize) -> Option<Aabb> {
+        self.transformables
+            .get(index)?
+            .as_ref()
+            .and_then(|transformable| transformable.borrow().world_bounds())
     }
 
     #[must_use]
@@ -81,10 +159,80 @@ impl Scene3D {
         &self.drawables
     }
 
-    pub fn render(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+    pub fn render_shadow(&self, render_pass: &mut wgpu::RenderPass<'_>) {
         for drawable in self.drawables() {
+            drawable.borrow().as_ref().render_shadow(render_pass);
+        }
+    }
+
+    // Index in the scene of the drawable under the cursor, among the ones built with a pick module
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn pick(&mut self, context: &DrawContext, cursor_ndc: [f32; 2]) -> Option<usize> {
+        self.pick_instance(context, cursor_ndc)
+            .map(|(index, _)| index)
+    }
+
+    // Same as pick, also returning the instance index for instanced drawables
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn pick_instance(
+        &mut self,
+        context: &DrawContext,
+        cursor_ndc: [f32; 2],
+    ) -> Option<(usize, u32)> {
+        let dimensions = context.surface_dimensions();
+        let pick_target = match &mut self.pick_target {
+            Some(pick_target) if pick_target.dimensions() == dimensions => pick_target,
+            pick_target => pick_target.insert(PickTarget::new(context, dimensions)),
+        };
+        let pixel = pick_target.ndc_to_pixel(cursor_ndc)?;
+        pick_target.render(context, |render_pass| {
+            for (index, drawable) in self.drawables.iter().enumerate() {
+                let object_id = u32::try_from(index + 1).expect("Scene should fit u32 ids");
+                drawable
+                    .borrow()
+                    .as_ref()
+                    .render_pick(render_pass, object_id);
+            }
+        });
+        let [object_id, instance] = pick_target
+            .read_id(context, pixel)
+            .inspect_err(|err| log::warn!("Cannot read back the picked object: {err}"))
+            .ok()?;
+        (object_id != PickTarget::NO_OBJECT_ID).then(|| (object_id as usize - 1, instance))
+    }
+
+    pub fn render(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        let visible_drawables: Vec<_> = self
+            .drawables
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| !self.is_culled(*index))
+            .map(|(_, drawable)| drawable)
+            .collect();
+        for drawable in &visible_drawables {
+            drawable.borrow().as_ref().render_depth_prepass(render_pass);
+        }
+        for drawable in &visible_drawables {
             drawable.borrow().as_ref().render(render_pass);
         }
+        self.last_draw_count.set(visible_drawables.len());
+    }
+
+    // Frame of the camera plugins: the camera follows the surface size, then its matrices and
+    // the pending transforms are uploaded before drawing
+    pub fn render_with_camera(
+        &mut self,
+        draw_context: &DrawContext,
+        camera: &mut Camera,
+        render_pass: &mut wgpu::RenderPass<'_>,
+    ) {
+        camera.resize_screen(draw_context.surface_dimensions());
+        self.update(camera);
+        self.flush_transforms();
+        draw_context
+            .debug_draw()
+            .set_view_projection(camera.get_camera_matrix());
+        self.render(render_pass);
     }
 }
 
@@ -93,6 +241,19 @@ pub struct Scene3DPlugin {
     pub scene: Scene3D,
 }
 
+impl Scene3DPlugin {
+    // The aspect ratio of the camera is set from the surface, then kept in sync at each render
+    #[must_use]
+    pub fn new(context: &DrawContext, camera: Camera) -> Self {
+        let mut camera = InteractiveCamera::new(camera);
+        camera.update_screen_size(context.surface_dimensions());
+        Self {
+            camera,
+            scene: Scene3D::new(context),
+        }
+    }
+}
+
 impl Plugin for Scene3DPlugin {
     fn on_mouse_event(&mut self, event: &DeviceEvent) -> EventState {
         self.camera.mouse_event_listener(event);
@@ -108,9 +269,7 @@ impl Plugin for Scene3DPlugin {
         render_pass: &mut wgpu::RenderPass<'static>,
     ) {
         let Self { camera, scene } = self;
-        camera.update_screen_size(draw_context.surface_dimensions());
         camera.update_control();
-        scene.update(&camera.controled_camera);
-        scene.render(render_pass);
+        scene.render_with_camera(draw_context, &mut camera.controled_camera, render_pass);
     }
 }
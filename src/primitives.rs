@@ -22,18 +22,30 @@ OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 SOFTWARE.
 */
 
+pub mod arrow;
+pub mod bounds;
 pub mod canvas;
+pub mod capsule;
 pub mod color;
 pub mod cube;
+pub mod curve;
+pub mod mesh;
+pub mod parametric;
+pub mod pbr;
+pub mod phong;
+pub mod sphere;
 pub mod triangle;
+pub mod wirecube;
 
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 
-use crate::draw_context::{DrawContext, Drawable, StorageBuffer};
+use crate::draw_context::{BindingSlot, DrawContext, Drawable, StorageBuffer};
 use crate::draw_context::{Uniform, UnitformType};
-use cgmath::{InnerSpace, Matrix, Matrix3, Matrix4};
+use bounds::Aabb;
+use cgmath::{InnerSpace, Matrix, Matrix3, Matrix4, Quaternion, Vector3};
 use cgmath::{Rotation3, SquareMatrix};
+#[cfg(not(target_arch = "wasm32"))]
 use rayon::iter::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
 
 fn extract_rotation(matrix: Matrix4<f32>) -> Matrix3<f32> {
@@ -48,6 +60,24 @@ fn extract_rotation(matrix: Matrix4<f32>) -> Matrix3<f32> {
     Matrix3::from_cols(a.x.normalize(), a.y.normalize(), a.z.normalize())
 }
 
+fn extract_scale(matrix: Matrix4<f32>) -> Vector3<f32> {
+    Vector3::new(
+        matrix.x.truncate().magnitude(),
+        matrix.y.truncate().magnitude(),
+        matrix.z.truncate().magnitude(),
+    )
+}
+
+fn compose_trs(
+    translation: Vector3<f32>,
+    rotation: Matrix3<f32>,
+    scale: Vector3<f32>,
+) -> Matrix4<f32> {
+    Matrix4::from_translation(translation)
+        * Matrix4::from(rotation)
+        * Matrix4::from_nonuniform_scale(scale.x, scale.y, scale.z)
+}
+
 pub trait Shareable: Sized {
     fn into_shareable(self) -> Rc<RefCell<Self>> {
         Rc::new(RefCell::new(self))
@@ -58,6 +88,12 @@ pub trait Transforms {
     fn set_transform(&mut self, transform: Matrix4<f32>);
     fn get_transform(&self) -> &Matrix4<f32>;
     fn apply_transform(&mut self, transform: Matrix4<f32>);
+    // Uploads the transform changes an implementation may have kept pending
+    fn flush(&mut self) {}
+    // Bounds with the transform applied, None when the geometry bounds are unknown
+    fn world_bounds(&self) -> Option<Aabb> {
+        None
+    }
 }
 
 pub struct Object3DUniforms {
@@ -65,58 +101,173 @@ pub struct Object3DUniforms {
     pub normals: Option<Uniform<[[f32; 3]; 3]>>,
 }
 
+impl Object3DUniforms {
+    fn update_normal_mat(&mut self, transform: &Matrix4<f32>) {
+        let Some(normal_tranform) = &mut self.normals else {
+            return;
+        };
+        let rotation_mat = extract_rotation(*transform);
+        let normal_mat = rotation_mat
+            .invert()
+            .expect("It should be possible to inverse the matrix")
+            .transpose();
+        normal_tranform.write_uniform(normal_mat.into());
+    }
+    fn write_transform(&mut self, transform: &Matrix4<f32>) {
+        self.view.write_uniform((*transform).into());
+        self.update_normal_mat(transform);
+    }
+}
+
 pub struct Object3D {
     drawable: Drawable,
     transform: Matrix4<f32>,
     opacity: f32,
+    opacity_uniform: Option<Uniform<f32>>,
     uniforms: Object3DUniforms,
+    deferred_upload: bool,
+    dirty: bool,
+    uniform_uploads: u64,
+    local_bounds: Option<Aabb>,
+    world_bounds: Cell<Option<Aabb>>,
 }
 
 impl Object3D {
+    // Binding of the model bind group holding the opacity, see with_opacity_uniform
+    pub const BINDING_OPACITY: u32 = 2;
+
     #[must_use]
     pub fn new(drawable: Drawable, uniforms: Object3DUniforms) -> Self {
         Self {
             drawable,
             transform: Matrix4::<f32>::identity(),
             opacity: 1.0,
+            opacity_uniform: None,
             uniforms,
+            deferred_upload: false,
+            dirty: false,
+            uniform_uploads: 0,
+            local_bounds: None,
+            world_bounds: Cell::new(None),
         }
     }
-    fn update_normal_mat(&mut self) {
-        let Some(normal_tranform) = &mut self.uniforms.normals else {
-            return;
-        };
-        let rotation_mat = extract_rotation(self.transform);
-        let normal_mat = rotation_mat
-            .invert()
-            .expect("It should be possible to inverse the matrix")
-            .transpose();
-        normal_tranform.write_uniform(normal_mat.into());
+    #[must_use]
+    pub fn with_local_bounds(mut self, bounds: Aabb) -> Self {
+        self.set_local_bounds(bounds);
+        self
+    }
+    // For shaders premultiplying their colors: set_opacity writes this uniform instead of
+    // setting the blend constant
+    #[must_use]
+    pub fn with_opacity_uniform(mut self, uniform: Option<Uniform<f32>>) -> Self {
+        self.opacity_uniform = uniform;
+        self
+    }
+    // Bounds of the geometry before the transform is applied
+    pub fn set_local_bounds(&mut self, bounds: Aabb) {
+        self.local_bounds = Some(bounds);
+        self.world_bounds.set(None);
+    }
+    #[must_use]
+    pub fn local_bounds(&self) -> Option<Aabb> {
+        self.local_bounds
+    }
+    // When deferred, transform changes are only uploaded by flush, once per frame at most
+    pub fn set_deferred_upload(&mut self, deferred: bool) {
+        self.deferred_upload = deferred;
+        if !deferred {
+            self.flush();
+        }
+    }
+    #[must_use]
+    pub fn is_deferred_upload(&self) -> bool {
+        self.deferred_upload
+    }
+    #[must_use]
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+    #[must_use]
+    pub fn uniform_upload_count(&self) -> u64 {
+        self.uniform_uploads
+    }
+    fn transform_changed(&mut self) {
+        self.world_bounds.set(None);
+        self.dirty = true;
+        if !self.deferred_upload {
+            self.flush();
+        }
     }
     pub fn set_opacity(&mut self, value: f32) {
         self.opacity = value.clamp(0., 1.);
-        self.drawable
-            .set_blend_color_opacity(f64::from(self.opacity));
+        match &mut self.opacity_uniform {
+            Some(opacity_uniform) => opacity_uniform.write_uniform(self.opacity),
+            None => self
+                .drawable
+                .set_blend_color_opacity(f64::from(self.opacity)),
+        }
     }
     #[must_use]
     pub fn get_opacity(&self) -> f32 {
         self.opacity
     }
+    pub fn set_translation(&mut self, translation: Vector3<f32>) {
+        let mut transform = self.transform;
+        transform.w = translation.extend(1.0);
+        self.set_transform(transform);
+    }
+    pub fn set_rotation(&mut self, rotation: Quaternion<f32>) {
+        self.set_trs(
+            self.transform.w.truncate(),
+            rotation,
+            extract_scale(self.transform),
+        );
+    }
+    pub fn set_scale(&mut self, scale: Vector3<f32>) {
+        self.set_transform(compose_trs(
+            self.transform.w.truncate(),
+            extract_rotation(self.transform),
+            scale,
+        ));
+    }
+    pub fn set_trs(
+        &mut self,
+        translation: Vector3<f32>,
+        rotation: Quaternion<f32>,
+        scale: Vector3<f32>,
+    ) {
+        self.set_transform(compose_trs(translation, Matrix3::from(rotation), scale));
+    }
 }
 
 impl Transforms for Object3D {
     fn set_transform(&mut self, transform: Matrix4<f32>) {
         self.transform = transform;
-        self.uniforms.view.write_uniform(self.transform.into());
-        self.update_normal_mat();
+        self.transform_changed();
     }
     fn get_transform(&self) -> &Matrix4<f32> {
         &self.transform
     }
     fn apply_transform(&mut self, transform: Matrix4<f32>) {
         self.transform = transform * self.transform;
-        self.uniforms.view.write_uniform(self.transform.into());
-        self.update_normal_mat();
+        self.transform_changed();
+    }
+    fn flush(&mut self) {
+        if !self.dirty {
+            return;
+        }
+        self.uniforms.write_transform(&self.transform);
+        self.uniform_uploads += 1;
+        self.dirty = false;
+    }
+    // Computed on demand, then kept until the transform or the local bounds change
+    fn world_bounds(&self) -> Option<Aabb> {
+        if let Some(world_bounds) = self.world_bounds.get() {
+            return Some(world_bounds);
+        }
+        let world_bounds = self.local_bounds?.transformed(&self.transform);
+        self.world_bounds.set(Some(world_bounds));
+        Some(world_bounds)
     }
 }
 
@@ -132,6 +283,7 @@ pub struct Object3DInstanceGroupHandlers {
     instances: Vec<Object3DInstance>,
     transforms: StorageBuffer<[[f32; 4]; 4]>,
     normal_mats: StorageBuffer<[[f32; 3]; 3]>,
+    group_uniforms: Object3DUniforms,
 }
 
 impl Object3DInstanceGroupHandlers {
@@ -140,32 +292,85 @@ impl Object3DInstanceGroupHandlers {
             instances: vec![Object3DInstance::default(); count as usize],
             transforms: StorageBuffer::new_array(context, &vec![[[0.; 4]; 4]; count as usize]),
             normal_mats: StorageBuffer::new_array(context, &vec![[[0.; 3]; 3]; count as usize]),
+            group_uniforms: Object3DUniforms {
+                view: Uniform::new(context, Matrix4::identity().into()),
+                normals: Some(Uniform::new(context, Matrix3::identity().into())),
+            },
         }
     }
+    #[must_use]
+    pub fn model_binding_slots(&self) -> [BindingSlot<'_>; 4] {
+        [
+            BindingSlot {
+                bind_group: DrawContext::BIND_GROUP_INDEX_MODEL,
+                binding: 0,
+                resource: &self.transforms,
+            },
+            BindingSlot {
+                bind_group: DrawContext::BIND_GROUP_INDEX_MODEL,
+                binding: 1,
+                resource: &self.normal_mats,
+            },
+            BindingSlot {
+                bind_group: DrawContext::BIND_GROUP_INDEX_MODEL,
+                binding: 2,
+                resource: &self.group_uniforms.view,
+            },
+            BindingSlot {
+                bind_group: DrawContext::BIND_GROUP_INDEX_MODEL,
+                binding: 3,
+                resource: self
+                    .group_uniforms
+                    .normals
+                    .as_ref()
+                    .expect("Group normal uniform should be present for instances"),
+            },
+        ]
+    }
+    // Returns true if the storage buffers were reallocated
+    fn set_count(&mut self, context: &DrawContext, count: u32) -> bool {
+        let new_instance = Object3DInstance::default();
+        let count = count as usize;
+        self.instances.resize(count, new_instance.clone());
+        let transforms_reallocated =
+            self.transforms
+                .resize(context, count, new_instance.get_transform().into());
+        let normal_mats_reallocated =
+            self.normal_mats
+                .resize(context, count, new_instance.get_normal_matrix().into());
+        transforms_reallocated || normal_mats_reallocated
+    }
     pub fn update_instances<F>(&mut self, f: F)
     where
         F: Fn(usize, &mut Object3DInstance) + 'static + Send + Sync,
     {
-        let transforms_writer = self.transforms.start_write();
-        let transforms_iter = transforms_writer.storage_buffer.local_buffer.par_iter_mut();
-        let normal_mats_writer = self.normal_mats.start_write();
-        let normals_iter = normal_mats_writer
-            .storage_buffer
-            .local_buffer
-            .par_iter_mut();
-
-        self.instances
-            .par_iter_mut()
-            .enumerate()
-            .zip(transforms_iter)
-            .zip(normals_iter)
-            .for_each(|(((idx, obj_instance), t), n)| {
+        let mut transforms_writer = self.transforms.start_write();
+        let mut normal_mats_writer = self.normal_mats.start_write();
+        let transforms = transforms_writer.aligned_values_mut();
+        let normal_mats = normal_mats_writer.aligned_values_mut();
+        let update =
+            |(((idx, obj_instance), t), n): (((usize, &mut Object3DInstance), &mut _), &mut _)| {
                 f(idx, obj_instance);
                 // TODO Alignement should be more invisible/hidden to apply, is there a way?
                 *t = Into::<[[f32; 4]; 4]>::into(obj_instance.get_transform()).apply_alignment();
                 *n =
                     Into::<[[f32; 3]; 3]>::into(obj_instance.get_normal_matrix()).apply_alignment();
-            });
+            };
+        // The browser runs WASM on a single thread, where Rayon would only add overhead
+        #[cfg(not(target_arch = "wasm32"))]
+        self.instances
+            .par_iter_mut()
+            .enumerate()
+            .zip(transforms.par_iter_mut())
+            .zip(normal_mats.par_iter_mut())
+            .for_each(update);
+        #[cfg(target_arch = "wasm32")]
+        self.instances
+            .iter_mut()
+            .enumerate()
+            .zip(transforms.iter_mut())
+            .zip(normal_mats.iter_mut())
+            .for_each(update);
     }
 }
 
@@ -212,6 +417,7 @@ impl Object3DInstance {
 
 pub struct Object3DInstanceGroup {
     drawable: Drawable,
+    transform: Matrix4<f32>,
     opacity: f32,
     handlers: Object3DInstanceGroupHandlers,
 }
@@ -221,6 +427,7 @@ impl Object3DInstanceGroup {
     pub fn new(drawable: Drawable, handlers: Object3DInstanceGroupHandlers) -> Self {
         Self {
             drawable,
+            transform: Matrix4::<f32>::identity(),
             opacity: 0.,
             handlers,
         }
@@ -231,6 +438,22 @@ impl Object3DInstanceGroup {
     {
         self.handlers.update_instances(f);
     }
+    #[must_use]
+    pub fn count(&self) -> u32 {
+        self.drawable.instance_count()
+    }
+    pub fn set_count(&mut self, context: &DrawContext, count: u32) {
+        if self.handlers.set_count(context, count) {
+            self.drawable
+                .rebuild_bind_group(
+                    context,
+                    DrawContext::BIND_GROUP_INDEX_MODEL,
+                    &self.handlers.model_binding_slots(),
+                )
+                .expect("Model bind group should be rebuilt with the same layout");
+        }
+        self.drawable.set_instance_count(count);
+    }
     pub fn set_opacity(&mut self, value: f32) {
         self.opacity = value.clamp(0., 1.);
         self.drawable
@@ -242,6 +465,24 @@ impl Object3DInstanceGroup {
     }
 }
 
+impl Transforms for Object3DInstanceGroup {
+    fn set_transform(&mut self, transform: Matrix4<f32>) {
+        self.transform = transform;
+        self.handlers
+            .group_uniforms
+            .write_transform(&self.transform);
+    }
+    fn get_transform(&self) -> &Matrix4<f32> {
+        &self.transform
+    }
+    fn apply_transform(&mut self, transform: Matrix4<f32>) {
+        self.transform = transform * self.transform;
+        self.handlers
+            .group_uniforms
+            .write_transform(&self.transform);
+    }
+}
+
 impl Shareable for Object3DInstanceGroup {}
 
 impl AsRef<Drawable> for Object3DInstanceGroup {
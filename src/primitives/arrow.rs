@@ -0,0 +1,139 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use std::f32::consts::{PI, TAU};
+
+use cgmath::{EuclideanSpace, InnerSpace, Matrix4, Point3, Quaternion, Rad, Rotation3, Vector3};
+
+use crate::draw_context::DrawContext;
+use crate::plugins::scene_3d::Scene3DUniforms;
+use crate::primitives::Object3D;
+use crate::primitives::mesh::{self, MeshData, MeshOptions};
+use crate::primitives::parametric::parametric_mesh;
+
+// Subdivisions around the axis of the shaft and of the head
+const SEGMENTS: u32 = 16;
+// Radius of the base of the head, relative to its length
+const HEAD_RADIUS_RATIO: f32 = 0.5;
+
+// Cylinder shaft from `from`, ended by a cone head of length head_size whose tip is `to`.
+// The head is shortened to the full length for arrows shorter than head_size.
+#[must_use]
+pub fn arrow_mesh(
+    from: Point3<f32>,
+    to: Point3<f32>,
+    shaft_radius: f32,
+    head_size: f32,
+) -> MeshData {
+    let vector = to - from;
+    let length = vector.magnitude();
+    let head_length = head_size.min(length);
+    let shaft_length = length - head_length;
+    let head_radius = head_size * HEAD_RADIUS_RATIO;
+    // Built along +Y from the origin, then moved onto the arrow
+    let shaft = parametric_mesh(
+        |u, v| {
+            let (sin, cos) = (TAU * u).sin_cos();
+            [shaft_radius * cos, v * shaft_length, shaft_radius * sin]
+        },
+        SEGMENTS,
+        1,
+    );
+    let shaft_cap = disk(shaft_radius, 0.0);
+    let head = parametric_mesh(
+        |u, v| {
+            let (sin, cos) = (TAU * u).sin_cos();
+            let radius = (1.0 - v) * head_radius;
+            [
+                radius * cos,
+                v.mul_add(head_length, shaft_length),
+                radius * sin,
+            ]
+        },
+        SEGMENTS,
+        1,
+    );
+    let head_base = disk(head_radius, shaft_length);
+    let transform =
+        Matrix4::from_translation(from.to_vec()) * Matrix4::from(rotation_from_y(vector));
+    mesh::merge(&[
+        (transform, shaft),
+        (transform, shaft_cap),
+        (transform, head),
+        (transform, head_base),
+    ])
+}
+
+// Disk facing -Y at the given height
+fn disk(radius: f32, height: f32) -> MeshData {
+    parametric_mesh(
+        |u, v| {
+            let (sin, cos) = (TAU * u).sin_cos();
+            [v * radius * cos, height, v * radius * sin]
+        },
+        SEGMENTS,
+        1,
+    )
+}
+
+// Shortest rotation bringing +Y onto the direction. The rotation axis Y x direction vanishes
+// when they are parallel: no rotation is needed if they point the same way, and a half turn
+// around any perpendicular axis, here X, flips an antiparallel direction.
+fn rotation_from_y(direction: Vector3<f32>) -> Quaternion<f32> {
+    if direction.magnitude2() <= f32::EPSILON {
+        return Quaternion::from_sv(1.0, Vector3::new(0.0, 0.0, 0.0));
+    }
+    let direction = direction.normalize();
+    let axis = Vector3::unit_y().cross(direction);
+    let cos = Vector3::unit_y().dot(direction);
+    if axis.magnitude2() <= f32::EPSILON {
+        return if cos > 0.0 {
+            Quaternion::from_sv(1.0, Vector3::new(0.0, 0.0, 0.0))
+        } else {
+            Quaternion::from_axis_angle(Vector3::unit_x(), Rad(PI))
+        };
+    }
+    Quaternion::from_axis_angle(axis.normalize(), Rad(cos.clamp(-1.0, 1.0).acos()))
+}
+
+// Same bindings as mesh::create_mesh_with_options
+pub fn create_arrow(
+    context: &DrawContext,
+    vtx_module: &wgpu::ShaderModule,
+    frg_module: &wgpu::ShaderModule,
+    uniforms: &Scene3DUniforms,
+    (from, to): (Point3<f32>, Point3<f32>),
+    shaft_radius: f32,
+    head_size: f32,
+) -> Object3D {
+    let mesh = arrow_mesh(from, to, shaft_radius, head_size);
+    mesh::create_mesh_with_options(
+        context,
+        vtx_module,
+        frg_module,
+        uniforms,
+        &mesh,
+        &MeshOptions::default(),
+    )
+}
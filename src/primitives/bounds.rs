@@ -0,0 +1,107 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use cgmath::{EuclideanSpace, InnerSpace, Matrix4, Point3, Transform, Vector3};
+
+// Axis-aligned box, in the local space of a geometry or in world space
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: Point3<f32>,
+    pub max: Point3<f32>,
+}
+
+impl Aabb {
+    #[must_use]
+    pub const fn new(min: Point3<f32>, max: Point3<f32>) -> Self {
+        Self { min, max }
+    }
+    // None for an empty geometry
+    #[must_use]
+    pub fn from_points(points: &[[f32; 3]]) -> Option<Self> {
+        let (first, others) = points.split_first()?;
+        let first = Point3::from(*first);
+        Some(others.iter().fold(Self::new(first, first), |aabb, &point| {
+            aabb.including(Point3::from(point))
+        }))
+    }
+    #[must_use]
+    pub fn including(self, point: Point3<f32>) -> Self {
+        Self {
+            min: Point3::new(
+                self.min.x.min(point.x),
+                self.min.y.min(point.y),
+                self.min.z.min(point.z),
+            ),
+            max: Point3::new(
+                self.max.x.max(point.x),
+                self.max.y.max(point.y),
+                self.max.z.max(point.z),
+            ),
+        }
+    }
+    #[must_use]
+    pub fn union(self, other: Self) -> Self {
+        self.including(other.min).including(other.max)
+    }
+    #[must_use]
+    pub fn center(&self) -> Point3<f32> {
+        self.min.midpoint(self.max)
+    }
+    #[must_use]
+    pub fn half_extents(&self) -> Vector3<f32> {
+        (self.max - self.min) / 2.0
+    }
+    // Radius of the bounding sphere sharing the center of the box
+    #[must_use]
+    pub fn radius(&self) -> f32 {
+        self.half_extents().magnitude()
+    }
+    #[must_use]
+    pub fn contains(&self, point: Point3<f32>) -> bool {
+        (self.min.x..=self.max.x).contains(&point.x)
+            && (self.min.y..=self.max.y).contains(&point.y)
+            && (self.min.z..=self.max.z).contains(&point.z)
+    }
+    #[must_use]
+    pub fn corners(&self) -> [Point3<f32>; 8] {
+        let (min, max) = (self.min, self.max);
+        std::array::from_fn(|index| {
+            Point3::new(
+                if index & 1 == 0 { min.x } else { max.x },
+                if index & 2 == 0 { min.y } else { max.y },
+                if index & 4 == 0 { min.z } else { max.z },
+            )
+        })
+    }
+    // Box enclosing the transformed corners, so it may be larger than the transformed geometry
+    #[must_use]
+    pub fn transformed(&self, transform: &Matrix4<f32>) -> Self {
+        let [first, others @ ..] = self
+            .corners()
+            .map(|corner| transform.transform_point(corner));
+        others
+            .into_iter()
+            .fold(Self::new(first, first), Self::including)
+    }
+}
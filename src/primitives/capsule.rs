@@ -0,0 +1,71 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use std::f32::consts::FRAC_PI_2;
+
+use crate::draw_context::DrawContext;
+use crate::plugins::scene_3d::Scene3DUniforms;
+use crate::primitives::Object3D;
+use crate::primitives::mesh::{self, MeshData, MeshOptions};
+use crate::primitives::sphere::latitude_mesh;
+
+const MIN_RINGS: u32 = 2;
+const MIN_SEGMENTS: u32 = 3;
+
+// Capsule along Y centred on the origin, two hemispheres of the given radius joined by a
+// cylinder of cylinder_height. Each hemisphere has rings bands of latitude, at least 2, and
+// the capsule has segments bands of longitude, at least 3. The equators of both hemispheres
+// are the ends of the cylinder: their normals are horizontal, continuous across the seams.
+// There are 2 + 2 * rings * segments vertices.
+#[must_use]
+pub fn capsule_mesh(radius: f32, cylinder_height: f32, rings: u32, segments: u32) -> MeshData {
+    let rings = rings.max(MIN_RINGS);
+    let half_height = cylinder_height.max(0.0) / 2.0;
+    #[allow(clippy::cast_precision_loss)]
+    let polar_angle = |ring: u32| FRAC_PI_2 * ring as f32 / rings as f32;
+    let latitudes: Vec<_> = (1..=rings)
+        .map(|ring| (polar_angle(ring), half_height))
+        .chain((0..rings).map(|ring| (FRAC_PI_2 + polar_angle(ring), -half_height)))
+        .collect();
+    latitude_mesh(
+        &latitudes,
+        segments.max(MIN_SEGMENTS),
+        radius,
+        (half_height, -half_height),
+    )
+}
+
+// Same bindings as sphere::create_uv_sphere
+pub fn create_capsule(
+    context: &DrawContext,
+    vtx_module: &wgpu::ShaderModule,
+    frg_module: &wgpu::ShaderModule,
+    uniforms: &Scene3DUniforms,
+    (radius, cylinder_height): (f32, f32),
+    (rings, segments): (u32, u32),
+    options: &MeshOptions,
+) -> Object3D {
+    let mesh = capsule_mesh(radius, cylinder_height, rings, segments);
+    mesh::create_mesh_with_options(context, vtx_module, frg_module, uniforms, &mesh, options)
+}
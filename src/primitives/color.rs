@@ -22,6 +22,9 @@ OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 SOFTWARE.
 */
 
+// Colors are authored in sRGB, as picked in a color editor, while shaders work in linear space:
+// the sRGB surface encodes them back when writing. Convert authored colors before upload.
+// The constants below only use 0 and 1, identical in both spaces.
 pub const COLOR_BLACK: [f32; 3] = [0., 0., 0.];
 pub const COLOR_WHITE: [f32; 3] = [1., 1., 1.];
 pub const COLOR_RED: [f32; 3] = [1., 0., 0.];
@@ -30,3 +33,35 @@ pub const COLOR_BLUE: [f32; 3] = [0., 0., 1.];
 pub const COLOR_YELLOW: [f32; 3] = [1., 1., 0.];
 pub const COLOR_CYAN: [f32; 3] = [0., 1., 1.];
 pub const COLOR_MAGENTA: [f32; 3] = [1., 0., 1.];
+
+// Include in a shader to convert colors read from textures or uniforms authored in sRGB
+pub const SHADER_SNIPPET: &str = include_str!("../shaders/color.wgsl");
+
+#[must_use]
+pub fn srgb_to_linear(component: f32) -> f32 {
+    if component <= 0.040_45 {
+        component / 12.92
+    } else {
+        ((component + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+#[must_use]
+pub fn linear_to_srgb(component: f32) -> f32 {
+    if component <= 0.003_130_8 {
+        component * 12.92
+    } else {
+        1.055f32.mul_add(component.powf(1.0 / 2.4), -0.055)
+    }
+}
+
+#[must_use]
+pub fn srgb_to_linear_rgb(color: [f32; 3]) -> [f32; 3] {
+    color.map(srgb_to_linear)
+}
+
+// For colors given as 8-bit sRGB values, e.g. from a hex code
+#[must_use]
+pub fn srgb8_to_linear_rgb(color: [u8; 3]) -> [f32; 3] {
+    color.map(|component| srgb_to_linear(f32::from(component) / 255.0))
+}
@@ -24,21 +24,27 @@ SOFTWARE.
 
 use std::sync::LazyLock;
 
-use cgmath::SquareMatrix;
+use cgmath::{Point3, SquareMatrix};
 
 use crate::BindingSlot;
 use crate::draw_context::DrawContext;
 use crate::draw_context::DrawModeParams;
 use crate::draw_context::DrawableBuilder;
 use crate::draw_context::IndexData;
+use crate::draw_context::Material;
 use crate::draw_context::Uniform;
 use crate::plugins::scene_3d::Scene3DUniforms;
 use crate::primitives::Object3D;
 use crate::primitives::color;
+use crate::shadows::ShadowCaster;
+use crate::support::environment::EnvironmentMap;
 
 use super::Object3DInstanceGroup;
 use super::Object3DInstanceGroupHandlers;
 use super::Object3DUniforms;
+use super::bounds::Aabb;
+
+const CUBE_BOUNDS: Aabb = Aabb::new(Point3::new(-0.5, -0.5, -0.5), Point3::new(0.5, 0.5, 0.5));
 
 #[rustfmt::skip]
 const CUBE_GEOMETRY_COMPACT: &[[f32; 3]] = &[
@@ -156,14 +162,68 @@ static CUBE_NORMALS_DUPLICATES: LazyLock<Vec<[f32; 3]>> = LazyLock::new(|| {
     normals
 });
 
-pub struct CubeOptions {
+// Opacity given by the blend constant, see Drawable::set_blend_color_opacity
+pub const CONSTANT_ALPHA_BLEND: wgpu::BlendState = wgpu::BlendState {
+    color: wgpu::BlendComponent {
+        src_factor: wgpu::BlendFactor::Constant,
+        dst_factor: wgpu::BlendFactor::OneMinusConstant,
+        operation: wgpu::BlendOperation::Add,
+    },
+    alpha: wgpu::BlendComponent::REPLACE,
+};
+
+// For fragment shaders outputting premultiplied colors, see color::SHADER_SNIPPET premultiply_alpha
+pub const PREMULTIPLIED_ALPHA_BLEND: wgpu::BlendState =
+    wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING;
+
+// Flat colors with the opacity of Object3D::BINDING_OPACITY, for the premultiplied_alpha option
+pub const FLAT_PREMULTIPLIED_SHADER: &str = concat!(
+    include_str!("../shaders/color.wgsl"),
+    include_str!("../shaders/flat_premultiplied.wgsl")
+);
+
+pub struct CubeOptions<'a> {
     pub with_alpha: bool,
+    // With with_alpha, uses PREMULTIPLIED_ALPHA_BLEND instead of CONSTANT_ALPHA_BLEND: the fragment
+    // shader premultiplies its colors by the opacity bound at Object3D::BINDING_OPACITY, instances
+    // get no opacity binding and premultiply with their own alpha
+    pub premultiplied_alpha: bool,
+    pub depth_prepass: bool,
+    pub shadow_caster: Option<ShadowCaster<'a>>,
+    // Binds the irradiance map sampled by EnvironmentMap::SHADER_SNIPPET
+    pub environment_map: Option<&'a EnvironmentMap>,
+    pub pick_module: Option<&'a wgpu::ShaderModule>,
+    // Fragment module drawing the edges on top of the faces
+    pub wireframe_module: Option<&'a wgpu::ShaderModule>,
+    pub target_format: Option<wgpu::TextureFormat>,
 }
 
 #[allow(clippy::derivable_impls)]
-impl Default for CubeOptions {
+impl Default for CubeOptions<'_> {
     fn default() -> Self {
-        Self { with_alpha: false }
+        Self {
+            with_alpha: false,
+            premultiplied_alpha: false,
+            depth_prepass: false,
+            shadow_caster: None,
+            environment_map: None,
+            pick_module: None,
+            wireframe_module: None,
+            target_format: None,
+        }
+    }
+}
+
+impl CubeOptions<'_> {
+    fn alpha_blend(&self) -> Option<wgpu::BlendState> {
+        match (self.with_alpha, self.premultiplied_alpha) {
+            (false, _) => None,
+            (true, false) => Some(CONSTANT_ALPHA_BLEND),
+            (true, true) => Some(PREMULTIPLIED_ALPHA_BLEND),
+        }
+    }
+    fn opacity_uniform(&self, context: &DrawContext) -> Option<Uniform<f32>> {
+        (self.with_alpha && self.premultiplied_alpha).then(|| Uniform::new(context, 1.0))
     }
 }
 
@@ -175,13 +235,14 @@ pub fn create_cube_with_colors(
     options: &CubeOptions,
 ) -> Object3D {
     let transform_uniform = Uniform::new(context, cgmath::Matrix4::identity().into());
+    let opacity_uniform = options.opacity_uniform(context);
 
     let mut drawable_builder = DrawableBuilder::new(
         context,
         vtx_module,
         frg_module,
         DrawModeParams::Indexed {
-            index_data: IndexData::U16(CUBE_INDICES_COMPACT),
+            index_data: IndexData::U16(CUBE_INDICES_COMPACT.into()),
         },
     );
     drawable_builder
@@ -200,26 +261,38 @@ pub fn create_cube_with_colors(
         )
         .expect("Location should be different than for another attribute.")
         .add_binding_slot(&BindingSlot {
-            bind_group: 0,
-            binding: 0,
+            bind_group: DrawContext::BIND_GROUP_INDEX_CAMERA,
+            binding: Scene3DUniforms::BINDING_CAMERA_MAT,
             resource: &uniforms.camera_mat,
         })
         .expect("Bind group or binding should be different from other uniforms.")
         .add_binding_slot(&BindingSlot {
-            bind_group: 1,
+            bind_group: DrawContext::BIND_GROUP_INDEX_MODEL,
             binding: 0,
             resource: &transform_uniform,
         })
         .expect("Bind group or binding should be different from other uniforms.");
-    if options.with_alpha {
-        drawable_builder.set_blend_option(wgpu::BlendState {
-            color: wgpu::BlendComponent {
-                src_factor: wgpu::BlendFactor::Constant,
-                dst_factor: wgpu::BlendFactor::OneMinusConstant,
-                operation: wgpu::BlendOperation::Add,
-            },
-            alpha: wgpu::BlendComponent::default(),
-        });
+    drawable_builder.set_depth_prepass(options.depth_prepass);
+    if let Some(target_format) = options.target_format {
+        drawable_builder.set_color_target_format(target_format);
+    }
+    if let Some(pick_module) = options.pick_module {
+        drawable_builder.set_pick_module(pick_module);
+    }
+    if let Some(wireframe_module) = options.wireframe_module {
+        drawable_builder.set_wireframe_overlay(wireframe_module);
+    }
+    if let Some(blend) = options.alpha_blend() {
+        drawable_builder.set_blend_option(blend);
+    }
+    if let Some(opacity_uniform) = &opacity_uniform {
+        drawable_builder
+            .add_binding_slot(&BindingSlot {
+                bind_group: DrawContext::BIND_GROUP_INDEX_MODEL,
+                binding: Object3D::BINDING_OPACITY,
+                resource: opacity_uniform,
+            })
+            .expect("Bind group or binding should be different from other uniforms.");
     }
     let drawable = drawable_builder.build();
     Object3D::new(
@@ -229,6 +302,50 @@ pub fn create_cube_with_colors(
             normals: None,
         },
     )
+    .with_local_bounds(CUBE_BOUNDS)
+    .with_opacity_uniform(opacity_uniform)
+}
+
+// Geometry and colors of create_cube_with_colors, the shaders, settings and camera bindings
+// coming from the material, so the cubes created with it share the same pipeline
+pub fn create_cube_from_material(
+    context: &DrawContext,
+    material: &Material,
+) -> anyhow::Result<Object3D> {
+    let transform_uniform = Uniform::new(context, cgmath::Matrix4::identity().into());
+    let mut drawable_builder = DrawableBuilder::from_material(
+        context,
+        material,
+        DrawModeParams::Indexed {
+            index_data: IndexData::U16(CUBE_INDICES_COMPACT.into()),
+        },
+    )?;
+    drawable_builder
+        .add_attribute(
+            0,
+            wgpu::VertexStepMode::Vertex,
+            CUBE_GEOMETRY_COMPACT,
+            wgpu::VertexFormat::Float32x3,
+        )?
+        .add_attribute(
+            1,
+            wgpu::VertexStepMode::Vertex,
+            CUBE_COLOR_COMPACT,
+            wgpu::VertexFormat::Float32x3,
+        )?
+        .add_binding_slot(&BindingSlot {
+            bind_group: DrawContext::BIND_GROUP_INDEX_MODEL,
+            binding: 0,
+            resource: &transform_uniform,
+        })?;
+    Ok(Object3D::new(
+        drawable_builder.build(),
+        Object3DUniforms {
+            view: transform_uniform,
+            normals: None,
+        },
+    )
+    .with_local_bounds(CUBE_BOUNDS))
 }
 
 pub fn create_cube_with_normals(
@@ -240,6 +357,7 @@ pub fn create_cube_with_normals(
 ) -> Object3D {
     let transform_uniform = Uniform::new(context, cgmath::Matrix4::identity().into());
     let normals_uniform = Uniform::new(context, cgmath::Matrix3::identity().into());
+    let opacity_uniform = options.opacity_uniform(context);
 
     let mut drawable_builder = DrawableBuilder::new(
         context,
@@ -266,39 +384,66 @@ pub fn create_cube_with_normals(
         )
         .expect("Location should be different than for another attribute.")
         .add_binding_slot(&BindingSlot {
-            bind_group: 0,
-            binding: 0,
+            bind_group: DrawContext::BIND_GROUP_INDEX_CAMERA,
+            binding: Scene3DUniforms::BINDING_CAMERA_MAT,
             resource: &uniforms.camera_mat,
         })
         .expect("Bind group or binding should be different from other uniforms.")
         .add_binding_slot(&BindingSlot {
-            bind_group: 0,
-            binding: 1,
+            bind_group: DrawContext::BIND_GROUP_INDEX_CAMERA,
+            binding: Scene3DUniforms::BINDING_CAMERA_POS,
             resource: &uniforms.camera_pos,
         })
         .expect("Bind group or binding should be different from other uniforms.")
         .add_binding_slot(&BindingSlot {
-            bind_group: 1,
+            bind_group: DrawContext::BIND_GROUP_INDEX_MODEL,
             binding: 0,
             resource: &transform_uniform,
         })
         .expect("Bind group or binding should be different from other uniforms.")
         .add_binding_slot(&BindingSlot {
-            bind_group: 1,
+            bind_group: DrawContext::BIND_GROUP_INDEX_MODEL,
             binding: 1,
             resource: &normals_uniform,
         })
         .expect("Bind group or binding should be different from other uniforms.");
+    if let Some(shadow_caster) = options.shadow_caster {
+        for binding_slot in &shadow_caster.shadow_map.binding_slots() {
+            drawable_builder
+                .add_binding_slot(binding_slot)
+                .expect("Bind group or binding should be different from other uniforms.");
+        }
+        drawable_builder.set_shadow_caster(shadow_caster);
+    }
+    if let Some(environment_map) = options.environment_map {
+        for binding_slot in &environment_map.binding_slots() {
+            drawable_builder
+                .add_binding_slot(binding_slot)
+                .expect("Bind group or binding should be different from other uniforms.");
+        }
+    }
 
-    if options.with_alpha {
-        drawable_builder.set_blend_option(wgpu::BlendState {
-            color: wgpu::BlendComponent {
-                src_factor: wgpu::BlendFactor::Constant,
-                dst_factor: wgpu::BlendFactor::OneMinusConstant,
-                operation: wgpu::BlendOperation::Add,
-            },
-            alpha: wgpu::BlendComponent::default(),
-        });
+    drawable_builder.set_depth_prepass(options.depth_prepass);
+    if let Some(target_format) = options.target_format {
+        drawable_builder.set_color_target_format(target_format);
+    }
+    if let Some(pick_module) = options.pick_module {
+        drawable_builder.set_pick_module(pick_module);
+    }
+    if let Some(wireframe_module) = options.wireframe_module {
+        drawable_builder.set_wireframe_overlay(wireframe_module);
+    }
+    if let Some(blend) = options.alpha_blend() {
+        drawable_builder.set_blend_option(blend);
+    }
+    if let Some(opacity_uniform) = &opacity_uniform {
+        drawable_builder
+            .add_binding_slot(&BindingSlot {
+                bind_group: DrawContext::BIND_GROUP_INDEX_MODEL,
+                binding: Object3D::BINDING_OPACITY,
+                resource: opacity_uniform,
+            })
+            .expect("Bind group or binding should be different from other uniforms.");
     }
     let drawable = drawable_builder.build();
     Object3D::new(
@@ -308,6 +453,8 @@ pub fn create_cube_with_normals(
             normals: Some(normals_uniform),
         },
     )
+    .with_local_bounds(CUBE_BOUNDS)
+    .with_opacity_uniform(opacity_uniform)
 }
 
 pub fn create_cube_with_normals_instances(
@@ -345,39 +492,35 @@ pub fn create_cube_with_normals_instances(
         )
         .expect("Location should be different than for another attribute.")
         .add_binding_slot(&BindingSlot {
-            bind_group: 0,
-            binding: 0,
+            bind_group: DrawContext::BIND_GROUP_INDEX_CAMERA,
+            binding: Scene3DUniforms::BINDING_CAMERA_MAT,
             resource: &uniforms.camera_mat,
         })
         .expect("Bind group or binding should be different from other uniforms.")
         .add_binding_slot(&BindingSlot {
-            bind_group: 0,
-            binding: 1,
+            bind_group: DrawContext::BIND_GROUP_INDEX_CAMERA,
+            binding: Scene3DUniforms::BINDING_CAMERA_POS,
             resource: &uniforms.camera_pos,
         })
-        .expect("Bind group or binding should be different from other uniforms.")
-        .add_binding_slot(&BindingSlot {
-            bind_group: 1,
-            binding: 0,
-            resource: &handlers.transforms,
-        })
-        .expect("Bind group or binding should be different from other uniforms.")
-        .add_binding_slot(&BindingSlot {
-            bind_group: 1,
-            binding: 1,
-            resource: &handlers.normal_mats,
-        })
         .expect("Bind group or binding should be different from other uniforms.");
+    for binding_slot in &handlers.model_binding_slots() {
+        drawable_builder
+            .add_binding_slot(binding_slot)
+            .expect("Bind group or binding should be different from other uniforms.");
+    }
 
-    if options.with_alpha {
-        drawable_builder.set_blend_option(wgpu::BlendState {
-            color: wgpu::BlendComponent {
-                src_factor: wgpu::BlendFactor::Constant,
-                dst_factor: wgpu::BlendFactor::OneMinusConstant,
-                operation: wgpu::BlendOperation::Add,
-            },
-            alpha: wgpu::BlendComponent::default(),
-        });
+    drawable_builder.set_depth_prepass(options.depth_prepass);
+    if let Some(target_format) = options.target_format {
+        drawable_builder.set_color_target_format(target_format);
+    }
+    if let Some(pick_module) = options.pick_module {
+        drawable_builder.set_pick_module(pick_module);
+    }
+    if let Some(wireframe_module) = options.wireframe_module {
+        drawable_builder.set_wireframe_overlay(wireframe_module);
+    }
+    if let Some(blend) = options.alpha_blend() {
+        drawable_builder.set_blend_option(blend);
     }
     let drawable = drawable_builder.build();
     Object3DInstanceGroup::new(drawable, handlers)
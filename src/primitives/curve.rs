@@ -0,0 +1,261 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use anyhow::bail;
+use cgmath::{EuclideanSpace, InnerSpace, Matrix4, Point3, SquareMatrix, Vector3};
+
+use crate::draw_context::{
+    BindingSlot, DrawContext, DrawModeParams, DrawableBuilder, IndexData, Uniform,
+};
+use crate::plugins::scene_3d::Scene3DUniforms;
+use crate::primitives::bounds::Aabb;
+use crate::primitives::{Object3D, Object3DUniforms};
+
+// Segments of the polyline approximating the curve to measure its length
+const ARC_LENGTH_RESOLUTION: usize = 256;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CurveKind {
+    // Single curve of degree n - 1, only passing through the first and last control points
+    Bezier,
+    // Passes through all the control points
+    CatmullRom,
+}
+
+#[derive(Clone, Debug)]
+pub struct Curve {
+    control_points: Vec<Point3<f32>>,
+    kind: CurveKind,
+    // Cumulated length at each of the ARC_LENGTH_RESOLUTION + 1 evenly spaced parameters
+    arc_lengths: Vec<f32>,
+}
+
+impl Curve {
+    pub fn new(control_points: &[Point3<f32>], kind: CurveKind) -> anyhow::Result<Self> {
+        if control_points.is_empty() {
+            bail!("A curve needs at least one control point");
+        }
+        let mut curve = Self {
+            control_points: control_points.to_vec(),
+            kind,
+            arc_lengths: Vec::with_capacity(ARC_LENGTH_RESOLUTION + 1),
+        };
+        let mut length = 0.0;
+        let mut previous = curve.evaluate(0.0);
+        curve.arc_lengths.push(0.0);
+        for step in 1..=ARC_LENGTH_RESOLUTION {
+            #[allow(clippy::cast_precision_loss)]
+            let point = curve.evaluate(step as f32 / ARC_LENGTH_RESOLUTION as f32);
+            length += (point - previous).magnitude();
+            curve.arc_lengths.push(length);
+            previous = point;
+        }
+        Ok(curve)
+    }
+
+    #[must_use]
+    pub fn control_points(&self) -> &[Point3<f32>] {
+        &self.control_points
+    }
+
+    #[must_use]
+    pub fn kind(&self) -> CurveKind {
+        self.kind
+    }
+
+    #[must_use]
+    pub fn length(&self) -> f32 {
+        self.arc_lengths[ARC_LENGTH_RESOLUTION]
+    }
+
+    // Point at the curve parameter t in [0, 1]. The speed along the curve varies with the
+    // spacing of the control points, see evaluate_uniform for a constant speed.
+    #[must_use]
+    pub fn evaluate(&self, t: f32) -> Point3<f32> {
+        let t = t.clamp(0.0, 1.0);
+        match self.kind {
+            CurveKind::Bezier => bezier(&self.control_points, t),
+            CurveKind::CatmullRom => catmull_rom(&self.control_points, t),
+        }
+    }
+
+    // Point at the fraction s in [0, 1] of the length of the curve
+    #[must_use]
+    pub fn evaluate_uniform(&self, s: f32) -> Point3<f32> {
+        self.evaluate(self.parameter_at(s))
+    }
+
+    // Unit direction of the curve at the fraction s of its length, +Z where it is degenerate
+    #[must_use]
+    pub fn tangent_uniform(&self, s: f32) -> Vector3<f32> {
+        #[allow(clippy::cast_precision_loss)]
+        let step = 1.0 / ARC_LENGTH_RESOLUTION as f32;
+        let t = self.parameter_at(s).clamp(step, 1.0 - step);
+        let tangent = self.evaluate(t + step) - self.evaluate(t - step);
+        if tangent.magnitude2() > 0.0 {
+            tangent.normalize()
+        } else {
+            Vector3::unit_z()
+        }
+    }
+
+    // Points evenly spaced along the curve, including both ends
+    #[must_use]
+    pub fn samples(&self, count: u32) -> Vec<Point3<f32>> {
+        let count = count.max(2);
+        (0..count)
+            .map(|index| {
+                #[allow(clippy::cast_precision_loss)]
+                self.evaluate_uniform(index as f32 / (count - 1) as f32)
+            })
+            .collect()
+    }
+
+    // Inverts the arc length table, interpolating linearly between its entries
+    fn parameter_at(&self, s: f32) -> f32 {
+        let length = self.length();
+        if length <= 0.0 {
+            return s.clamp(0.0, 1.0);
+        }
+        let target = s.clamp(0.0, 1.0) * length;
+        let index = self
+            .arc_lengths
+            .partition_point(|&arc_length| arc_length < target)
+            .clamp(1, ARC_LENGTH_RESOLUTION);
+        let (start, end) = (self.arc_lengths[index - 1], self.arc_lengths[index]);
+        let fraction = if end > start {
+            (target - start) / (end - start)
+        } else {
+            0.0
+        };
+        #[allow(clippy::cast_precision_loss)]
+        let parameter = (index as f32 - 1.0 + fraction) / ARC_LENGTH_RESOLUTION as f32;
+        parameter
+    }
+}
+
+// De Casteljau algorithm
+fn bezier(control_points: &[Point3<f32>], t: f32) -> Point3<f32> {
+    let mut points = control_points.to_vec();
+    for level in (1..points.len()).rev() {
+        for index in 0..level {
+            points[index] = points[index] + (points[index + 1] - points[index]) * t;
+        }
+    }
+    points[0]
+}
+
+// Uniform Catmull-Rom spline, each control point being reached at an equal share of t. The
+// missing neighbours of the end points are mirrored from the next ones, so the curve leaves
+// them heading toward their neighbour.
+fn catmull_rom(control_points: &[Point3<f32>], t: f32) -> Point3<f32> {
+    let count = control_points.len();
+    if count == 1 {
+        return control_points[0];
+    }
+    let point = |index: isize| match usize::try_from(index) {
+        Err(_) => control_points[0] + (control_points[0] - control_points[1]),
+        Ok(index) if index >= count => {
+            control_points[count - 1] + (control_points[count - 1] - control_points[count - 2])
+        }
+        Ok(index) => control_points[index],
+    };
+    #[allow(clippy::cast_precision_loss)]
+    let scaled = t * (count - 1) as f32;
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let segment = (scaled.floor() as usize).min(count - 2);
+    #[allow(clippy::cast_precision_loss)]
+    let local = scaled - segment as f32;
+    let segment = isize::try_from(segment).expect("Control point count should fit in isize");
+    let [p0, p1, p2, p3] = [-1, 0, 1, 2].map(|offset| point(segment + offset).to_vec());
+    let (local2, local3) = (local * local, local * local * local);
+    Point3::from_vec(
+        (p1 * 2.0
+            + (p2 - p0) * local
+            + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * local2
+            + (p1 * 3.0 - p0 - p2 * 3.0 + p3) * local3)
+            * 0.5,
+    )
+}
+
+// Line list of samples points evenly spaced along the curve, with a position attribute at
+// location 0, the camera matrix and the model transform bound as for the meshes
+pub fn create_curve(
+    context: &DrawContext,
+    vtx_module: &wgpu::ShaderModule,
+    frg_module: &wgpu::ShaderModule,
+    uniforms: &Scene3DUniforms,
+    control_points: &[Point3<f32>],
+    samples: u32,
+    kind: CurveKind,
+) -> anyhow::Result<Object3D> {
+    let positions: Vec<[f32; 3]> = Curve::new(control_points, kind)?
+        .samples(samples)
+        .into_iter()
+        .map(Into::into)
+        .collect();
+    let segment_count = u32::try_from(positions.len() - 1).expect("Sample count should fit in u32");
+    let indices: Vec<u32> = (0..segment_count)
+        .flat_map(|index| [index, index + 1])
+        .collect();
+    let transform_uniform = Uniform::new(context, Matrix4::identity().into());
+    let mut drawable_builder = DrawableBuilder::new(
+        context,
+        vtx_module,
+        frg_module,
+        DrawModeParams::Indexed {
+            index_data: IndexData::from_u32_auto(&indices),
+        },
+    );
+    drawable_builder
+        .add_attribute(
+            0,
+            wgpu::VertexStepMode::Vertex,
+            &positions,
+            wgpu::VertexFormat::Float32x3,
+        )?
+        .add_binding_slot(&BindingSlot {
+            bind_group: DrawContext::BIND_GROUP_INDEX_CAMERA,
+            binding: Scene3DUniforms::BINDING_CAMERA_MAT,
+            resource: &uniforms.camera_mat,
+        })?
+        .add_binding_slot(&BindingSlot {
+            bind_group: DrawContext::BIND_GROUP_INDEX_MODEL,
+            binding: 0,
+            resource: &transform_uniform,
+        })?
+        .set_topology(wgpu::PrimitiveTopology::LineList)
+        .set_double_sided(true);
+    let object = Object3D::new(
+        drawable_builder.build(),
+        Object3DUniforms {
+            view: transform_uniform,
+            normals: None,
+        },
+    );
+    Ok(match Aabb::from_points(&positions) {
+        Some(bounds) => object.with_local_bounds(bounds),
+        None => object,
+    })
+}
@@ -0,0 +1,310 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use std::collections::HashSet;
+
+use cgmath::{InnerSpace, Matrix3, Matrix4, SquareMatrix, Vector3};
+
+use crate::draw_context::{
+    BindingSlot, DrawContext, DrawModeParams, DrawableBuilder, IndexData, Uniform,
+};
+use crate::plugins::scene_3d::Scene3DUniforms;
+use crate::primitives::bounds::Aabb;
+use crate::primitives::{
+    Object3D, Object3DInstanceGroup, Object3DInstanceGroupHandlers, Object3DUniforms,
+};
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MeshData {
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Vec<[f32; 3]>,
+    pub indices: Vec<u32>,
+}
+
+impl MeshData {
+    #[must_use]
+    pub fn bounds(&self) -> Option<Aabb> {
+        Aabb::from_points(&self.positions)
+    }
+    // For imported meshes wound clockwise, so their front faces survive back-face culling
+    pub fn flip_winding(&mut self) {
+        flip_winding(&mut self.indices);
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MeshOptions {
+    pub double_sided: bool,
+    // Instance count of create_mesh_instances, a single instance when not set
+    pub instances: Option<u32>,
+}
+
+// Line list of the unique edges of a triangle list, for wireframe rendering
+#[must_use]
+pub fn triangle_edges(indices: &[u32]) -> Vec<u32> {
+    let mut visited = HashSet::new();
+    let mut edges = Vec::new();
+    for triangle in indices.chunks_exact(3) {
+        for (start, end) in [
+            (triangle[0], triangle[1]),
+            (triangle[1], triangle[2]),
+            (triangle[2], triangle[0]),
+        ] {
+            if visited.insert((start.min(end), start.max(end))) {
+                edges.extend([start, end]);
+            }
+        }
+    }
+    edges
+}
+
+// Swaps the last two indices of each triangle of a triangle list
+pub fn flip_winding(indices: &mut [u32]) {
+    for triangle in indices.chunks_exact_mut(3) {
+        triangle.swap(1, 2);
+    }
+}
+
+// Bakes each transform into its mesh, so the result can be drawn in a single draw call
+#[must_use]
+pub fn merge(meshes: &[(Matrix4<f32>, MeshData)]) -> MeshData {
+    let mut merged = MeshData::default();
+    for (transform, mesh) in meshes {
+        let index_offset =
+            u32::try_from(merged.positions.len()).expect("Vertex count should fit in u32");
+        let linear = Matrix3::from_cols(
+            transform.x.truncate(),
+            transform.y.truncate(),
+            transform.z.truncate(),
+        );
+        // Cofactor matrix, the inverse transpose scaled by the determinant, still defined for
+        // singular transforms such as zero-scale parts. Its sign is kept positive for mirrors.
+        let cofactor = Matrix3::from_cols(
+            linear.y.cross(linear.z),
+            linear.z.cross(linear.x),
+            linear.x.cross(linear.y),
+        );
+        let is_mirrored = linear.determinant() < 0.0;
+        let normal_mat = if is_mirrored { -cofactor } else { cofactor };
+        merged
+            .positions
+            .extend(mesh.positions.iter().map(|&position| {
+                Into::<[f32; 3]>::into((transform * Vector3::from(position).extend(1.0)).truncate())
+            }));
+        merged.normals.extend(
+            mesh.normals.iter().map(|&normal| {
+                Into::<[f32; 3]>::into(normalize(normal_mat * Vector3::from(normal)))
+            }),
+        );
+        let first_index = merged.indices.len();
+        merged
+            .indices
+            .extend(mesh.indices.iter().map(|index| index + index_offset));
+        // Mirrors reverse the winding, which would let back-face culling hide the front faces
+        if is_mirrored {
+            flip_winding(&mut merged.indices[first_index..]);
+        }
+    }
+    merged
+}
+
+fn normalize(vector: Vector3<f32>) -> Vector3<f32> {
+    if vector.magnitude2() > 0.0 {
+        vector.normalize()
+    } else {
+        vector
+    }
+}
+
+pub fn create_mesh_with_normals(
+    context: &DrawContext,
+    vtx_module: &wgpu::ShaderModule,
+    frg_module: &wgpu::ShaderModule,
+    uniforms: &Scene3DUniforms,
+    mesh: &MeshData,
+) -> Object3D {
+    create_mesh_with_options(
+        context,
+        vtx_module,
+        frg_module,
+        uniforms,
+        mesh,
+        &MeshOptions::default(),
+    )
+}
+
+pub fn create_mesh_with_options(
+    context: &DrawContext,
+    vtx_module: &wgpu::ShaderModule,
+    frg_module: &wgpu::ShaderModule,
+    uniforms: &Scene3DUniforms,
+    mesh: &MeshData,
+    options: &MeshOptions,
+) -> Object3D {
+    create_mesh_with_bindings(
+        context,
+        (vtx_module, frg_module),
+        uniforms,
+        mesh,
+        options,
+        &[],
+    )
+}
+
+// With the material bindings of the shaders, in addition to the camera and model ones
+pub(crate) fn create_mesh_with_bindings(
+    context: &DrawContext,
+    (vtx_module, frg_module): (&wgpu::ShaderModule, &wgpu::ShaderModule),
+    uniforms: &Scene3DUniforms,
+    mesh: &MeshData,
+    options: &MeshOptions,
+    binding_slots: &[BindingSlot],
+) -> Object3D {
+    let transform_uniform = Uniform::new(context, Matrix4::identity().into());
+    let normals_uniform = Uniform::new(context, Matrix3::identity().into());
+
+    let mut drawable_builder = DrawableBuilder::new(
+        context,
+        vtx_module,
+        frg_module,
+        DrawModeParams::Indexed {
+            index_data: IndexData::from_u32_auto(&mesh.indices),
+        },
+    );
+    drawable_builder
+        .add_attribute(
+            0,
+            wgpu::VertexStepMode::Vertex,
+            &mesh.positions,
+            wgpu::VertexFormat::Float32x3,
+        )
+        .expect("Location should be different than for another attribute.")
+        .add_attribute(
+            1,
+            wgpu::VertexStepMode::Vertex,
+            &mesh.normals,
+            wgpu::VertexFormat::Float32x3,
+        )
+        .expect("Location should be different than for another attribute.")
+        .add_binding_slot(&BindingSlot {
+            bind_group: DrawContext::BIND_GROUP_INDEX_CAMERA,
+            binding: Scene3DUniforms::BINDING_CAMERA_MAT,
+            resource: &uniforms.camera_mat,
+        })
+        .expect("Bind group or binding should be different from other uniforms.")
+        .add_binding_slot(&BindingSlot {
+            bind_group: DrawContext::BIND_GROUP_INDEX_CAMERA,
+            binding: Scene3DUniforms::BINDING_CAMERA_POS,
+            resource: &uniforms.camera_pos,
+        })
+        .expect("Bind group or binding should be different from other uniforms.")
+        .add_binding_slot(&BindingSlot {
+            bind_group: DrawContext::BIND_GROUP_INDEX_MODEL,
+            binding: 0,
+            resource: &transform_uniform,
+        })
+        .expect("Bind group or binding should be different from other uniforms.")
+        .add_binding_slot(&BindingSlot {
+            bind_group: DrawContext::BIND_GROUP_INDEX_MODEL,
+            binding: 1,
+            resource: &normals_uniform,
+        })
+        .expect("Bind group or binding should be different from other uniforms.")
+        .set_double_sided(options.double_sided);
+    for binding_slot in binding_slots {
+        drawable_builder
+            .add_binding_slot(binding_slot)
+            .expect("Bind group or binding should be different from other uniforms.");
+    }
+    let drawable = drawable_builder.build();
+    let object = Object3D::new(
+        drawable,
+        Object3DUniforms {
+            view: transform_uniform,
+            normals: Some(normals_uniform),
+        },
+    );
+    match mesh.bounds() {
+        Some(bounds) => object.with_local_bounds(bounds),
+        None => object,
+    }
+}
+
+// Same bindings as the instanced cube, the transform and normal matrix of each instance being
+// read from storage buffers in the model bind group
+pub fn create_mesh_instances(
+    context: &DrawContext,
+    vtx_module: &wgpu::ShaderModule,
+    frg_module: &wgpu::ShaderModule,
+    uniforms: &Scene3DUniforms,
+    mesh: &MeshData,
+    options: &MeshOptions,
+) -> Object3DInstanceGroup {
+    let count = options.instances.unwrap_or(1);
+    let handlers = Object3DInstanceGroupHandlers::new(context, count);
+    let mut drawable_builder = DrawableBuilder::new(
+        context,
+        vtx_module,
+        frg_module,
+        DrawModeParams::Indexed {
+            index_data: IndexData::from_u32_auto(&mesh.indices),
+        },
+    );
+    drawable_builder
+        .set_instance_count(count)
+        .add_attribute(
+            0,
+            wgpu::VertexStepMode::Vertex,
+            &mesh.positions,
+            wgpu::VertexFormat::Float32x3,
+        )
+        .expect("Location should be different than for another attribute.")
+        .add_attribute(
+            1,
+            wgpu::VertexStepMode::Vertex,
+            &mesh.normals,
+            wgpu::VertexFormat::Float32x3,
+        )
+        .expect("Location should be different than for another attribute.")
+        .add_binding_slot(&BindingSlot {
+            bind_group: DrawContext::BIND_GROUP_INDEX_CAMERA,
+            binding: Scene3DUniforms::BINDING_CAMERA_MAT,
+            resource: &uniforms.camera_mat,
+        })
+        .expect("Bind group or binding should be different from other uniforms.")
+        .add_binding_slot(&BindingSlot {
+            bind_group: DrawContext::BIND_GROUP_INDEX_CAMERA,
+            binding: Scene3DUniforms::BINDING_CAMERA_POS,
+            resource: &uniforms.camera_pos,
+        })
+        .expect("Bind group or binding should be different from other uniforms.")
+        .set_double_sided(options.double_sided);
+    for binding_slot in &handlers.model_binding_slots() {
+        drawable_builder
+            .add_binding_slot(binding_slot)
+            .expect("Bind group or binding should be different from other uniforms.");
+    }
+    let drawable = drawable_builder.build();
+    Object3DInstanceGroup::new(drawable, handlers)
+}
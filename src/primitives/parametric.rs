@@ -0,0 +1,117 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use cgmath::{InnerSpace, Vector3};
+
+use crate::draw_context::DrawContext;
+use crate::plugins::scene_3d::Scene3DUniforms;
+use crate::primitives::Object3D;
+use crate::primitives::mesh::{self, MeshData, MeshOptions};
+
+// Parameter offset of the central differences giving the tangents
+const DERIVATIVE_STEP: f32 = 1e-3;
+// Offset toward the center of the domain where the tangents are degenerate
+const DEGENERATE_PROBE: f32 = 1e-2;
+
+// Grid of (u_steps + 1) x (v_steps + 1) vertices sampling f over [0, 1]², with the normals of
+// the surface itself rather than of the triangles. They come from the tangents along u and v,
+// by central differences: f is also sampled just outside of the domain. On closed surfaces,
+// the vertices of the seam are duplicated, and share the same normal if f is periodic. Where
+// the tangents are degenerate, like the poles of a sphere, the normal is taken next to them.
+// The normal is dv x du, e.g. up for [u, 0, v], the triangles facing the same side.
+pub fn parametric_mesh(f: impl Fn(f32, f32) -> [f32; 3], u_steps: u32, v_steps: u32) -> MeshData {
+    let (u_steps, v_steps) = (u_steps.max(1), v_steps.max(1));
+    let mut mesh = MeshData::default();
+    for v_index in 0..=v_steps {
+        for u_index in 0..=u_steps {
+            #[allow(clippy::cast_precision_loss)]
+            let (u, v) = (
+                u_index as f32 / u_steps as f32,
+                v_index as f32 / v_steps as f32,
+            );
+            mesh.positions.push(f(u, v));
+            mesh.normals.push(surface_normal(&f, u, v).into());
+        }
+    }
+    let row = u_steps + 1;
+    for v_index in 0..v_steps {
+        for u_index in 0..u_steps {
+            let bottom_left = v_index * row + u_index;
+            let (bottom_right, top_left) = (bottom_left + 1, bottom_left + row);
+            let top_right = top_left + 1;
+            mesh.indices.extend([
+                bottom_left,
+                bottom_right,
+                top_left,
+                bottom_right,
+                top_right,
+                top_left,
+            ]);
+        }
+    }
+    mesh
+}
+
+fn surface_normal(f: &impl Fn(f32, f32) -> [f32; 3], u: f32, v: f32) -> Vector3<f32> {
+    let (du, dv) = tangents(f, u, v);
+    let normal = dv.cross(du);
+    if normal.magnitude2() > f32::EPSILON * f32::EPSILON {
+        return normal.normalize();
+    }
+    // A row collapsing into a point, e.g. a pole, has a null tangent along it: the normal is
+    // taken on the next row, keeping the parameter along the row so the seam stays consistent
+    let is_null = |tangent: Vector3<f32>| tangent.magnitude2() <= f32::EPSILON * f32::EPSILON;
+    let toward_center = |value: f32| DEGENERATE_PROBE.copysign(0.5 - value);
+    let probe_u = if is_null(dv) { u + toward_center(u) } else { u };
+    let probe_v = if is_null(du) { v + toward_center(v) } else { v };
+    let (du, dv) = tangents(f, probe_u, probe_v);
+    let normal = dv.cross(du);
+    if normal.magnitude2() > 0.0 {
+        normal.normalize()
+    } else {
+        normal
+    }
+}
+
+fn tangents(f: &impl Fn(f32, f32) -> [f32; 3], u: f32, v: f32) -> (Vector3<f32>, Vector3<f32>) {
+    let sample = |u, v| Vector3::from(f(u, v));
+    (
+        sample(u + DERIVATIVE_STEP, v) - sample(u - DERIVATIVE_STEP, v),
+        sample(u, v + DERIVATIVE_STEP) - sample(u, v - DERIVATIVE_STEP),
+    )
+}
+
+// Same bindings as mesh::create_mesh_with_options, steps being the cell counts along u and v
+pub fn create_parametric(
+    context: &DrawContext,
+    vtx_module: &wgpu::ShaderModule,
+    frg_module: &wgpu::ShaderModule,
+    uniforms: &Scene3DUniforms,
+    f: impl Fn(f32, f32) -> [f32; 3],
+    (u_steps, v_steps): (u32, u32),
+    options: &MeshOptions,
+) -> Object3D {
+    let mesh = parametric_mesh(f, u_steps, v_steps);
+    mesh::create_mesh_with_options(context, vtx_module, frg_module, uniforms, &mesh, options)
+}
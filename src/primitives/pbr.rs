@@ -0,0 +1,152 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::draw_context::{BindingSlot, DrawContext, Uniform};
+use crate::plugins::scene_3d::Scene3DUniforms;
+use crate::primitives::Object3D;
+use crate::primitives::mesh::{self, MeshData, MeshOptions};
+use crate::primitives::phong::PhongLight;
+use crate::support::environment::EnvironmentMap;
+
+const PBR_SHADER: &str = include_str!("../shaders/pbr.wgsl");
+// Without an environment map, a dim constant ambient light
+const CONSTANT_AMBIENT: &str = "
+fn pbr_ambient(normal: vec3<f32>, albedo: vec3<f32>) -> vec3<f32> {
+    return 0.03 * albedo;
+}
+";
+const IBL_AMBIENT: &str = "
+fn pbr_ambient(normal: vec3<f32>, albedo: vec3<f32>) -> vec3<f32> {
+    return ibl_diffuse(normal, albedo);
+}
+";
+
+// Metallic-roughness parameters, the base color being in linear space
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PbrMaterial {
+    pub base_color: [f32; 3],
+    // Between 0 for a dielectric and 1 for a metal
+    pub metallic: f32,
+    // Between 0 for a mirror-like surface and 1 for a fully rough one
+    pub roughness: f32,
+}
+
+impl Default for PbrMaterial {
+    fn default() -> Self {
+        Self {
+            base_color: [0.8, 0.8, 0.8],
+            metallic: 0.0,
+            roughness: 0.5,
+        }
+    }
+}
+
+impl PbrMaterial {
+    fn uniform_value(&self) -> [[f32; 4]; 2] {
+        let [r, g, b] = self.base_color;
+        [[r, g, b, 1.0], [self.metallic, self.roughness, 0.0, 0.0]]
+    }
+}
+
+// Cook-Torrance shading under a single light, the ambient term sampling the irradiance of
+// the environment map if any. The material bindings follow the ones of the environment map.
+pub struct PbrShading<'a> {
+    shader_module: wgpu::ShaderModule,
+    material: Uniform<[[f32; 4]; 2]>,
+    light: Uniform<[[f32; 4]; 2]>,
+    environment_map: Option<&'a EnvironmentMap>,
+}
+
+impl<'a> PbrShading<'a> {
+    pub const BINDING_MATERIAL: u32 = 2;
+    pub const BINDING_LIGHT: u32 = 3;
+
+    #[must_use]
+    pub fn new(
+        context: &DrawContext,
+        material: &PbrMaterial,
+        light: &PhongLight,
+        environment_map: Option<&'a EnvironmentMap>,
+    ) -> Self {
+        let shader = match environment_map {
+            Some(_) => format!(
+                "{}\n{IBL_AMBIENT}\n{PBR_SHADER}",
+                EnvironmentMap::SHADER_SNIPPET
+            ),
+            None => format!("{CONSTANT_AMBIENT}\n{PBR_SHADER}"),
+        };
+        Self {
+            shader_module: context.create_shader_module(&shader),
+            material: Uniform::new(context, material.uniform_value()),
+            light: Uniform::new(context, light.uniform_value()),
+            environment_map,
+        }
+    }
+
+    pub fn set_material(&mut self, material: &PbrMaterial) {
+        self.material.write_uniform(material.uniform_value());
+    }
+
+    pub fn set_light(&mut self, light: &PhongLight) {
+        self.light.write_uniform(light.uniform_value());
+    }
+
+    #[must_use]
+    pub fn binding_slots(&self) -> Vec<BindingSlot<'_>> {
+        let mut binding_slots = vec![
+            BindingSlot {
+                bind_group: DrawContext::BIND_GROUP_INDEX_MATERIAL,
+                binding: Self::BINDING_MATERIAL,
+                resource: &self.material,
+            },
+            BindingSlot {
+                bind_group: DrawContext::BIND_GROUP_INDEX_MATERIAL,
+                binding: Self::BINDING_LIGHT,
+                resource: &self.light,
+            },
+        ];
+        if let Some(environment_map) = self.environment_map {
+            binding_slots.extend(environment_map.binding_slots());
+        }
+        binding_slots
+    }
+}
+
+// Same requirements as phong::create_phong_mesh
+pub fn create_pbr_mesh(
+    context: &DrawContext,
+    uniforms: &Scene3DUniforms,
+    mesh: &MeshData,
+    shading: &PbrShading,
+    options: &MeshOptions,
+) -> Object3D {
+    mesh::create_mesh_with_bindings(
+        context,
+        (&shading.shader_module, &shading.shader_module),
+        uniforms,
+        mesh,
+        options,
+        &shading.binding_slots(),
+    )
+}
@@ -0,0 +1,157 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use cgmath::{Point3, Vector3};
+
+use crate::draw_context::{BindingSlot, DrawContext, Uniform};
+use crate::plugins::scene_3d::Scene3DUniforms;
+use crate::primitives::Object3D;
+use crate::primitives::mesh::{self, MeshData, MeshOptions};
+
+// Colors in linear space, the specular highlight narrowing as the shininess grows
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PhongMaterial {
+    pub ambient: [f32; 3],
+    pub diffuse: [f32; 3],
+    pub specular: [f32; 3],
+    pub shininess: f32,
+}
+
+impl Default for PhongMaterial {
+    fn default() -> Self {
+        Self {
+            ambient: [0.1, 0.1, 0.1],
+            diffuse: [0.8, 0.8, 0.8],
+            specular: [1.0, 1.0, 1.0],
+            shininess: 32.0,
+        }
+    }
+}
+
+impl PhongMaterial {
+    fn uniform_value(&self) -> [[f32; 4]; 3] {
+        let [r, g, b] = self.ambient;
+        let ambient = [r, g, b, self.shininess];
+        let [r, g, b] = self.diffuse;
+        let diffuse = [r, g, b, 0.0];
+        let [r, g, b] = self.specular;
+        [ambient, diffuse, [r, g, b, 0.0]]
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PhongLight {
+    // Direction the light travels in, e.g. downward for a light above the scene
+    Directional {
+        direction: Vector3<f32>,
+        color: [f32; 3],
+    },
+    Point {
+        position: Point3<f32>,
+        color: [f32; 3],
+    },
+}
+
+impl Default for PhongLight {
+    fn default() -> Self {
+        Self::Directional {
+            direction: Vector3::new(0.0, -1.0, -1.0),
+            color: [1.0, 1.0, 1.0],
+        }
+    }
+}
+
+impl PhongLight {
+    pub(crate) fn uniform_value(&self) -> [[f32; 4]; 2] {
+        let (vector, [r, g, b]) = match *self {
+            Self::Directional { direction, color } => (direction.extend(0.0), color),
+            Self::Point { position, color } => (position.to_homogeneous(), color),
+        };
+        [vector.into(), [r, g, b, 1.0]]
+    }
+}
+
+// Shader and uniforms of the material and its light, which can be shared by several meshes
+pub struct PhongShading {
+    shader_module: wgpu::ShaderModule,
+    material: Uniform<[[f32; 4]; 3]>,
+    light: Uniform<[[f32; 4]; 2]>,
+}
+
+impl PhongShading {
+    pub const BINDING_MATERIAL: u32 = 0;
+    pub const BINDING_LIGHT: u32 = 1;
+    pub const SHADER: &str = include_str!("../shaders/phong.wgsl");
+
+    #[must_use]
+    pub fn new(context: &DrawContext, material: &PhongMaterial, light: &PhongLight) -> Self {
+        Self {
+            shader_module: context.create_shader_module(Self::SHADER),
+            material: Uniform::new(context, material.uniform_value()),
+            light: Uniform::new(context, light.uniform_value()),
+        }
+    }
+
+    pub fn set_material(&mut self, material: &PhongMaterial) {
+        self.material.write_uniform(material.uniform_value());
+    }
+
+    pub fn set_light(&mut self, light: &PhongLight) {
+        self.light.write_uniform(light.uniform_value());
+    }
+
+    #[must_use]
+    pub fn binding_slots(&self) -> [BindingSlot<'_>; 2] {
+        [
+            BindingSlot {
+                bind_group: DrawContext::BIND_GROUP_INDEX_MATERIAL,
+                binding: Self::BINDING_MATERIAL,
+                resource: &self.material,
+            },
+            BindingSlot {
+                bind_group: DrawContext::BIND_GROUP_INDEX_MATERIAL,
+                binding: Self::BINDING_LIGHT,
+                resource: &self.light,
+            },
+        ]
+    }
+}
+
+// The mesh needs normals, the camera position coming from the scene uniforms
+pub fn create_phong_mesh(
+    context: &DrawContext,
+    uniforms: &Scene3DUniforms,
+    mesh: &MeshData,
+    shading: &PhongShading,
+    options: &MeshOptions,
+) -> Object3D {
+    mesh::create_mesh_with_bindings(
+        context,
+        (&shading.shader_module, &shading.shader_module),
+        uniforms,
+        mesh,
+        options,
+        &shading.binding_slots(),
+    )
+}
@@ -0,0 +1,135 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use std::f32::consts::{PI, TAU};
+
+use crate::draw_context::DrawContext;
+use crate::plugins::scene_3d::Scene3DUniforms;
+use crate::primitives::mesh::{self, MeshData, MeshOptions};
+use crate::primitives::{Object3D, Object3DInstanceGroup};
+
+// Same extent as the cube
+const RADIUS: f32 = 0.5;
+const MIN_SUBDIVISIONS: u32 = 3;
+
+// Sphere of radius 0.5 split into rings bands of latitude and sectors bands of longitude, both
+// clamped to at least 3. Each pole is a single vertex closed by a fan of triangles, so no
+// triangle collapses into a line there.
+#[must_use]
+pub fn uv_sphere_mesh(rings: u32, sectors: u32) -> MeshData {
+    let rings = rings.max(MIN_SUBDIVISIONS);
+    let latitudes: Vec<_> = (1..rings)
+        .map(|ring| {
+            #[allow(clippy::cast_precision_loss)]
+            let polar_angle = PI * ring as f32 / rings as f32;
+            (polar_angle, 0.0)
+        })
+        .collect();
+    latitude_mesh(
+        &latitudes,
+        sectors.max(MIN_SUBDIVISIONS),
+        RADIUS,
+        (0.0, 0.0),
+    )
+}
+
+// Rings of vertices at the given polar angles from +Y, each one raised by its height offset,
+// between a top and a bottom pole themselves raised by pole_offsets. The normals are those of
+// a sphere, so rings sharing a polar angle at different heights are joined by a cylinder
+// with continuous normals. The last sector is joined to the first one instead of duplicating
+// the vertices of the seam.
+pub(crate) fn latitude_mesh(
+    latitudes: &[(f32, f32)],
+    sectors: u32,
+    radius: f32,
+    (top_offset, bottom_offset): (f32, f32),
+) -> MeshData {
+    let mut mesh = MeshData::default();
+    let mut push_vertex = |normal: [f32; 3], offset: f32| {
+        let [x, y, z] = normal.map(|value| value * radius);
+        mesh.positions.push([x, y + offset, z]);
+        mesh.normals.push(normal);
+    };
+    push_vertex([0.0, 1.0, 0.0], top_offset);
+    for &(polar_angle, offset) in latitudes {
+        let (sin_phi, cos_phi) = polar_angle.sin_cos();
+        for sector in 0..sectors {
+            #[allow(clippy::cast_precision_loss)]
+            let (sin_theta, cos_theta) = (TAU * sector as f32 / sectors as f32).sin_cos();
+            push_vertex([sin_phi * cos_theta, cos_phi, sin_phi * sin_theta], offset);
+        }
+    }
+    push_vertex([0.0, -1.0, 0.0], bottom_offset);
+    let ring_count = u32::try_from(latitudes.len()).expect("Ring count should fit in u32");
+    // Vertex of the given sector on the given ring, the first one being 0
+    let ring_vertex = |ring: u32, sector: u32| 1 + ring * sectors + sector % sectors;
+    let (top, bottom) = (0, ring_vertex(ring_count, 0));
+    for sector in 0..sectors {
+        mesh.indices
+            .extend([top, ring_vertex(0, sector), ring_vertex(0, sector + 1)]);
+        for ring in 0..ring_count - 1 {
+            let (upper, upper_next) = (ring_vertex(ring, sector), ring_vertex(ring, sector + 1));
+            let (lower, lower_next) = (
+                ring_vertex(ring + 1, sector),
+                ring_vertex(ring + 1, sector + 1),
+            );
+            mesh.indices
+                .extend([upper, lower, lower_next, upper, lower_next, upper_next]);
+        }
+        mesh.indices.extend([
+            ring_vertex(ring_count - 1, sector),
+            bottom,
+            ring_vertex(ring_count - 1, sector + 1),
+        ]);
+    }
+    mesh
+}
+
+// Same bindings as create_cube_with_normals, with the model transform and normal matrix
+pub fn create_uv_sphere(
+    context: &DrawContext,
+    vtx_module: &wgpu::ShaderModule,
+    frg_module: &wgpu::ShaderModule,
+    uniforms: &Scene3DUniforms,
+    rings: u32,
+    sectors: u32,
+    options: &MeshOptions,
+) -> Object3D {
+    let mesh = uv_sphere_mesh(rings, sectors);
+    mesh::create_mesh_with_options(context, vtx_module, frg_module, uniforms, &mesh, options)
+}
+
+// Same bindings as create_cube_with_normals_instances, the count being options.instances
+pub fn create_uv_sphere_instances(
+    context: &DrawContext,
+    vtx_module: &wgpu::ShaderModule,
+    frg_module: &wgpu::ShaderModule,
+    uniforms: &Scene3DUniforms,
+    rings: u32,
+    sectors: u32,
+    options: &MeshOptions,
+) -> Object3DInstanceGroup {
+    let mesh = uv_sphere_mesh(rings, sectors);
+    mesh::create_mesh_instances(context, vtx_module, frg_module, uniforms, &mesh, options)
+}
@@ -28,6 +28,7 @@ use crate::BindingSlot;
 use crate::draw_context::{DrawContext, DrawableBuilder, Uniform};
 use crate::primitives::Object3D;
 
+use super::bounds::Aabb;
 use super::{Object3DUniforms, color};
 
 #[rustfmt::skip]
@@ -94,4 +95,7 @@ pub fn create_equilateral_triangle(
             normals: None,
         },
     )
+    .with_local_bounds(
+        Aabb::from_points(TRIANGLE_GEOMETRY).expect("The triangle should have vertices"),
+    )
 }
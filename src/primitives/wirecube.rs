@@ -0,0 +1,114 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use cgmath::{EuclideanSpace, Matrix4, Point3, SquareMatrix, Vector3};
+
+use crate::draw_context::{BindingSlot, DrawContext, DrawModeParams, DrawableBuilder, Uniform};
+use crate::plugins::scene_3d::Scene3DUniforms;
+use crate::primitives::bounds::Aabb;
+use crate::primitives::{Object3D, Object3DUniforms};
+
+// Pairs of end points of the 12 edges of the box centred on the origin. The corners joined by
+// an edge only differ along one axis, i.e. by one bit of their index in Aabb::corners.
+#[must_use]
+pub fn wire_cube_edges(half_extents: Vector3<f32>) -> Vec<[f32; 3]> {
+    let bounds = Aabb::new(
+        Point3::from_vec(-half_extents),
+        Point3::from_vec(half_extents),
+    );
+    let corners = bounds.corners();
+    let mut edges = Vec::with_capacity(24);
+    for (index, corner) in corners.iter().enumerate() {
+        for axis_bit in [1, 2, 4] {
+            if index & axis_bit == 0 {
+                edges.extend([*corner, corners[index | axis_bit]].map(Into::<[f32; 3]>::into));
+            }
+        }
+    }
+    edges
+}
+
+// Line list of the edges of a box, e.g. to show bounds. The position and the color are the
+// attributes at locations 0 and 1, bound with the camera matrix and the model transform like
+// flat.wgsl expects.
+pub fn create_wire_cube(
+    context: &DrawContext,
+    vtx_module: &wgpu::ShaderModule,
+    frg_module: &wgpu::ShaderModule,
+    uniforms: &Scene3DUniforms,
+    half_extents: Vector3<f32>,
+    color: [f32; 3],
+) -> Object3D {
+    let positions = wire_cube_edges(half_extents);
+    let colors = vec![color; positions.len()];
+    let transform_uniform = Uniform::new(context, Matrix4::identity().into());
+    let mut drawable_builder = DrawableBuilder::new(
+        context,
+        vtx_module,
+        frg_module,
+        DrawModeParams::Direct {
+            vertex_count: u32::try_from(positions.len()).expect("Edge count should fit in u32"),
+        },
+    );
+    drawable_builder
+        .add_attribute(
+            0,
+            wgpu::VertexStepMode::Vertex,
+            &positions,
+            wgpu::VertexFormat::Float32x3,
+        )
+        .expect("Location should be different than for another attribute.")
+        .add_attribute(
+            1,
+            wgpu::VertexStepMode::Vertex,
+            &colors,
+            wgpu::VertexFormat::Float32x3,
+        )
+        .expect("Location should be different than for another attribute.")
+        .add_binding_slot(&BindingSlot {
+            bind_group: DrawContext::BIND_GROUP_INDEX_CAMERA,
+            binding: Scene3DUniforms::BINDING_CAMERA_MAT,
+            resource: &uniforms.camera_mat,
+        })
+        .expect("Bind group or binding should be different from other uniforms.")
+        .add_binding_slot(&BindingSlot {
+            bind_group: DrawContext::BIND_GROUP_INDEX_MODEL,
+            binding: 0,
+            resource: &transform_uniform,
+        })
+        .expect("Bind group or binding should be different from other uniforms.")
+        .set_topology(wgpu::PrimitiveTopology::LineList)
+        .set_double_sided(true);
+    let object = Object3D::new(
+        drawable_builder.build(),
+        Object3DUniforms {
+            view: transform_uniform,
+            normals: None,
+        },
+    );
+    match Aabb::from_points(&positions) {
+        Some(bounds) => object.with_local_bounds(bounds),
+        None => object,
+    }
+}
@@ -23,26 +23,345 @@ SOFTWARE.
 */
 
 use crate::{draw_context::DrawContext, plugins::PluginRegistry};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use web_time::{Duration, Instant};
+use winit::dpi::PhysicalPosition;
 use winit::event::{DeviceEvent, KeyEvent, WindowEvent};
+use winit::event_loop::ControlFlow;
+use winit::window::Window;
 
 #[allow(clippy::manual_non_exhaustive)]
 pub struct TimeInfo {
     pub init_start: Instant,
     pub processing_delta: Duration,
+    // Wall-clock time accumulated by the render loop, the same for every handler of a frame
+    pub elapsed: Duration,
+    // Clock of the animations, driven by the TimeController of the draw context
+    pub animation_time: Duration,
+    pub animation_delta: Duration,
     pub(crate) _private: (),
 }
 
+impl TimeInfo {
+    #[must_use]
+    pub fn new(init_start: Instant, processing_delta: Duration) -> Self {
+        Self {
+            init_start,
+            processing_delta,
+            elapsed: init_start.elapsed(),
+            animation_time: init_start.elapsed(),
+            animation_delta: processing_delta,
+            _private: (),
+        }
+    }
+    #[must_use]
+    pub fn with_time_controller(
+        init_start: Instant,
+        processing_delta: Duration,
+        time_controller: &TimeController,
+    ) -> Self {
+        Self {
+            init_start,
+            processing_delta,
+            elapsed: time_controller.elapsed(),
+            animation_time: time_controller.animation_time(),
+            animation_delta: time_controller.last_delta(),
+            _private: (),
+        }
+    }
+}
+
 impl Default for TimeInfo {
     fn default() -> Self {
         Self {
             init_start: Instant::now(),
             processing_delta: Duration::new(0, 0),
+            elapsed: Duration::new(0, 0),
+            animation_time: Duration::new(0, 0),
+            animation_delta: Duration::new(0, 0),
             _private: (),
         }
     }
 }
 
+// Scenario clock which can be paused, slowed down or scrubbed while frames keep being rendered,
+// e.g. to freeze a shader while still moving the camera
+pub struct TimeController {
+    elapsed: Duration,
+    animation_time: Duration,
+    last_delta: Duration,
+    time_scale: f32,
+    paused: bool,
+}
+
+impl Default for TimeController {
+    fn default() -> Self {
+        Self {
+            elapsed: Duration::new(0, 0),
+            animation_time: Duration::new(0, 0),
+            last_delta: Duration::new(0, 0),
+            time_scale: 1.0,
+            paused: false,
+        }
+    }
+}
+
+impl TimeController {
+    // Called once per frame by the render loop with the wall-clock time of the last frame
+    pub fn advance(&mut self, processing_delta: Duration) -> Duration {
+        self.elapsed += processing_delta;
+        self.last_delta = if self.paused {
+            Duration::new(0, 0)
+        } else {
+            processing_delta.mul_f32(self.time_scale)
+        };
+        self.animation_time += self.last_delta;
+        self.last_delta
+    }
+    #[must_use]
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+    #[must_use]
+    pub fn animation_time(&self) -> Duration {
+        self.animation_time
+    }
+    #[must_use]
+    pub fn last_delta(&self) -> Duration {
+        self.last_delta
+    }
+    // Jumps to another point of the animations, whether paused or not
+    pub fn seek(&mut self, animation_time: Duration) {
+        self.animation_time = animation_time;
+    }
+    // Negative values are clamped to 0, the clock cannot run backward
+    pub fn set_time_scale(&mut self, time_scale: f32) {
+        if time_scale < 0.0 {
+            log::warn!("Time scale {time_scale} is negative, using 0 instead");
+        }
+        self.time_scale = time_scale.max(0.0);
+    }
+    #[must_use]
+    pub fn time_scale(&self) -> f32 {
+        self.time_scale
+    }
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+    #[must_use]
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+}
+
+pub(crate) const TARGET_DRAW_FPS: f64 = 60.0;
+
+// Frames taking longer than the threshold are logged as warnings, by default twice the target
+// frame period
+pub struct FrameBudget {
+    slow_frame_threshold: Option<Duration>,
+}
+
+impl Default for FrameBudget {
+    fn default() -> Self {
+        Self {
+            slow_frame_threshold: Some(Duration::from_secs_f64(2.0 / TARGET_DRAW_FPS)),
+        }
+    }
+}
+
+impl FrameBudget {
+    // None disables the warning
+    pub fn set_slow_frame_threshold(&mut self, threshold: Option<Duration>) {
+        self.slow_frame_threshold = threshold;
+    }
+    #[must_use]
+    pub fn slow_frame_threshold(&self) -> Option<Duration> {
+        self.slow_frame_threshold
+    }
+    pub fn check(&self, processing_delta: Duration, scenario_name: &str) {
+        match self.slow_frame_threshold {
+            Some(threshold) if processing_delta > threshold => log::warn!(
+                "Slow frame in {scenario_name}: {:.1} ms, budget is {:.1} ms",
+                processing_delta.as_secs_f64() * 1_000.0,
+                threshold.as_secs_f64() * 1_000.0
+            ),
+            _ => {}
+        }
+    }
+    // Without continuous redraws, the time since the previous frame includes the idle time
+    // waiting for events, so only the time spent updating and rendering is checked
+    pub fn check_redraw(
+        &self,
+        handler: &dyn RenderLoopHandler,
+        draw_delta: Duration,
+        processing_delta: Duration,
+    ) {
+        let delta = if handler.needs_continuous_redraw() {
+            draw_delta
+        } else {
+            processing_delta
+        };
+        self.check(delta, handler.name());
+    }
+}
+
+// Counters of the frames given to DrawContext::render_frame since the start
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FrameStats {
+    rendered_frames: u64,
+    presented_frames: u64,
+    skipped_frames: u64,
+    outdated_surface_events: u64,
+    timeout_events: u64,
+    total_present_latency: Duration,
+}
+
+impl FrameStats {
+    pub fn record_rendered_frame(&mut self) {
+        self.rendered_frames += 1;
+    }
+    // Latency between the acquisition of the surface texture and its presentation
+    pub fn record_presented_frame(&mut self, present_latency: Duration) {
+        self.presented_frames += 1;
+        self.total_present_latency += present_latency;
+    }
+    // The frame is skipped when the surface texture cannot be acquired
+    pub fn record_surface_error(&mut self, error: &wgpu::SurfaceError) {
+        self.skipped_frames += 1;
+        match error {
+            wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated => {
+                self.outdated_surface_events += 1;
+            }
+            wgpu::SurfaceError::Timeout => self.timeout_events += 1,
+            wgpu::SurfaceError::OutOfMemory | wgpu::SurfaceError::Other => {}
+        }
+    }
+    #[must_use]
+    pub fn rendered_frames(&self) -> u64 {
+        self.rendered_frames
+    }
+    // Stays at 0 when drawing into a texture
+    #[must_use]
+    pub fn presented_frames(&self) -> u64 {
+        self.presented_frames
+    }
+    #[must_use]
+    pub fn skipped_frames(&self) -> u64 {
+        self.skipped_frames
+    }
+    // Lost or outdated surface, reconfigured before the next frame
+    #[must_use]
+    pub fn outdated_surface_events(&self) -> u64 {
+        self.outdated_surface_events
+    }
+    #[must_use]
+    pub fn timeout_events(&self) -> u64 {
+        self.timeout_events
+    }
+    #[must_use]
+    pub fn average_present_latency(&self) -> Option<Duration> {
+        let presented_frames = u32::try_from(self.presented_frames).ok()?;
+        self.total_present_latency.checked_div(presented_frames)
+    }
+}
+
+// Tells a click from a drag: the drag only starts once the pressed cursor travelled beyond
+// the threshold, in pixels, so a slightly shaky click is still a click
+pub struct ClickDetector {
+    threshold: f64,
+    travel: Option<f64>,
+    dragging: bool,
+}
+
+impl Default for ClickDetector {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_THRESHOLD)
+    }
+}
+
+impl ClickDetector {
+    pub const DEFAULT_THRESHOLD: f64 = 4.0;
+
+    #[must_use]
+    pub const fn new(threshold: f64) -> Self {
+        Self {
+            threshold,
+            travel: None,
+            dragging: false,
+        }
+    }
+    pub const fn press(&mut self) {
+        self.travel = Some(0.0);
+        self.dragging = false;
+    }
+    // Returns true for the motion starting the drag
+    pub fn motion(&mut self, delta: (f64, f64)) -> bool {
+        let Some(travel) = &mut self.travel else {
+            return false;
+        };
+        if self.dragging {
+            return false;
+        }
+        *travel += delta.0.hypot(delta.1);
+        self.dragging = *travel > self.threshold;
+        self.dragging
+    }
+    // Returns true if the press ends as a click
+    pub const fn release(&mut self) -> bool {
+        let is_click = self.travel.is_some() && !self.dragging;
+        self.cancel();
+        is_click
+    }
+    pub const fn cancel(&mut self) {
+        self.travel = None;
+        self.dragging = false;
+    }
+    #[must_use]
+    pub const fn is_dragging(&self) -> bool {
+        self.dragging
+    }
+}
+
+// Pairs the clicks told by ClickDetector: the second click of a double-click comes within the
+// interval and the distance, in pixels, of the first one. A third click starts a new pair.
+pub struct DoubleClickDetector {
+    interval: Duration,
+    distance: f64,
+    last_click: Option<(Instant, PhysicalPosition<f64>)>,
+}
+
+impl Default for DoubleClickDetector {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_INTERVAL, Self::DEFAULT_DISTANCE)
+    }
+}
+
+impl DoubleClickDetector {
+    pub const DEFAULT_INTERVAL: Duration = Duration::from_millis(500);
+    pub const DEFAULT_DISTANCE: f64 = 4.0;
+
+    #[must_use]
+    pub const fn new(interval: Duration, distance: f64) -> Self {
+        Self {
+            interval,
+            distance,
+            last_click: None,
+        }
+    }
+    // Returns true if the click completes a double-click
+    pub fn click(&mut self, at: Instant, position: PhysicalPosition<f64>) -> bool {
+        let is_double = self.last_click.is_some_and(|(last_at, last_position)| {
+            at.saturating_duration_since(last_at) <= self.interval
+                && (position.x - last_position.x).hypot(position.y - last_position.y)
+                    <= self.distance
+        });
+        self.last_click = (!is_double).then_some((at, position));
+        is_double
+    }
+}
+
 #[derive(Default)]
 pub struct EventState {
     pub processed: bool,
@@ -52,6 +371,11 @@ pub struct EventState {
 pub trait RenderLoopHandler {
     fn on_mouse_event(&mut self, event: &DeviceEvent) {}
     fn on_keyboard_event(&mut self, event: &KeyEvent) {}
+    fn on_cursor_moved(&mut self, position: PhysicalPosition<f64>) {}
+    // Press and release of the left button without dragging, e.g. to pick an object
+    fn on_click(&mut self, draw_context: &DrawContext, position: PhysicalPosition<f64>) {}
+    // Second click close in time and position to the previous one, after its on_click
+    fn on_double_click(&mut self, draw_context: &DrawContext, position: PhysicalPosition<f64>) {}
     fn on_window_event(&mut self, event: &WindowEvent) -> EventState {
         EventState::default()
     }
@@ -74,6 +398,79 @@ pub trait RenderLoopHandler {
     fn is_finished(&self) -> bool {
         false
     }
+    // A static scene can return false so the loop sleeps until an event or an explicit redraw request
+    fn needs_continuous_redraw(&self) -> bool {
+        true
+    }
+    // Last call before the render loop stops, for instance to persist state
+    fn on_exit(&mut self, draw_context: &mut DrawContext) {}
+    // Used in the logs of the render loop
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+}
+
+// What the render loop does once the pending events are processed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RedrawSchedule {
+    pub control_flow: ControlFlow,
+    pub request_redraw: bool,
+}
+
+#[must_use]
+pub fn schedule_redraw(
+    handler: &dyn RenderLoopHandler,
+    since_last_draw: Duration,
+    draw_period_target: Duration,
+) -> RedrawSchedule {
+    if !handler.needs_continuous_redraw() {
+        return RedrawSchedule {
+            control_flow: ControlFlow::Wait,
+            request_redraw: false,
+        };
+    }
+    match draw_period_target.checked_sub(since_last_draw) {
+        Some(remaining) if !remaining.is_zero() => RedrawSchedule {
+            control_flow: ControlFlow::WaitUntil(Instant::now() + remaining),
+            request_redraw: false,
+        },
+        _ => RedrawSchedule {
+            control_flow: ControlFlow::Poll,
+            request_redraw: true,
+        },
+    }
+}
+
+// Wakes the render loop from handlers without access to the draw context, e.g. an animation
+// started by an event while needs_continuous_redraw returns false. Cloned from the draw context.
+#[derive(Debug, Clone, Default)]
+pub struct RedrawHandle {
+    requested: Arc<AtomicBool>,
+    window: Option<Arc<Window>>,
+}
+
+impl RedrawHandle {
+    pub(crate) fn new(window: Option<Arc<Window>>) -> Self {
+        Self {
+            requested: Arc::default(),
+            window,
+        }
+    }
+    pub fn request_redraw(&self) {
+        self.requested.store(true, Ordering::Relaxed);
+        if let Some(window) = &self.window {
+            window.request_redraw();
+        }
+    }
+    #[must_use]
+    pub fn is_redraw_requested(&self) -> bool {
+        self.requested.load(Ordering::Relaxed)
+    }
+    // Clears the request, returning whether there was one
+    #[must_use]
+    pub fn take_redraw_request(&self) -> bool {
+        self.requested.swap(false, Ordering::Relaxed)
+    }
 }
 
 pub struct LaunchContext<'a> {
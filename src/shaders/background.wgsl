@@ -0,0 +1,33 @@
+const fullscreen: array<vec2<f32>, 3> = array(
+    vec2<f32>(-1.0, -1.0),
+    vec2<f32>(3.0, -1.0),
+    vec2<f32>(-1.0, 3.0)
+);
+
+struct GradientColors {
+    top: vec4<f32>,
+    bottom: vec4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> colors: GradientColors;
+
+struct FragmentInput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) height: f32,
+};
+
+@vertex
+fn vtx_main(@builtin(vertex_index) vertex_index: u32) -> FragmentInput {
+    let position = fullscreen[vertex_index];
+    var out: FragmentInput;
+    out.position = vec4<f32>(position, 1.0, 1.0);
+    // 0 at the bottom of the screen, 1 at the top
+    out.height = position.y * 0.5 + 0.5;
+    return out;
+}
+
+@fragment
+fn frg_main(frg_in: FragmentInput) -> @location(0) vec4<f32> {
+    return mix(colors.bottom, colors.top, clamp(frg_in.height, 0.0, 1.0));
+}
@@ -0,0 +1,62 @@
+const fullscreen: array<vec2<f32>, 3> = array(
+    vec2<f32>(-1.0, -1.0),
+    vec2<f32>(3.0, -1.0),
+    vec2<f32>(-1.0, 3.0)
+);
+
+// Extract: x = threshold
+// Blur: xy = texel step along the blur direction
+// Composite: x = intensity
+@group(0) @binding(0)
+var source_texture: texture_2d<f32>;
+@group(0) @binding(1)
+var source_sampler: sampler;
+@group(0) @binding(2)
+var<uniform> params: vec4<f32>;
+
+struct FragmentInput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@vertex
+fn vtx_main(@builtin(vertex_index) vertex_index: u32) -> FragmentInput {
+    let position = fullscreen[vertex_index];
+    var out: FragmentInput;
+    out.position = vec4<f32>(position, 0.0, 1.0);
+    out.uv = vec2<f32>(position.x * 0.5 + 0.5, 0.5 - position.y * 0.5);
+    return out;
+}
+
+@fragment
+fn frg_extract(frg_in: FragmentInput) -> @location(0) vec4<f32> {
+    let color = textureSample(source_texture, source_sampler, frg_in.uv).rgb;
+    let brightness = max(color.r, max(color.g, color.b));
+    let contribution = max(brightness - params.x, 0.0) / max(brightness, 0.0001);
+    return vec4<f32>(color * contribution, 1.0);
+}
+
+@fragment
+fn frg_downsample(frg_in: FragmentInput) -> @location(0) vec4<f32> {
+    return vec4<f32>(textureSample(source_texture, source_sampler, frg_in.uv).rgb, 1.0);
+}
+
+// 9-tap Gaussian, using linear filtering to fetch two texels per sample
+@fragment
+fn frg_blur(frg_in: FragmentInput) -> @location(0) vec4<f32> {
+    let offsets = array<f32, 3>(0.0, 1.3846153846, 3.2307692308);
+    let weights = array<f32, 3>(0.2270270270, 0.3162162162, 0.0702702703);
+    var color = textureSample(source_texture, source_sampler, frg_in.uv).rgb * weights[0];
+    for (var i = 1; i < 3; i++) {
+        let offset = params.xy * offsets[i];
+        color += textureSample(source_texture, source_sampler, frg_in.uv + offset).rgb * weights[i];
+        color += textureSample(source_texture, source_sampler, frg_in.uv - offset).rgb * weights[i];
+    }
+    return vec4<f32>(color, 1.0);
+}
+
+@fragment
+fn frg_composite(frg_in: FragmentInput) -> @location(0) vec4<f32> {
+    let color = textureSample(source_texture, source_sampler, frg_in.uv).rgb;
+    return vec4<f32>(color * params.x, 0.0);
+}
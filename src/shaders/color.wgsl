@@ -0,0 +1,12 @@
+// Same piecewise sRGB transfer function as primitives::color::srgb_to_linear
+fn srgb_to_linear(color: vec3<f32>) -> vec3<f32> {
+    let low = color / 12.92;
+    let high = pow((color + 0.055) / 1.055, vec3<f32>(2.4));
+    return select(high, low, color <= vec3<f32>(0.04045));
+}
+
+// Shaders work with straight alpha, premultiply when writing to a target blended with
+// primitives::cube::PREMULTIPLIED_ALPHA_BLEND
+fn premultiply_alpha(color: vec4<f32>) -> vec4<f32> {
+    return vec4<f32>(color.rgb * color.a, color.a);
+}
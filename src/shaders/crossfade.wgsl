@@ -0,0 +1,43 @@
+const fullscreen: array<vec2<f32>, 3> = array(
+    vec2<f32>(-1.0, -1.0),
+    vec2<f32>(3.0, -1.0),
+    vec2<f32>(-1.0, 3.0)
+);
+
+struct CrossfadeParams {
+    progress: f32,
+    _padding_1: f32,
+    _padding_2: f32,
+    _padding_3: f32,
+};
+
+@group(0) @binding(0)
+var from_texture: texture_2d<f32>;
+@group(0) @binding(1)
+var source_sampler: sampler;
+@group(0) @binding(2)
+var<uniform> params: CrossfadeParams;
+@group(0) @binding(3)
+var to_texture: texture_2d<f32>;
+
+struct FragmentInput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@vertex
+fn vtx_main(@builtin(vertex_index) vertex_index: u32) -> FragmentInput {
+    let position = fullscreen[vertex_index];
+    var out: FragmentInput;
+    // Depth at the far plane, so the pass does not hide what is drawn after it
+    out.position = vec4<f32>(position, 1.0, 1.0);
+    out.uv = vec2<f32>(position.x * 0.5 + 0.5, 0.5 - position.y * 0.5);
+    return out;
+}
+
+@fragment
+fn frg_main(frg_in: FragmentInput) -> @location(0) vec4<f32> {
+    let from_color = textureSample(from_texture, source_sampler, frg_in.uv);
+    let to_color = textureSample(to_texture, source_sampler, frg_in.uv);
+    return mix(from_color, to_color, clamp(params.progress, 0.0, 1.0));
+}
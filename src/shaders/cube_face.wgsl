@@ -0,0 +1,40 @@
+// Shared by the passes rendering the faces of a cubemap, each face drawing the vertices
+// 3 * face to 3 * face + 2, in the +X, -X, +Y, -Y, +Z, -Z layer order
+
+const fullscreen: array<vec2<f32>, 3> = array(
+    vec2<f32>(-1.0, -1.0),
+    vec2<f32>(3.0, -1.0),
+    vec2<f32>(-1.0, 3.0)
+);
+
+const PI: f32 = 3.14159265359;
+
+struct FaceVertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) @interpolate(flat) face: u32,
+};
+
+@vertex
+fn vtx_main(@builtin(vertex_index) vertex_index: u32) -> FaceVertexOutput {
+    let corner = fullscreen[vertex_index % 3u];
+    var out: FaceVertexOutput;
+    out.position = vec4<f32>(corner, 0.0, 1.0);
+    // Between -1 and 1, from the top left of the layer
+    out.uv = vec2<f32>(corner.x, -corner.y);
+    out.face = vertex_index / 3u;
+    return out;
+}
+
+fn cube_face_direction(face: u32, uv: vec2<f32>) -> vec3<f32> {
+    var direction: vec3<f32>;
+    switch face {
+        case 0u: { direction = vec3<f32>(1.0, -uv.y, -uv.x); }
+        case 1u: { direction = vec3<f32>(-1.0, -uv.y, uv.x); }
+        case 2u: { direction = vec3<f32>(uv.x, 1.0, uv.y); }
+        case 3u: { direction = vec3<f32>(uv.x, -1.0, -uv.y); }
+        case 4u: { direction = vec3<f32>(uv.x, -uv.y, 1.0); }
+        default: { direction = vec3<f32>(-uv.x, -uv.y, -1.0); }
+    }
+    return normalize(direction);
+}
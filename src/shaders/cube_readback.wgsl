@@ -0,0 +1,13 @@
+// Concatenated after cube_face.wgsl. Copies a face of a cubemap into a 2D texture, which the
+// GL backend cannot do with a texture copy. Sampled at the texel centers, so values are exact.
+
+@group(0) @binding(0)
+var cube: texture_cube<f32>;
+@group(0) @binding(1)
+var cube_sampler: sampler;
+
+@fragment
+fn frg_main(frg_in: FaceVertexOutput) -> @location(0) vec4<f32> {
+    let direction = cube_face_direction(frg_in.face, frg_in.uv);
+    return textureSampleLevel(cube, cube_sampler, direction, 0.0);
+}
@@ -0,0 +1,22 @@
+// Copies the scene depth, or its first sample when multisampled, into a single-sampled depth
+// texture. The depth is bound as an unfilterable float texture, loading from depth textures not
+// being supported by every backend.
+
+const fullscreen: array<vec2<f32>, 3> = array(
+    vec2<f32>(-1.0, -1.0),
+    vec2<f32>(3.0, -1.0),
+    vec2<f32>(-1.0, 3.0)
+);
+
+@group(0) @binding(0)
+var scene_depth: texture_multisampled_2d<f32>;
+
+@vertex
+fn vtx_main(@builtin(vertex_index) vertex_index: u32) -> @builtin(position) vec4<f32> {
+    return vec4<f32>(fullscreen[vertex_index], 0.0, 1.0);
+}
+
+@fragment
+fn frg_main(@builtin(position) position: vec4<f32>) -> @builtin(frag_depth) f32 {
+    return textureLoad(scene_depth, vec2<i32>(position.xy), 0).r;
+}
@@ -0,0 +1,35 @@
+// Concatenated after cube_face.wgsl. The equirectangular image is loaded rather than sampled,
+// 32-bit float textures not being filterable everywhere, and interpolated here instead.
+
+@group(0) @binding(0)
+var equirect: texture_2d<f32>;
+
+fn load_equirect(texel: vec2<i32>, size: vec2<i32>) -> vec4<f32> {
+    // Wraps around horizontally, clamps at the poles
+    let wrapped = vec2<i32>((texel.x + size.x) % size.x, clamp(texel.y, 0, size.y - 1));
+    return textureLoad(equirect, wrapped, 0);
+}
+
+@fragment
+fn frg_main(frg_in: FaceVertexOutput) -> @location(0) vec4<f32> {
+    let direction = cube_face_direction(frg_in.face, frg_in.uv);
+    let size = vec2<i32>(textureDimensions(equirect));
+    let uv = vec2<f32>(
+        atan2(direction.z, direction.x) / (2.0 * PI) + 0.5,
+        acos(clamp(direction.y, -1.0, 1.0)) / PI,
+    );
+    let position = uv * vec2<f32>(size) - 0.5;
+    let origin = vec2<i32>(floor(position));
+    let weight = fract(position);
+    let top = mix(
+        load_equirect(origin, size),
+        load_equirect(origin + vec2<i32>(1, 0), size),
+        weight.x,
+    );
+    let bottom = mix(
+        load_equirect(origin + vec2<i32>(0, 1), size),
+        load_equirect(origin + vec2<i32>(1, 1), size),
+        weight.x,
+    );
+    return vec4<f32>(mix(top, bottom, weight.y).rgb, 1.0);
+}
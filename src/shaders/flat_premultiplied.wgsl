@@ -0,0 +1,36 @@
+// Flat cube whose opacity is read from the model bind group, for the premultiplied_alpha
+// option of primitives::cube; expects color.wgsl to be prepended for premultiply_alpha
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) color: vec3<f32>,
+};
+
+struct FragmentInput {
+    @location(0) @interpolate(flat) color: vec3<f32>,
+    @builtin(position) position: vec4<f32>,
+};
+
+struct TransformUniform {
+    m: mat4x4<f32>,
+};
+@group(1) @binding(0)
+var<uniform> transform: TransformUniform;
+@group(1) @binding(2)
+var<uniform> opacity: f32;
+
+@group(0) @binding(0)
+var<uniform> camera: TransformUniform;
+
+
+@vertex
+fn vtx_main(vtx_in: VertexInput) -> FragmentInput {
+    var out: FragmentInput;
+    out.color = vtx_in.color;
+    out.position = camera.m * transform.m * vec4<f32>(vtx_in.position, 1.0);
+    return out;
+}
+
+@fragment
+fn frg_main(frg_in: FragmentInput) -> @location(0) vec4<f32> {
+    return premultiply_alpha(vec4<f32>(frg_in.color, opacity));
+}
@@ -0,0 +1,149 @@
+const fullscreen: array<vec2<f32>, 3> = array(
+    vec2<f32>(-1.0, -1.0),
+    vec2<f32>(3.0, -1.0),
+    vec2<f32>(-1.0, 3.0)
+);
+
+struct FxaaParams {
+    edge_threshold: f32,
+    edge_threshold_min: f32,
+    subpixel: f32,
+    search_steps: f32,
+};
+
+@group(0) @binding(0)
+var source_texture: texture_2d<f32>;
+@group(0) @binding(1)
+var source_sampler: sampler;
+@group(0) @binding(2)
+var<uniform> params: FxaaParams;
+
+struct FragmentInput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@vertex
+fn vtx_main(@builtin(vertex_index) vertex_index: u32) -> FragmentInput {
+    let position = fullscreen[vertex_index];
+    var out: FragmentInput;
+    // Depth at the far plane, so the pass does not hide what is drawn after it
+    out.position = vec4<f32>(position, 1.0, 1.0);
+    out.uv = vec2<f32>(position.x * 0.5 + 0.5, 0.5 - position.y * 0.5);
+    return out;
+}
+
+fn sample_color(uv: vec2<f32>) -> vec3<f32> {
+    return textureSampleLevel(source_texture, source_sampler, uv, 0.0).rgb;
+}
+
+// Perceptual luma, the source being linear
+fn luma(color: vec3<f32>) -> f32 {
+    return sqrt(dot(color, vec3<f32>(0.299, 0.587, 0.114)));
+}
+
+fn sample_luma(uv: vec2<f32>, offset: vec2<f32>, texel: vec2<f32>) -> f32 {
+    return luma(sample_color(uv + offset * texel));
+}
+
+// Based on the FXAA 3.11 quality algorithm from Timothy Lottes
+@fragment
+fn frg_main(frg_in: FragmentInput) -> @location(0) vec4<f32> {
+    let uv = frg_in.uv;
+    let texel = 1.0 / vec2<f32>(textureDimensions(source_texture));
+    let center = sample_color(uv);
+    let l_c = luma(center);
+    let l_n = sample_luma(uv, vec2<f32>(0.0, -1.0), texel);
+    let l_s = sample_luma(uv, vec2<f32>(0.0, 1.0), texel);
+    let l_e = sample_luma(uv, vec2<f32>(1.0, 0.0), texel);
+    let l_w = sample_luma(uv, vec2<f32>(-1.0, 0.0), texel);
+    let l_min = min(l_c, min(min(l_n, l_s), min(l_e, l_w)));
+    let l_max = max(l_c, max(max(l_n, l_s), max(l_e, l_w)));
+    let range = l_max - l_min;
+    if (range < max(params.edge_threshold_min, l_max * params.edge_threshold)) {
+        return vec4<f32>(center, 1.0);
+    }
+
+    let l_nw = sample_luma(uv, vec2<f32>(-1.0, -1.0), texel);
+    let l_ne = sample_luma(uv, vec2<f32>(1.0, -1.0), texel);
+    let l_sw = sample_luma(uv, vec2<f32>(-1.0, 1.0), texel);
+    let l_se = sample_luma(uv, vec2<f32>(1.0, 1.0), texel);
+
+    // Subpixel aliasing amount, from the contrast with the neighborhood average
+    let l_average = (2.0 * (l_n + l_s + l_e + l_w) + l_nw + l_ne + l_sw + l_se) / 12.0;
+    let subpixel_contrast = smoothstep(0.0, 1.0, clamp(abs(l_average - l_c) / range, 0.0, 1.0));
+    let subpixel_offset = subpixel_contrast * subpixel_contrast * params.subpixel;
+
+    let edge_horizontal = abs(l_nw + l_sw - 2.0 * l_w)
+        + 2.0 * abs(l_n + l_s - 2.0 * l_c)
+        + abs(l_ne + l_se - 2.0 * l_e);
+    let edge_vertical = abs(l_nw + l_ne - 2.0 * l_n)
+        + 2.0 * abs(l_w + l_e - 2.0 * l_c)
+        + abs(l_sw + l_se - 2.0 * l_s);
+    let is_horizontal = edge_horizontal >= edge_vertical;
+
+    // Side of the edge with the steepest gradient
+    let l_negative = select(l_w, l_n, is_horizontal);
+    let l_positive = select(l_e, l_s, is_horizontal);
+    let gradient_negative = abs(l_negative - l_c);
+    let gradient_positive = abs(l_positive - l_c);
+    let is_negative_side = gradient_negative >= gradient_positive;
+    let gradient_scaled = 0.25 * max(gradient_negative, gradient_positive);
+    var step_length = select(texel.x, texel.y, is_horizontal);
+    var l_local_average = 0.5 * (l_positive + l_c);
+    if (is_negative_side) {
+        step_length = -step_length;
+        l_local_average = 0.5 * (l_negative + l_c);
+    }
+    var edge_uv = uv;
+    if (is_horizontal) {
+        edge_uv.y += step_length * 0.5;
+    } else {
+        edge_uv.x += step_length * 0.5;
+    }
+
+    // Search both ends of the edge
+    let along_edge = select(vec2<f32>(0.0, texel.y), vec2<f32>(texel.x, 0.0), is_horizontal);
+    var uv_backward = edge_uv - along_edge;
+    var uv_forward = edge_uv + along_edge;
+    var end_backward = luma(sample_color(uv_backward)) - l_local_average;
+    var end_forward = luma(sample_color(uv_forward)) - l_local_average;
+    var reached_backward = abs(end_backward) >= gradient_scaled;
+    var reached_forward = abs(end_forward) >= gradient_scaled;
+    let search_steps = i32(params.search_steps);
+    for (var i = 0; i < search_steps && !(reached_backward && reached_forward); i++) {
+        let step_scale = select(1.0, 2.0, i >= 4);
+        if (!reached_backward) {
+            uv_backward -= along_edge * step_scale;
+            end_backward = luma(sample_color(uv_backward)) - l_local_average;
+            reached_backward = abs(end_backward) >= gradient_scaled;
+        }
+        if (!reached_forward) {
+            uv_forward += along_edge * step_scale;
+            end_forward = luma(sample_color(uv_forward)) - l_local_average;
+            reached_forward = abs(end_forward) >= gradient_scaled;
+        }
+    }
+
+    var distance_backward = uv.y - uv_backward.y;
+    var distance_forward = uv_forward.y - uv.y;
+    if (is_horizontal) {
+        distance_backward = uv.x - uv_backward.x;
+        distance_forward = uv_forward.x - uv.x;
+    }
+    let is_backward_closer = distance_backward < distance_forward;
+    let closest_distance = min(distance_backward, distance_forward);
+    let edge_length = distance_backward + distance_forward;
+    let edge_offset = 0.5 - closest_distance / edge_length;
+    let is_center_darker = l_c < l_local_average;
+    let end_darker = select(end_forward < 0.0, end_backward < 0.0, is_backward_closer);
+    let final_offset = max(select(0.0, edge_offset, end_darker != is_center_darker), subpixel_offset);
+
+    var final_uv = uv;
+    if (is_horizontal) {
+        final_uv.y += final_offset * step_length;
+    } else {
+        final_uv.x += final_offset * step_length;
+    }
+    return vec4<f32>(sample_color(final_uv), 1.0);
+}
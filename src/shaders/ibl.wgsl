@@ -0,0 +1,13 @@
+// Diffuse ambient lighting from an EnvironmentMap, see EnvironmentMap::binding_slots
+
+@group(2) @binding(0)
+var irradiance_map: texture_cube<f32>;
+
+@group(2) @binding(1)
+var irradiance_sampler: sampler;
+
+// World space normal, sampled at level 0 so it can be called from non-uniform control flow
+fn ibl_diffuse(normal: vec3<f32>, albedo: vec3<f32>) -> vec3<f32> {
+    let irradiance = textureSampleLevel(irradiance_map, irradiance_sampler, normalize(normal), 0.0).rgb;
+    return albedo * irradiance;
+}
@@ -0,0 +1,34 @@
+// Concatenated after cube_face.wgsl. Cosine weighted integral of the environment over the
+// hemisphere around each direction, sampled at regular angle steps.
+
+const PHI_STEPS: u32 = 64u;
+const THETA_STEPS: u32 = 16u;
+
+@group(0) @binding(0)
+var environment: texture_cube<f32>;
+@group(0) @binding(1)
+var environment_sampler: sampler;
+
+@fragment
+fn frg_main(frg_in: FaceVertexOutput) -> @location(0) vec4<f32> {
+    let normal = cube_face_direction(frg_in.face, frg_in.uv);
+    var up = vec3<f32>(0.0, 1.0, 0.0);
+    if abs(normal.y) > 0.999 {
+        up = vec3<f32>(0.0, 0.0, 1.0);
+    }
+    let right = normalize(cross(up, normal));
+    up = cross(normal, right);
+    var irradiance = vec3<f32>(0.0);
+    for (var i = 0u; i < PHI_STEPS; i++) {
+        let phi = 2.0 * PI * (f32(i) + 0.5) / f32(PHI_STEPS);
+        for (var j = 0u; j < THETA_STEPS; j++) {
+            let theta = 0.5 * PI * (f32(j) + 0.5) / f32(THETA_STEPS);
+            let tangent = vec3<f32>(sin(theta) * cos(phi), sin(theta) * sin(phi), cos(theta));
+            let direction = tangent.x * right + tangent.y * up + tangent.z * normal;
+            let radiance = textureSampleLevel(environment, environment_sampler, direction, 0.0).rgb;
+            irradiance += radiance * cos(theta) * sin(theta);
+        }
+    }
+    irradiance = PI * irradiance / f32(PHI_STEPS * THETA_STEPS);
+    return vec4<f32>(irradiance, 1.0);
+}
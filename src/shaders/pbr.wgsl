@@ -0,0 +1,98 @@
+// Cook-Torrance shading of a mesh with normals, see primitives::pbr. Concatenated after a
+// definition of pbr_ambient, sampling the environment map or returning a constant term.
+
+const PI: f32 = 3.14159265359;
+// Below, the highlight of a directional light becomes too small to be rasterized
+const MIN_ROUGHNESS: f32 = 0.04;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+};
+
+struct FragmentInput {
+    @location(0) normal: vec3<f32>,
+    @location(1) world_position: vec3<f32>,
+    @builtin(position) clip_position: vec4<f32>,
+};
+
+struct PbrMaterial {
+    base_color: vec4<f32>,
+    // Metallic in x, roughness in y
+    metallic_roughness: vec4<f32>,
+};
+
+struct PbrLight {
+    // Direction the light travels when w is 0, position of a point light when w is 1
+    vector: vec4<f32>,
+    color: vec4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> camera_mat: mat4x4<f32>;
+@group(0) @binding(1)
+var<uniform> camera_pos: vec3<f32>;
+@group(1) @binding(0)
+var<uniform> transform: mat4x4<f32>;
+@group(1) @binding(1)
+var<uniform> normal_mat: mat3x3<f32>;
+@group(2) @binding(2)
+var<uniform> material: PbrMaterial;
+@group(2) @binding(3)
+var<uniform> light: PbrLight;
+
+@vertex
+fn vtx_main(vtx_in: VertexInput) -> FragmentInput {
+    var out: FragmentInput;
+    let world_position = transform * vec4<f32>(vtx_in.position, 1.0);
+    out.normal = normal_mat * vtx_in.normal;
+    out.world_position = world_position.xyz;
+    out.clip_position = camera_mat * world_position;
+    return out;
+}
+
+// Trowbridge-Reitz GGX normal distribution
+fn distribution_ggx(n_dot_h: f32, roughness: f32) -> f32 {
+    let alpha = roughness * roughness;
+    let alpha2 = alpha * alpha;
+    let denominator = n_dot_h * n_dot_h * (alpha2 - 1.0) + 1.0;
+    return alpha2 / (PI * denominator * denominator);
+}
+
+// Smith method with the Schlick-GGX approximation, remapped for direct lighting
+fn geometry_smith(n_dot_v: f32, n_dot_l: f32, roughness: f32) -> f32 {
+    let k = (roughness + 1.0) * (roughness + 1.0) / 8.0;
+    let g_v = n_dot_v / (n_dot_v * (1.0 - k) + k);
+    let g_l = n_dot_l / (n_dot_l * (1.0 - k) + k);
+    return g_v * g_l;
+}
+
+fn fresnel_schlick(cos_theta: f32, f0: vec3<f32>) -> vec3<f32> {
+    return f0 + (1.0 - f0) * pow(clamp(1.0 - cos_theta, 0.0, 1.0), 5.0);
+}
+
+@fragment
+fn frg_main(frg_in: FragmentInput) -> @location(0) vec4<f32> {
+    let base_color = material.base_color.rgb;
+    let metallic = clamp(material.metallic_roughness.x, 0.0, 1.0);
+    let roughness = clamp(material.metallic_roughness.y, MIN_ROUGHNESS, 1.0);
+    let normal = normalize(frg_in.normal);
+    let view_dir = normalize(camera_pos - frg_in.world_position);
+    var light_dir = normalize(-light.vector.xyz);
+    if light.vector.w > 0.5 {
+        light_dir = normalize(light.vector.xyz - frg_in.world_position);
+    }
+    let half_dir = normalize(view_dir + light_dir);
+    let n_dot_l = max(dot(normal, light_dir), 0.0);
+    let n_dot_v = max(dot(normal, view_dir), 1e-4);
+    let n_dot_h = max(dot(normal, half_dir), 0.0);
+    // Dielectrics reflect about 4% at normal incidence, metals reflect their color
+    let f0 = mix(vec3<f32>(0.04), base_color, metallic);
+    let fresnel = fresnel_schlick(max(dot(half_dir, view_dir), 0.0), f0);
+    let specular = distribution_ggx(n_dot_h, roughness) * geometry_smith(n_dot_v, n_dot_l, roughness)
+        * fresnel / (4.0 * n_dot_v * max(n_dot_l, 1e-4));
+    let diffuse = (1.0 - fresnel) * (1.0 - metallic) * base_color / PI;
+    let direct = (diffuse + specular) * light.color.rgb * n_dot_l;
+    let ambient = pbr_ambient(normal, (1.0 - metallic) * base_color);
+    return vec4<f32>(ambient + direct, 1.0);
+}
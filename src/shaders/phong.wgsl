@@ -0,0 +1,69 @@
+// Ambient, diffuse and specular shading of a mesh with normals, see primitives::phong
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+};
+
+struct FragmentInput {
+    @location(0) normal: vec3<f32>,
+    @location(1) world_position: vec3<f32>,
+    @builtin(position) clip_position: vec4<f32>,
+};
+
+struct PhongMaterial {
+    // Shininess in w
+    ambient: vec4<f32>,
+    diffuse: vec4<f32>,
+    specular: vec4<f32>,
+};
+
+struct PhongLight {
+    // Direction the light travels when w is 0, position of a point light when w is 1
+    vector: vec4<f32>,
+    color: vec4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> camera_mat: mat4x4<f32>;
+@group(0) @binding(1)
+var<uniform> camera_pos: vec3<f32>;
+@group(1) @binding(0)
+var<uniform> transform: mat4x4<f32>;
+@group(1) @binding(1)
+var<uniform> normal_mat: mat3x3<f32>;
+@group(2) @binding(0)
+var<uniform> material: PhongMaterial;
+@group(2) @binding(1)
+var<uniform> light: PhongLight;
+
+@vertex
+fn vtx_main(vtx_in: VertexInput) -> FragmentInput {
+    var out: FragmentInput;
+    let world_position = transform * vec4<f32>(vtx_in.position, 1.0);
+    out.normal = normal_mat * vtx_in.normal;
+    out.world_position = world_position.xyz;
+    out.clip_position = camera_mat * world_position;
+    return out;
+}
+
+@fragment
+fn frg_main(frg_in: FragmentInput) -> @location(0) vec4<f32> {
+    let normal = normalize(frg_in.normal);
+    var light_dir = normalize(-light.vector.xyz);
+    if light.vector.w > 0.5 {
+        light_dir = normalize(light.vector.xyz - frg_in.world_position);
+    }
+    let view_dir = normalize(camera_pos - frg_in.world_position);
+    let diffuse_strength = max(dot(normal, light_dir), 0.0);
+    let reflect_dir = reflect(-light_dir, normal);
+    var specular_strength = 0.0;
+    if diffuse_strength > 0.0 {
+        specular_strength = pow(max(dot(view_dir, reflect_dir), 0.0), material.ambient.w);
+    }
+    let light_color = light.color.rgb;
+    let color = material.ambient.rgb
+        + material.diffuse.rgb * diffuse_strength * light_color
+        + material.specular.rgb * specular_strength * light_color;
+    return vec4<f32>(color, 1.0);
+}
@@ -0,0 +1,15 @@
+// Concatenated with a vertex shader returning PickVertexOutput, with the instance index
+// of the vertex, or 0 when the drawable is not instanced
+
+@group(3) @binding(0)
+var<uniform> pick_object_id: u32;
+
+struct PickVertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) @interpolate(flat) instance: u32,
+};
+
+@fragment
+fn pick_frg_main(frg_in: PickVertexOutput) -> @location(0) vec2<u32> {
+    return vec2<u32>(pick_object_id, frg_in.instance);
+}
@@ -0,0 +1,30 @@
+@group(3) @binding(0)
+var<uniform> light_view_proj: mat4x4<f32>;
+
+@group(3) @binding(1)
+var shadow_texture: texture_depth_2d;
+
+@group(3) @binding(2)
+var shadow_sampler: sampler_comparison;
+
+fn shadow_clip_position(world_pos: vec4<f32>) -> vec4<f32> {
+    return light_view_proj * world_pos;
+}
+
+fn shadow_visibility(world_pos: vec4<f32>) -> f32 {
+    let light_pos = light_view_proj * world_pos;
+    let ndc = light_pos.xyz / light_pos.w;
+    let uv = vec2<f32>(ndc.x * 0.5 + 0.5, 0.5 - ndc.y * 0.5);
+    if (any(uv < vec2<f32>(0.0)) || any(uv > vec2<f32>(1.0)) || ndc.z > 1.0) {
+        return 1.0;
+    }
+    let texel = 1.0 / vec2<f32>(textureDimensions(shadow_texture));
+    var visibility = 0.0;
+    for (var y = -1; y <= 1; y++) {
+        for (var x = -1; x <= 1; x++) {
+            let offset = vec2<f32>(f32(x), f32(y)) * texel;
+            visibility += textureSampleCompareLevel(shadow_texture, shadow_sampler, uv + offset, ndc.z);
+        }
+    }
+    return visibility / 9.0;
+}
@@ -0,0 +1,35 @@
+struct VertexInput {
+    @location(0) position: vec2<f32>,
+    @location(1) uv: vec2<f32>,
+    @location(2) color: vec4<f32>,
+};
+
+struct FragmentInput {
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+    @builtin(position) position: vec4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> screen_size: vec2<f32>;
+@group(0) @binding(1)
+var atlas_texture: texture_2d<f32>;
+@group(0) @binding(2)
+var atlas_sampler: sampler;
+
+// Positions are in pixels, with the origin at the top left corner
+@vertex
+fn vtx_main(vtx_in: VertexInput) -> FragmentInput {
+    var out: FragmentInput;
+    let ndc = vtx_in.position / screen_size * 2.0 - 1.0;
+    out.position = vec4<f32>(ndc.x, -ndc.y, 0.0, 1.0);
+    out.uv = vtx_in.uv;
+    out.color = vtx_in.color;
+    return out;
+}
+
+@fragment
+fn frg_main(frg_in: FragmentInput) -> @location(0) vec4<f32> {
+    let coverage = textureSample(atlas_texture, atlas_sampler, frg_in.uv).r;
+    return vec4<f32>(frg_in.color.rgb, frg_in.color.a * coverage);
+}
@@ -0,0 +1,67 @@
+const fullscreen: array<vec2<f32>, 3> = array(
+    vec2<f32>(-1.0, -1.0),
+    vec2<f32>(3.0, -1.0),
+    vec2<f32>(-1.0, 3.0)
+);
+
+const CURVE_REINHARD: u32 = 0u;
+const CURVE_ACES: u32 = 1u;
+
+struct TonemapParams {
+    exposure: f32,
+    curve: f32,
+    apply_gamma: f32,
+    _padding: f32,
+};
+
+@group(0) @binding(0)
+var source_texture: texture_2d<f32>;
+@group(0) @binding(1)
+var source_sampler: sampler;
+@group(0) @binding(2)
+var<uniform> params: TonemapParams;
+
+struct FragmentInput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@vertex
+fn vtx_main(@builtin(vertex_index) vertex_index: u32) -> FragmentInput {
+    let position = fullscreen[vertex_index];
+    var out: FragmentInput;
+    // Depth at the far plane, so the pass does not hide what is drawn after it
+    out.position = vec4<f32>(position, 1.0, 1.0);
+    out.uv = vec2<f32>(position.x * 0.5 + 0.5, 0.5 - position.y * 0.5);
+    return out;
+}
+
+fn reinhard(color: vec3<f32>) -> vec3<f32> {
+    return color / (color + vec3<f32>(1.0));
+}
+
+// Krzysztof Narkowicz's fit of the ACES filmic curve
+fn aces(color: vec3<f32>) -> vec3<f32> {
+    let a = 2.51;
+    let b = 0.03;
+    let c = 2.43;
+    let d = 0.59;
+    let e = 0.14;
+    return clamp((color * (a * color + b)) / (color * (c * color + d) + e), vec3<f32>(0.0), vec3<f32>(1.0));
+}
+
+@fragment
+fn frg_main(frg_in: FragmentInput) -> @location(0) vec4<f32> {
+    let hdr = textureSample(source_texture, source_sampler, frg_in.uv);
+    let exposed = hdr.rgb * params.exposure;
+    var mapped: vec3<f32>;
+    if (u32(params.curve) == CURVE_ACES) {
+        mapped = aces(exposed);
+    } else {
+        mapped = reinhard(exposed);
+    }
+    if (params.apply_gamma > 0.5) {
+        mapped = pow(mapped, vec3<f32>(1.0 / 2.2));
+    }
+    return vec4<f32>(mapped, 1.0);
+}
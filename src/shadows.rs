@@ -0,0 +1,158 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use cgmath::{Matrix4, SquareMatrix};
+
+use crate::cameras::Camera;
+use crate::draw_context::{BindingSlot, DrawContext, Sampler, TextureBinding, Uniform};
+
+#[derive(Clone, Copy)]
+pub struct ShadowCaster<'a> {
+    pub shadow_map: &'a ShadowMap,
+    pub vtx_module: &'a wgpu::ShaderModule,
+}
+
+pub struct ShadowMap {
+    light_view_proj: Uniform<[[f32; 4]; 4]>,
+    depth_texture: wgpu::Texture,
+    depth_binding: TextureBinding,
+    sampler: Sampler,
+}
+
+impl ShadowMap {
+    pub const BINDING_LIGHT_VIEW_PROJ: u32 = 0;
+    pub const BINDING_DEPTH_TEXTURE: u32 = 1;
+    pub const BINDING_SAMPLER: u32 = 2;
+    pub const SHADER_SNIPPET: &str = include_str!("shaders/shadow.wgsl");
+    pub(crate) const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+    pub(crate) const DEPTH_BIAS: wgpu::DepthBiasState = wgpu::DepthBiasState {
+        constant: 2,
+        slope_scale: 2.0,
+        clamp: 0.0,
+    };
+
+    #[must_use]
+    pub fn new(context: &DrawContext, size: u32) -> Self {
+        let depth_texture = context.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Shadow Map Texture"),
+            size: wgpu::Extent3d {
+                width: size,
+                height: size,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: Self::DEPTH_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let depth_binding = TextureBinding::new(&depth_texture, wgpu::TextureSampleType::Depth);
+        let sampler = Sampler::new(
+            context,
+            &wgpu::SamplerDescriptor {
+                label: Some("Shadow Map Sampler"),
+                address_mode_u: wgpu::AddressMode::ClampToEdge,
+                address_mode_v: wgpu::AddressMode::ClampToEdge,
+                address_mode_w: wgpu::AddressMode::ClampToEdge,
+                mag_filter: wgpu::FilterMode::Linear,
+                min_filter: wgpu::FilterMode::Linear,
+                compare: Some(wgpu::CompareFunction::LessEqual),
+                ..Default::default()
+            },
+        );
+        Self {
+            light_view_proj: Uniform::new(context, Matrix4::identity().into()),
+            depth_texture,
+            depth_binding,
+            sampler,
+        }
+    }
+
+    pub fn set_light_camera(&mut self, camera: &Camera) {
+        self.set_light_view_proj(camera.get_camera_matrix());
+    }
+
+    pub fn set_light_view_proj(&mut self, view_proj: Matrix4<f32>) {
+        self.light_view_proj.write_uniform(view_proj.into());
+    }
+
+    #[must_use]
+    pub fn light_view_proj(&self) -> &Uniform<[[f32; 4]; 4]> {
+        &self.light_view_proj
+    }
+
+    #[must_use]
+    pub fn binding_slots(&self) -> [BindingSlot<'_>; 3] {
+        [
+            BindingSlot {
+                bind_group: DrawContext::BIND_GROUP_INDEX_SHADOW,
+                binding: Self::BINDING_LIGHT_VIEW_PROJ,
+                resource: &self.light_view_proj,
+            },
+            BindingSlot {
+                bind_group: DrawContext::BIND_GROUP_INDEX_SHADOW,
+                binding: Self::BINDING_DEPTH_TEXTURE,
+                resource: &self.depth_binding,
+            },
+            BindingSlot {
+                bind_group: DrawContext::BIND_GROUP_INDEX_SHADOW,
+                binding: Self::BINDING_SAMPLER,
+                resource: &self.sampler,
+            },
+        ]
+    }
+
+    pub fn render<C>(&self, context: &DrawContext, callback: C)
+    where
+        C: FnOnce(&mut wgpu::RenderPass<'_>),
+    {
+        let depth_view = self
+            .depth_texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        let mut encoder = context
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Shadow Command Encoder"),
+            });
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Shadow pass"),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+                color_attachments: &[],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &depth_view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Store,
+                    }),
+                    stencil_ops: None,
+                }),
+            });
+            callback(&mut render_pass);
+        }
+        context.submit(Some(encoder.finish()));
+    }
+}
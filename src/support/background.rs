@@ -0,0 +1,176 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use std::mem::size_of;
+
+use crate::draw_context::DrawContext;
+
+const BACKGROUND_SHADER: &str = include_str!("../shaders/background.wgsl");
+
+// What the color target holds before the scene is drawn. Gradient, from the top to the bottom
+// color of the screen, is drawn by a fullscreen pass before the scene passes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Background {
+    // The previous content of the target is kept
+    None,
+    Solid(wgpu::Color),
+    Gradient(wgpu::Color, wgpu::Color),
+}
+
+impl Background {
+    // Load operation of the first scene pass, which only clears for a solid color
+    #[must_use]
+    pub fn clear_color(&self) -> Option<wgpu::Color> {
+        match self {
+            Self::Solid(color) => Some(*color),
+            Self::None | Self::Gradient(..) => None,
+        }
+    }
+}
+
+impl From<Option<wgpu::Color>> for Background {
+    fn from(color: Option<wgpu::Color>) -> Self {
+        color.map_or(Self::None, Self::Solid)
+    }
+}
+
+fn color_to_array(color: wgpu::Color) -> [f32; 4] {
+    #[allow(clippy::cast_possible_truncation)]
+    [
+        color.r as f32,
+        color.g as f32,
+        color.b as f32,
+        color.a as f32,
+    ]
+}
+
+// GPU side of the Gradient background
+pub(crate) struct GradientRenderer {
+    pipeline: wgpu::RenderPipeline,
+    sample_count: u32,
+    colors_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+}
+
+impl GradientRenderer {
+    pub(crate) fn new(context: &DrawContext) -> Self {
+        let device = &context.device;
+        let sample_count = context.multisample_config.get_multisample_count();
+        let shader_module = context.create_shader_module(BACKGROUND_SHADER);
+        let colors_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Background Gradient Buffer"),
+            size: size_of::<[[f32; 4]; 2]>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Background Gradient Bind Group Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Background Gradient Bind Group"),
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: colors_buffer.as_entire_binding(),
+            }],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Background Gradient Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Background Gradient Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader_module,
+                entry_point: None,
+                buffers: &[],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader_module,
+                entry_point: None,
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: context.surface_config.format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                ..Default::default()
+            },
+            multiview: None,
+            cache: None,
+        });
+        Self {
+            pipeline,
+            sample_count,
+            colors_buffer,
+            bind_group,
+        }
+    }
+
+    #[must_use]
+    pub(crate) fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    pub(crate) fn render(
+        &self,
+        context: &DrawContext,
+        encoder: &mut wgpu::CommandEncoder,
+        (top, bottom): (wgpu::Color, wgpu::Color),
+        color_attachment: wgpu::RenderPassColorAttachment<'_>,
+    ) {
+        let colors = [color_to_array(top), color_to_array(bottom)];
+        context
+            .queue
+            .write_buffer(&self.colors_buffer, 0, bytemuck::cast_slice(&colors));
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Background Gradient Render Pass"),
+            color_attachments: &[Some(color_attachment)],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
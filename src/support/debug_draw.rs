@@ -0,0 +1,273 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use std::mem::size_of;
+
+use bytemuck::{Pod, Zeroable};
+use cgmath::{Matrix4, SquareMatrix};
+
+use crate::draw_context::DrawContext;
+
+const DEBUG_DRAW_SHADER: &str = include_str!("../shaders/debug_draw.wgsl");
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+struct DebugVertex {
+    position: [f32; 3],
+    color: [f32; 3],
+}
+
+// World-space lines queued by scenarios and plugins, drawn over the scene at the end of the
+// frame then discarded
+pub struct DebugDraw {
+    vertices: Vec<DebugVertex>,
+    view_projection: Matrix4<f32>,
+}
+
+impl Default for DebugDraw {
+    fn default() -> Self {
+        Self {
+            vertices: Vec::new(),
+            view_projection: Matrix4::identity(),
+        }
+    }
+}
+
+impl DebugDraw {
+    // Set each frame by Scene3DPlugin from its camera
+    pub fn set_view_projection(&mut self, view_projection: Matrix4<f32>) {
+        self.view_projection = view_projection;
+    }
+
+    #[must_use]
+    pub fn view_projection(&self) -> Matrix4<f32> {
+        self.view_projection
+    }
+
+    pub fn line(&mut self, from: [f32; 3], to: [f32; 3], color: [f32; 3]) {
+        self.vertices.push(DebugVertex {
+            position: from,
+            color,
+        });
+        self.vertices.push(DebugVertex {
+            position: to,
+            color,
+        });
+    }
+
+    pub fn aabb(&mut self, min: [f32; 3], max: [f32; 3], color: [f32; 3]) {
+        let corner = |index: usize| {
+            [
+                if index & 1 == 0 { min[0] } else { max[0] },
+                if index & 2 == 0 { min[1] } else { max[1] },
+                if index & 4 == 0 { min[2] } else { max[2] },
+            ]
+        };
+        for index in 0..8 {
+            // Each edge joins two corners differing by one axis, only counted from the lower one
+            for axis in [1, 2, 4] {
+                if index & axis == 0 {
+                    self.line(corner(index), corner(index | axis), color);
+                }
+            }
+        }
+    }
+
+    // Drawn as three axis-aligned segments crossing at the position
+    pub fn point(&mut self, position: [f32; 3], size: f32, color: [f32; 3]) {
+        let half_size = size / 2.0;
+        for axis in 0..3 {
+            let mut from = position;
+            let mut to = position;
+            from[axis] -= half_size;
+            to[axis] += half_size;
+            self.line(from, to, color);
+        }
+    }
+
+    #[must_use]
+    pub fn line_count(&self) -> usize {
+        self.vertices.len() / 2
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.vertices.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.vertices.clear();
+    }
+}
+
+// GPU side of DebugDraw, the vertex buffer growing with the number of queued lines
+pub(crate) struct DebugDrawRenderer {
+    pipeline: wgpu::RenderPipeline,
+    sample_count: u32,
+    camera_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    vertex_buffer: Option<wgpu::Buffer>,
+}
+
+impl DebugDrawRenderer {
+    pub(crate) fn new(context: &DrawContext) -> Self {
+        let device = &context.device;
+        let sample_count = context.multisample_config.get_multisample_count();
+        let shader_module = context.create_shader_module(DEBUG_DRAW_SHADER);
+        let camera_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Debug Draw Camera Buffer"),
+            size: size_of::<[[f32; 4]; 4]>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Debug Draw Bind Group Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Debug Draw Bind Group"),
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: camera_buffer.as_entire_binding(),
+            }],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Debug Draw Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Debug Draw Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader_module,
+                entry_point: None,
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: size_of::<DebugVertex>() as wgpu::BufferAddress,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: &wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x3],
+                }],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader_module,
+                entry_point: None,
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: context.surface_config.format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::LineList,
+                ..Default::default()
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: context.depth_format(),
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                ..Default::default()
+            },
+            multiview: None,
+            cache: None,
+        });
+        Self {
+            pipeline,
+            sample_count,
+            camera_buffer,
+            bind_group,
+            vertex_buffer: None,
+        }
+    }
+
+    #[must_use]
+    pub(crate) fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    pub(crate) fn render(
+        &mut self,
+        context: &DrawContext,
+        encoder: &mut wgpu::CommandEncoder,
+        debug_draw: &DebugDraw,
+        color_attachment: wgpu::RenderPassColorAttachment<'_>,
+        depth_view: &wgpu::TextureView,
+    ) {
+        let contents: &[u8] = bytemuck::cast_slice(&debug_draw.vertices);
+        let vertex_buffer = match &mut self.vertex_buffer {
+            Some(buffer) if buffer.size() >= contents.len() as wgpu::BufferAddress => buffer,
+            vertex_buffer => {
+                vertex_buffer.insert(context.device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some("Debug Draw Vertex Buffer"),
+                    size: (contents.len() as wgpu::BufferAddress).next_power_of_two(),
+                    usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                }))
+            }
+        };
+        context.queue.write_buffer(vertex_buffer, 0, contents);
+        let view_projection: [[f32; 4]; 4] = debug_draw.view_projection.into();
+        context.queue.write_buffer(
+            &self.camera_buffer,
+            0,
+            bytemuck::cast_slice(&view_projection),
+        );
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Debug Draw Render Pass"),
+            color_attachments: &[Some(color_attachment)],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: depth_view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, vertex_buffer.slice(..contents.len() as u64));
+        let vertex_count =
+            u32::try_from(debug_draw.vertices.len()).expect("Value should fit in u32");
+        render_pass.draw(0..vertex_count, 0..1);
+    }
+}
@@ -0,0 +1,343 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use anyhow::{Result, bail};
+
+use crate::draw_context::{
+    AsBindingResource, BindingSlot, Dimensions, DrawContext, Sampler, TextureBinding,
+};
+
+const CUBE_FACE_SHADER: &str = include_str!("../shaders/cube_face.wgsl");
+const EQUIRECT_TO_CUBE_SHADER: &str = include_str!("../shaders/equirect_to_cube.wgsl");
+const IRRADIANCE_SHADER: &str = include_str!("../shaders/irradiance.wgsl");
+#[cfg(not(target_arch = "wasm32"))]
+const CUBE_READBACK_SHADER: &str = include_str!("../shaders/cube_readback.wgsl");
+const CUBE_FACE_COUNT: u32 = 6;
+
+// Image-based ambient lighting: the equirectangular environment is rendered into a cubemap,
+// which is then convolved into a small irradiance cubemap sampled by SHADER_SNIPPET
+pub struct EnvironmentMap {
+    environment: wgpu::Texture,
+    irradiance: wgpu::Texture,
+    irradiance_binding: TextureBinding,
+    sampler: Sampler,
+}
+
+impl EnvironmentMap {
+    pub const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+    pub const ENVIRONMENT_SIZE: u32 = 128;
+    // Irradiance varies slowly with the direction, so a few texels per face are enough
+    pub const IRRADIANCE_SIZE: u32 = 16;
+    pub const BINDING_IRRADIANCE: u32 = 0;
+    pub const BINDING_SAMPLER: u32 = 1;
+    pub const SHADER_SNIPPET: &str = include_str!("../shaders/ibl.wgsl");
+
+    // Linear RGBA radiance, row by row from the top of the image, the top row looking up
+    pub fn from_equirectangular(
+        context: &DrawContext,
+        dimensions: Dimensions,
+        rgba: &[[f32; 4]],
+    ) -> Result<Self> {
+        let Dimensions { width, height } = dimensions;
+        if width == 0 || height == 0 {
+            bail!("Environment dimensions should not be empty, got {width}x{height}");
+        }
+        let expected_len = width as usize * height as usize;
+        if rgba.len() != expected_len {
+            bail!(
+                "Expected {expected_len} pixels for a {width}x{height} environment, got {}",
+                rgba.len()
+            );
+        }
+        let size = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+        let equirect = context.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Equirectangular Environment Texture"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba32Float,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        context.queue.write_texture(
+            equirect.as_image_copy(),
+            bytemuck::cast_slice(rgba),
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(16 * width),
+                rows_per_image: Some(height),
+            },
+            size,
+        );
+        let environment =
+            create_cube_texture(context, "Environment Cubemap", Self::ENVIRONMENT_SIZE);
+        let equirect_binding = TextureBinding::new(
+            &equirect,
+            wgpu::TextureSampleType::Float { filterable: false },
+        );
+        render_cube_faces(
+            context,
+            &format!("{CUBE_FACE_SHADER}\n{EQUIRECT_TO_CUBE_SHADER}"),
+            &[&equirect_binding],
+            &environment,
+        );
+        let sampler = Sampler::new(
+            context,
+            &wgpu::SamplerDescriptor {
+                label: Some("Environment Sampler"),
+                mag_filter: wgpu::FilterMode::Linear,
+                min_filter: wgpu::FilterMode::Linear,
+                ..Default::default()
+            },
+        );
+        let irradiance = create_cube_texture(context, "Irradiance Cubemap", Self::IRRADIANCE_SIZE);
+        let environment_binding = TextureBinding::new_cube(
+            &environment,
+            wgpu::TextureSampleType::Float { filterable: true },
+        );
+        render_cube_faces(
+            context,
+            &format!("{CUBE_FACE_SHADER}\n{IRRADIANCE_SHADER}"),
+            &[&environment_binding, &sampler],
+            &irradiance,
+        );
+        let irradiance_binding = TextureBinding::new_cube(
+            &irradiance,
+            wgpu::TextureSampleType::Float { filterable: true },
+        );
+        Ok(Self {
+            environment,
+            irradiance,
+            irradiance_binding,
+            sampler,
+        })
+    }
+
+    // Radiance HDR file, as exported by most environment map sources
+    #[cfg(all(feature = "hdr", not(target_arch = "wasm32")))]
+    pub fn from_hdr(context: &DrawContext, hdr_bytes: &[u8]) -> Result<Self> {
+        let image =
+            image::load_from_memory_with_format(hdr_bytes, image::ImageFormat::Hdr)?.into_rgba32f();
+        let dimensions = Dimensions::new(image.width(), image.height());
+        Self::from_equirectangular(context, dimensions, bytemuck::cast_slice(image.as_raw()))
+    }
+
+    #[must_use]
+    pub fn environment_texture(&self) -> &wgpu::Texture {
+        &self.environment
+    }
+
+    #[must_use]
+    pub fn irradiance_texture(&self) -> &wgpu::Texture {
+        &self.irradiance
+    }
+
+    #[must_use]
+    pub fn binding_slots(&self) -> [BindingSlot<'_>; 2] {
+        [
+            BindingSlot {
+                bind_group: DrawContext::BIND_GROUP_INDEX_MATERIAL,
+                binding: Self::BINDING_IRRADIANCE,
+                resource: &self.irradiance_binding,
+            },
+            BindingSlot {
+                bind_group: DrawContext::BIND_GROUP_INDEX_MATERIAL,
+                binding: Self::BINDING_SAMPLER,
+                resource: &self.sampler,
+            },
+        ]
+    }
+}
+
+// Pixels of a layer of a cubemap, in the +X, -X, +Y, -Y, +Z, -Z order, as with
+// DrawContext::read_texture_pixels
+#[cfg(not(target_arch = "wasm32"))]
+pub fn read_cube_face(context: &DrawContext, cube: &wgpu::Texture, face: u32) -> Result<Vec<u8>> {
+    if face >= CUBE_FACE_COUNT || cube.depth_or_array_layers() != CUBE_FACE_COUNT {
+        bail!("Face {face} is not a layer of a cubemap");
+    }
+    let face_texture = context.device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Cube Face Readback Texture"),
+        size: wgpu::Extent3d {
+            depth_or_array_layers: 1,
+            ..cube.size()
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: cube.format(),
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    });
+    let cube_binding =
+        TextureBinding::new_cube(cube, wgpu::TextureSampleType::Float { filterable: true });
+    let sampler = Sampler::new(context, &wgpu::SamplerDescriptor::default());
+    render_faces(
+        context,
+        &format!("{CUBE_FACE_SHADER}\n{CUBE_READBACK_SHADER}"),
+        &[&cube_binding, &sampler],
+        face_texture.format(),
+        &[(
+            face,
+            face_texture.create_view(&wgpu::TextureViewDescriptor::default()),
+        )],
+    );
+    context.read_texture_pixels(&face_texture)
+}
+
+fn create_cube_texture(context: &DrawContext, label: &str, size: u32) -> wgpu::Texture {
+    context.device.create_texture(&wgpu::TextureDescriptor {
+        label: Some(label),
+        size: wgpu::Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: CUBE_FACE_COUNT,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: EnvironmentMap::FORMAT,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+            | wgpu::TextureUsages::TEXTURE_BINDING
+            | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    })
+}
+
+fn render_cube_faces(
+    context: &DrawContext,
+    shader: &str,
+    sources: &[&dyn AsBindingResource],
+    target: &wgpu::Texture,
+) {
+    let face_views = (0..CUBE_FACE_COUNT)
+        .map(|face| {
+            let view = target.create_view(&wgpu::TextureViewDescriptor {
+                dimension: Some(wgpu::TextureViewDimension::D2),
+                base_array_layer: face,
+                array_layer_count: Some(1),
+                ..Default::default()
+            });
+            (face, view)
+        })
+        .collect::<Vec<_>>();
+    render_faces(context, shader, sources, target.format(), &face_views);
+}
+
+// Each face is a render pass into its view, the shader deriving the face from the vertex index
+fn render_faces(
+    context: &DrawContext,
+    shader: &str,
+    sources: &[&dyn AsBindingResource],
+    format: wgpu::TextureFormat,
+    face_views: &[(u32, wgpu::TextureView)],
+) {
+    let device = &context.device;
+    let shader_module = context.create_shader_module(shader);
+    let layout_entries = sources
+        .iter()
+        .zip(0..)
+        .map(|(source, binding)| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: source.binding_type(),
+            count: None,
+        })
+        .collect::<Vec<_>>();
+    let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("Cube Face Bind Group Layout"),
+        entries: &layout_entries,
+    });
+    let bind_group_entries = sources
+        .iter()
+        .zip(0..)
+        .map(|(source, binding)| wgpu::BindGroupEntry {
+            binding,
+            resource: source.binding_resource(),
+        })
+        .collect::<Vec<_>>();
+    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Cube Face Bind Group"),
+        layout: &bind_group_layout,
+        entries: &bind_group_entries,
+    });
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Cube Face Pipeline Layout"),
+        bind_group_layouts: &[&bind_group_layout],
+        push_constant_ranges: &[],
+    });
+    let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Cube Face Pipeline"),
+        layout: Some(&pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: &shader_module,
+            entry_point: None,
+            buffers: &[],
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &shader_module,
+            entry_point: None,
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: None,
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        }),
+        primitive: wgpu::PrimitiveState::default(),
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+        cache: None,
+    });
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Cube Face Encoder"),
+    });
+    for &(face, ref face_view) in face_views {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Cube Face Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: face_view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_pipeline(&pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[]);
+        render_pass.draw(3 * face..3 * face + 3, 0..1);
+    }
+    context.submit(std::iter::once(encoder.finish()));
+}
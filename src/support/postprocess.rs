@@ -0,0 +1,608 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::draw_context::{
+    AsBindingResource, BindingSlot, DrawContext, DrawModeParams, Drawable, DrawableBuilder,
+    Sampler, TextureBinding, Uniform,
+};
+use crate::support::render_texture::RenderTexture;
+
+const TONEMAP_SHADER: &str = include_str!("../shaders/tonemap.wgsl");
+const BLOOM_SHADER: &str = include_str!("../shaders/bloom.wgsl");
+const FXAA_SHADER: &str = include_str!("../shaders/fxaa.wgsl");
+const CROSSFADE_SHADER: &str = include_str!("../shaders/crossfade.wgsl");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TonemapCurve {
+    Reinhard,
+    Aces,
+}
+
+impl TonemapCurve {
+    fn shader_value(self) -> f32 {
+        match self {
+            Self::Reinhard => 0.0,
+            Self::Aces => 1.0,
+        }
+    }
+}
+
+// Fullscreen drawable sampling source render textures, drawn into the current render pass
+struct SourcePass {
+    drawable: Drawable,
+    sources: Vec<TextureBinding>,
+    sampler: Sampler,
+    params: Uniform<[f32; 4]>,
+}
+
+impl SourcePass {
+    const BINDING_TEXTURE: u32 = 0;
+    const BINDING_SAMPLER: u32 = 1;
+    const BINDING_PARAMS: u32 = 2;
+    // Bindings of the sources after the first one follow
+    const BINDING_EXTRA_TEXTURES: u32 = 3;
+
+    fn new(
+        context: &DrawContext,
+        shader: &str,
+        sources: &[&RenderTexture],
+        params: [f32; 4],
+    ) -> Self {
+        let params = Uniform::new(context, params);
+        let sources = sources
+            .iter()
+            .map(|source| source.texture_binding())
+            .collect::<Vec<_>>();
+        let sampler = Sampler::new(
+            context,
+            &wgpu::SamplerDescriptor {
+                label: Some("Post-process Sampler"),
+                mag_filter: wgpu::FilterMode::Linear,
+                min_filter: wgpu::FilterMode::Linear,
+                ..Default::default()
+            },
+        );
+        let shader_module = context.create_shader_module(shader);
+        let mut drawable_builder = DrawableBuilder::new(
+            context,
+            &shader_module,
+            &shader_module,
+            DrawModeParams::Direct { vertex_count: 3 },
+        );
+        for binding_slot in &Self::binding_slots(&sources, &sampler, &params) {
+            drawable_builder
+                .add_binding_slot(binding_slot)
+                .expect("Bind group or binding should be different from other uniforms.");
+        }
+        let drawable = drawable_builder.build();
+        Self {
+            drawable,
+            sources,
+            sampler,
+            params,
+        }
+    }
+
+    fn binding_slots<'a>(
+        sources: &'a [TextureBinding],
+        sampler: &'a Sampler,
+        params: &'a Uniform<[f32; 4]>,
+    ) -> Vec<BindingSlot<'a>> {
+        let texture_bindings =
+            std::iter::once(Self::BINDING_TEXTURE).chain(Self::BINDING_EXTRA_TEXTURES..);
+        sources
+            .iter()
+            .zip(texture_bindings)
+            .map(|(source, binding)| BindingSlot {
+                bind_group: 0,
+                binding,
+                resource: source as &dyn AsBindingResource,
+            })
+            .chain([
+                BindingSlot {
+                    bind_group: 0,
+                    binding: Self::BINDING_SAMPLER,
+                    resource: sampler,
+                },
+                BindingSlot {
+                    bind_group: 0,
+                    binding: Self::BINDING_PARAMS,
+                    resource: params,
+                },
+            ])
+            .collect()
+    }
+
+    fn set_sources(&mut self, context: &DrawContext, sources: &[&RenderTexture]) {
+        self.sources = sources
+            .iter()
+            .map(|source| source.texture_binding())
+            .collect();
+        self.drawable
+            .rebuild_bind_group(
+                context,
+                0,
+                &Self::binding_slots(&self.sources, &self.sampler, &self.params),
+            )
+            .expect("Post-process bind group should be rebuilt with the same layout");
+    }
+
+    fn params(&self) -> [f32; 4] {
+        *self.params.read_uniform()
+    }
+
+    fn update_params<F: FnOnce(&mut [f32; 4])>(&mut self, update: F) {
+        let mut params = self.params();
+        update(&mut params);
+        self.params.write_uniform(params);
+    }
+
+    fn render(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        self.drawable.render(render_pass);
+    }
+}
+
+// Draws a fullscreen pass mapping an HDR render texture into the current render pass
+pub struct Tonemap {
+    // exposure, curve, gamma correction flag, padding
+    pass: SourcePass,
+}
+
+impl Tonemap {
+    #[must_use]
+    pub fn new(context: &DrawContext, source: &RenderTexture, curve: TonemapCurve) -> Self {
+        // Without an sRGB surface, the gamma has to be applied by the shader
+        let apply_gamma = if context.surface_config.format.is_srgb() {
+            0.0
+        } else {
+            1.0
+        };
+        Self {
+            pass: SourcePass::new(
+                context,
+                TONEMAP_SHADER,
+                &[source],
+                [1.0, curve.shader_value(), apply_gamma, 0.0],
+            ),
+        }
+    }
+
+    // To call when the source render texture was recreated, e.g. after a resize
+    pub fn set_source(&mut self, context: &DrawContext, source: &RenderTexture) {
+        self.pass.set_sources(context, &[source]);
+    }
+
+    #[must_use]
+    pub fn exposure(&self) -> f32 {
+        self.pass.params()[0]
+    }
+
+    pub fn set_exposure(&mut self, exposure: f32) {
+        self.pass.update_params(|params| params[0] = exposure);
+    }
+
+    pub fn set_curve(&mut self, curve: TonemapCurve) {
+        self.pass
+            .update_params(|params| params[1] = curve.shader_value());
+    }
+
+    pub fn render(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        self.pass.render(render_pass);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FxaaQuality {
+    Low,
+    Medium,
+    High,
+}
+
+impl FxaaQuality {
+    // edge threshold, minimum edge threshold, subpixel blending, edge search steps
+    fn shader_params(self) -> [f32; 4] {
+        match self {
+            Self::Low => [0.250, 0.0833, 0.50, 4.0],
+            Self::Medium => [0.166, 0.0625, 0.75, 8.0],
+            Self::High => [0.125, 0.0312, 0.75, 12.0],
+        }
+    }
+}
+
+// Anti-aliases a render texture into the current render pass, a cheaper alternative to MSAA
+pub struct Fxaa {
+    pass: SourcePass,
+}
+
+impl Fxaa {
+    #[must_use]
+    pub fn new(context: &DrawContext, source: &RenderTexture, quality: FxaaQuality) -> Self {
+        Self {
+            pass: SourcePass::new(context, FXAA_SHADER, &[source], quality.shader_params()),
+        }
+    }
+
+    // To call when the source render texture was recreated, e.g. after a resize
+    pub fn set_source(&mut self, context: &DrawContext, source: &RenderTexture) {
+        self.pass.set_sources(context, &[source]);
+    }
+
+    pub fn set_quality(&mut self, quality: FxaaQuality) {
+        self.pass
+            .update_params(|params| *params = quality.shader_params());
+    }
+
+    pub fn render(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        self.pass.render(render_pass);
+    }
+}
+
+// Blends two render textures into the current render pass, from the first one at progress 0
+// to the second one at progress 1
+pub struct Crossfade {
+    pass: SourcePass,
+}
+
+impl Crossfade {
+    #[must_use]
+    pub fn new(context: &DrawContext, from: &RenderTexture, to: &RenderTexture) -> Self {
+        Self {
+            pass: SourcePass::new(context, CROSSFADE_SHADER, &[from, to], [0.0; 4]),
+        }
+    }
+
+    pub fn set_sources(&mut self, context: &DrawContext, from: &RenderTexture, to: &RenderTexture) {
+        self.pass.set_sources(context, &[from, to]);
+    }
+
+    #[must_use]
+    pub fn progress(&self) -> f32 {
+        self.pass.params()[0]
+    }
+
+    pub fn set_progress(&mut self, progress: f32) {
+        self.pass
+            .update_params(|params| params[0] = progress.clamp(0.0, 1.0));
+    }
+
+    pub fn render(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        self.pass.render(render_pass);
+    }
+}
+
+// Fullscreen pass sampling one texture, used for the intermediate passes of the effects
+struct FullscreenPipeline {
+    pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+}
+
+impl FullscreenPipeline {
+    fn new(
+        context: &DrawContext,
+        shader_module: &wgpu::ShaderModule,
+        fragment_entry_point: &str,
+        format: wgpu::TextureFormat,
+        blend: Option<wgpu::BlendState>,
+    ) -> Self {
+        let device = &context.device;
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Fullscreen Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Fullscreen Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            cache: None,
+            label: Some(fragment_entry_point),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: shader_module,
+                entry_point: Some("vtx_main"),
+                buffers: &[],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shader_module,
+                entry_point: Some(fragment_entry_point),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+        Self {
+            pipeline,
+            bind_group_layout,
+        }
+    }
+
+    fn create_bind_group(
+        &self,
+        context: &DrawContext,
+        texture: &wgpu::Texture,
+        sampler: &wgpu::Sampler,
+        params: &Uniform<[f32; 4]>,
+    ) -> wgpu::BindGroup {
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        context
+            .device
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Fullscreen Bind Group"),
+                layout: &self.bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(sampler),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: params.binding_resource(),
+                    },
+                ],
+            })
+    }
+
+    fn draw(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::Texture,
+        load: wgpu::LoadOp<wgpu::Color>,
+        bind_group: &wgpu::BindGroup,
+    ) {
+        let view = target.create_view(&wgpu::TextureViewDescriptor::default());
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Fullscreen pass"),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
+
+struct BloomLevel {
+    // Blurred result of the level, also the source of the next level
+    blurred: wgpu::Texture,
+    scratch: wgpu::Texture,
+    blur_horizontal: wgpu::BindGroup,
+    blur_vertical: wgpu::BindGroup,
+    downsample: wgpu::BindGroup,
+    composite: wgpu::BindGroup,
+}
+
+// Adds a glow around the bright parts of an HDR render texture, before tonemapping
+pub struct Bloom {
+    source: wgpu::Texture,
+    extract_pipeline: FullscreenPipeline,
+    downsample_pipeline: FullscreenPipeline,
+    blur_pipeline: FullscreenPipeline,
+    composite_pipeline: FullscreenPipeline,
+    extract: wgpu::BindGroup,
+    levels: Vec<BloomLevel>,
+    threshold: Uniform<[f32; 4]>,
+    intensity: Uniform<[f32; 4]>,
+    _blur_steps: Vec<Uniform<[f32; 4]>>,
+}
+
+impl Bloom {
+    pub const DEFAULT_THRESHOLD: f32 = 1.0;
+    pub const DEFAULT_INTENSITY: f32 = 0.5;
+
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn new(context: &DrawContext, source: &RenderTexture, level_count: u32) -> Self {
+        let format = source.format();
+        let shader_module = context.create_shader_module(BLOOM_SHADER);
+        let additive = wgpu::BlendState {
+            color: wgpu::BlendComponent {
+                src_factor: wgpu::BlendFactor::One,
+                dst_factor: wgpu::BlendFactor::One,
+                operation: wgpu::BlendOperation::Add,
+            },
+            alpha: wgpu::BlendComponent {
+                src_factor: wgpu::BlendFactor::Zero,
+                dst_factor: wgpu::BlendFactor::One,
+                operation: wgpu::BlendOperation::Add,
+            },
+        };
+        let extract_pipeline =
+            FullscreenPipeline::new(context, &shader_module, "frg_extract", format, None);
+        let downsample_pipeline =
+            FullscreenPipeline::new(context, &shader_module, "frg_downsample", format, None);
+        let blur_pipeline =
+            FullscreenPipeline::new(context, &shader_module, "frg_blur", format, None);
+        let composite_pipeline = FullscreenPipeline::new(
+            context,
+            &shader_module,
+            "frg_composite",
+            format,
+            Some(additive),
+        );
+        let sampler = context.device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Bloom Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let threshold = Uniform::new(context, [Self::DEFAULT_THRESHOLD, 0.0, 0.0, 0.0]);
+        let intensity = Uniform::new(context, [Self::DEFAULT_INTENSITY, 0.0, 0.0, 0.0]);
+        let extract =
+            extract_pipeline.create_bind_group(context, source.texture(), &sampler, &threshold);
+
+        let mut blur_steps = Vec::new();
+        let mut levels = Vec::<BloomLevel>::new();
+        let mut dimensions = source.dimensions();
+        for _ in 0..level_count.max(1) {
+            dimensions.width = (dimensions.width / 2).max(1);
+            dimensions.height = (dimensions.height / 2).max(1);
+            let create_texture = |label| {
+                context.device.create_texture(&wgpu::TextureDescriptor {
+                    label: Some(label),
+                    size: wgpu::Extent3d {
+                        width: dimensions.width,
+                        height: dimensions.height,
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format,
+                    usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                        | wgpu::TextureUsages::TEXTURE_BINDING,
+                    view_formats: &[],
+                })
+            };
+            let blurred = create_texture("Bloom Texture");
+            let scratch = create_texture("Bloom Scratch Texture");
+            let horizontal_step =
+                Uniform::new(context, [1.0 / dimensions.width as f32, 0.0, 0.0, 0.0]);
+            let vertical_step =
+                Uniform::new(context, [0.0, 1.0 / dimensions.height as f32, 0.0, 0.0]);
+            let blur_horizontal =
+                blur_pipeline.create_bind_group(context, &blurred, &sampler, &horizontal_step);
+            let blur_vertical =
+                blur_pipeline.create_bind_group(context, &scratch, &sampler, &vertical_step);
+            // The downsample pass has no parameter, any uniform fits the layout
+            let downsample =
+                downsample_pipeline.create_bind_group(context, &blurred, &sampler, &intensity);
+            let composite =
+                composite_pipeline.create_bind_group(context, &blurred, &sampler, &intensity);
+            blur_steps.extend([horizontal_step, vertical_step]);
+            levels.push(BloomLevel {
+                blurred,
+                scratch,
+                blur_horizontal,
+                blur_vertical,
+                downsample,
+                composite,
+            });
+        }
+        Self {
+            source: source.texture().clone(),
+            extract_pipeline,
+            downsample_pipeline,
+            blur_pipeline,
+            composite_pipeline,
+            extract,
+            levels,
+            threshold,
+            intensity,
+            _blur_steps: blur_steps,
+        }
+    }
+
+    pub fn set_threshold(&mut self, threshold: f32) {
+        self.threshold.write_uniform([threshold, 0.0, 0.0, 0.0]);
+    }
+
+    pub fn set_intensity(&mut self, intensity: f32) {
+        self.intensity.write_uniform([intensity, 0.0, 0.0, 0.0]);
+    }
+
+    // Runs the bloom passes and adds their result into the source render texture
+    pub fn apply(&self, context: &DrawContext) {
+        let clear = wgpu::LoadOp::Clear(wgpu::Color::BLACK);
+        let mut encoder = context
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Bloom Command Encoder"),
+            });
+        self.extract_pipeline
+            .draw(&mut encoder, &self.levels[0].blurred, clear, &self.extract);
+        for (index, level) in self.levels.iter().enumerate() {
+            if index > 0 {
+                let previous = &self.levels[index - 1];
+                self.downsample_pipeline.draw(
+                    &mut encoder,
+                    &level.blurred,
+                    clear,
+                    &previous.downsample,
+                );
+            }
+            self.blur_pipeline
+                .draw(&mut encoder, &level.scratch, clear, &level.blur_horizontal);
+            self.blur_pipeline
+                .draw(&mut encoder, &level.blurred, clear, &level.blur_vertical);
+        }
+        for level in &self.levels {
+            self.composite_pipeline.draw(
+                &mut encoder,
+                &self.source,
+                wgpu::LoadOp::Load,
+                &level.composite,
+            );
+        }
+        context.submit(Some(encoder.finish()));
+    }
+}
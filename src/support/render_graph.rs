@@ -0,0 +1,256 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use std::collections::{BTreeSet, HashMap};
+
+use anyhow::{anyhow, bail};
+
+use crate::draw_context::DrawContext;
+use crate::support::render_texture::RenderTexture;
+
+type PassCallback<'a> = Box<dyn FnOnce(&mut wgpu::RenderPass<'_>) + 'a>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PassOutput<'a> {
+    // Name of a texture registered in the graph
+    Texture(&'a str),
+    // Draw target of the context, either the surface or the headless texture
+    Target,
+}
+
+struct RenderGraphPass<'a> {
+    name: String,
+    inputs: Vec<String>,
+    output: Option<String>,
+    callback: PassCallback<'a>,
+}
+
+struct OffscreenPass<'a> {
+    callback: PassCallback<'a>,
+    texture: &'a RenderTexture,
+    load: wgpu::LoadOp<wgpu::Color>,
+}
+
+// Sequences named passes according to the render textures they read and write, and records
+// them in a single command encoder
+#[derive(Default)]
+pub struct RenderGraph<'a> {
+    textures: HashMap<String, (&'a RenderTexture, wgpu::Color)>,
+    passes: Vec<RenderGraphPass<'a>>,
+}
+
+impl<'a> RenderGraph<'a> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // The first pass writing into the texture clears it with the given color
+    pub fn add_texture(
+        &mut self,
+        name: &str,
+        texture: &'a RenderTexture,
+        clear_color: wgpu::Color,
+    ) -> anyhow::Result<&mut Self> {
+        if self.textures.contains_key(name) {
+            bail!("Texture {name} is already registered");
+        }
+        self.textures
+            .insert(name.to_string(), (texture, clear_color));
+        Ok(self)
+    }
+
+    pub fn add_pass<C>(
+        &mut self,
+        name: &str,
+        inputs: &[&str],
+        output: PassOutput,
+        callback: C,
+    ) -> anyhow::Result<&mut Self>
+    where
+        C: FnOnce(&mut wgpu::RenderPass<'_>) + 'a,
+    {
+        if self.passes.iter().any(|pass| pass.name == name) {
+            bail!("Pass {name} is already registered");
+        }
+        let output = match output {
+            PassOutput::Texture(texture) => Some(texture.to_string()),
+            PassOutput::Target => {
+                if self.passes.iter().any(|pass| pass.output.is_none()) {
+                    bail!("Only one pass can render into the draw target");
+                }
+                None
+            }
+        };
+        for texture in inputs.iter().copied().chain(output.as_deref()) {
+            if !self.textures.contains_key(texture) {
+                bail!("Texture {texture} used by pass {name} is not registered");
+            }
+        }
+        self.passes.push(RenderGraphPass {
+            name: name.to_string(),
+            inputs: inputs.iter().map(ToString::to_string).collect(),
+            output,
+            callback: Box::new(callback),
+        });
+        Ok(self)
+    }
+
+    // A pass runs after every pass writing into one of its inputs, and after the passes
+    // registered before it writing into the same output. Otherwise, registration order is kept.
+    pub fn execution_order(&self) -> anyhow::Result<Vec<&str>> {
+        let dependencies: Vec<BTreeSet<usize>> = self
+            .passes
+            .iter()
+            .enumerate()
+            .map(|(index, pass)| {
+                self.passes
+                    .iter()
+                    .enumerate()
+                    .filter(|(other_index, other)| {
+                        *other_index != index
+                            && other.output.as_ref().is_some_and(|output| {
+                                pass.inputs.contains(output)
+                                    || (*other_index < index
+                                        && pass.output.as_ref() == Some(output))
+                            })
+                    })
+                    .map(|(other_index, _)| other_index)
+                    .collect()
+            })
+            .collect();
+        let mut order = Vec::with_capacity(self.passes.len());
+        let mut done = vec![false; self.passes.len()];
+        while order.len() < self.passes.len() {
+            // The draw target pass is always the last one, since no pass can read from it
+            let next = (0..self.passes.len())
+                .filter(|&index| !done[index])
+                .find(|&index| {
+                    dependencies[index]
+                        .iter()
+                        .all(|&dependency| done[dependency])
+                        && (self.passes[index].output.is_some()
+                            || order.len() + 1 == self.passes.len())
+                })
+                .ok_or_else(|| anyhow!("Render graph passes have cyclic dependencies"))?;
+            done[next] = true;
+            order.push(next);
+        }
+        Ok(order
+            .into_iter()
+            .map(|index| self.passes[index].name.as_str())
+            .collect())
+    }
+
+    // Submits all passes at once, and presents the frame when a pass renders into the draw target
+    pub fn execute(self, context: &DrawContext) -> anyhow::Result<Vec<String>> {
+        let (offscreen_passes, target_pass, order) = self.into_ordered_passes()?;
+        if let Some(target_pass) = target_pass {
+            context.render_frame(
+                |encoder| Self::encode_offscreen_passes(encoder, offscreen_passes),
+                |mut render_pass| (target_pass.callback)(&mut render_pass),
+            )?;
+        } else {
+            let mut encoder = Self::create_command_encoder(context);
+            Self::encode_offscreen_passes(&mut encoder, offscreen_passes);
+            context.submit(Some(encoder.finish()));
+        }
+        Ok(order)
+    }
+
+    // To use from a render loop handler: offscreen passes are submitted right away, and the
+    // draw target pass is recorded into the render pass of the frame
+    pub fn execute_in(
+        self,
+        context: &DrawContext,
+        render_pass: &mut wgpu::RenderPass<'_>,
+    ) -> anyhow::Result<Vec<String>> {
+        let (offscreen_passes, target_pass, order) = self.into_ordered_passes()?;
+        let mut encoder = Self::create_command_encoder(context);
+        Self::encode_offscreen_passes(&mut encoder, offscreen_passes);
+        context.submit(Some(encoder.finish()));
+        if let Some(target_pass) = target_pass {
+            (target_pass.callback)(render_pass);
+        }
+        Ok(order)
+    }
+
+    fn create_command_encoder(context: &DrawContext) -> wgpu::CommandEncoder {
+        context
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Render Graph Command Encoder"),
+            })
+    }
+
+    fn into_ordered_passes(
+        mut self,
+    ) -> anyhow::Result<(
+        Vec<OffscreenPass<'a>>,
+        Option<RenderGraphPass<'a>>,
+        Vec<String>,
+    )> {
+        let order: Vec<String> = self
+            .execution_order()?
+            .into_iter()
+            .map(ToString::to_string)
+            .collect();
+        let mut cleared_textures = BTreeSet::new();
+        let mut offscreen_passes = Vec::with_capacity(order.len());
+        let mut target_pass = None;
+        for name in &order {
+            let position = self
+                .passes
+                .iter()
+                .position(|pass| &pass.name == name)
+                .expect("Ordered pass should be registered");
+            let pass = self.passes.swap_remove(position);
+            let Some(output) = pass.output else {
+                target_pass = Some(pass);
+                continue;
+            };
+            let (texture, clear_color) = self.textures[&output];
+            let load = if cleared_textures.insert(output) {
+                wgpu::LoadOp::Clear(clear_color)
+            } else {
+                wgpu::LoadOp::Load
+            };
+            offscreen_passes.push(OffscreenPass {
+                callback: pass.callback,
+                texture,
+                load,
+            });
+        }
+        Ok((offscreen_passes, target_pass, order))
+    }
+
+    fn encode_offscreen_passes(
+        encoder: &mut wgpu::CommandEncoder,
+        offscreen_passes: Vec<OffscreenPass<'a>>,
+    ) {
+        for pass in offscreen_passes {
+            pass.texture.encode_pass(encoder, pass.load, pass.callback);
+        }
+    }
+}
@@ -0,0 +1,211 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::draw_context::{Dimensions, DrawContext, TextureBinding, stencil_operations};
+
+pub struct RenderTexture {
+    texture: wgpu::Texture,
+    multisample_texture: Option<wgpu::Texture>,
+    depth_texture: wgpu::Texture,
+}
+
+impl RenderTexture {
+    pub const DEFAULT_HDR_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+    #[must_use]
+    pub fn new(context: &DrawContext, dimensions: Dimensions, format: wgpu::TextureFormat) -> Self {
+        let sample_count = context.multisample_config.get_multisample_count();
+        Self::with_sample_count(context, dimensions, format, sample_count)
+    }
+
+    // Drawables rendered into it must be built with the same sample count
+    #[must_use]
+    pub fn with_sample_count(
+        context: &DrawContext,
+        dimensions: Dimensions,
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+    ) -> Self {
+        let create_texture = |label, format, sample_count, usage| {
+            context.device.create_texture(&wgpu::TextureDescriptor {
+                label: Some(label),
+                size: wgpu::Extent3d {
+                    width: dimensions.width,
+                    height: dimensions.height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage,
+                view_formats: &[],
+            })
+        };
+        let texture = create_texture(
+            "Render Texture",
+            format,
+            1,
+            wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC,
+        );
+        let multisample_texture = (sample_count > 1).then(|| {
+            create_texture(
+                "Render Texture Multisample",
+                format,
+                sample_count,
+                wgpu::TextureUsages::RENDER_ATTACHMENT,
+            )
+        });
+        // Same depth format as the one expected by the drawables
+        let depth_texture = create_texture(
+            "Render Texture Depth",
+            context.depth_format(),
+            sample_count,
+            wgpu::TextureUsages::RENDER_ATTACHMENT,
+        );
+        Self {
+            texture,
+            multisample_texture,
+            depth_texture,
+        }
+    }
+
+    #[must_use]
+    pub fn texture(&self) -> &wgpu::Texture {
+        &self.texture
+    }
+
+    #[must_use]
+    pub fn sample_count(&self) -> u32 {
+        self.depth_texture.sample_count()
+    }
+
+    #[must_use]
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.texture.format()
+    }
+
+    #[must_use]
+    pub fn dimensions(&self) -> Dimensions {
+        Dimensions::new(self.texture.width(), self.texture.height())
+    }
+
+    #[must_use]
+    pub fn texture_binding(&self) -> TextureBinding {
+        let filterable = self
+            .format()
+            .sample_type(None, None)
+            .is_some_and(|sample_type| {
+                matches!(
+                    sample_type,
+                    wgpu::TextureSampleType::Float { filterable: true }
+                )
+            });
+        TextureBinding::new(&self.texture, wgpu::TextureSampleType::Float { filterable })
+    }
+
+    pub fn render<C>(&self, context: &DrawContext, clear_color: wgpu::Color, callback: C)
+    where
+        C: FnOnce(&mut wgpu::RenderPass<'_>),
+    {
+        let mut encoder = context
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Render Texture Command Encoder"),
+            });
+        self.encode_pass(&mut encoder, wgpu::LoadOp::Clear(clear_color), callback);
+        context.submit(Some(encoder.finish()));
+    }
+
+    // Records the pass without submitting it; depth is only cleared along with the color
+    pub fn encode_pass<C>(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        color_load: wgpu::LoadOp<wgpu::Color>,
+        callback: C,
+    ) where
+        C: FnOnce(&mut wgpu::RenderPass<'_>),
+    {
+        let mut render_pass = self.begin_pass(encoder, color_load);
+        callback(&mut render_pass);
+    }
+
+    // View of the resolved texture, and of the multisampled one to render into when enabled
+    pub(crate) fn color_views(&self) -> (wgpu::TextureView, Option<wgpu::TextureView>) {
+        let texture_view = self
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        let multisample_view = self
+            .multisample_texture
+            .as_ref()
+            .map(|texture| texture.create_view(&wgpu::TextureViewDescriptor::default()));
+        (texture_view, multisample_view)
+    }
+
+    pub(crate) fn depth_view(&self) -> wgpu::TextureView {
+        self.depth_texture
+            .create_view(&wgpu::TextureViewDescriptor::default())
+    }
+
+    pub fn begin_pass<'e>(
+        &self,
+        encoder: &'e mut wgpu::CommandEncoder,
+        color_load: wgpu::LoadOp<wgpu::Color>,
+    ) -> wgpu::RenderPass<'e> {
+        let (texture_view, multisample_view) = self.color_views();
+        let (view, resolve_target) = multisample_view
+            .as_ref()
+            .map_or((&texture_view, None), |multisample_view| {
+                (multisample_view, Some(&texture_view))
+            });
+        let depth_view = self.depth_view();
+        let depth_load = match color_load {
+            wgpu::LoadOp::Clear(_) => wgpu::LoadOp::Clear(1.0),
+            wgpu::LoadOp::Load => wgpu::LoadOp::Load,
+        };
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Render texture pass"),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target,
+                ops: wgpu::Operations {
+                    load: color_load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &depth_view,
+                depth_ops: Some(wgpu::Operations {
+                    load: depth_load,
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: stencil_operations(self.depth_texture.format(), depth_load),
+            }),
+        })
+    }
+}
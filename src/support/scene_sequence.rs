@@ -0,0 +1,354 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use std::collections::VecDeque;
+
+use log::debug;
+use web_time::Duration;
+use winit::dpi::PhysicalPosition;
+use winit::event::{DeviceEvent, KeyEvent, WindowEvent};
+
+use crate::draw_context::DrawContext;
+use crate::plugins::PluginRegistry;
+use crate::render_loop::{EventState, LaunchContext, RenderLoopHandler, TimeInfo};
+use crate::support::background::Background;
+use crate::support::postprocess::Crossfade;
+use crate::support::render_texture::RenderTexture;
+
+pub enum Trigger {
+    // Advances after the scenario ran for the duration
+    Duration(Duration),
+    // Advances once the scenario reports it is finished
+    Finished,
+}
+
+struct SequencedScenario {
+    scenario: Box<dyn RenderLoopHandler>,
+    plugin_registry: PluginRegistry,
+    trigger: Trigger,
+}
+
+// During a crossfade, both scenarios render into their own texture with their own plugins,
+// the registry of the render loop stays empty until the incoming scenario takes over
+struct Transition {
+    outgoing: Box<dyn RenderLoopHandler>,
+    outgoing_registry: PluginRegistry,
+    outgoing_background: Background,
+    outgoing_target: RenderTexture,
+    incoming_registry: PluginRegistry,
+    incoming_background: Background,
+    incoming_target: RenderTexture,
+    crossfade: Crossfade,
+    elapsed: Duration,
+}
+
+// Runs scenarios one after the other, each one with its own plugin registry swapped in when
+// it starts; the sequence is finished after the last one
+pub struct SceneSequence {
+    pending: VecDeque<SequencedScenario>,
+    current: Option<(Box<dyn RenderLoopHandler>, Trigger)>,
+    elapsed: Duration,
+    crossfade_duration: Option<Duration>,
+    transition: Option<Transition>,
+}
+
+impl SceneSequence {
+    #[must_use]
+    pub fn new(
+        launch_context: LaunchContext,
+        scenarios: Vec<(Box<dyn RenderLoopHandler>, Trigger)>,
+    ) -> Self {
+        Self::with_plugin_registries(
+            launch_context,
+            scenarios
+                .into_iter()
+                .map(|(scenario, trigger)| (scenario, PluginRegistry::default(), trigger))
+                .collect(),
+        )
+    }
+
+    // For scenarios registering their plugins when built, in a registry of their own
+    #[must_use]
+    pub fn with_plugin_registries(
+        LaunchContext {
+            draw_context,
+            plugin_registry,
+        }: LaunchContext,
+        scenarios: Vec<(Box<dyn RenderLoopHandler>, PluginRegistry, Trigger)>,
+    ) -> Self {
+        let mut sequence = Self {
+            pending: scenarios
+                .into_iter()
+                .map(|(scenario, plugin_registry, trigger)| SequencedScenario {
+                    scenario,
+                    plugin_registry,
+                    trigger,
+                })
+                .collect(),
+            current: None,
+            elapsed: Duration::ZERO,
+            crossfade_duration: None,
+            transition: None,
+        };
+        sequence.start_next_scenario(plugin_registry, draw_context);
+        sequence
+    }
+
+    #[must_use]
+    pub fn remaining_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    // Blends the scenarios over the duration instead of cutting from one to the next
+    pub fn set_crossfade(&mut self, duration: Option<Duration>) -> &mut Self {
+        self.crossfade_duration = duration;
+        self
+    }
+
+    #[must_use]
+    pub fn is_transitioning(&self) -> bool {
+        self.transition.is_some()
+    }
+
+    fn start_next_scenario(
+        &mut self,
+        plugin_registry: &mut PluginRegistry,
+        draw_context: &mut DrawContext,
+    ) {
+        self.elapsed = Duration::ZERO;
+        let outgoing = self.current.take();
+        let Some(mut next) = self.pending.pop_front() else {
+            debug!("No more scenarios to run, stopping");
+            if let Some((mut outgoing, _)) = outgoing {
+                outgoing.on_exit(draw_context);
+            }
+            return;
+        };
+        debug!("Switching to next scenario");
+        match (outgoing, self.crossfade_duration) {
+            (Some((outgoing, _)), Some(duration)) if !duration.is_zero() => {
+                let outgoing_background = draw_context.background();
+                let outgoing_registry = std::mem::take(plugin_registry);
+                next.scenario
+                    .on_init(&mut next.plugin_registry, draw_context);
+                let create_target = || {
+                    RenderTexture::new(
+                        draw_context,
+                        draw_context.surface_dimensions(),
+                        draw_context.surface_config.format,
+                    )
+                };
+                let outgoing_target = create_target();
+                let incoming_target = create_target();
+                let crossfade = Crossfade::new(draw_context, &outgoing_target, &incoming_target);
+                self.transition = Some(Transition {
+                    outgoing,
+                    outgoing_registry,
+                    outgoing_background,
+                    outgoing_target,
+                    incoming_registry: next.plugin_registry,
+                    incoming_background: draw_context.background(),
+                    incoming_target,
+                    crossfade,
+                    elapsed: Duration::ZERO,
+                });
+            }
+            (outgoing, _) => {
+                if let Some((mut outgoing, _)) = outgoing {
+                    outgoing.on_exit(draw_context);
+                }
+                *plugin_registry = next.plugin_registry;
+                next.scenario.on_init(plugin_registry, draw_context);
+            }
+        }
+        self.current = Some((next.scenario, next.trigger));
+    }
+
+    fn update_transition(
+        &mut self,
+        plugin_registry: &mut PluginRegistry,
+        draw_context: &mut DrawContext,
+        time_info: &TimeInfo,
+    ) {
+        let (Some(transition), Some(duration)) = (&mut self.transition, self.crossfade_duration)
+        else {
+            return;
+        };
+        transition.elapsed += time_info.processing_delta;
+        if transition.elapsed < duration {
+            transition
+                .crossfade
+                .set_progress(transition.elapsed.as_secs_f32() / duration.as_secs_f32());
+            return;
+        }
+        debug!("Crossfade is finished");
+        if let Some(mut transition) = self.transition.take() {
+            transition.outgoing.on_exit(draw_context);
+            // The outgoing scenario may restore a background saved before the incoming one started
+            draw_context.set_background(transition.incoming_background);
+            *plugin_registry = transition.incoming_registry;
+        }
+    }
+
+    fn render_into(
+        encoder: &mut wgpu::CommandEncoder,
+        target: &RenderTexture,
+        background: Background,
+        scenario: &mut dyn RenderLoopHandler,
+        plugin_registry: &mut PluginRegistry,
+        draw_context: &DrawContext,
+        time_info: &TimeInfo,
+    ) {
+        // Gradients are not drawn into the crossfade targets, only their bottom color
+        let load = match background {
+            Background::None => wgpu::LoadOp::Load,
+            Background::Solid(color) | Background::Gradient(_, color) => wgpu::LoadOp::Clear(color),
+        };
+        let render_pass = &mut target.begin_pass(encoder, load).forget_lifetime();
+        scenario.on_render(plugin_registry, draw_context, time_info, render_pass);
+        for listener in plugin_registry.iter_mut() {
+            listener.on_render(draw_context, time_info, render_pass);
+        }
+    }
+
+    fn is_current_done(&self) -> bool {
+        match &self.current {
+            Some((_, Trigger::Duration(duration))) => self.elapsed >= *duration,
+            Some((scenario, Trigger::Finished)) => scenario.is_finished(),
+            None => false,
+        }
+    }
+}
+
+impl RenderLoopHandler for SceneSequence {
+    fn on_mouse_event(&mut self, event: &DeviceEvent) {
+        if let Some((scenario, _)) = &mut self.current {
+            scenario.on_mouse_event(event);
+        }
+    }
+    fn on_keyboard_event(&mut self, event: &KeyEvent) {
+        if let Some((scenario, _)) = &mut self.current {
+            scenario.on_keyboard_event(event);
+        }
+    }
+    fn on_cursor_moved(&mut self, position: PhysicalPosition<f64>) {
+        if let Some((scenario, _)) = &mut self.current {
+            scenario.on_cursor_moved(position);
+        }
+    }
+    fn on_window_event(&mut self, event: &WindowEvent) -> EventState {
+        self.current
+            .as_mut()
+            .map_or_else(EventState::default, |(scenario, _)| {
+                scenario.on_window_event(event)
+            })
+    }
+    fn on_update(
+        &mut self,
+        plugin_registry: &mut PluginRegistry,
+        draw_context: &mut DrawContext,
+        time_info: &TimeInfo,
+    ) {
+        self.elapsed += time_info.processing_delta;
+        self.update_transition(plugin_registry, draw_context, time_info);
+        if self.transition.is_none() && self.is_current_done() {
+            self.start_next_scenario(plugin_registry, draw_context);
+        }
+        let Some((scenario, _)) = &mut self.current else {
+            return;
+        };
+        let Some(transition) = &mut self.transition else {
+            scenario.on_update(plugin_registry, draw_context, time_info);
+            return;
+        };
+        // The render loop only updates the plugins of its own registry, empty during a crossfade
+        for (handler, registry) in [
+            (&mut transition.outgoing, &mut transition.outgoing_registry),
+            (scenario, &mut transition.incoming_registry),
+        ] {
+            handler.on_update(registry, draw_context, time_info);
+            for listener in registry.iter_mut() {
+                listener.on_update(draw_context, time_info);
+            }
+        }
+    }
+    fn on_render(
+        &mut self,
+        plugin_registry: &mut PluginRegistry,
+        draw_context: &DrawContext,
+        time_info: &TimeInfo,
+        render_pass: &mut wgpu::RenderPass<'static>,
+    ) {
+        let Some((scenario, _)) = &mut self.current else {
+            return;
+        };
+        let Some(transition) = &mut self.transition else {
+            scenario.on_render(plugin_registry, draw_context, time_info, render_pass);
+            return;
+        };
+        let mut encoder =
+            draw_context
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("Crossfade Command Encoder"),
+                });
+        Self::render_into(
+            &mut encoder,
+            &transition.outgoing_target,
+            transition.outgoing_background,
+            transition.outgoing.as_mut(),
+            &mut transition.outgoing_registry,
+            draw_context,
+            time_info,
+        );
+        Self::render_into(
+            &mut encoder,
+            &transition.incoming_target,
+            transition.incoming_background,
+            scenario.as_mut(),
+            &mut transition.incoming_registry,
+            draw_context,
+            time_info,
+        );
+        draw_context.submit(Some(encoder.finish()));
+        transition.crossfade.render(render_pass);
+    }
+    fn is_finished(&self) -> bool {
+        self.current.is_none()
+    }
+    fn on_exit(&mut self, draw_context: &mut DrawContext) {
+        if let Some(mut transition) = self.transition.take() {
+            transition.outgoing.on_exit(draw_context);
+        }
+        if let Some((mut scenario, _)) = self.current.take() {
+            scenario.on_exit(draw_context);
+        }
+    }
+    fn name(&self) -> &str {
+        match &self.current {
+            Some((scenario, _)) => scenario.name(),
+            None => std::any::type_name::<Self>(),
+        }
+    }
+}
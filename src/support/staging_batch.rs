@@ -0,0 +1,148 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use std::ops::Range;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use wgpu::util::DeviceExt;
+
+struct PendingWrite {
+    target: wgpu::Buffer,
+    offset: wgpu::BufferAddress,
+    range: Range<usize>,
+}
+
+#[derive(Default)]
+struct PendingWrites {
+    writes: Vec<PendingWrite>,
+    data: Vec<u8>,
+}
+
+// Buffer writes of a frame gathered in a single staging buffer, copied to their targets
+// before the commands of the next submission. Disabled by default, each write then goes
+// through queue.write_buffer.
+pub struct StagingBatch {
+    device: wgpu::Device,
+    enabled: AtomicBool,
+    pending: Mutex<PendingWrites>,
+    staging_allocations: AtomicU64,
+}
+
+impl StagingBatch {
+    pub(crate) fn new(device: wgpu::Device) -> Self {
+        Self {
+            device,
+            enabled: AtomicBool::default(),
+            pending: Mutex::default(),
+            staging_allocations: AtomicU64::default(),
+        }
+    }
+    #[must_use]
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+    // Writes already batched are still flushed with the next frame
+    pub(crate) fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+    #[must_use]
+    pub fn pending_write_count(&self) -> usize {
+        self.pending
+            .lock()
+            .expect("Staging batch lock should not be poisoned")
+            .writes
+            .len()
+    }
+    // Staging buffers created by the flushes since the creation of the context
+    #[must_use]
+    pub fn staging_allocation_count(&self) -> u64 {
+        self.staging_allocations.load(Ordering::Relaxed)
+    }
+    pub(crate) fn write_buffer(
+        &self,
+        queue: &wgpu::Queue,
+        target: &wgpu::Buffer,
+        offset: wgpu::BufferAddress,
+        data: &[u8],
+    ) {
+        // Buffer copies need aligned offsets and sizes
+        let is_aligned = offset.is_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT)
+            && (data.len() as u64).is_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT);
+        if !self.is_enabled() || !is_aligned {
+            // The batched writes are submitted first, so this one still lands after them
+            if let Some(command_buffer) = self.flush() {
+                queue.submit(Some(command_buffer));
+            }
+            queue.write_buffer(target, offset, data);
+            return;
+        }
+        let mut pending = self
+            .pending
+            .lock()
+            .expect("Staging batch lock should not be poisoned");
+        let start = pending.data.len();
+        pending.data.extend_from_slice(data);
+        let range = start..pending.data.len();
+        pending.writes.push(PendingWrite {
+            target: target.clone(),
+            offset,
+            range,
+        });
+    }
+    // Writes to the same target are copied in order, so the last one wins like with the queue
+    pub(crate) fn flush(&self) -> Option<wgpu::CommandBuffer> {
+        let PendingWrites { writes, data } = std::mem::take(
+            &mut *self
+                .pending
+                .lock()
+                .expect("Staging batch lock should not be poisoned"),
+        );
+        if writes.is_empty() {
+            return None;
+        }
+        let staging_buffer = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Staging Batch Buffer"),
+                contents: &data,
+                usage: wgpu::BufferUsages::COPY_SRC,
+            });
+        self.staging_allocations.fetch_add(1, Ordering::Relaxed);
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Staging Batch Encoder"),
+            });
+        for write in writes {
+            encoder.copy_buffer_to_buffer(
+                &staging_buffer,
+                write.range.start as wgpu::BufferAddress,
+                &write.target,
+                write.offset,
+                write.range.len() as wgpu::BufferAddress,
+            );
+        }
+        Some(encoder.finish())
+    }
+}
@@ -0,0 +1,119 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, bail};
+
+use crate::draw_context::{Dimensions, DrawContext};
+
+// When set, the reference images are overwritten by the rendered frames instead of compared
+pub const UPDATE_SNAPSHOTS_ENV: &str = "UPDATE_SNAPSHOTS";
+
+const DIFF_COLOR: [u8; 4] = [255, 0, 255, 255];
+
+// Golden-image assertion on the last frame rendered into the target, for headless contexts.
+// Pixels match when no channel differs by more than the tolerance. On mismatch, the frame and a
+// diff image, where differing pixels are magenta, are written next to the reference.
+#[track_caller]
+pub fn assert_frame_matches(
+    context: &DrawContext,
+    reference_png_path: impl AsRef<Path>,
+    tolerance: u8,
+) {
+    if let Err(error) = compare_frame(context, reference_png_path.as_ref(), tolerance) {
+        panic!("{error:#}");
+    }
+}
+
+pub fn compare_frame(
+    context: &DrawContext,
+    reference_png_path: &Path,
+    tolerance: u8,
+) -> anyhow::Result<()> {
+    let pixels = context.read_target_pixels()?;
+    let Dimensions { width, height } = context.surface_dimensions();
+    if env::var_os(UPDATE_SNAPSHOTS_ENV).is_some() {
+        if let Some(parent) = reference_png_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        return save_png(reference_png_path, &pixels, width, height);
+    }
+    let reference = image::open(reference_png_path)
+        .with_context(|| {
+            format!(
+                "Cannot read reference {}, set {UPDATE_SNAPSHOTS_ENV} to create it",
+                reference_png_path.display()
+            )
+        })?
+        .into_rgba8();
+    if reference.dimensions() != (width, height) {
+        bail!(
+            "Frame is {width}x{height} while reference {} is {}x{}",
+            reference_png_path.display(),
+            reference.width(),
+            reference.height()
+        );
+    }
+    let mut mismatch_count = 0;
+    let mut diff = Vec::with_capacity(pixels.len());
+    for (actual, expected) in pixels.chunks(4).zip(reference.as_raw().chunks(4)) {
+        let matches = actual
+            .iter()
+            .zip(expected)
+            .all(|(actual, expected)| actual.abs_diff(*expected) <= tolerance);
+        if matches {
+            // Dimmed reference, so the differing pixels stand out
+            diff.extend(expected[..3].iter().map(|channel| channel / 4));
+            diff.push(255);
+        } else {
+            mismatch_count += 1;
+            diff.extend(DIFF_COLOR);
+        }
+    }
+    if mismatch_count == 0 {
+        return Ok(());
+    }
+    let actual_path = sibling_path(reference_png_path, "actual");
+    let diff_path = sibling_path(reference_png_path, "diff");
+    save_png(&actual_path, &pixels, width, height)?;
+    save_png(&diff_path, &diff, width, height)?;
+    bail!(
+        "{mismatch_count} pixels differ by more than {tolerance} from {}, see {} and {}",
+        reference_png_path.display(),
+        actual_path.display(),
+        diff_path.display()
+    );
+}
+
+fn sibling_path(reference_png_path: &Path, suffix: &str) -> PathBuf {
+    reference_png_path.with_extension(format!("{suffix}.png"))
+}
+
+fn save_png(path: &Path, rgba: &[u8], width: u32, height: u32) -> anyhow::Result<()> {
+    image::save_buffer(path, rgba, width, height, image::ExtendedColorType::Rgba8)
+        .with_context(|| format!("Cannot write {}", path.display()))
+}
@@ -0,0 +1,441 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use std::collections::HashMap;
+use std::mem::size_of;
+
+use ab_glyph::{Font, FontRef, OutlinedGlyph, PxScale, ScaleFont, point};
+use anyhow::bail;
+use bytemuck::{Pod, Zeroable};
+
+use crate::draw_context::DrawContext;
+
+const TEXT_SHADER: &str = include_str!("../shaders/text.wgsl");
+const ATLAS_WIDTH: u32 = 512;
+const GLYPH_PADDING: u32 = 1;
+const FALLBACK_CHAR: char = '?';
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+struct TextVertex {
+    position: [f32; 2],
+    uv: [f32; 2],
+    color: [f32; 4],
+}
+
+// Placement in pixels at the baked height, the offset being relative to the top of the line
+#[derive(Debug, Clone, Copy)]
+struct BakedGlyph {
+    advance: f32,
+    offset: [f32; 2],
+    size: [f32; 2],
+    uv_min: [f32; 2],
+    uv_max: [f32; 2],
+}
+
+// Coverage of the printable ASCII characters, packed row by row
+struct GlyphAtlas {
+    pixels: Vec<u8>,
+    height: u32,
+    glyphs: HashMap<char, BakedGlyph>,
+    line_height: f32,
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+#[allow(clippy::cast_precision_loss)]
+fn bake_atlas(font_data: &[u8], pixel_height: f32) -> anyhow::Result<GlyphAtlas> {
+    if pixel_height <= 0.0 {
+        bail!("Text pixel height should be positive, got {pixel_height}");
+    }
+    let font = FontRef::try_from_slice(font_data)?;
+    let scale = PxScale::from(pixel_height);
+    let scaled_font = font.as_scaled(scale);
+    let outlines: Vec<(char, f32, Option<OutlinedGlyph>)> = (' '..='~')
+        .map(|character| {
+            let glyph_id = font.glyph_id(character);
+            let glyph = glyph_id.with_scale_and_position(scale, point(0.0, scaled_font.ascent()));
+            (
+                character,
+                scaled_font.h_advance(glyph_id),
+                font.outline_glyph(glyph),
+            )
+        })
+        .collect();
+    // Positions are computed first, to know the atlas height before drawing into it
+    let (mut cursor_x, mut cursor_y, mut row_height) = (0, 0, 0);
+    let mut placements = Vec::with_capacity(outlines.len());
+    for (_, _, outline) in &outlines {
+        let Some(outline) = outline else {
+            placements.push(None);
+            continue;
+        };
+        let bounds = outline.px_bounds();
+        let (width, height) = (bounds.width() as u32, bounds.height() as u32);
+        if cursor_x + width + GLYPH_PADDING > ATLAS_WIDTH {
+            (cursor_x, cursor_y, row_height) = (0, cursor_y + row_height + GLYPH_PADDING, 0);
+        }
+        placements.push(Some((cursor_x, cursor_y, width, height)));
+        cursor_x += width + GLYPH_PADDING;
+        row_height = row_height.max(height);
+    }
+    let atlas_height = (cursor_y + row_height).max(1);
+    let mut pixels = vec![0; (ATLAS_WIDTH * atlas_height) as usize];
+    let mut glyphs = HashMap::with_capacity(outlines.len());
+    for ((character, advance, outline), placement) in outlines.into_iter().zip(placements) {
+        let mut baked = BakedGlyph {
+            advance,
+            offset: [0.0; 2],
+            size: [0.0; 2],
+            uv_min: [0.0; 2],
+            uv_max: [0.0; 2],
+        };
+        if let (Some(outline), Some((x, y, width, height))) = (outline, placement) {
+            outline.draw(|glyph_x, glyph_y, coverage| {
+                let index = (y + glyph_y) * ATLAS_WIDTH + x + glyph_x;
+                pixels[index as usize] = (coverage.clamp(0.0, 1.0) * 255.0) as u8;
+            });
+            let bounds = outline.px_bounds();
+            baked.offset = [bounds.min.x, bounds.min.y];
+            baked.size = [width as f32, height as f32];
+            baked.uv_min = [
+                x as f32 / ATLAS_WIDTH as f32,
+                y as f32 / atlas_height as f32,
+            ];
+            baked.uv_max = [
+                (x + width) as f32 / ATLAS_WIDTH as f32,
+                (y + height) as f32 / atlas_height as f32,
+            ];
+        }
+        glyphs.insert(character, baked);
+    }
+    Ok(GlyphAtlas {
+        pixels,
+        height: atlas_height,
+        glyphs,
+        line_height: scaled_font.height() + scaled_font.line_gap(),
+    })
+}
+
+// Screen-space text, e.g. for a HUD without egui. The printable ASCII characters of a font are
+// baked once into an atlas; strings queued during the frame are drawn as one batch of textured
+// quads by render, then discarded.
+pub struct TextRenderer {
+    glyphs: HashMap<char, BakedGlyph>,
+    baked_height: f32,
+    line_height: f32,
+    vertices: Vec<TextVertex>,
+    bind_group_layout: wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup,
+    screen_size_buffer: wgpu::Buffer,
+    pipeline: wgpu::RenderPipeline,
+    sample_count: u32,
+    vertex_buffer: Option<wgpu::Buffer>,
+}
+
+impl TextRenderer {
+    // Glyphs are downscaled from this height, so it should be at least the largest drawn size
+    pub const DEFAULT_PIXEL_HEIGHT: f32 = 48.0;
+
+    pub fn new(context: &DrawContext, font_data: &[u8], pixel_height: f32) -> anyhow::Result<Self> {
+        let atlas = bake_atlas(font_data, pixel_height)?;
+        let device = &context.device;
+        let size = wgpu::Extent3d {
+            width: ATLAS_WIDTH,
+            height: atlas.height,
+            depth_or_array_layers: 1,
+        };
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Text Atlas Texture"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::R8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        context.queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            &atlas.pixels,
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(ATLAS_WIDTH),
+                rows_per_image: Some(atlas.height),
+            },
+            size,
+        );
+        let screen_size_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Text Screen Size Buffer"),
+            size: size_of::<[f32; 2]>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group_layout = Self::create_bind_group_layout(device);
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Text Atlas Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Text Bind Group"),
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: screen_size_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+        });
+        let sample_count = context.multisample_config.get_multisample_count();
+        Ok(Self {
+            glyphs: atlas.glyphs,
+            baked_height: pixel_height,
+            line_height: atlas.line_height,
+            vertices: Vec::new(),
+            pipeline: Self::create_pipeline(context, &bind_group_layout, sample_count),
+            bind_group_layout,
+            bind_group,
+            screen_size_buffer,
+            sample_count,
+            vertex_buffer: None,
+        })
+    }
+
+    // Monospace font embedded by egui
+    #[must_use]
+    pub fn with_default_font(context: &DrawContext) -> Self {
+        Self::new(
+            context,
+            epaint_default_fonts::HACK_REGULAR,
+            Self::DEFAULT_PIXEL_HEIGHT,
+        )
+        .expect("Embedded default font should be valid")
+    }
+
+    // Position of the top left corner in pixels, size being the line height in pixels. Characters
+    // outside of printable ASCII are drawn as '?', '\n' starts a new line.
+    pub fn queue_text(&mut self, text: &str, position: [f32; 2], size: f32, color: [f32; 4]) {
+        let scale = size / self.baked_height;
+        let mut pen = position;
+        for character in text.chars() {
+            if character == '\n' {
+                pen = [position[0], self.line_height.mul_add(scale, pen[1])];
+                continue;
+            }
+            let glyph = self.glyph(character);
+            if glyph.size[0] > 0.0 && glyph.size[1] > 0.0 {
+                let x0 = glyph.offset[0].mul_add(scale, pen[0]);
+                let y0 = glyph.offset[1].mul_add(scale, pen[1]);
+                let x1 = glyph.size[0].mul_add(scale, x0);
+                let y1 = glyph.size[1].mul_add(scale, y0);
+                let corner = |x: f32, y: f32, u: f32, v: f32| TextVertex {
+                    position: [x, y],
+                    uv: [u, v],
+                    color,
+                };
+                let (u0, v0) = (glyph.uv_min[0], glyph.uv_min[1]);
+                let (u1, v1) = (glyph.uv_max[0], glyph.uv_max[1]);
+                self.vertices.extend([
+                    corner(x0, y0, u0, v0),
+                    corner(x0, y1, u0, v1),
+                    corner(x1, y1, u1, v1),
+                    corner(x0, y0, u0, v0),
+                    corner(x1, y1, u1, v1),
+                    corner(x1, y0, u1, v0),
+                ]);
+            }
+            pen[0] = glyph.advance.mul_add(scale, pen[0]);
+        }
+    }
+
+    // Width in pixels of the longest line
+    #[must_use]
+    pub fn text_width(&self, text: &str, size: f32) -> f32 {
+        let scale = size / self.baked_height;
+        text.lines()
+            .map(|line| line.chars().map(|c| self.glyph(c).advance).sum::<f32>() * scale)
+            .fold(0.0, f32::max)
+    }
+
+    #[must_use]
+    pub fn queued_glyph_count(&self) -> usize {
+        self.vertices.len() / 6
+    }
+
+    pub fn clear(&mut self) {
+        self.vertices.clear();
+    }
+
+    // Draws the queued strings over what is already in the pass, then clears the queue. The
+    // vertex buffer is shared by the calls, so it should be called once per frame.
+    pub fn render(&mut self, context: &DrawContext, render_pass: &mut wgpu::RenderPass<'_>) {
+        let sample_count = context.multisample_config.get_multisample_count();
+        if sample_count != self.sample_count {
+            self.pipeline = Self::create_pipeline(context, &self.bind_group_layout, sample_count);
+            self.sample_count = sample_count;
+        }
+        if self.vertices.is_empty() {
+            return;
+        }
+        let contents: &[u8] = bytemuck::cast_slice(&self.vertices);
+        let vertex_buffer = match &mut self.vertex_buffer {
+            Some(buffer) if buffer.size() >= contents.len() as wgpu::BufferAddress => buffer,
+            vertex_buffer => {
+                vertex_buffer.insert(context.device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some("Text Vertex Buffer"),
+                    size: (contents.len() as wgpu::BufferAddress).next_power_of_two(),
+                    usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                }))
+            }
+        };
+        context.queue.write_buffer(vertex_buffer, 0, contents);
+        let dimensions = context.surface_dimensions();
+        #[allow(clippy::cast_precision_loss)]
+        let screen_size = [dimensions.width as f32, dimensions.height as f32];
+        context.queue.write_buffer(
+            &self.screen_size_buffer,
+            0,
+            bytemuck::cast_slice(&screen_size),
+        );
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, vertex_buffer.slice(..contents.len() as u64));
+        let vertex_count = u32::try_from(self.vertices.len()).expect("Value should fit in u32");
+        render_pass.draw(0..vertex_count, 0..1);
+        self.vertices.clear();
+    }
+
+    fn glyph(&self, character: char) -> BakedGlyph {
+        self.glyphs
+            .get(&character)
+            .or_else(|| self.glyphs.get(&FALLBACK_CHAR))
+            .copied()
+            .expect("The fallback character should be baked")
+    }
+
+    fn create_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Text Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        })
+    }
+
+    fn create_pipeline(
+        context: &DrawContext,
+        bind_group_layout: &wgpu::BindGroupLayout,
+        sample_count: u32,
+    ) -> wgpu::RenderPipeline {
+        let device = &context.device;
+        let shader_module = context.create_shader_module(TEXT_SHADER);
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Text Pipeline Layout"),
+            bind_group_layouts: &[bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Text Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader_module,
+                entry_point: None,
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: size_of::<TextVertex>() as wgpu::BufferAddress,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: &wgpu::vertex_attr_array![
+                        0 => Float32x2, 1 => Float32x2, 2 => Float32x4
+                    ],
+                }],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader_module,
+                entry_point: None,
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: context.surface_config.format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            // Drawn over the scene, whatever its depth
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: context.depth_format(),
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::Always,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                ..Default::default()
+            },
+            multiview: None,
+            cache: None,
+        })
+    }
+}
@@ -0,0 +1,390 @@
+/*
+MIT License
+
+Copyright (c) 2021, 2022, 2024, 2025 Vincent Hiribarren
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use anyhow::{Result, bail};
+use std::num::NonZeroU32;
+
+use crate::draw_context::{AsBindingResource, Dimensions, DrawContext, TextureBinding};
+
+const MIPMAP_SHADER: &str = include_str!("shaders/mipmap.wgsl");
+const DEPTH_RESOLVE_SHADER: &str = include_str!("shaders/depth_resolve.wgsl");
+
+// Sampled texture whose view spans all its mip levels
+pub struct Texture2D {
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+}
+
+impl Texture2D {
+    pub const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+    // Uploads RGBA8 pixels in mip 0, the other levels being downsampled from it if requested
+    pub fn new(
+        context: &DrawContext,
+        dimensions: Dimensions,
+        rgba: &[u8],
+        generate_mips: bool,
+    ) -> Result<Self> {
+        let Dimensions { width, height } = dimensions;
+        if width == 0 || height == 0 {
+            bail!("Texture dimensions should not be empty, got {width}x{height}");
+        }
+        let expected_len = 4 * width as usize * height as usize;
+        if rgba.len() != expected_len {
+            bail!(
+                "Expected {expected_len} bytes for a {width}x{height} RGBA8 texture, got {}",
+                rgba.len()
+            );
+        }
+        let mip_level_count = if generate_mips {
+            Self::full_mip_count(dimensions)
+        } else {
+            1
+        };
+        let size = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+        let texture = context.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Texture 2D"),
+            size,
+            mip_level_count,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: Self::FORMAT,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_DST
+                | wgpu::TextureUsages::COPY_SRC
+                | wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+        context.queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            rgba,
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * width),
+                rows_per_image: Some(height),
+            },
+            size,
+        );
+        if mip_level_count > 1 {
+            Self::generate_mips(context, &texture);
+        }
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        Ok(Self { texture, view })
+    }
+
+    // Down to a 1x1 level
+    #[must_use]
+    pub fn full_mip_count(dimensions: Dimensions) -> u32 {
+        u32::BITS - dimensions.width.max(dimensions.height).leading_zeros()
+    }
+
+    #[must_use]
+    pub fn texture(&self) -> &wgpu::Texture {
+        &self.texture
+    }
+
+    #[must_use]
+    pub fn mip_level_count(&self) -> u32 {
+        self.texture.mip_level_count()
+    }
+
+    // Each level is rendered from the previous one, the linear sampler averaging 2x2 texels
+    fn generate_mips(context: &DrawContext, texture: &wgpu::Texture) {
+        let device = &context.device;
+        let shader_module = context.create_shader_module(MIPMAP_SHADER);
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Mipmap Pipeline"),
+            layout: None,
+            vertex: wgpu::VertexState {
+                module: &shader_module,
+                entry_point: None,
+                buffers: &[],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader_module,
+                entry_point: None,
+                targets: &[Some(texture.format().into())],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Mipmap Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let level_views = (0..texture.mip_level_count())
+            .map(|level| {
+                texture.create_view(&wgpu::TextureViewDescriptor {
+                    label: Some("Mip Level View"),
+                    base_mip_level: level,
+                    mip_level_count: Some(1),
+                    ..Default::default()
+                })
+            })
+            .collect::<Vec<_>>();
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Mipmap Encoder"),
+        });
+        for pair in level_views.windows(2) {
+            let [source, target] = pair else {
+                unreachable!()
+            };
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Mipmap Bind Group"),
+                layout: &pipeline.get_bind_group_layout(0),
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(source),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&sampler),
+                    },
+                ],
+            });
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Mipmap Render Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: target,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            render_pass.set_pipeline(&pipeline);
+            render_pass.set_bind_group(0, &bind_group, &[]);
+            render_pass.draw(0..3, 0..1);
+        }
+        context.submit(Some(encoder.finish()));
+    }
+}
+
+impl AsBindingResource for Texture2D {
+    fn binding_resource(&self) -> wgpu::BindingResource<'_> {
+        wgpu::BindingResource::TextureView(&self.view)
+    }
+    fn binding_type(&self) -> wgpu::BindingType {
+        wgpu::BindingType::Texture {
+            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+            view_dimension: wgpu::TextureViewDimension::D2,
+            multisampled: false,
+        }
+    }
+}
+
+// Textures bound as an array at a single binding, for shaders indexing them at runtime
+pub struct TextureArrayBinding<'a> {
+    views: Vec<&'a wgpu::TextureView>,
+}
+
+impl<'a> TextureArrayBinding<'a> {
+    pub fn new(context: &DrawContext, textures: &[&'a Texture2D]) -> Result<Self> {
+        if !context
+            .device
+            .features()
+            .contains(wgpu::Features::TEXTURE_BINDING_ARRAY)
+        {
+            bail!("Binding arrays of textures require the TEXTURE_BINDING_ARRAY feature");
+        }
+        if textures.is_empty() {
+            bail!("A texture array binding should contain at least one texture");
+        }
+        Ok(Self {
+            views: textures.iter().map(|texture| &texture.view).collect(),
+        })
+    }
+}
+
+impl AsBindingResource for TextureArrayBinding<'_> {
+    fn binding_resource(&self) -> wgpu::BindingResource<'_> {
+        wgpu::BindingResource::TextureViewArray(&self.views)
+    }
+    fn binding_type(&self) -> wgpu::BindingType {
+        wgpu::BindingType::Texture {
+            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+            view_dimension: wgpu::TextureViewDimension::D2,
+            multisampled: false,
+        }
+    }
+    fn count(&self) -> Option<NonZeroU32> {
+        NonZeroU32::new(u32::try_from(self.views.len()).expect("Texture count should fit u32"))
+    }
+}
+
+// Single-sampled copy of the scene depth, so a later pass can sample it while the depth
+// attachment is in use
+pub(crate) struct SampledDepth {
+    texture: wgpu::Texture,
+    pipeline: wgpu::RenderPipeline,
+    bind_group: wgpu::BindGroup,
+}
+
+impl SampledDepth {
+    pub(crate) fn new(context: &DrawContext) -> Self {
+        let device = &context.device;
+        let source = &context.depth_texture;
+        let multisampled = source.sample_count() > 1;
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Sampled Depth Texture"),
+            size: source.size(),
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: source.format(),
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+        // Depth cannot be resolved nor always copied by the backends, so it is drawn instead
+        let shader = if multisampled {
+            DEPTH_RESOLVE_SHADER.to_owned()
+        } else {
+            DEPTH_RESOLVE_SHADER.replace("texture_multisampled_2d<f32>", "texture_2d<f32>")
+        };
+        let shader_module = context.create_shader_module(&shader);
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Depth Resolve Bind Group Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled,
+                },
+                count: None,
+            }],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Depth Resolve Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Depth Resolve Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader_module,
+                entry_point: None,
+                buffers: &[],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader_module,
+                entry_point: None,
+                targets: &[],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: source.format(),
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Always,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Depth Resolve Bind Group"),
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(&source.create_view(
+                    &wgpu::TextureViewDescriptor {
+                        aspect: wgpu::TextureAspect::DepthOnly,
+                        ..Default::default()
+                    },
+                )),
+            }],
+        });
+        Self {
+            texture,
+            pipeline,
+            bind_group,
+        }
+    }
+
+    // Bound as an unfilterable float texture, which depth formats allow, since the GL backend
+    // only supports depth sample types for comparison sampling
+    pub(crate) fn binding(&self) -> TextureBinding {
+        TextureBinding::new(
+            &self.texture,
+            wgpu::TextureSampleType::Float { filterable: false },
+        )
+    }
+
+    pub(crate) fn update(&self, context: &DrawContext) {
+        let mut encoder = context
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Depth Resolve Encoder"),
+            });
+        let target_view = self
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Depth Resolve Render Pass"),
+            color_attachments: &[],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &target_view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+        drop(render_pass);
+        context.submit(Some(encoder.finish()));
+    }
+}
@@ -23,68 +23,82 @@ SOFTWARE.
 */
 
 use crate::LaunchContext;
-use crate::draw_context::{self, Dimensions, DrawContext};
+use crate::draw_context::{self, Dimensions, DrawContext, DrawContextConfig};
+use crate::launcher::LaunchConfig;
 use crate::plugins::PluginRegistry;
-use crate::render_loop::{RenderLoopBuilder, RenderLoopHandler, TimeInfo};
+use crate::render_loop::{
+    self, ClickDetector, DoubleClickDetector, RenderLoopBuilder, RenderLoopHandler,
+    TARGET_DRAW_FPS, TimeInfo,
+};
 use log::{debug, info};
 use std::sync::Arc;
 use web_time::{Duration, Instant};
 use winit::application::ApplicationHandler;
+use winit::dpi::{PhysicalPosition, PhysicalSize};
 use winit::event::{DeviceEvent, ElementState, MouseButton, WindowEvent};
 use winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoop, EventLoopProxy};
 use winit::window::{CursorIcon, Window, WindowId};
 
-#[cfg(target_arch = "wasm32")]
-const WEBAPP_CANVAS_ID: &str = "target";
-
-const TARGET_DRAW_FPS: f64 = 60.0;
 const TARGET_FPS_DISPLAY_PERIOD: Duration = Duration::from_secs(1);
 
 struct MouseState {
     is_cursor_inside: bool,
-    mouse_rotation_enabled: bool,
+    click_detector: ClickDetector,
+    double_click_detector: DoubleClickDetector,
 }
 
 impl MouseState {
-    fn new() -> Self {
+    fn new(double_click_interval: Duration) -> Self {
         Self {
             is_cursor_inside: false,
-            mouse_rotation_enabled: false,
+            click_detector: ClickDetector::default(),
+            double_click_detector: DoubleClickDetector::new(
+                double_click_interval,
+                DoubleClickDetector::DEFAULT_DISTANCE,
+            ),
         }
     }
-    fn left_button_action(&mut self, action: ElementState, window: &Window) {
+    // Returns true when the release ends a click rather than a drag
+    fn left_button_action(&mut self, action: ElementState, window: &Window) -> bool {
         if !self.is_cursor_inside {
-            return;
+            return false;
         }
         match action {
             ElementState::Pressed => {
-                self.mouse_rotation_enabled = true;
-                // FIXME disabled due to winit error when resizing in web context: already borrowed: BorrowMutError on window.set_cursor
-                #[cfg(not(target_arch = "wasm32"))]
-                window.set_cursor_visible(false);
+                self.click_detector.press();
+                false
             }
             ElementState::Released => {
-                self.mouse_rotation_enabled = false;
                 // FIXME disabled due to winit error when resizing in web context: already borrowed: BorrowMutError on window.set_cursor
                 #[cfg(not(target_arch = "wasm32"))]
                 window.set_cursor_visible(true);
+                self.click_detector.release()
             }
         }
     }
 
+    // The rotation only starts once the pressed cursor moved beyond the drag threshold
+    fn motion_action(&mut self, delta: (f64, f64), window: &Window) {
+        if self.click_detector.motion(delta) {
+            // FIXME disabled due to winit error when resizing in web context: already borrowed: BorrowMutError on window.set_cursor
+            #[cfg(not(target_arch = "wasm32"))]
+            window.set_cursor_visible(false);
+        }
+    }
+
     fn resize_action(&mut self, window: &Window) {
-        self.mouse_rotation_enabled = false;
+        self.click_detector.cancel();
         // FIXME disabled due to winit error when resizing in web context: already borrowed: BorrowMutError on window.set_cursor
         #[cfg(not(target_arch = "wasm32"))]
         window.set_cursor_visible(true);
     }
 
     fn is_mouse_rotation_enabled(&self) -> bool {
-        self.mouse_rotation_enabled
+        self.click_detector.is_dragging()
     }
 
     fn move_action(&mut self) {
-        self.mouse_rotation_enabled = false;
+        self.click_detector.cancel();
     }
 }
 
@@ -98,24 +112,30 @@ struct App {
     draw_context: DrawContext,
     scenario: Box<dyn RenderLoopHandler>,
     plugin_registry: PluginRegistry,
+    has_exited: bool,
 }
 
 impl App {
     async fn async_new(
         window: Window,
         dimensions: Option<Dimensions>,
+        double_click_interval: Duration,
+        draw_context_config: DrawContextConfig,
         builder: Box<RenderLoopBuilder>,
     ) -> Self {
         let window = Arc::new(window);
-        let mouse_state = MouseState::new();
+        let mouse_state = MouseState::new(double_click_interval);
         let scenario_start = Instant::now();
         let last_draw_instant = scenario_start;
         let last_fps_instant = scenario_start;
         let draw_period_target = Duration::from_secs_f64(1.0 / TARGET_DRAW_FPS);
-        let mut draw_context =
-            draw_context::DrawContext::new(Some(Arc::clone(&window)), dimensions)
-                .await
-                .unwrap();
+        let mut draw_context = draw_context::DrawContext::new_with_config(
+            Some(Arc::clone(&window)),
+            dimensions,
+            draw_context_config,
+        )
+        .await
+        .unwrap();
 
         let mut plugin_registry = PluginRegistry::default();
         let mut scenario = builder(LaunchContext {
@@ -133,19 +153,161 @@ impl App {
             draw_context,
             scenario,
             plugin_registry,
+            has_exited: false,
+        }
+    }
+
+    fn measure_draw_delta(&mut self) -> Duration {
+        let draw_delta = self.last_draw_instant.elapsed();
+        self.last_draw_instant = Instant::now();
+        if self.last_fps_instant.elapsed() >= TARGET_FPS_DISPLAY_PERIOD {
+            info!("FPS: {}", (1.0 / draw_delta.as_secs_f64()).round());
+            self.last_fps_instant = self.last_draw_instant;
+        }
+        draw_delta
+    }
+
+    fn redraw(&mut self, event_loop: &ActiveEventLoop) {
+        if self.scenario.is_finished() {
+            info!("Scenario is finished, stopping");
+            self.exit(event_loop);
+            return;
+        }
+        let draw_delta = self.measure_draw_delta();
+        // The first frame also measures the initialization of the scenario
+        let is_first_frame = self.draw_context.frame_index() == 0;
+        self.draw_context.time_controller_mut().advance(draw_delta);
+        let plugin_registry = &mut self.plugin_registry;
+        let time_info = &TimeInfo::with_time_controller(
+            self.scenario_start,
+            draw_delta,
+            self.draw_context.time_controller(),
+        );
+        self.scenario
+            .on_update(plugin_registry, &mut self.draw_context, time_info);
+        for listener in plugin_registry.iter_mut() {
+            listener.on_update(&self.draw_context, time_info);
+        }
+        self.draw_context
+            .render_scene(|render_pass| {
+                let rpass = &mut render_pass.forget_lifetime();
+                self.scenario
+                    .on_render(plugin_registry, &self.draw_context, time_info, rpass);
+                for listener in plugin_registry.iter_mut() {
+                    listener.on_render(&self.draw_context, time_info, rpass);
+                }
+            })
+            .unwrap();
+        if !is_first_frame {
+            self.draw_context.frame_budget().check_redraw(
+                self.scenario.as_ref(),
+                draw_delta,
+                self.last_draw_instant.elapsed(),
+            );
+        }
+    }
+
+    // The window is asked for the constrained size, the platform being free to ignore it: the
+    // surface follows the size the window reports, the next Resized event if the request is applied
+    fn resize(&mut self, reported: Dimensions, constrained: Dimensions) {
+        let mut dimensions = reported;
+        if constrained != reported {
+            let Dimensions { width, height } = constrained;
+            if let Some(applied) = self
+                .window
+                .request_inner_size(PhysicalSize::new(width, height))
+            {
+                dimensions = applied.into();
+            }
+        }
+        self.draw_context
+            .resize(dimensions.width, dimensions.height);
+    }
+
+    fn left_button_action(&mut self, state: ElementState) {
+        if !self
+            .mouse_state
+            .left_button_action(state, self.window.as_ref())
+        {
+            return;
+        }
+        let Some(position) = self.draw_context.cursor_position() else {
+            return;
+        };
+        let is_double_click = self
+            .mouse_state
+            .double_click_detector
+            .click(Instant::now(), position);
+        self.dispatch_click(position);
+        if is_double_click {
+            self.dispatch_double_click(position);
+        }
+    }
+
+    fn dispatch_click(&mut self, position: PhysicalPosition<f64>) {
+        for listener in self.plugin_registry.iter_mut_rev() {
+            if listener.on_click(&self.draw_context, position).processed {
+                return;
+            }
+        }
+        self.scenario.on_click(&self.draw_context, position);
+    }
+
+    fn dispatch_double_click(&mut self, position: PhysicalPosition<f64>) {
+        for listener in self.plugin_registry.iter_mut_rev() {
+            if listener
+                .on_double_click(&self.draw_context, position)
+                .processed
+            {
+                return;
+            }
+        }
+        self.scenario.on_double_click(&self.draw_context, position);
+    }
+
+    // Without continuous redraws, the scene only changes in response to events
+    fn redraw_on_event(&self) {
+        if !self.scenario.needs_continuous_redraw() {
+            self.window.as_ref().request_redraw();
         }
     }
+
+    // Windows opened by plugins only concern the plugins
+    fn dispatch_secondary_window_event(&mut self, id: WindowId, event: &WindowEvent) {
+        for listener in self.plugin_registry.iter_mut_rev() {
+            let event_response = listener.on_secondary_window_event(&self.draw_context, id, event);
+            if event_response.processed {
+                return;
+            }
+        }
+    }
+
+    // The event loop can still dispatch events after an exit request, so the scenario is only notified once
+    fn exit(&mut self, event_loop: &ActiveEventLoop) {
+        if !self.has_exited {
+            self.has_exited = true;
+            self.scenario.on_exit(&mut self.draw_context);
+        }
+        event_loop.exit();
+    }
 }
 
 struct AppHandlerState {
+    #[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+    launch_config: LaunchConfig,
     builder: Option<Box<RenderLoopBuilder>>,
     state: Option<App>,
     event_loop_proxy: Option<EventLoopProxy<App>>,
 }
 
 impl AppHandlerState {
-    fn new(event_loop: &EventLoop<App>, builder: Box<RenderLoopBuilder>) -> Self {
+    fn new(
+        event_loop: &EventLoop<App>,
+        launch_config: LaunchConfig,
+        builder: Box<RenderLoopBuilder>,
+    ) -> Self {
         Self {
+            launch_config,
             builder: Some(builder),
             state: None,
             event_loop_proxy: Some(event_loop.create_proxy()),
@@ -165,23 +327,39 @@ impl ApplicationHandler<App> for AppHandlerState {
         #[cfg(target_arch = "wasm32")]
         {
             use wasm_bindgen::JsCast;
-            use winit::dpi::PhysicalSize;
             use winit::platform::web::WindowAttributesExtWebSys;
             let dom_window = web_sys::window().unwrap();
             let dom_document = dom_window.document().unwrap();
-            let dom_canvas = dom_document.get_element_by_id(WEBAPP_CANVAS_ID).unwrap();
+            let canvas_id = &self.launch_config.canvas_id;
+            let dom_canvas = dom_document
+                .get_element_by_id(canvas_id)
+                .unwrap_or_else(|| panic!("No canvas element with id \"{canvas_id}\" in the page"));
             let canvas = dom_canvas.dyn_into::<web_sys::HtmlCanvasElement>().unwrap();
             let width = dom_window.inner_width().unwrap().as_f64().unwrap() as u32;
             let height = dom_window.inner_height().unwrap().as_f64().unwrap() as u32;
-            dimensions.replace(Dimensions { width, height });
+            dimensions.replace(Dimensions::new(width, height));
             // FIXME winit window has size of 0 at startup, so also passing dimensions to draw context
             window_attributes = window_attributes
                 .with_canvas(Some(canvas))
                 .with_inner_size(PhysicalSize::new(width, height));
         }
+        if let Some(Dimensions { width, height }) = self.launch_config.min_inner_size {
+            window_attributes =
+                window_attributes.with_min_inner_size(PhysicalSize::new(width, height));
+        }
+        if let Some(Dimensions { width, height }) = self.launch_config.max_inner_size {
+            window_attributes =
+                window_attributes.with_max_inner_size(PhysicalSize::new(width, height));
+        }
         let window = event_loop.create_window(window_attributes).unwrap();
         window.set_cursor(CursorIcon::Grab);
-        let app_future = App::async_new(window, dimensions, self.builder.take().unwrap());
+        let app_future = App::async_new(
+            window,
+            dimensions,
+            self.launch_config.double_click_interval,
+            self.launch_config.draw_context_config,
+            self.builder.take().unwrap(),
+        );
         let event_loop_proxy = self.event_loop_proxy.take().unwrap();
         #[cfg(target_arch = "wasm32")]
         {
@@ -202,10 +380,24 @@ impl ApplicationHandler<App> for AppHandlerState {
         self.state = Some(event);
     }
 
-    fn window_event(&mut self, event_loop: &ActiveEventLoop, _id: WindowId, event: WindowEvent) {
+    fn window_event(&mut self, event_loop: &ActiveEventLoop, id: WindowId, event: WindowEvent) {
         let Some(ref mut app) = self.state else {
             return;
         };
+        if id != app.window.id() {
+            app.dispatch_secondary_window_event(id, &event);
+            return;
+        }
+        if event != WindowEvent::RedrawRequested {
+            app.redraw_on_event();
+        }
+        // Tracked before the listeners, which could consume the event
+        if let Some(position) = app.draw_context.track_cursor_event(&event) {
+            for listener in app.plugin_registry.iter_mut_rev() {
+                listener.on_cursor_moved(position);
+            }
+            app.scenario.on_cursor_moved(position);
+        }
         // TODO Refactor
         for listener in app.plugin_registry.iter_mut_rev() {
             let event_response = listener.on_window_event(&event);
@@ -220,13 +412,20 @@ impl ApplicationHandler<App> for AppHandlerState {
         match event {
             WindowEvent::CloseRequested => {
                 debug!("Closing app");
-                event_loop.exit();
+                app.exit(event_loop);
             }
             WindowEvent::Resized(physical_size) => {
                 debug!("Window is resizing");
                 app.mouse_state.resize_action(&app.window);
-                app.draw_context
-                    .resize(physical_size.width, physical_size.height);
+                app.resize(
+                    physical_size.into(),
+                    self.launch_config
+                        .constrain_inner_size(physical_size.into()),
+                );
+            }
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                debug!("Scale factor changed to {scale_factor}");
+                app.draw_context.set_scale_factor(scale_factor);
             }
             WindowEvent::KeyboardInput { ref event, .. } => {
                 debug!("Key pressed {:?}", event.physical_key);
@@ -245,52 +444,15 @@ impl ApplicationHandler<App> for AppHandlerState {
             WindowEvent::CursorLeft { .. } => {
                 app.mouse_state.is_cursor_inside = false;
             }
-            WindowEvent::MouseInput { state, button, .. } => {
+            WindowEvent::MouseInput {
+                state,
+                button: MouseButton::Left,
+                ..
+            } => {
                 // Works with WASM and browser canvas
-                if button == MouseButton::Left {
-                    app.mouse_state
-                        .left_button_action(state, app.window.as_ref());
-                }
-            }
-            WindowEvent::RedrawRequested => {
-                if app.scenario.is_finished() {
-                    info!("Scenario is finished, stopping");
-                    event_loop.exit();
-                    return;
-                }
-                let draw_delta = app.last_draw_instant.elapsed();
-                app.last_draw_instant = Instant::now();
-                if app.last_fps_instant.elapsed() >= TARGET_FPS_DISPLAY_PERIOD {
-                    info!("FPS: {}", (1.0 / draw_delta.as_secs_f64()).round());
-                    app.last_fps_instant = app.last_draw_instant;
-                }
-
-                let plugin_registry = &mut app.plugin_registry;
-                let time_info = &TimeInfo {
-                    init_start: app.scenario_start,
-                    processing_delta: draw_delta,
-                    _private: (),
-                };
-                app.scenario
-                    .on_update(plugin_registry, &mut app.draw_context, time_info);
-                for listener in plugin_registry.iter_mut() {
-                    listener.on_update(&app.draw_context, time_info);
-                }
-                app.draw_context
-                    .render_scene(|render_pass| {
-                        let rpass = &mut render_pass.forget_lifetime();
-                        app.scenario.on_render(
-                            plugin_registry,
-                            &app.draw_context,
-                            time_info,
-                            rpass,
-                        );
-                        for listener in plugin_registry.iter_mut() {
-                            listener.on_render(&app.draw_context, time_info, rpass);
-                        }
-                    })
-                    .unwrap();
+                app.left_button_action(state);
             }
+            WindowEvent::RedrawRequested => app.redraw(event_loop),
             _ => {}
         }
     }
@@ -307,11 +469,14 @@ impl ApplicationHandler<App> for AppHandlerState {
         if let DeviceEvent::Button { button, state } = event {
             // Works with MacOS
             if button == 0 {
-                app.mouse_state
-                    .left_button_action(state, app.window.as_ref());
+                app.left_button_action(state);
             }
         }
+        if let DeviceEvent::MouseMotion { delta } = event {
+            app.mouse_state.motion_action(delta, app.window.as_ref());
+        }
         if app.mouse_state.is_mouse_rotation_enabled() {
+            app.redraw_on_event();
             for listener in app.plugin_registry.iter_mut_rev() {
                 let event_state = listener.on_mouse_event(&event);
                 if event_state.processed {
@@ -326,25 +491,26 @@ impl ApplicationHandler<App> for AppHandlerState {
         let Some(ref mut app) = self.state else {
             return;
         };
-        let since_last_draw = app.last_draw_instant.elapsed();
-        if since_last_draw >= app.draw_period_target {
+        for listener in app.plugin_registry.iter_mut() {
+            listener.on_about_to_wait(&app.draw_context, event_loop);
+        }
+        let schedule = render_loop::schedule_redraw(
+            app.scenario.as_ref(),
+            app.last_draw_instant.elapsed(),
+            app.draw_period_target,
+        );
+        // A handler may have asked for a frame while the loop waits for events
+        let redraw_requested = app.draw_context.redraw_handle().take_redraw_request();
+        if schedule.request_redraw || redraw_requested {
             app.window.as_ref().request_redraw();
-            event_loop.set_control_flow(ControlFlow::Poll);
-        } else {
-            event_loop.set_control_flow(ControlFlow::WaitUntil(
-                Instant::now()
-                    + app
-                        .draw_period_target
-                        .checked_sub(since_last_draw)
-                        .expect("Substraction of a Duration from an Instant should not underflow"),
-            ));
         }
+        event_loop.set_control_flow(schedule.control_flow);
     }
 }
 
-pub(crate) fn init_event_loop(builder: Box<RenderLoopBuilder>) {
+pub(crate) fn init_event_loop(launch_config: LaunchConfig, builder: Box<RenderLoopBuilder>) {
     let event_loop = EventLoop::with_user_event().build().unwrap();
     event_loop.set_control_flow(ControlFlow::Poll);
-    let app_handler_state = &mut AppHandlerState::new(&event_loop, builder);
+    let app_handler_state = &mut AppHandlerState::new(&event_loop, launch_config, builder);
     event_loop.run_app(app_handler_state).unwrap();
 }
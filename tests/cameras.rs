@@ -0,0 +1,308 @@
+use cgmath::{InnerSpace, MetricSpace, Point3};
+use pollster::FutureExt;
+use wgpu_igniter::cameras::{
+    Camera, CameraView, FreeFlightView, InteractiveCamera, OrthogonalCameraConfig,
+    PerspectiveCameraConfig,
+};
+use wgpu_igniter::plugins::scene_3d::Scene3D;
+use wgpu_igniter::primitives::Shareable;
+use wgpu_igniter::primitives::cube::{self, CubeOptions};
+use wgpu_igniter::{Dimensions, DrawContext};
+use winit::dpi::PhysicalPosition;
+use winit::event::{DeviceEvent, MouseScrollDelta};
+
+const DEFAULT_SHADER: &str = include_str!("../src/shaders/default.wgsl");
+
+const VIEWPORT: Dimensions = Dimensions {
+    width: 800,
+    height: 600,
+};
+
+fn centered_camera() -> Camera {
+    let mut camera = Camera::default();
+    camera.resize_screen(VIEWPORT);
+    camera
+}
+
+fn assert_close(actual: (f32, f32), expected: (f32, f32)) {
+    assert!(
+        (actual.0 - expected.0).abs() < 1e-3 && (actual.1 - expected.1).abs() < 1e-3,
+        "{actual:?} != {expected:?}"
+    );
+}
+
+#[test]
+fn world_to_screen_projects_scene_center_to_screen_center() {
+    let camera = centered_camera();
+    let screen = camera
+        .world_to_screen(Point3::new(0.0, 0.0, 0.0), VIEWPORT)
+        .unwrap();
+    assert_close(screen, (400.0, 300.0));
+}
+
+#[test]
+fn world_to_screen_follows_screen_axes() {
+    let camera = centered_camera();
+    let (x, y) = camera
+        .world_to_screen(Point3::new(1.0, 1.0, 0.0), VIEWPORT)
+        .unwrap();
+    // Right of the center, and up means a smaller row index
+    assert!(x > 400.0);
+    assert!(y < 300.0);
+    // Farther points along the same line of sight project closer to the center
+    let (far_x, far_y) = camera
+        .world_to_screen(Point3::new(1.0, 1.0, 10.0), VIEWPORT)
+        .unwrap();
+    assert!(far_x > 400.0 && far_x < x);
+    assert!(far_y < 300.0 && far_y > y);
+}
+
+#[test]
+fn world_to_screen_ignores_points_behind_the_camera() {
+    let camera = centered_camera();
+    assert!(
+        camera
+            .world_to_screen(Point3::new(0.0, 0.0, -20.0), VIEWPORT)
+            .is_none()
+    );
+}
+
+#[test]
+fn set_far_updates_camera_matrix() {
+    let mut camera = centered_camera();
+    let matrix = camera.get_camera_matrix();
+    camera.set_far(50.0);
+    assert!((camera.far() - 50.0).abs() < f32::EPSILON);
+    assert_ne!(camera.get_camera_matrix(), matrix);
+    // A point beyond the new far plane is now clipped
+    let beyond = camera.get_camera_matrix() * cgmath::Vector4::new(0.0, 0.0, 45.0, 1.0);
+    assert!(beyond.z / beyond.w > 1.0);
+}
+
+#[test]
+fn fit_near_far_encloses_the_bounding_sphere() {
+    let mut camera = centered_camera();
+    camera.fit_near_far(Point3::new(0.0, 0.0, 0.0), 2.0, 0.1);
+    // The eye is 10 units away from the scene center
+    assert!((camera.near() - 8.0).abs() < 1e-4);
+    assert!((camera.far() - 12.0).abs() < 1e-4);
+    camera.fit_near_far(Point3::new(0.0, 0.0, 0.0), 20.0, 0.1);
+    assert!((camera.near() - 0.1).abs() < 1e-4);
+}
+
+#[test]
+fn invalid_near_far_planes_are_clamped() {
+    let is_finite = |camera: &Camera| {
+        let matrix: [[f32; 4]; 4] = camera.get_camera_matrix().into();
+        matrix.as_flattened().iter().all(|value| value.is_finite())
+    };
+    let mut camera = centered_camera();
+    camera.set_near(0.0);
+    assert!(camera.near() > 0.0);
+    assert!(is_finite(&camera));
+    camera.set_near(2.0);
+    camera.set_far(1.0);
+    assert!(camera.far() > camera.near());
+    assert!(is_finite(&camera));
+    camera.set_far(2.0);
+    assert!(camera.far() > camera.near());
+    camera.set_near(5_000.0);
+    assert!(camera.far() > camera.near());
+    // The eye is inside the bounding sphere
+    camera.fit_near_far(Point3::new(0.0, 0.0, 0.0), 20.0, 0.0);
+    assert!(camera.near() > 0.0);
+    assert!(camera.far() > camera.near());
+    assert!(is_finite(&camera));
+}
+
+fn ortho_camera(world_height: f32) -> Camera {
+    Camera::new(
+        CameraView::default(),
+        Box::new(OrthogonalCameraConfig {
+            width: world_height,
+            height: world_height,
+            ..OrthogonalCameraConfig::default()
+        }),
+    )
+}
+
+#[test]
+fn ortho_resize_keeps_world_height() {
+    let mut camera = ortho_camera(10.0);
+    let top = Point3::new(0.0, 5.0, 0.0);
+    for viewport in [
+        VIEWPORT,
+        Dimensions {
+            width: 400,
+            height: 1200,
+        },
+    ] {
+        camera.resize_screen(viewport);
+        let screen = camera.world_to_screen(top, viewport).unwrap();
+        assert_close(screen, (viewport.width as f32 / 2.0, 0.0));
+    }
+}
+
+#[test]
+fn ortho_zoom_shrinks_visible_extent() {
+    let mut camera = ortho_camera(10.0);
+    camera.resize_screen(VIEWPORT);
+    camera.set_zoom(2.0);
+    assert!((camera.zoom() - 2.0).abs() < f32::EPSILON);
+    let screen = camera
+        .world_to_screen(Point3::new(0.0, 2.5, 0.0), VIEWPORT)
+        .unwrap();
+    assert_close(screen, (400.0, 0.0));
+}
+
+#[test]
+fn looking_at_keeps_screen_axes_from_behind() {
+    // Looking toward -z, the world +x is now on the left of the screen
+    let mut camera =
+        Camera::new_looking_at(Point3::new(0.0, 0.0, 10.0), Point3::new(0.0, 0.0, 0.0));
+    camera.resize_screen(VIEWPORT);
+    let (x, y) = camera
+        .world_to_screen(Point3::new(1.0, 1.0, 0.0), VIEWPORT)
+        .unwrap();
+    assert!(x < 400.0);
+    assert!(y < 300.0);
+}
+
+// Color at the center of the screen, and at a corner where only the background is expected
+fn render_origin_cube(eye: Point3<f32>) -> ([u8; 4], [u8; 4]) {
+    let context = DrawContext::new(None, None)
+        .block_on()
+        .expect("A headless draw context should be available");
+    let dimensions = context.surface_dimensions();
+    let mut camera = Camera::new_looking_at(eye, Point3::new(0.0, 0.0, 0.0));
+    camera.resize_screen(dimensions);
+    let shader_module = context.create_shader_module(DEFAULT_SHADER);
+    let mut scene = Scene3D::new(&context);
+    let cube = cube::create_cube_with_colors(
+        &context,
+        &shader_module,
+        &shader_module,
+        scene.scene_uniforms(),
+        &CubeOptions::default(),
+    );
+    scene.add(cube.into_shareable());
+    scene.update(&camera);
+    context
+        .render_scene(|mut render_pass| scene.render(&mut render_pass))
+        .unwrap();
+    let pixels = context.read_target_pixels().unwrap();
+    let center = ((dimensions.height / 2 * dimensions.width + dimensions.width / 2) * 4) as usize;
+    (
+        pixels[center..center + 4].try_into().unwrap(),
+        pixels[0..4].try_into().unwrap(),
+    )
+}
+
+#[test]
+fn object_at_origin_is_visible_from_any_side() {
+    let eyes = [
+        Point3::new(0.0, 0.0, -5.0),
+        Point3::new(0.0, 0.0, 5.0),
+        Point3::new(4.0, 3.0, 2.0),
+        Point3::new(0.0, 6.0, 0.0),
+        Point3::new(0.0, -6.0, 0.0),
+    ];
+    for eye in eyes {
+        let (center, background) = render_origin_cube(eye);
+        assert_ne!(center, background, "Cube not visible from {eye:?}");
+    }
+}
+
+#[test]
+fn free_flight_basis_stays_orthonormal_after_many_rotations() {
+    let mut view =
+        FreeFlightView::looking_at(Point3::new(1.0, 2.0, -5.0), Point3::new(0.0, 0.0, 0.0));
+    for step in 0..10_000 {
+        let angle = 0.001 * (step % 7) as f32;
+        view.pan(angle);
+        view.tilt(-angle * 0.5);
+        view.roll(angle * 0.3);
+    }
+    let (forward, up, right) = (view.forward(), view.up(), view.right());
+    for axis in [forward, up, right] {
+        assert!((axis.magnitude() - 1.0).abs() < 1e-4, "{axis:?}");
+    }
+    assert!(forward.dot(up).abs() < 1e-4);
+    assert!(forward.dot(right).abs() < 1e-4);
+    assert!(up.dot(right).abs() < 1e-4);
+}
+
+#[test]
+fn free_flight_camera_matches_look_at_camera() {
+    let eye = Point3::new(3.0, 1.0, -4.0);
+    let target = Point3::new(0.0, 0.5, 0.0);
+    let mut look_at = Camera::new_looking_at(eye, target);
+    let mut free_flight = Camera::new_free_flight(
+        FreeFlightView::looking_at(eye, target),
+        Box::new(PerspectiveCameraConfig::default()),
+    );
+    for camera in [&mut look_at, &mut free_flight] {
+        camera.resize_screen(VIEWPORT);
+        camera.pan(0.2);
+        camera.roll(0.1);
+        camera.move_z(1.5);
+    }
+    let point = Point3::new(0.5, 0.5, 0.5);
+    assert_close(
+        free_flight.world_to_screen(point, VIEWPORT).unwrap(),
+        look_at.world_to_screen(point, VIEWPORT).unwrap(),
+    );
+}
+
+#[test]
+fn mouse_wheel_moves_the_interactive_camera_forward() {
+    let eye = Point3::new(0.0, 0.0, 10.0);
+    let mut camera =
+        InteractiveCamera::new(Camera::new_looking_at(eye, Point3::new(0.0, 0.0, 0.0)));
+    camera.set_zoom_speed(2.0);
+    camera.mouse_event_listener(&DeviceEvent::MouseWheel {
+        delta: MouseScrollDelta::LineDelta(0.0, 1.0),
+    });
+    let after_line = camera.controled_camera.eye_position();
+    assert!((after_line - Point3::new(0.0, 0.0, 8.0)).magnitude() < 1e-5);
+    // A trackpad scrolling the height of a line moves as far as a wheel notch
+    camera.mouse_event_listener(&DeviceEvent::MouseWheel {
+        delta: MouseScrollDelta::PixelDelta(PhysicalPosition::new(0.0, -40.0)),
+    });
+    assert!((camera.controled_camera.eye_position() - eye).magnitude() < 1e-5);
+}
+
+#[test]
+fn dolly_zoom_keeps_the_subject_size() {
+    let subject = Point3::new(0.0, 0.0, 0.0);
+    let mut camera = Camera::new_looking_at(Point3::new(0.0, 0.0, 6.0), subject);
+    camera.resize_screen(VIEWPORT);
+    let projected_height = |camera: &Camera| {
+        let (_, top) = camera
+            .world_to_screen(Point3::new(0.0, 0.5, 0.0), VIEWPORT)
+            .unwrap();
+        let (_, bottom) = camera
+            .world_to_screen(Point3::new(0.0, -0.5, 0.0), VIEWPORT)
+            .unwrap();
+        bottom - top
+    };
+    let height = projected_height(&camera);
+    for fovy in [0.2, 0.5, 1.2, 2.0] {
+        let distance = camera.eye_position().distance(subject);
+        camera.dolly_zoom(fovy, distance);
+        assert!((camera.fovy().unwrap() - fovy).abs() < 1e-6);
+        assert!(
+            (projected_height(&camera) - height).abs() < 0.01,
+            "{fovy} {}",
+            projected_height(&camera)
+        );
+    }
+    // Wider field of view, closer eye
+    assert!(camera.eye_position().distance(subject) < 6.0);
+
+    let mut ortho_camera = ortho_camera(4.0);
+    let eye = ortho_camera.eye_position();
+    ortho_camera.dolly_zoom(1.0, 5.0);
+    assert_eq!(ortho_camera.fovy(), None);
+    assert_eq!(ortho_camera.eye_position(), eye);
+}
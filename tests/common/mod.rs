@@ -0,0 +1,56 @@
+// Fixtures shared by the test binaries, each of them only using some
+#![allow(dead_code)]
+
+use pollster::FutureExt;
+use std::sync::{Mutex, Once};
+use wgpu_igniter::DrawContext;
+
+// Context rendering into a texture target, without window
+pub fn create_headless_context() -> DrawContext {
+    DrawContext::new(None, None)
+        .block_on()
+        .expect("A headless draw context should be available")
+}
+
+struct CapturingLogger {
+    warnings: Mutex<Vec<String>>,
+}
+
+impl log::Log for CapturingLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::Level::Warn
+    }
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            self.warnings
+                .lock()
+                .unwrap()
+                .push(record.args().to_string());
+        }
+    }
+    fn flush(&self) {}
+}
+
+static LOGGER: CapturingLogger = CapturingLogger {
+    warnings: Mutex::new(Vec::new()),
+};
+
+static LOGGER_INIT: Once = Once::new();
+
+// Must be the first logger installed by the test binary
+pub fn install_warning_capture() {
+    LOGGER_INIT.call_once(|| {
+        log::set_logger(&LOGGER).unwrap();
+        log::set_max_level(log::LevelFilter::Warn);
+    });
+}
+
+// The tests run concurrently, so each one only takes the warnings it is looking for
+pub fn take_warnings_containing(pattern: &str) -> Vec<String> {
+    let mut warnings = LOGGER.warnings.lock().unwrap();
+    let (taken, kept) = warnings
+        .drain(..)
+        .partition(|warning| warning.contains(pattern));
+    *warnings = kept;
+    taken
+}
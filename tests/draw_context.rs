@@ -0,0 +1,1199 @@
+use pollster::FutureExt;
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+use wgpu_igniter::support::background::Background;
+use wgpu_igniter::support::render_texture::RenderTexture;
+use wgpu_igniter::wgpu;
+use wgpu_igniter::{
+    BindingSlot, Dimensions, DrawContext, DrawContextConfig, DrawModeParams, Drawable,
+    DrawableBuilder, FrameUniform, IndexData, MultiSampleConfig, SamplerConfig, StorageBuffer,
+    Uniform, Viewport, query_surface_capabilities,
+};
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::event::{DeviceId, WindowEvent};
+
+mod common;
+
+use common::create_headless_context;
+
+#[test]
+fn dimensions_tuple_round_trip() {
+    let dimensions = Dimensions::from((800, 600));
+    assert_eq!(dimensions, Dimensions::new(800, 600));
+    let (width, height) = dimensions.into();
+    assert_eq!((width, height), (800, 600));
+}
+
+#[test]
+fn dimensions_from_physical_size() {
+    let dimensions: Dimensions = PhysicalSize::new(1920, 1080).into();
+    assert_eq!(dimensions.width, 1920);
+    assert_eq!(dimensions.height, 1080);
+    assert_eq!(<(u32, u32)>::from(dimensions), (1920, 1080));
+}
+
+#[test]
+fn index_data_auto_selects_u16() {
+    let index_data = IndexData::from_u32_auto(&[0, 1, 2, 65_535]);
+    assert_eq!(index_data.format(), wgpu::IndexFormat::Uint16);
+    assert_eq!(index_data.size(), 4);
+    assert_eq!(
+        index_data.data(),
+        bytemuck::cast_slice::<u16, u8>(&[0, 1, 2, 65_535])
+    );
+}
+
+#[test]
+fn index_data_auto_selects_u32() {
+    let index_data = IndexData::from_u32_auto(&[0, 1, 65_536]);
+    assert_eq!(index_data.format(), wgpu::IndexFormat::Uint32);
+    assert_eq!(index_data.size(), 3);
+    assert_eq!(
+        index_data.data(),
+        bytemuck::cast_slice::<u32, u8>(&[0, 1, 65_536])
+    );
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "does not fit in u16")]
+fn index_data_u16_overflow_is_detected() {
+    let _ = IndexData::u16_from_u32(&[0, 70_000]);
+}
+
+const INSTANCED_SHADER: &str = r"
+@vertex
+fn vtx_main(@builtin(vertex_index) vertex: u32, @builtin(instance_index) instance: u32) -> @builtin(position) vec4<f32> {
+    let x = f32(vertex) - 1.0 + f32(instance) * 0.01;
+    return vec4<f32>(x, f32(vertex % 2u), 0.5, 1.0);
+}
+
+@fragment
+fn frg_main() -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0, 0.0, 0.0, 1.0);
+}
+";
+
+fn create_instanced_drawable(context: &DrawContext, instance_count: u32) -> Drawable {
+    let shader_module = context.create_shader_module(INSTANCED_SHADER);
+    let mut drawable_builder = DrawableBuilder::new(
+        context,
+        &shader_module,
+        &shader_module,
+        DrawModeParams::Direct { vertex_count: 3 },
+    );
+    drawable_builder.set_instance_count(instance_count);
+    drawable_builder.build()
+}
+
+const INSTANCE_SLOTS_SHADER: &str = r"
+const SLOT_COUNT: f32 = 10.0;
+
+@vertex
+fn vtx_main(@builtin(vertex_index) vertex: u32, @builtin(instance_index) instance: u32) -> @builtin(position) vec4<f32> {
+    // Two triangles covering the column of the instance
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(0.0, 0.0), vec2<f32>(1.0, 0.0), vec2<f32>(1.0, 1.0),
+        vec2<f32>(0.0, 0.0), vec2<f32>(1.0, 1.0), vec2<f32>(0.0, 1.0),
+    );
+    let corner = corners[vertex];
+    let x = (f32(instance) + corner.x) / SLOT_COUNT * 2.0 - 1.0;
+    return vec4<f32>(x, corner.y * 2.0 - 1.0, 0.5, 1.0);
+}
+
+@fragment
+fn frg_main() -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0, 0.0, 0.0, 1.0);
+}
+";
+
+const INSTANCE_SLOT_COUNT: u32 = 10;
+
+// Slots of the instance columns covered by the drawn instances
+fn drawn_instance_slots(context: &DrawContext, drawable: &Drawable, range: Range<u32>) -> Vec<u32> {
+    context
+        .render_scene(|mut render_pass| {
+            drawable.render_instances(&mut render_pass, range).unwrap();
+        })
+        .unwrap();
+    let pixels = context.read_target_pixels().unwrap();
+    let Dimensions { width, height } = context.surface_dimensions();
+    (0..INSTANCE_SLOT_COUNT)
+        .filter(|slot| {
+            let x = (2 * slot + 1) * width / (2 * INSTANCE_SLOT_COUNT);
+            let offset = ((height / 2 * width + x) * 4) as usize;
+            pixels[offset] == 255
+        })
+        .collect()
+}
+
+#[test]
+fn render_instances_validates_range() {
+    let mut context = create_headless_context();
+    context.set_clear_color(Some(wgpu::Color::BLACK));
+    let shader_module = context.create_shader_module(INSTANCE_SLOTS_SHADER);
+    let mut drawable_builder = DrawableBuilder::new(
+        &context,
+        &shader_module,
+        &shader_module,
+        DrawModeParams::Direct { vertex_count: 6 },
+    );
+    drawable_builder.set_instance_count(INSTANCE_SLOT_COUNT);
+    let drawable = drawable_builder.build();
+    assert!(drawn_instance_slots(&context, &drawable, 0..0).is_empty());
+    assert_eq!(
+        drawn_instance_slots(&context, &drawable, 0..4),
+        [0, 1, 2, 3]
+    );
+    assert_eq!(drawn_instance_slots(&context, &drawable, 3..5), [3, 4]);
+    assert_eq!(
+        drawn_instance_slots(&context, &drawable, 0..INSTANCE_SLOT_COUNT),
+        (0..INSTANCE_SLOT_COUNT).collect::<Vec<_>>()
+    );
+    context
+        .render_scene(|mut render_pass| {
+            assert!(drawable.render_instances(&mut render_pass, 0..11).is_err());
+        })
+        .unwrap();
+}
+
+#[test]
+fn read_target_pixels_with_multisampling() {
+    let context = create_headless_context();
+    assert!(context.multisample_config.is_multisample_enabled());
+    let drawable = create_instanced_drawable(&context, 1);
+    context
+        .render_scene(|mut render_pass| drawable.render(&mut render_pass))
+        .unwrap();
+    let pixels = context.read_target_pixels().unwrap();
+    let dimensions = context.surface_dimensions();
+    assert_eq!(
+        pixels.len(),
+        (dimensions.width * dimensions.height * 4) as usize
+    );
+}
+
+const FRAME_COLOR_SHADER: &str = r"
+@group(0) @binding(0)
+var<uniform> color: vec4<f32>;
+
+@vertex
+fn vtx_main(@builtin(vertex_index) vertex: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32((vertex << 1u) & 2u), f32(vertex & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.5, 1.0);
+}
+
+@fragment
+fn frg_main() -> @location(0) vec4<f32> {
+    return color;
+}
+";
+
+#[test]
+fn staging_batch_coalesces_uniform_writes() {
+    let mut context = create_headless_context();
+    context.set_staging_batch_enabled(true);
+    let mut colors: Vec<_> = (0..50)
+        .map(|_| Uniform::new(&context, [0.0f32, 0.0, 0.0, 1.0]))
+        .collect();
+    let shader_module = context.create_shader_module(FRAME_COLOR_SHADER);
+    let mut drawable_builder = DrawableBuilder::new(
+        &context,
+        &shader_module,
+        &shader_module,
+        DrawModeParams::Direct { vertex_count: 3 },
+    );
+    drawable_builder
+        .add_binding_slot(&BindingSlot {
+            bind_group: 0,
+            binding: 0,
+            resource: &colors[0],
+        })
+        .unwrap();
+    let drawable = drawable_builder.build();
+    for color in &mut colors {
+        color.write_uniform([1.0, 0.0, 0.0, 1.0]);
+        color.write_uniform([0.0, 1.0, 0.0, 1.0]);
+    }
+    let staging_batch = context.staging_batch();
+    assert_eq!(staging_batch.pending_write_count(), 100);
+    assert_eq!(staging_batch.staging_allocation_count(), 0);
+    context
+        .render_scene(|mut render_pass| drawable.render(&mut render_pass))
+        .unwrap();
+    let staging_batch = context.staging_batch();
+    assert_eq!(staging_batch.pending_write_count(), 0);
+    assert_eq!(staging_batch.staging_allocation_count(), 1);
+    // The last write of the frame wins, as with direct queue writes
+    let pixels = context.read_target_pixels().unwrap();
+    assert_eq!(&pixels[..4], &[0, 255, 0, 255]);
+}
+
+fn create_frame_color_drawable(context: &DrawContext, color: &Uniform<[f32; 4]>) -> Drawable {
+    let shader_module = context.create_shader_module(FRAME_COLOR_SHADER);
+    let mut drawable_builder = DrawableBuilder::new(
+        context,
+        &shader_module,
+        &shader_module,
+        DrawModeParams::Direct { vertex_count: 3 },
+    );
+    drawable_builder
+        .add_binding_slot(&BindingSlot {
+            bind_group: 0,
+            binding: 0,
+            resource: color,
+        })
+        .unwrap();
+    drawable_builder.build()
+}
+
+#[test]
+fn staging_batch_is_flushed_by_every_submission() {
+    let mut context = create_headless_context();
+    context.set_staging_batch_enabled(true);
+    let mut color = Uniform::new(&context, [0.0f32, 0.0, 0.0, 1.0]);
+    let drawable = create_frame_color_drawable(&context, &color);
+    color.write_uniform([0.0, 1.0, 0.0, 1.0]);
+    let render_texture = RenderTexture::new(
+        &context,
+        Dimensions::new(8, 8),
+        wgpu::TextureFormat::Rgba8UnormSrgb,
+    );
+    render_texture.render(&context, wgpu::Color::BLACK, |render_pass| {
+        drawable.render(render_pass);
+    });
+    assert_eq!(context.staging_batch().pending_write_count(), 0);
+    let pixels = context
+        .read_texture_pixels(render_texture.texture())
+        .unwrap();
+    assert_eq!(&pixels[..4], &[0, 255, 0, 255]);
+
+    // Skipped frames do not keep the writes
+    context.set_rendering_enabled(false);
+    color.write_uniform([1.0, 0.0, 0.0, 1.0]);
+    context.render_scene(|_| {}).unwrap();
+    assert_eq!(context.staging_batch().pending_write_count(), 0);
+    context.set_rendering_enabled(true);
+
+    // A direct write still lands after the batched ones
+    color.write_uniform([0.0, 0.0, 1.0, 1.0]);
+    context.set_staging_batch_enabled(false);
+    color.write_uniform([1.0, 1.0, 0.0, 1.0]);
+    assert_eq!(context.staging_batch().pending_write_count(), 0);
+    context
+        .render_scene(|mut render_pass| drawable.render(&mut render_pass))
+        .unwrap();
+    let pixels = context.read_target_pixels().unwrap();
+    assert_eq!(&pixels[..4], &[255, 255, 0, 255]);
+}
+
+fn check_frame_uniform_written_each_frame(staging_batch_enabled: bool) {
+    let mut context = create_headless_context();
+    context.set_staging_batch_enabled(staging_batch_enabled);
+    let mut color = FrameUniform::new(&context, [0.0f32, 0.0, 0.0, 1.0]);
+    let shader_module = context.create_shader_module(FRAME_COLOR_SHADER);
+    let mut drawable_builder = DrawableBuilder::new(
+        &context,
+        &shader_module,
+        &shader_module,
+        DrawModeParams::Direct { vertex_count: 3 },
+    );
+    drawable_builder
+        .add_binding_slot(&BindingSlot {
+            bind_group: 0,
+            binding: 0,
+            resource: &color,
+        })
+        .unwrap();
+    let drawable = drawable_builder.build();
+    let colors = [
+        [1.0, 0.0, 0.0, 1.0],
+        [0.0, 1.0, 0.0, 1.0],
+        [0.0, 0.0, 1.0, 1.0],
+        [1.0, 1.0, 0.0, 1.0],
+        [0.0, 1.0, 1.0, 1.0],
+    ];
+    for (frame, frame_color) in colors.into_iter().enumerate() {
+        assert_eq!(context.frame_index(), frame as u64);
+        let pending_writes = context.staging_batch().pending_write_count();
+        color.write_uniform(frame_color);
+        // Ordered with the other batched writes
+        assert_eq!(
+            context.staging_batch().pending_write_count(),
+            pending_writes + usize::from(staging_batch_enabled)
+        );
+        assert_eq!(
+            color.current_slot(),
+            frame as u32 % FrameUniform::<[f32; 4]>::FRAMES_IN_FLIGHT
+        );
+        context
+            .render_scene(|mut render_pass| drawable.render(&mut render_pass))
+            .unwrap();
+        let pixels = context.read_target_pixels().unwrap();
+        let expected = frame_color.map(|channel| (channel * 255.0) as u8);
+        assert_eq!(&pixels[..4], &expected);
+    }
+}
+
+#[test]
+fn frame_uniform_reads_value_written_each_frame() {
+    check_frame_uniform_written_each_frame(false);
+    check_frame_uniform_written_each_frame(true);
+}
+
+#[test]
+fn cursor_moved_updates_the_stored_position() {
+    let mut context = create_headless_context();
+    assert_eq!(context.cursor_position(), None);
+    let device_id = DeviceId::dummy();
+    let position = PhysicalPosition::new(125.0, 375.0);
+    assert_eq!(
+        context.track_cursor_event(&WindowEvent::CursorMoved {
+            device_id,
+            position
+        }),
+        Some(position)
+    );
+    assert_eq!(context.cursor_position(), Some(position));
+    assert_eq!(context.cursor_ndc(), Some([-0.5, -0.5]));
+    assert_eq!(
+        context.track_cursor_event(&WindowEvent::CursorLeft { device_id }),
+        None
+    );
+    assert_eq!(context.cursor_position(), None);
+}
+
+#[test]
+fn gradient_background_differs_from_top_to_bottom() {
+    let mut draw_context = create_headless_context();
+    draw_context.set_background(Background::Gradient(wgpu::Color::RED, wgpu::Color::BLUE));
+    assert_eq!(draw_context.clear_color(), None);
+    draw_context.render_scene(|_| {}).unwrap();
+    let pixels = draw_context.read_target_pixels().unwrap();
+    let Dimensions { width, height } = draw_context.surface_dimensions();
+    let pixel = |y: u32| {
+        let index = ((y * width + width / 2) * 4) as usize;
+        &pixels[index..index + 4]
+    };
+    let (top, bottom) = (pixel(0), pixel(height - 1));
+    assert!(top[0] > 200 && top[2] < 50, "{top:?}");
+    assert!(bottom[2] > 200 && bottom[0] < 50, "{bottom:?}");
+    draw_context.set_clear_color(Some(wgpu::Color::GREEN));
+    assert_eq!(
+        draw_context.background(),
+        Background::Solid(wgpu::Color::GREEN)
+    );
+}
+
+#[test]
+fn pop_clear_color_restores_pushed_colors() {
+    let mut draw_context = DrawContext::new(None, None).block_on().unwrap();
+    let original = draw_context.clear_color();
+    draw_context.push_clear_color(Some(wgpu::Color::BLUE));
+    draw_context.push_clear_color(None);
+    assert_eq!(draw_context.clear_color(), None);
+    draw_context.pop_clear_color();
+    assert_eq!(draw_context.clear_color(), Some(wgpu::Color::BLUE));
+    draw_context.pop_clear_color();
+    assert_eq!(draw_context.clear_color(), original);
+    // Nothing left to restore
+    draw_context.pop_clear_color();
+    assert_eq!(draw_context.clear_color(), original);
+}
+
+#[test]
+fn frame_complete_callback_fires_after_submission() {
+    let draw_context = DrawContext::new(None, None).block_on().unwrap();
+    let (sender, receiver) = std::sync::mpsc::channel();
+    draw_context.on_frame_complete(move |frame_index| sender.send(frame_index).unwrap());
+    assert!(receiver.try_recv().is_err());
+    draw_context.render_scene(|_| {}).unwrap();
+    draw_context.device.poll(wgpu::Maintain::Wait);
+    let frame_index = receiver
+        .recv_timeout(std::time::Duration::from_secs(5))
+        .expect("The callback should have been called");
+    assert_eq!(frame_index, 0);
+    assert_eq!(draw_context.frame_index(), 1);
+}
+
+#[test]
+fn low_latency_mode_keeps_a_single_frame_in_flight() {
+    let mut draw_context = DrawContext::new(None, None).block_on().unwrap();
+    draw_context.set_low_latency(true);
+    assert!(draw_context.is_low_latency());
+    let completed = Arc::new(Mutex::new(Vec::new()));
+    for frame in 0..5u64 {
+        let frame_completed = Arc::clone(&completed);
+        draw_context.on_frame_complete(move |frame_index| {
+            frame_completed.lock().unwrap().push(frame_index);
+        });
+        draw_context
+            .render_scene(|_| {
+                // Recording a frame starts once the previous one is done
+                let completed = completed.lock().unwrap();
+                assert_eq!(completed.len() as u64, frame, "{completed:?}");
+            })
+            .unwrap();
+    }
+    draw_context.device.poll(wgpu::Maintain::Wait);
+    assert_eq!(*completed.lock().unwrap(), vec![0, 1, 2, 3, 4]);
+}
+
+#[test]
+fn multisampling_can_be_toggled_at_runtime() {
+    let mut context = create_headless_context();
+    let drawable = create_instanced_drawable(&context, 1);
+    assert_eq!(drawable.sample_count(), 4);
+    context.set_multisample(false, 1).unwrap();
+    assert!(!drawable.is_compatible_with(&context));
+    let drawable = create_instanced_drawable(&context, 1);
+    assert_eq!(drawable.sample_count(), 1);
+    context
+        .render_scene(|mut render_pass| drawable.render(&mut render_pass))
+        .unwrap();
+    context.set_multisample(true, 4).unwrap();
+    assert!(!drawable.is_compatible_with(&context));
+    let drawable = create_instanced_drawable(&context, 1);
+    assert!(drawable.is_compatible_with(&context));
+    context
+        .render_scene(|mut render_pass| drawable.render(&mut render_pass))
+        .unwrap();
+}
+
+#[test]
+fn invalid_multisample_counts_are_rejected() {
+    let mut context = create_headless_context();
+    assert!(context.set_multisample(true, 1).is_err());
+    assert!(context.set_multisample(true, 2).is_err());
+    assert!(context.set_multisample(true, 3).is_err());
+    assert!(context.set_multisample(true, 8).is_err());
+    assert!(context.set_multisample(true, 64).is_err());
+    // The previous configuration is kept
+    assert_eq!(context.multisample_config.get_multisample_count(), 4);
+    context.render_scene(|_| {}).unwrap();
+}
+
+const DEPTH_QUAD_SHADER: &str = r"
+@vertex
+fn vtx_main(@location(0) position: vec3<f32>) -> @builtin(position) vec4<f32> {
+    return vec4<f32>(position, 1.0);
+}
+
+@fragment
+fn frg_main() -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0, 0.0, 0.0, 1.0);
+}
+";
+
+const FOG_SHADER: &str = r"
+@group(0) @binding(0)
+var scene_depth: texture_2d<f32>;
+@group(0) @binding(1)
+var depth_sampler: sampler;
+
+struct FragmentInput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+@vertex
+fn vtx_main(@builtin(vertex_index) vertex: u32) -> FragmentInput {
+    let uv = vec2<f32>(f32((vertex << 1u) & 2u), f32(vertex & 2u));
+    var out: FragmentInput;
+    out.position = vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+    out.uv = vec2<f32>(uv.x, 1.0 - uv.y);
+    return out;
+}
+
+// White fog, its opacity being the scene depth
+@fragment
+fn frg_main(frg_in: FragmentInput) -> @location(0) vec4<f32> {
+    let depth = textureSample(scene_depth, depth_sampler, frg_in.uv).r;
+    return vec4<f32>(1.0, 1.0, 1.0, depth);
+}
+";
+
+#[test]
+fn mismatched_sample_counts_are_rejected() {
+    let mut draw_context = DrawContext::new(None, None).block_on().unwrap();
+    assert!(draw_context.multisample_config.is_multisample_enabled());
+    draw_context.multisample_config = MultiSampleConfig::new(false, 1);
+    let error = draw_context.render_scene(|_| {}).unwrap_err();
+    assert!(error.to_string().contains("resize"), "{error}");
+    draw_context.resize(500, 500);
+    draw_context.render_scene(|_| {}).unwrap();
+}
+
+#[test]
+fn fog_pass_reads_scene_depth() {
+    let mut draw_context = DrawContext::new(None, None).block_on().unwrap();
+    // Multisampled depth textures cannot be sampled with every backend
+    draw_context.multisample_config = MultiSampleConfig::new(false, 1);
+    draw_context.resize(500, 500);
+    assert!(draw_context.is_depth_sampling_supported());
+    draw_context.set_clear_color(Some(wgpu::Color::BLACK));
+    // Left half of the screen, close to the camera
+    let quad = [
+        [-1.0f32, -1.0, 0.25],
+        [0.0, -1.0, 0.25],
+        [0.0, 1.0, 0.25],
+        [-1.0, 1.0, 0.25],
+    ];
+    let quad_shader = draw_context.create_shader_module(DEPTH_QUAD_SHADER);
+    let mut quad_builder = DrawableBuilder::new(
+        &draw_context,
+        &quad_shader,
+        &quad_shader,
+        DrawModeParams::Indexed {
+            index_data: IndexData::from_u32_auto(&[0, 1, 2, 0, 2, 3]),
+        },
+    );
+    quad_builder
+        .add_attribute(
+            0,
+            wgpu::VertexStepMode::Vertex,
+            &quad,
+            wgpu::VertexFormat::Float32x3,
+        )
+        .unwrap();
+    let quad = quad_builder.build();
+    draw_context
+        .render_scene(|mut render_pass| quad.render(&mut render_pass))
+        .unwrap();
+    draw_context.resolve_depth().unwrap();
+
+    assert!(
+        draw_context
+            .create_depth_sampler(&SamplerConfig::default())
+            .is_err()
+    );
+    let depth_sampler = draw_context
+        .create_depth_sampler(&SamplerConfig {
+            mag_filter: wgpu::FilterMode::Nearest,
+            min_filter: wgpu::FilterMode::Nearest,
+            ..SamplerConfig::default()
+        })
+        .unwrap();
+    let scene_depth = draw_context.depth_view_for_sampling().unwrap();
+    let fog_shader = draw_context.create_shader_module(FOG_SHADER);
+    let mut fog_builder = DrawableBuilder::new(
+        &draw_context,
+        &fog_shader,
+        &fog_shader,
+        DrawModeParams::Direct { vertex_count: 3 },
+    );
+    fog_builder
+        .add_binding_slot(&BindingSlot {
+            bind_group: 0,
+            binding: 0,
+            resource: &scene_depth,
+        })
+        .unwrap()
+        .add_binding_slot(&BindingSlot {
+            bind_group: 0,
+            binding: 1,
+            resource: &depth_sampler,
+        })
+        .unwrap()
+        .set_blend_option(wgpu::BlendState::ALPHA_BLENDING);
+    let fog = fog_builder.build();
+    draw_context.set_clear_color(None);
+    draw_context
+        .render_scene(|mut render_pass| fog.render(&mut render_pass))
+        .unwrap();
+
+    let pixels = draw_context.read_target_pixels().unwrap();
+    let pixel = |x: usize| &pixels[(250 * 500 + x) * 4..(250 * 500 + x) * 4 + 4];
+    // Far background fully fogged, the close quad mostly visible
+    assert_eq!(pixel(400), [255, 255, 255, 255]);
+    let quad_pixel = pixel(100);
+    assert_eq!(quad_pixel[0], 255);
+    assert!((120..=145).contains(&quad_pixel[1]), "{quad_pixel:?}");
+}
+
+#[test]
+fn debug_lines_are_drawn_once() {
+    let mut draw_context = create_headless_context();
+    draw_context.resize(100, 100);
+    draw_context.set_clear_color(Some(wgpu::Color::BLACK));
+    let is_line_drawn = |context: &DrawContext| {
+        let pixels = context.read_target_pixels().unwrap();
+        // The line lies between two rows of pixels, partially covered with multisampling
+        [49, 50].iter().any(|row| {
+            let pixel = &pixels[(row * 100 + 20) * 4..(row * 100 + 20) * 4 + 3];
+            pixel[0] == 0 && pixel[1] > 0
+        })
+    };
+
+    draw_context
+        .debug_draw()
+        .line([-1.0, 0.0, 0.5], [1.0, 0.0, 0.5], [0.0, 1.0, 0.0]);
+    assert_eq!(draw_context.debug_draw().line_count(), 1);
+    draw_context.render_scene(|_| {}).unwrap();
+    assert!(is_line_drawn(&draw_context));
+    assert!(draw_context.debug_draw().is_empty());
+
+    draw_context.render_scene(|_| {}).unwrap();
+    assert!(!is_line_drawn(&draw_context));
+}
+
+#[test]
+fn debug_aabb_queues_its_twelve_edges() {
+    let draw_context = create_headless_context();
+    let mut debug_draw = draw_context.debug_draw();
+    debug_draw.aabb([-1.0, -1.0, -1.0], [1.0, 1.0, 1.0], [1.0, 1.0, 0.0]);
+    assert_eq!(debug_draw.line_count(), 12);
+    debug_draw.point([0.0, 0.0, 0.0], 0.5, [1.0, 1.0, 1.0]);
+    assert_eq!(debug_draw.line_count(), 15);
+}
+
+const INSTANCE_TRANSFORMS_SHADER: &str = r"
+@group(0) @binding(0)
+var<storage, read> transforms: array<mat4x4<f32>>;
+
+@vertex
+fn vtx_main(@builtin(vertex_index) vertex: u32, @builtin(instance_index) instance: u32) -> @builtin(position) vec4<f32> {
+    let corners = array(vec2<f32>(-1.0, -1.0), vec2<f32>(1.0, -1.0), vec2<f32>(0.0, 1.0));
+    return transforms[instance] * vec4<f32>(corners[vertex], 0.0, 1.0);
+}
+
+@fragment
+fn frg_main() -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0, 1.0, 1.0, 1.0);
+}
+";
+
+#[test]
+fn instance_transforms_set_the_instance_count() {
+    let mut context = create_headless_context();
+    context.resize(100, 100);
+    context.set_clear_color(Some(wgpu::Color::BLACK));
+    // Triangles centered on three of the four quadrants
+    let quadrant_centers = [[-0.5, -0.5], [0.5, -0.5], [-0.5, 0.5]];
+    let transforms = StorageBuffer::new_array(
+        &context,
+        &quadrant_centers.map(|[x, y]| {
+            (cgmath::Matrix4::from_translation(cgmath::Vector3::new(x, y, 0.5))
+                * cgmath::Matrix4::from_scale(0.3))
+            .into()
+        }),
+    );
+    let shader_module = context.create_shader_module(INSTANCE_TRANSFORMS_SHADER);
+    let mut drawable_builder = DrawableBuilder::new(
+        &context,
+        &shader_module,
+        &shader_module,
+        DrawModeParams::Direct { vertex_count: 3 },
+    );
+    drawable_builder
+        .set_instance_transforms(0, 0, &transforms)
+        .unwrap();
+    let drawable = drawable_builder.build();
+    assert_eq!(drawable.instance_count(), 3);
+    context
+        .render_scene(|mut render_pass| drawable.render(&mut render_pass))
+        .unwrap();
+
+    let pixels = context.read_target_pixels().unwrap();
+    let pixel = |x: usize, y: usize| pixels[(y * 100 + x) * 4];
+    // NDC y points up while pixel rows go down
+    assert_eq!(pixel(25, 75), 255);
+    assert_eq!(pixel(75, 75), 255);
+    assert_eq!(pixel(25, 25), 255);
+    assert_eq!(pixel(75, 25), 0);
+}
+
+#[test]
+fn storage_buffer_hides_alignment_padding() {
+    let context = create_headless_context();
+    let identity = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+    let mut normal_mats = StorageBuffer::new_array(&context, &[identity; 3]);
+    assert_eq!(normal_mats.len(), 3);
+    assert_eq!(normal_mats.get(1), Some(identity));
+    assert_eq!(normal_mats.get(3), None);
+
+    let scaled = identity.map(|column| column.map(|value| value * 2.0));
+    normal_mats.set(2, scaled);
+    assert_eq!(normal_mats.get(2), Some(scaled));
+    let mut writer = normal_mats.start_write();
+    writer.update_each(|index, value| value[0][0] = index as f32);
+    writer.par_update_each(|_, value| value[2][2] += 1.0);
+    assert_eq!(writer.get_value(2).unwrap()[0], [2.0, 0.0, 0.0]);
+    writer.apply_write();
+    assert_eq!(
+        normal_mats.get(1),
+        Some([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 2.0]])
+    );
+    assert_eq!(normal_mats.get(2).unwrap()[2], [0.0, 0.0, 3.0]);
+}
+
+#[test]
+fn index_count_limits_the_drawn_triangles() {
+    let mut context = create_headless_context();
+    context.resize(100, 100);
+    context.set_clear_color(Some(wgpu::Color::BLACK));
+    let shader_module = context.create_shader_module(DEPTH_QUAD_SHADER);
+    let mut drawable_builder = DrawableBuilder::new(
+        &context,
+        &shader_module,
+        &shader_module,
+        DrawModeParams::Indexed {
+            index_data: IndexData::U16(vec![0, 1, 2, 0, 2, 3].into()),
+        },
+    );
+    drawable_builder
+        .add_attribute(
+            0,
+            wgpu::VertexStepMode::Vertex,
+            &[
+                [-1.0f32, -1.0, 0.5],
+                [1.0, -1.0, 0.5],
+                [1.0, 1.0, 0.5],
+                [-1.0, 1.0, 0.5],
+            ],
+            wgpu::VertexFormat::Float32x3,
+        )
+        .unwrap();
+    let mut quad = drawable_builder.build();
+    assert_eq!(quad.index_capacity(), Some(6));
+    let render_corners = |quad: &Drawable| {
+        context
+            .render_scene(|mut render_pass| quad.render(&mut render_pass))
+            .unwrap();
+        let pixels = context.read_target_pixels().unwrap();
+        let red = |x: usize, y: usize| pixels[(y * 100 + x) * 4];
+        // Top left and bottom right corners
+        [red(10, 10), red(90, 90)]
+    };
+    assert_eq!(render_corners(&quad), [255, 255]);
+
+    quad.set_index_count(3).unwrap();
+    assert_eq!(render_corners(&quad), [0, 255]);
+    assert!(quad.set_index_count(7).is_err());
+
+    quad.update_indices(&context.queue, &IndexData::U16(vec![0, 2, 3].into()))
+        .unwrap();
+    assert_eq!(render_corners(&quad), [255, 0]);
+    assert!(
+        quad.update_indices(&context.queue, &IndexData::U32(vec![0, 2, 3].into()))
+            .is_err()
+    );
+    assert!(
+        quad.update_indices(&context.queue, &IndexData::U16(vec![0; 9].into()))
+            .is_err()
+    );
+}
+
+fn create_depth_quad(draw_context: &DrawContext, depth: f32) -> Drawable {
+    let quad = [
+        [-0.5f32, -0.5, depth],
+        [0.5, -0.5, depth],
+        [0.5, 0.5, depth],
+        [-0.5, 0.5, depth],
+    ];
+    let quad_shader = draw_context.create_shader_module(DEPTH_QUAD_SHADER);
+    let mut quad_builder = DrawableBuilder::new(
+        draw_context,
+        &quad_shader,
+        &quad_shader,
+        DrawModeParams::Indexed {
+            index_data: IndexData::from_u32_auto(&[0, 1, 2, 0, 2, 3]),
+        },
+    );
+    quad_builder
+        .add_attribute(
+            0,
+            wgpu::VertexStepMode::Vertex,
+            &quad,
+            wgpu::VertexFormat::Float32x3,
+        )
+        .unwrap();
+    quad_builder.build()
+}
+
+const TWO_FRAGMENTS_SHADER: &str = r"
+@vertex
+fn vtx_main(@location(0) position: vec3<f32>) -> @builtin(position) vec4<f32> {
+    return vec4<f32>(position, 1.0);
+}
+
+@fragment
+fn frg_red() -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0, 0.0, 0.0, 1.0);
+}
+
+@fragment
+fn frg_green() -> @location(0) vec4<f32> {
+    return vec4<f32>(0.0, 1.0, 0.0, 1.0);
+}
+";
+
+#[test]
+fn fragment_entry_point_can_be_selected() {
+    let mut draw_context = create_headless_context();
+    draw_context.set_clear_color(Some(wgpu::Color::BLACK));
+    let shader_module = draw_context.create_shader_module(TWO_FRAGMENTS_SHADER);
+    let mut builder = DrawableBuilder::new(
+        &draw_context,
+        &shader_module,
+        &shader_module,
+        DrawModeParams::Indexed {
+            index_data: IndexData::from_u32_auto(&[0, 1, 2, 0, 2, 3]),
+        },
+    );
+    builder
+        .add_attribute(
+            0,
+            wgpu::VertexStepMode::Vertex,
+            &[
+                [-0.5f32, -0.5, 0.5],
+                [0.5, -0.5, 0.5],
+                [0.5, 0.5, 0.5],
+                [-0.5, 0.5, 0.5],
+            ],
+            wgpu::VertexFormat::Float32x3,
+        )
+        .unwrap()
+        .set_vertex_entry("vtx_main")
+        .set_fragment_entry("frg_green");
+    let quad = builder.build();
+    draw_context
+        .render_scene(|mut render_pass| quad.render(&mut render_pass))
+        .unwrap();
+    let pixels = draw_context.read_target_pixels().unwrap();
+    let Dimensions { width, height } = draw_context.surface_dimensions();
+    let center = (((height / 2) * width + width / 2) * 4) as usize;
+    assert_eq!(&pixels[center..center + 3], &[0, 255, 0]);
+}
+
+#[test]
+fn occlusion_queries_count_visible_samples() {
+    let mut draw_context = create_headless_context();
+    assert!(draw_context.occlusion_results().is_err());
+    draw_context.set_occlusion_query_count(2).unwrap();
+    assert_eq!(draw_context.occlusion_query_count(), 2);
+    let front_quad = create_depth_quad(&draw_context, 0.25);
+    let hidden_quad = create_depth_quad(&draw_context, 0.75);
+    draw_context
+        .render_scene(|mut render_pass| {
+            front_quad.render_with_occlusion_query(&mut render_pass, 0);
+            hidden_quad.render_with_occlusion_query(&mut render_pass, 1);
+        })
+        .unwrap();
+    let results = draw_context.occlusion_results().unwrap();
+    assert_eq!(results.len(), 2);
+    assert!(results[0] > 0, "The front quad should be visible");
+    assert_eq!(results[1], 0, "The hidden quad should be occluded");
+    assert!(
+        draw_context
+            .set_occlusion_query_count(wgpu::QUERY_SET_MAX_QUERIES + 1)
+            .is_err()
+    );
+}
+
+#[test]
+fn second_pass_is_depth_tested_against_the_first() {
+    let mut draw_context = create_headless_context();
+    draw_context.set_occlusion_query_count(2).unwrap();
+    let scene_quad = create_depth_quad(&draw_context, 0.5);
+    let hidden_overlay = create_depth_quad(&draw_context, 0.75);
+    let visible_overlay = create_depth_quad(&draw_context, 0.25);
+    draw_context
+        .render_scene_passes(&mut [
+            &mut |render_pass| scene_quad.render(render_pass),
+            &mut |render_pass| {
+                hidden_overlay.render_with_occlusion_query(render_pass, 0);
+                visible_overlay.render_with_occlusion_query(render_pass, 1);
+            },
+        ])
+        .unwrap();
+    let results = draw_context.occlusion_results().unwrap();
+    assert_eq!(
+        results[0], 0,
+        "The overlay behind the scene should be occluded"
+    );
+    assert!(
+        results[1] > 0,
+        "The overlay in front of the scene should be visible"
+    );
+}
+
+#[test]
+fn near_depth_range_viewport_draws_in_front() {
+    let mut draw_context = create_headless_context();
+    draw_context.set_occlusion_query_count(1).unwrap();
+    let mut gizmo = create_depth_quad(&draw_context, 0.75);
+    let scene_quad = create_depth_quad(&draw_context, 0.25);
+    let render = |gizmo: &Drawable| {
+        draw_context
+            .render_scene(|mut render_pass| {
+                gizmo.render(&mut render_pass);
+                Viewport::full(draw_context.surface_dimensions()).apply(&mut render_pass);
+                scene_quad.render_with_occlusion_query(&mut render_pass, 0);
+            })
+            .unwrap();
+        draw_context.occlusion_results().unwrap()[0]
+    };
+    assert!(
+        render(&gizmo) > 0,
+        "The gizmo should be behind at the default range"
+    );
+    gizmo.set_viewport(Some(
+        Viewport::full(draw_context.surface_dimensions()).with_depth_range(0.0, 0.1),
+    ));
+    // The scene quad is drawn after the gizmo, with the default range restored
+    assert_eq!(render(&gizmo), 0, "The gizmo should occlude the scene quad");
+}
+
+#[test]
+fn parallel_passes_render_like_the_serial_path() {
+    let draw_context = create_headless_context();
+    let front_quad = create_depth_quad(&draw_context, 0.25);
+    let hidden_quad = create_depth_quad(&draw_context, 0.75);
+    let triangle = create_instanced_drawable(&draw_context, 1);
+    draw_context
+        .render_scene(|mut render_pass| {
+            front_quad.render(&mut render_pass);
+            hidden_quad.render(&mut render_pass);
+            triangle.render(&mut render_pass);
+        })
+        .unwrap();
+    let serial_pixels = draw_context.read_target_pixels().unwrap();
+    draw_context
+        .render_scene_parallel(vec![
+            Box::new(|render_pass| front_quad.render(render_pass)),
+            Box::new(|render_pass| hidden_quad.render(render_pass)),
+            Box::new(|render_pass| triangle.render(render_pass)),
+        ])
+        .unwrap();
+    let parallel_pixels = draw_context.read_target_pixels().unwrap();
+    assert!(serial_pixels.chunks(4).any(|pixel| pixel[0] > 0));
+    assert!(serial_pixels == parallel_pixels);
+}
+
+#[test]
+fn surface_capabilities_can_be_queried_without_window() {
+    let capabilities = query_surface_capabilities(None).block_on().unwrap();
+    assert!(capabilities.formats.is_empty());
+}
+
+#[test]
+fn screenshot_is_written_as_rendered() {
+    let mut context = create_headless_context();
+    context.set_clear_color(Some(wgpu::Color {
+        r: 0.5,
+        g: 0.25,
+        b: 0.0,
+        a: 1.0,
+    }));
+    context.render_scene(|_| {}).unwrap();
+    let directory = std::env::temp_dir().join("wgpu_igniter_screenshot_test");
+    let _ = std::fs::remove_dir_all(&directory);
+    let path = directory.join("nested").join("frame.png");
+    context.save_screenshot(&path).unwrap();
+    let image = image::open(&path).unwrap().into_rgba8();
+    assert_eq!(image.dimensions(), (500, 500));
+    // Same sRGB bytes as the target, not encoded a second time
+    assert_eq!(image.as_raw(), &context.read_target_pixels().unwrap());
+    assert_eq!(image.get_pixel(0, 0).0, [188, 137, 0, 255]);
+}
+
+#[test]
+fn present_mode_is_kept_without_a_surface() {
+    let mut context = create_headless_context();
+    assert_eq!(context.present_mode(), wgpu::PresentMode::Fifo);
+    assert_eq!(
+        context.set_present_mode(wgpu::PresentMode::Immediate),
+        wgpu::PresentMode::Immediate
+    );
+    assert_eq!(context.present_mode(), wgpu::PresentMode::Immediate);
+    // Kept across the reconfiguration of a resize
+    context.resize(200, 100);
+    assert_eq!(context.present_mode(), wgpu::PresentMode::Immediate);
+    context.render_scene(|_| {}).unwrap();
+}
+
+#[test]
+fn depth_format_is_shared_by_the_pipelines() {
+    assert_eq!(
+        create_headless_context().depth_format(),
+        wgpu::TextureFormat::Depth32Float
+    );
+    for depth_format in DrawContextConfig::SUPPORTED_DEPTH_FORMATS {
+        let mut draw_context =
+            DrawContext::new_with_config(None, None, DrawContextConfig { depth_format })
+                .block_on()
+                .unwrap();
+        assert_eq!(draw_context.depth_texture.format(), depth_format);
+        let front_quad = create_depth_quad(&draw_context, 0.25);
+        let hidden_quad = create_depth_quad(&draw_context, 0.75);
+        draw_context.set_occlusion_query_count(2).unwrap();
+        draw_context
+            .render_scene(|mut render_pass| {
+                front_quad.render_with_occlusion_query(&mut render_pass, 0);
+                hidden_quad.render_with_occlusion_query(&mut render_pass, 1);
+            })
+            .unwrap();
+        let results = draw_context.occlusion_results().unwrap();
+        assert!(results[0] > 0, "{depth_format:?}");
+        assert_eq!(results[1], 0, "{depth_format:?}");
+    }
+    let config = DrawContextConfig {
+        depth_format: wgpu::TextureFormat::Depth16Unorm,
+    };
+    assert!(
+        DrawContext::new_with_config(None, None, config)
+            .block_on()
+            .is_err()
+    );
+}
+
+fn create_stencil_quad(
+    draw_context: &DrawContext,
+    half_size: f32,
+    stencil_state: wgpu::StencilState,
+) -> anyhow::Result<Drawable> {
+    let quad = [
+        [-half_size, -half_size, 0.5],
+        [half_size, -half_size, 0.5],
+        [half_size, half_size, 0.5],
+        [-half_size, half_size, 0.5],
+    ];
+    let quad_shader = draw_context.create_shader_module(TWO_FRAGMENTS_SHADER);
+    let mut quad_builder = DrawableBuilder::new(
+        draw_context,
+        &quad_shader,
+        &quad_shader,
+        DrawModeParams::Indexed {
+            index_data: IndexData::from_u32_auto(&[0, 1, 2, 0, 2, 3]),
+        },
+    );
+    quad_builder
+        .add_attribute(
+            0,
+            wgpu::VertexStepMode::Vertex,
+            &quad,
+            wgpu::VertexFormat::Float32x3,
+        )?
+        .set_fragment_entry("frg_green")
+        .set_depth_write(false)
+        .set_stencil_state(stencil_state)?;
+    Ok(quad_builder.build())
+}
+
+fn stencil_state(
+    compare: wgpu::CompareFunction,
+    fail_op: wgpu::StencilOperation,
+) -> wgpu::StencilState {
+    let face = wgpu::StencilFaceState {
+        compare,
+        fail_op,
+        depth_fail_op: wgpu::StencilOperation::Keep,
+        pass_op: wgpu::StencilOperation::Keep,
+    };
+    wgpu::StencilState {
+        front: face,
+        back: face,
+        read_mask: 0xff,
+        write_mask: 0xff,
+    }
+}
+
+#[test]
+fn stencil_mask_limits_the_drawn_area() {
+    let config = DrawContextConfig {
+        depth_format: wgpu::TextureFormat::Depth24PlusStencil8,
+    };
+    let mut draw_context = DrawContext::new_with_config(None, None, config)
+        .block_on()
+        .unwrap();
+    draw_context.set_clear_color(Some(wgpu::Color::BLACK));
+    // Failing the stencil test, the mask only writes the reference into the stencil
+    let mask = create_stencil_quad(
+        &draw_context,
+        0.25,
+        stencil_state(
+            wgpu::CompareFunction::Never,
+            wgpu::StencilOperation::Replace,
+        ),
+    )
+    .unwrap();
+    let masked = create_stencil_quad(
+        &draw_context,
+        0.9,
+        stencil_state(wgpu::CompareFunction::Equal, wgpu::StencilOperation::Keep),
+    )
+    .unwrap();
+    draw_context
+        .render_scene(|mut render_pass| {
+            mask.render_with_stencil_reference(&mut render_pass, 1);
+            masked.render_with_stencil_reference(&mut render_pass, 1);
+        })
+        .unwrap();
+    let pixels = draw_context.read_target_pixels().unwrap();
+    let Dimensions { width, height } = draw_context.surface_dimensions();
+    let pixel = |x: u32, y: u32| {
+        let offset = ((y * width + x) * 4) as usize;
+        pixels[offset..offset + 3].to_vec()
+    };
+    assert_eq!(pixel(width / 2, height / 2), [0, 255, 0]);
+    // Inside the masked quad, outside of the mask
+    assert_eq!(pixel(width / 10, height / 2), [0, 0, 0]);
+    let default_context = create_headless_context();
+    assert!(
+        create_stencil_quad(
+            &default_context,
+            0.5,
+            stencil_state(wgpu::CompareFunction::Equal, wgpu::StencilOperation::Keep),
+        )
+        .is_err()
+    );
+}
+
+fn render_polygon_mode_quad(polygon_mode: wgpu::PolygonMode) -> anyhow::Result<Vec<u8>> {
+    let mut draw_context = create_headless_context();
+    draw_context.set_clear_color(Some(wgpu::Color::BLACK));
+    let quad = [
+        [-0.5, -0.5, 0.5],
+        [0.5, -0.5, 0.5],
+        [0.5, 0.5, 0.5],
+        [-0.5, 0.5, 0.5],
+    ];
+    let quad_shader = draw_context.create_shader_module(TWO_FRAGMENTS_SHADER);
+    let mut quad_builder = DrawableBuilder::new(
+        &draw_context,
+        &quad_shader,
+        &quad_shader,
+        DrawModeParams::Indexed {
+            index_data: IndexData::from_u32_auto(&[0, 1, 2, 0, 2, 3]),
+        },
+    );
+    quad_builder
+        .add_attribute(
+            0,
+            wgpu::VertexStepMode::Vertex,
+            &quad,
+            wgpu::VertexFormat::Float32x3,
+        )?
+        .set_fragment_entry("frg_green")
+        .set_polygon_mode(polygon_mode)?;
+    let quad = quad_builder.build();
+    draw_context.render_scene(|mut render_pass| quad.render(&mut render_pass))?;
+    draw_context.read_target_pixels()
+}
+
+#[test]
+fn polygon_mode_line_only_draws_the_edges() {
+    let Dimensions { width, .. } = create_headless_context().surface_dimensions();
+    // Inside the quad, away from its edges and from the diagonal between the two triangles
+    let offset = ((width / 2 - width / 10) * width + width / 2 - width / 5) as usize * 4;
+    let filled = render_polygon_mode_quad(wgpu::PolygonMode::Fill).unwrap();
+    assert_eq!(filled[offset..offset + 3], [0, 255, 0]);
+    let features = create_headless_context().device.features();
+    let lines = render_polygon_mode_quad(wgpu::PolygonMode::Line);
+    if features.contains(wgpu::Features::POLYGON_MODE_LINE) {
+        assert_eq!(lines.unwrap()[offset..offset + 3], [0, 0, 0]);
+    } else {
+        assert!(lines.is_err());
+    }
+}
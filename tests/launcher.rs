@@ -0,0 +1,174 @@
+use pollster::FutureExt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use wgpu_igniter::plugins::PluginRegistry;
+use wgpu_igniter::wgpu;
+use wgpu_igniter::{
+    Dimensions, DrawContext, LaunchConfig, RenderLoopHandler, TimeInfo, init_log,
+    render_headless_frames, run_headless,
+};
+
+mod common;
+
+use common::{create_headless_context, install_warning_capture, take_warnings_containing};
+
+#[test]
+fn resize_below_minimum_is_clamped() {
+    let config = LaunchConfig {
+        min_inner_size: Some(Dimensions::new(320, 240)),
+        max_inner_size: Some(Dimensions::new(1920, 1080)),
+        ..LaunchConfig::default()
+    };
+    let clamped = config.constrain_inner_size(Dimensions::new(100, 50));
+    assert_eq!(clamped, Dimensions::new(320, 240));
+    assert_eq!(
+        config.constrain_inner_size(Dimensions::new(4000, 500)),
+        Dimensions::new(1920, 500)
+    );
+    let mut context = create_headless_context();
+    context.resize(clamped.width, clamped.height);
+    assert_eq!(context.surface_dimensions(), clamped);
+
+    let config = LaunchConfig {
+        aspect_ratio: Some((16, 9)),
+        ..config
+    };
+    assert_eq!(
+        config.constrain_inner_size(Dimensions::new(800, 800)),
+        Dimensions::new(800, 450)
+    );
+    // The height of a 320 pixels wide window would go below the minimum
+    assert_eq!(
+        config.constrain_inner_size(Dimensions::new(100, 100)),
+        Dimensions::new(427, 240)
+    );
+}
+
+#[test]
+fn log_spec_overrides_the_launch_levels() {
+    let mut config = LaunchConfig {
+        log_level: log::LevelFilter::Warn,
+        ..LaunchConfig::default()
+    };
+    config.apply_log_spec("error, wgpu_igniter=trace, wgpu_core, naga=nonsense");
+    assert_eq!(config.log_level, log::LevelFilter::Error);
+    assert_eq!(
+        config.log_target_levels,
+        vec![
+            ("wgpu_igniter".to_string(), log::LevelFilter::Trace),
+            ("wgpu_core".to_string(), log::LevelFilter::Trace),
+        ]
+    );
+}
+
+struct ExitCounter {
+    exit_count: Arc<AtomicUsize>,
+}
+
+impl RenderLoopHandler for ExitCounter {
+    fn on_exit(&mut self, _draw_context: &mut DrawContext) {
+        self.exit_count.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn headless_run_calls_on_exit_once() {
+    let exit_count = Arc::new(AtomicUsize::new(0));
+    let handler_count = Arc::clone(&exit_count);
+    run_headless(move |_| {
+        Box::new(ExitCounter {
+            exit_count: Arc::clone(&handler_count),
+        })
+    });
+    assert_eq!(exit_count.load(Ordering::SeqCst), 1);
+}
+
+struct FrameCounter {
+    update_count: Arc<AtomicUsize>,
+    render_count: Arc<AtomicUsize>,
+}
+
+impl RenderLoopHandler for FrameCounter {
+    fn on_update(
+        &mut self,
+        _plugin_registry: &mut PluginRegistry,
+        _draw_context: &mut DrawContext,
+        _time_info: &TimeInfo,
+    ) {
+        self.update_count.fetch_add(1, Ordering::SeqCst);
+    }
+    fn on_render(
+        &mut self,
+        _plugin_registry: &mut PluginRegistry,
+        _draw_context: &DrawContext,
+        _time_info: &TimeInfo,
+        _render_pass: &mut wgpu::RenderPass<'static>,
+    ) {
+        self.render_count.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn disabled_rendering_still_runs_the_update_logic() {
+    let mut draw_context = create_headless_context();
+    draw_context.set_rendering_enabled(false);
+    let update_count = Arc::new(AtomicUsize::new(0));
+    let render_count = Arc::new(AtomicUsize::new(0));
+    render_headless_frames(&mut draw_context, 5, Duration::from_millis(20), |_| {
+        Box::new(FrameCounter {
+            update_count: Arc::clone(&update_count),
+            render_count: Arc::clone(&render_count),
+        })
+    })
+    .unwrap();
+    assert_eq!(update_count.load(Ordering::SeqCst), 5);
+    assert_eq!(render_count.load(Ordering::SeqCst), 0);
+    assert_eq!(
+        draw_context.time_controller().animation_time().as_millis(),
+        100
+    );
+    assert_eq!(draw_context.frame_stats().rendered_frames(), 0);
+    assert_eq!(draw_context.frame_index(), 0);
+}
+
+#[test]
+fn headless_frames_can_be_encoded_as_png() {
+    let mut draw_context = DrawContext::new_headless(64, 48).block_on().unwrap();
+    draw_context.set_clear_color(Some(wgpu::Color::RED));
+    let update_count = Arc::new(AtomicUsize::new(0));
+    let render_count = Arc::new(AtomicUsize::new(0));
+    let pixels = render_headless_frames(&mut draw_context, 3, Duration::from_millis(100), |_| {
+        Box::new(FrameCounter {
+            update_count: Arc::clone(&update_count),
+            render_count: Arc::clone(&render_count),
+        })
+    })
+    .unwrap();
+    assert_eq!(update_count.load(Ordering::SeqCst), 3);
+    assert_eq!(render_count.load(Ordering::SeqCst), 3);
+    assert_eq!(
+        draw_context.time_controller().animation_time().as_millis(),
+        300
+    );
+
+    let mut png = Vec::new();
+    image::ImageEncoder::write_image(
+        image::codecs::png::PngEncoder::new(&mut png),
+        &pixels,
+        64,
+        48,
+        image::ExtendedColorType::Rgba8,
+    )
+    .unwrap();
+    let decoded = image::load_from_memory(&png).unwrap().to_rgba8();
+    assert_eq!(decoded.dimensions(), (64, 48));
+    assert_eq!(decoded.get_pixel(10, 10).0, [255, 0, 0, 255]);
+}
+
+#[test]
+fn launching_with_an_existing_logger_does_not_panic() {
+    install_warning_capture();
+    assert!(!init_log(&LaunchConfig::default()));
+    assert!(!take_warnings_containing("logger").is_empty());
+}
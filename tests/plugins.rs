@@ -0,0 +1,418 @@
+use cgmath::{MetricSpace, Point3, Vector3};
+use pollster::FutureExt;
+use std::time::Duration;
+use web_time::Instant;
+use wgpu_igniter::cameras::Camera;
+use wgpu_igniter::plugins::Plugin;
+use wgpu_igniter::plugins::camera_path::{CameraPathLook, CameraPathPlugin};
+use wgpu_igniter::plugins::canvas::{CanvasPlugin, ResolutionConvention};
+use wgpu_igniter::plugins::egui::EquiPlugin;
+use wgpu_igniter::plugins::follow_camera::FollowCameraPlugin;
+use wgpu_igniter::plugins::ground_grid::GroundGridPlugin;
+use wgpu_igniter::plugins::orbit_controls::OrbitControlsPlugin;
+use wgpu_igniter::plugins::scene_3d::{Scene3D, Scene3DPlugin};
+use wgpu_igniter::primitives::Shareable;
+use wgpu_igniter::primitives::cube::{self, CubeOptions};
+use wgpu_igniter::primitives::curve::{Curve, CurveKind};
+use wgpu_igniter::{Dimensions, DrawContext, MultiSampleConfig, TimeInfo};
+use winit::event::{DeviceId, MouseScrollDelta, TouchPhase, WindowEvent};
+
+const CANVAS_SHADER: &str = r"
+@group(0) @binding(3)
+var<uniform> iResolution: vec3<f32>;
+@group(0) @binding(6)
+var<uniform> pixels_per_point: f32;
+
+@fragment
+fn fragment(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    let uv = position.xy / (iResolution.xy * pixels_per_point);
+    return vec4<f32>(uv, 0.0, 1.0);
+}
+";
+
+#[test]
+fn canvas_resolution_follows_convention_on_high_dpi() {
+    let mut draw_context = DrawContext::new(None, None)
+        .block_on()
+        .expect("A headless draw context should be available");
+    draw_context.set_scale_factor(2.0);
+    let shader = draw_context.create_shader_module(CANVAS_SHADER);
+    let mut canvas = CanvasPlugin::new(&draw_context, &shader, &[]).unwrap();
+    assert_eq!(
+        canvas.resolution_convention(),
+        ResolutionConvention::Physical
+    );
+    assert_eq!(canvas.resolution(&draw_context), [500.0, 500.0, 1.0]);
+    canvas.set_resolution_convention(ResolutionConvention::Logical);
+    assert_eq!(canvas.resolution(&draw_context), [250.0, 250.0, 1.0]);
+}
+
+const TIME_SHADER: &str = r"
+@group(0) @binding(0)
+var<uniform> iTime: f32;
+@group(0) @binding(2)
+var<uniform> iFrame: f32;
+
+@fragment
+fn fragment() -> @location(0) vec4<f32> {
+    return vec4<f32>(iTime / 4.0, iFrame / 8.0, 0.0, 1.0);
+}
+";
+
+#[test]
+fn zero_time_scale_freezes_canvas_time() {
+    let mut draw_context = DrawContext::new(None, None)
+        .block_on()
+        .expect("A headless draw context should be available");
+    let shader = draw_context.create_shader_module(TIME_SHADER);
+    let mut canvas = CanvasPlugin::new(&draw_context, &shader, &[]).unwrap();
+    let init_start = Instant::now();
+    let mut render_frame = |draw_context: &mut DrawContext| {
+        let frame_delta = Duration::from_millis(500);
+        draw_context.time_controller_mut().advance(frame_delta);
+        let time_info =
+            TimeInfo::with_time_controller(init_start, frame_delta, draw_context.time_controller());
+        canvas.on_update(draw_context, &time_info);
+        draw_context
+            .render_scene(|render_pass| {
+                canvas.on_render(draw_context, &time_info, &mut render_pass.forget_lifetime());
+            })
+            .unwrap();
+        let pixels = draw_context.read_target_pixels().unwrap();
+        [pixels[0], pixels[1]]
+    };
+
+    let [time_before, frame_before] = render_frame(&mut draw_context);
+    draw_context.time_controller_mut().set_time_scale(0.0);
+    let [time_frozen, frame_frozen] = render_frame(&mut draw_context);
+    let [time_still_frozen, frame_after] = render_frame(&mut draw_context);
+    assert!(time_before > 0);
+    assert_eq!(time_frozen, time_before);
+    assert_eq!(time_still_frozen, time_before);
+    // Frames are still rendered while the animation clock is frozen
+    assert!(frame_before < frame_frozen && frame_frozen < frame_after);
+
+    draw_context.time_controller_mut().set_time_scale(1.0);
+    draw_context.time_controller_mut().set_paused(true);
+    assert_eq!(render_frame(&mut draw_context)[0], time_before);
+    draw_context.time_controller_mut().set_paused(false);
+    assert!(render_frame(&mut draw_context)[0] > time_before);
+}
+
+#[test]
+fn toggling_multisample_with_egui_active_renders() {
+    let mut draw_context = DrawContext::new(None, None)
+        .block_on()
+        .expect("A headless draw context should be available");
+    let mut egui_plugin = EquiPlugin::new(&draw_context);
+    let time_info = TimeInfo::default();
+    let (width, height) = (
+        draw_context.surface_config.width,
+        draw_context.surface_config.height,
+    );
+    for multisample_config in [
+        MultiSampleConfig::new(false, 1),
+        MultiSampleConfig::new(true, 4),
+    ] {
+        draw_context.multisample_config = multisample_config;
+        draw_context.resize(width, height);
+        egui_plugin.draw(|ctx| {
+            egui::Window::new("Test").show(ctx, |ui| ui.label("MSAA"));
+        });
+        egui_plugin.on_update(&draw_context, &time_info);
+        draw_context
+            .render_scene(|render_pass| {
+                egui_plugin.on_render(
+                    &draw_context,
+                    &time_info,
+                    &mut render_pass.forget_lifetime(),
+                );
+            })
+            .unwrap();
+    }
+}
+
+#[test]
+fn headless_egui_window_is_rendered() {
+    let mut draw_context = DrawContext::new(None, None)
+        .block_on()
+        .expect("A headless draw context should be available");
+    draw_context.set_clear_color(Some(wgpu::Color::BLACK));
+    let mut egui_plugin = EquiPlugin::new(&draw_context);
+    egui_plugin.set_pixels_per_point(1.0);
+    let time_info = TimeInfo::default();
+    // egui windows are laid out but not shown on their first pass
+    for _ in 0..2 {
+        egui_plugin.draw(|ctx| {
+            egui::Window::new("Test")
+                .fixed_pos((10.0, 10.0))
+                .fixed_size((100.0, 100.0))
+                .show(ctx, |ui| ui.label("Headless"));
+        });
+        draw_context
+            .render_scene(|render_pass| {
+                egui_plugin.on_render(
+                    &draw_context,
+                    &time_info,
+                    &mut render_pass.forget_lifetime(),
+                );
+            })
+            .unwrap();
+    }
+    let width = draw_context.surface_config.width as usize;
+    let pixels = draw_context.read_target_pixels().unwrap();
+    let pixel = |x: usize, y: usize| &pixels[(y * width + x) * 4..(y * width + x) * 4 + 3];
+    assert_ne!(pixel(60, 40), [0, 0, 0], "The egui panel should be drawn");
+    assert_eq!(pixel(width - 5, 5), [0, 0, 0]);
+}
+
+#[test]
+fn deferred_viewport_is_requested_as_a_second_window() {
+    let draw_context = DrawContext::new(None, None)
+        .block_on()
+        .expect("A headless draw context should be available");
+    let mut egui_plugin = EquiPlugin::new(&draw_context);
+    egui_plugin.set_detachable_viewports(true);
+    let time_info = TimeInfo::default();
+    let viewport_id = egui::ViewportId::from_hash_of("tools");
+    let render_frame = |egui_plugin: &mut EquiPlugin, show_viewport: bool| {
+        egui_plugin.draw(|ctx| {
+            if show_viewport {
+                ctx.show_viewport_deferred(
+                    viewport_id,
+                    egui::ViewportBuilder::default().with_title("Tools"),
+                    |ctx, _| {
+                        egui::CentralPanel::default().show(ctx, |ui| ui.label("Detached"));
+                    },
+                );
+            }
+        });
+        draw_context
+            .render_scene(|render_pass| {
+                egui_plugin.on_render(
+                    &draw_context,
+                    &time_info,
+                    &mut render_pass.forget_lifetime(),
+                );
+            })
+            .unwrap();
+    };
+
+    render_frame(&mut egui_plugin, true);
+    assert_eq!(egui_plugin.detached_viewport(), Some(viewport_id));
+    render_frame(&mut egui_plugin, false);
+    assert_eq!(egui_plugin.detached_viewport(), None);
+}
+
+#[test]
+fn egui_theme_is_dark_by_default_and_can_be_changed() {
+    let draw_context = DrawContext::new(None, None)
+        .block_on()
+        .expect("A headless draw context should be available");
+    let mut egui_plugin = EquiPlugin::new(&draw_context);
+    assert!(egui_plugin.egui_context().style().visuals.dark_mode);
+    egui_plugin.set_theme(egui::Theme::Light);
+    assert!(!egui_plugin.egui_context().style().visuals.dark_mode);
+    let mut style = egui::Style::default();
+    style.spacing.item_spacing = egui::vec2(20.0, 20.0);
+    egui_plugin.set_style(style);
+    assert_eq!(
+        egui_plugin.egui_context().style().spacing.item_spacing,
+        egui::vec2(20.0, 20.0)
+    );
+}
+
+const DEFAULT_SHADER: &str = include_str!("../src/shaders/default.wgsl");
+
+#[test]
+fn scene_plugin_sizes_camera_for_non_square_target() {
+    let draw_context = DrawContext::new(None, Some(Dimensions::new(200, 100)))
+        .block_on()
+        .expect("A headless draw context should be available");
+    let camera = Camera::new_looking_at(Point3::new(0.0, 0.0, -2.5), Point3::new(0.0, 0.0, 0.0));
+    let mut scene_elements = Scene3DPlugin::new(&draw_context, camera);
+    let shader_module = draw_context.create_shader_module(DEFAULT_SHADER);
+    let cube = cube::create_cube_with_colors(
+        &draw_context,
+        &shader_module,
+        &shader_module,
+        scene_elements.scene.scene_uniforms(),
+        &CubeOptions::default(),
+    );
+    scene_elements.scene.add(cube.into_shareable());
+    draw_context
+        .render_scene(|render_pass| {
+            scene_elements.on_render(
+                &draw_context,
+                &TimeInfo::default(),
+                &mut render_pass.forget_lifetime(),
+            );
+        })
+        .unwrap();
+    let pixels = draw_context.read_target_pixels().unwrap();
+    let background = &pixels[0..4];
+    let is_covered = |x: u32, y: u32| {
+        let index = ((y * 200 + x) * 4) as usize;
+        &pixels[index..index + 4] != background
+    };
+    let width = (0..200).filter(|&x| is_covered(x, 50)).count();
+    let height = (0..100).filter(|&y| is_covered(100, y)).count();
+    assert!(width > 10, "{width}");
+    // The front face of the cube stays square on screen
+    assert!(width.abs_diff(height) <= 2, "{width}x{height}");
+}
+
+#[test]
+fn scroll_changes_the_orbit_distance() {
+    let draw_context = DrawContext::new(None, None).block_on().unwrap();
+    let target = Point3::new(0.0, 0.0, 1.0);
+    let mut orbit_controls = OrbitControlsPlugin::new(&draw_context, target, 10.0);
+    let scroll = |lines: f32| WindowEvent::MouseWheel {
+        device_id: DeviceId::dummy(),
+        delta: MouseScrollDelta::LineDelta(0.0, lines),
+        phase: TouchPhase::Moved,
+    };
+    assert!(orbit_controls.on_window_event(&scroll(2.0)).processed);
+    let closer = orbit_controls.distance();
+    assert!(closer < 10.0);
+    orbit_controls.on_window_event(&scroll(-2.0));
+    assert!((orbit_controls.distance() - 10.0).abs() < 1e-4);
+    // The eye stays at the orbit distance from the target
+    orbit_controls.set_distance(4.0);
+    let eye = orbit_controls.camera().eye_position();
+    assert!((eye.distance(target) - 4.0).abs() < 1e-4);
+    assert!(
+        !orbit_controls
+            .on_window_event(&WindowEvent::Focused(true))
+            .processed
+    );
+}
+
+#[test]
+fn ground_grid_does_not_cover_the_scene_geometry() {
+    let mut draw_context = DrawContext::new(None, None).block_on().unwrap();
+    // Without multisampling, the silhouette of the cube does not blend with the grid behind
+    draw_context.multisample_config = MultiSampleConfig::new(false, 1);
+    draw_context.resize(500, 500);
+    let camera = Camera::new_looking_at(Point3::new(0.0, 2.0, -4.0), Point3::new(0.0, 0.0, 0.0));
+    let mut scene_elements = Scene3DPlugin::new(&draw_context, camera);
+    let shader_module = draw_context.create_shader_module(DEFAULT_SHADER);
+    let cube = cube::create_cube_with_colors(
+        &draw_context,
+        &shader_module,
+        &shader_module,
+        scene_elements.scene.scene_uniforms(),
+        &CubeOptions::default(),
+    );
+    scene_elements.scene.add(cube.into_shareable());
+    // The bottom face of the cube lies on the grid
+    let mut ground_grid =
+        GroundGridPlugin::new(&draw_context, scene_elements.scene.scene_uniforms());
+    ground_grid.set_height(-0.5);
+    ground_grid.set_cell_size(0.25);
+    let mut render = |with_grid: bool| {
+        draw_context
+            .render_scene(|render_pass| {
+                let render_pass = &mut render_pass.forget_lifetime();
+                scene_elements.on_render(&draw_context, &TimeInfo::default(), render_pass);
+                if with_grid {
+                    ground_grid.on_render(&draw_context, &TimeInfo::default(), render_pass);
+                }
+            })
+            .unwrap();
+        draw_context.read_target_pixels().unwrap()
+    };
+    let without_grid = render(false);
+    let with_grid = render(true);
+    let background = &without_grid[0..4];
+    let (mut cube_pixels, mut grid_pixels) = (0, 0);
+    for (scene, blended) in without_grid.chunks(4).zip(with_grid.chunks(4)) {
+        if scene == background {
+            grid_pixels += usize::from(scene != blended);
+        } else {
+            cube_pixels += 1;
+            assert_eq!(scene, blended, "The grid shows through the cube");
+        }
+    }
+    assert!(cube_pixels > 1000, "{cube_pixels}");
+    assert!(grid_pixels > 1000, "{grid_pixels}");
+}
+
+#[test]
+fn follow_camera_converges_to_the_target_offset() {
+    let draw_context = DrawContext::new(None, None).block_on().unwrap();
+    let scene = Scene3D::new(&draw_context);
+    let shader_module = draw_context.create_shader_module(DEFAULT_SHADER);
+    let cube = cube::create_cube_with_colors(
+        &draw_context,
+        &shader_module,
+        &shader_module,
+        scene.scene_uniforms(),
+        &CubeOptions::default(),
+    )
+    .into_shareable();
+    let offset = Vector3::new(0.0, 2.0, -6.0);
+    let mut follow_camera = FollowCameraPlugin::new(&draw_context, scene, cube.clone(), offset);
+    let start = Point3::new(0.0, 0.0, 0.0) + offset;
+    assert!(follow_camera.camera().eye_position().distance(start) < 1e-5);
+
+    cube.borrow_mut()
+        .set_translation(Vector3::new(10.0, 0.0, 0.0));
+    let time_info = TimeInfo::new(Instant::now(), Duration::from_secs_f32(1.0 / 60.0));
+    let goal = Point3::new(10.0, 0.0, 0.0) + offset;
+    let mut distances = Vec::new();
+    for _ in 0..120 {
+        follow_camera.on_update(&draw_context, &time_info);
+        distances.push(follow_camera.camera().eye_position().distance(goal));
+    }
+    // Eased, getting closer at each update
+    assert!(distances[0] > 9.0, "{}", distances[0]);
+    assert!(distances.windows(2).all(|pair| pair[1] < pair[0]));
+    assert!(distances[119] < 0.01, "{}", distances[119]);
+
+    // A target moving fast drags the camera along
+    follow_camera.set_max_lag(Some(1.0));
+    cube.borrow_mut()
+        .set_translation(Vector3::new(-100.0, 0.0, 0.0));
+    follow_camera.on_update(&draw_context, &time_info);
+    let goal = Point3::new(-100.0, 0.0, 0.0) + offset;
+    assert!(follow_camera.camera().eye_position().distance(goal) <= 1.0 + 1e-3);
+}
+
+#[test]
+fn camera_path_starts_and_ends_on_the_control_points() {
+    let draw_context = DrawContext::new(None, None).block_on().unwrap();
+    let control_points = [
+        Point3::new(0.0, 1.0, 0.0),
+        Point3::new(4.0, 2.0, 1.0),
+        Point3::new(5.0, 0.0, 6.0),
+    ];
+    let path = Curve::new(&control_points, CurveKind::CatmullRom).unwrap();
+    let mut camera_path = CameraPathPlugin::new(
+        &draw_context,
+        Scene3D::new(&draw_context),
+        path,
+        Duration::from_secs(2),
+    );
+    let eye = |camera_path: &CameraPathPlugin| camera_path.camera().eye_position();
+    assert!(eye(&camera_path).distance(control_points[0]) < 1e-5);
+    camera_path.set_progress(1.0);
+    assert!(eye(&camera_path).distance(control_points[2]) < 1e-4);
+
+    // Driven by the update time, stopping at the end
+    camera_path.set_look(CameraPathLook::Target(Point3::new(0.0, 0.0, 0.0)));
+    camera_path.set_progress(0.0);
+    let time_info = TimeInfo::new(Instant::now(), Duration::from_millis(500));
+    camera_path.on_update(&draw_context, &time_info);
+    assert!((camera_path.progress() - 0.25).abs() < 1e-5);
+    for _ in 0..5 {
+        camera_path.on_update(&draw_context, &time_info);
+    }
+    assert!(camera_path.is_finished());
+    assert!(eye(&camera_path).distance(control_points[2]) < 1e-4);
+
+    // Looping starts over
+    camera_path.set_looping(true);
+    camera_path.on_update(&draw_context, &time_info);
+    assert!((camera_path.progress() - 0.25).abs() < 1e-5);
+}
@@ -0,0 +1,1136 @@
+use cgmath::{Deg, InnerSpace, Matrix4, Point3, Quaternion, Rotation3, Vector3};
+use pollster::FutureExt;
+use std::rc::Rc;
+use wgpu_igniter::cameras::Camera;
+use wgpu_igniter::picking::PickTarget;
+use wgpu_igniter::plugins::scene_3d::{Scene3D, Scene3DUniforms};
+use wgpu_igniter::primitives::arrow;
+use wgpu_igniter::primitives::bounds::Aabb;
+use wgpu_igniter::primitives::capsule;
+use wgpu_igniter::primitives::cube::{self, CubeOptions};
+use wgpu_igniter::primitives::curve;
+use wgpu_igniter::primitives::mesh::{self, MeshData};
+use wgpu_igniter::primitives::parametric;
+use wgpu_igniter::primitives::pbr::{self, PbrMaterial, PbrShading};
+use wgpu_igniter::primitives::phong::{self, PhongLight, PhongMaterial, PhongShading};
+use wgpu_igniter::primitives::sphere;
+use wgpu_igniter::primitives::wirecube;
+use wgpu_igniter::primitives::{
+    Object3D, Object3DInstance, Object3DInstanceGroup, Shareable, Transforms, color,
+};
+use wgpu_igniter::shadows::{ShadowCaster, ShadowMap};
+use wgpu_igniter::support::environment::EnvironmentMap;
+use wgpu_igniter::{
+    BindingSlot, Dimensions, DrawContext, DrawModeParams, Drawable, DrawableBuilder, IndexData,
+    Material, wgpu,
+};
+
+mod common;
+
+use common::create_headless_context;
+
+const DEFAULT_SHADER: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/src/shaders/default.wgsl"
+));
+
+fn create_cube(context: &DrawContext) -> Object3D {
+    let shader_module = context.create_shader_module(DEFAULT_SHADER);
+    let scene = Scene3D::new(context);
+    cube::create_cube_with_colors(
+        context,
+        &shader_module,
+        &shader_module,
+        scene.scene_uniforms(),
+        &CubeOptions::default(),
+    )
+}
+
+fn assert_matrix_eq(left: &Matrix4<f32>, right: &Matrix4<f32>) {
+    let left: &[f32; 16] = left.as_ref();
+    let right: &[f32; 16] = right.as_ref();
+    for (l, r) in left.iter().zip(right) {
+        assert!((l - r).abs() < 1e-5, "{left:?} != {right:?}");
+    }
+}
+
+#[test]
+fn set_trs_matches_manual_composition() {
+    let context = create_headless_context();
+    let mut cube = create_cube(&context);
+    let translation = Vector3::new(1.0, -2.0, 3.0);
+    let rotation = Quaternion::from_axis_angle(Vector3::unit_y(), Deg(30.0));
+    let scale = Vector3::new(2.0, 0.5, 1.5);
+    cube.set_trs(translation, rotation, scale);
+    let expected = Matrix4::from_translation(translation)
+        * Matrix4::from(rotation)
+        * Matrix4::from_nonuniform_scale(scale.x, scale.y, scale.z);
+    assert_matrix_eq(cube.get_transform(), &expected);
+}
+
+#[test]
+fn trs_setters_keep_other_components() {
+    let context = create_headless_context();
+    let mut cube = create_cube(&context);
+    let translation = Vector3::new(1.0, 2.0, 3.0);
+    let rotation = Quaternion::from_axis_angle(Vector3::unit_z(), Deg(45.0));
+    let scale = Vector3::new(2.0, 2.0, 2.0);
+    cube.set_scale(scale);
+    cube.set_rotation(rotation);
+    cube.set_translation(translation);
+    let expected =
+        Matrix4::from_translation(translation) * Matrix4::from(rotation) * Matrix4::from_scale(2.0);
+    assert_matrix_eq(cube.get_transform(), &expected);
+}
+
+#[test]
+fn deferred_transforms_are_uploaded_once_per_flush() {
+    let context = create_headless_context();
+    let mut immediate_cube = create_cube(&context);
+    immediate_cube.apply_transform(Matrix4::from_scale(2.0));
+    immediate_cube.apply_transform(Matrix4::from_scale(2.0));
+    assert_eq!(immediate_cube.uniform_upload_count(), 2);
+
+    let mut scene = Scene3D::new(&context);
+    let cube = create_cube(&context).into_shareable();
+    cube.borrow_mut().set_deferred_upload(true);
+    scene.add_transformable(Rc::clone(&cube));
+    for _ in 0..3 {
+        cube.borrow_mut()
+            .apply_transform(Matrix4::from_translation(Vector3::unit_x()));
+    }
+    cube.borrow_mut().set_opacity(0.5);
+    assert!(cube.borrow().is_dirty());
+    assert_eq!(cube.borrow().uniform_upload_count(), 0);
+    scene.flush_transforms();
+    scene.flush_transforms();
+    assert!(!cube.borrow().is_dirty());
+    assert_eq!(cube.borrow().uniform_upload_count(), 1);
+    assert_matrix_eq(
+        cube.borrow().get_transform(),
+        &Matrix4::from_translation(Vector3::new(3.0, 0.0, 0.0)),
+    );
+}
+
+#[test]
+fn world_bounds_follow_the_transform() {
+    let context = create_headless_context();
+    let mut cube = create_cube(&context);
+    let local_bounds = cube.local_bounds().unwrap();
+    assert_eq!(cube.world_bounds(), Some(local_bounds));
+    cube.set_translation(Vector3::new(2.0, 0.0, -1.0));
+    let world_bounds = cube.world_bounds().unwrap();
+    assert_eq!(world_bounds.min, Point3::new(1.5, -0.5, -1.5));
+    assert_eq!(world_bounds.max, Point3::new(2.5, 0.5, -0.5));
+    cube.apply_transform(Matrix4::from_scale(2.0));
+    let world_bounds = cube.world_bounds().unwrap();
+    assert_eq!(world_bounds.center(), Point3::new(4.0, 0.0, -2.0));
+    assert_eq!(world_bounds.half_extents(), Vector3::new(1.0, 1.0, 1.0));
+
+    cube.set_local_bounds(Aabb::new(
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(1.0, 1.0, 1.0),
+    ));
+    assert_eq!(cube.world_bounds().unwrap().max, Point3::new(6.0, 2.0, 0.0));
+}
+
+#[test]
+fn culled_scene_reports_fewer_draws() {
+    let context = create_headless_context();
+    let mut camera =
+        Camera::new_looking_at(Point3::new(0.0, 0.0, -5.0), Point3::new(0.0, 0.0, 0.0));
+    camera.resize_screen(context.surface_dimensions());
+    let mut scene = Scene3D::new(&context);
+    let visible_cube = create_cube(&context).into_shareable();
+    let hidden_cube = create_cube(&context).into_shareable();
+    hidden_cube
+        .borrow_mut()
+        .set_translation(Vector3::new(0.0, 0.0, -20.0));
+    let drawable = visible_cube.borrow();
+    assert_eq!(drawable.as_ref().vertex_count(), 36);
+    assert_eq!(drawable.as_ref().instance_count(), 1);
+    drop(drawable);
+    scene.add_transformable(visible_cube);
+    scene.add_transformable(hidden_cube);
+    scene.update(&camera);
+
+    let render = |scene: &Scene3D| {
+        context
+            .render_scene(|mut render_pass| scene.render(&mut render_pass))
+            .unwrap();
+        scene.last_draw_count()
+    };
+    assert_eq!(render(&scene), 2);
+    scene.set_frustum_culling(true);
+    assert_eq!(render(&scene), 1);
+}
+
+const INSTANCES_SHADER: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/examples/cube_instances/cube_instances.wgsl"
+));
+
+fn render_cube_instances(depth_prepass: bool) -> Vec<u8> {
+    let context = create_headless_context();
+    let shader_module = context.create_shader_module(INSTANCES_SHADER);
+    let mut scene = Scene3D::new(&context);
+    let mut cubes = cube::create_cube_with_normals_instances(
+        &context,
+        &shader_module,
+        &shader_module,
+        scene.scene_uniforms(),
+        4,
+        &CubeOptions {
+            depth_prepass,
+            ..Default::default()
+        },
+    );
+    cubes.update_instances(|idx, instance| {
+        let offset = idx as f32 * 0.2 - 0.3;
+        instance.set_translation(Vector3::new(offset, offset, 0.5));
+        instance.set_rotation(Quaternion::from_axis_angle(
+            Vector3::new(1.0, 1.0, 0.0).normalize(),
+            Deg(idx as f32 * 20.0),
+        ));
+    });
+    scene.add(cubes.into_shareable());
+    context
+        .render_scene(|mut render_pass| scene.render(&mut render_pass))
+        .unwrap();
+    context.read_target_pixels().unwrap()
+}
+
+#[test]
+fn instance_group_grows_and_shrinks() {
+    let context = create_headless_context();
+    let shader_module = context.create_shader_module(INSTANCES_SHADER);
+    let scene = Scene3D::new(&context);
+    let mut cubes = cube::create_cube_with_normals_instances(
+        &context,
+        &shader_module,
+        &shader_module,
+        scene.scene_uniforms(),
+        2,
+        &CubeOptions::default(),
+    );
+    for count in [10, 100, 3] {
+        cubes.set_count(&context, count);
+        assert_eq!(cubes.count(), count);
+        cubes.update_instances(|idx, instance| {
+            instance.set_translation(Vector3::new(idx as f32 * 0.01, 0.0, 0.5));
+        });
+        context
+            .render_scene(|mut render_pass| {
+                let drawable: &Drawable = cubes.as_ref();
+                assert!(
+                    drawable
+                        .render_instances(&mut render_pass, 0..count)
+                        .is_ok()
+                );
+                assert!(
+                    drawable
+                        .render_instances(&mut render_pass, 0..count + 1)
+                        .is_err()
+                );
+            })
+            .unwrap();
+    }
+}
+
+#[test]
+fn instances_regrown_within_capacity_start_from_identity() {
+    let context = create_headless_context();
+    let shader_module = context.create_shader_module(INSTANCES_SHADER);
+    let mut camera =
+        Camera::new_looking_at(Point3::new(0.0, 0.0, -5.0), Point3::new(0.0, 0.0, 0.0));
+    camera.resize_screen(context.surface_dimensions());
+    let render_cubes = |configure: &dyn Fn(&mut Object3DInstanceGroup)| {
+        let mut scene = Scene3D::new(&context);
+        let mut cubes = cube::create_cube_with_normals_instances(
+            &context,
+            &shader_module,
+            &shader_module,
+            scene.scene_uniforms(),
+            2,
+            &CubeOptions::default(),
+        );
+        configure(&mut cubes);
+        scene.add(cubes.into_shareable());
+        scene.update(&camera);
+        context
+            .render_scene(|mut render_pass| scene.render(&mut render_pass))
+            .unwrap();
+        context.read_target_pixels().unwrap()
+    };
+    let move_out_of_view = |cubes: &mut Object3DInstanceGroup| {
+        cubes.update_instances(|_, instance| {
+            instance.set_translation(Vector3::new(100.0, 0.0, 0.0));
+        });
+    };
+    let empty_pixels = render_cubes(&move_out_of_view);
+    // A new instance is drawn at the identity transform, not where the dropped one was
+    let regrown_pixels = render_cubes(&|cubes| {
+        move_out_of_view(cubes);
+        cubes.set_count(&context, 1);
+        cubes.set_count(&context, 2);
+    });
+    assert!(regrown_pixels != empty_pixels);
+    let expected_pixels = render_cubes(&|cubes| {
+        move_out_of_view(cubes);
+        cubes.set_count(&context, 1);
+        cubes.set_count(&context, 2);
+        cubes.update_instances(|idx, instance| {
+            if idx == 1 {
+                *instance = Object3DInstance::default();
+            }
+        });
+    });
+    assert!(regrown_pixels == expected_pixels);
+}
+
+#[test]
+fn depth_prepass_gives_identical_output() {
+    let without_prepass = render_cube_instances(false);
+    let with_prepass = render_cube_instances(true);
+    let background = &without_prepass[..4];
+    assert!(
+        without_prepass
+            .chunks_exact(4)
+            .any(|pixel| pixel != background)
+    );
+    assert_eq!(without_prepass, with_prepass);
+}
+
+const SHADOW_SHADER: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/examples/shadow_map/shadow_map.wgsl"
+));
+const SHADOW_CASTER_SHADER: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/examples/shadow_map/shadow_caster.wgsl"
+));
+
+fn render_ground_with_shadow(cube_casts_shadow: bool) -> Vec<u8> {
+    let context = create_headless_context();
+    let shader_module =
+        context.create_shader_module(&format!("{}\n{}", ShadowMap::SHADER_SNIPPET, SHADOW_SHADER));
+    let shadow_shader_module = context.create_shader_module(&format!(
+        "{}\n{}",
+        ShadowMap::SHADER_SNIPPET,
+        SHADOW_CASTER_SHADER
+    ));
+    let mut shadow_map = ShadowMap::new(&context, 512);
+    shadow_map.set_light_view_proj(
+        Matrix4::from_translation(Vector3::new(0.0, 0.0, 0.45))
+            * Matrix4::from_scale(0.4)
+            * Matrix4::from_angle_y(Deg(30.0)),
+    );
+    let scene = Scene3D::new(&context);
+    let options = CubeOptions {
+        shadow_caster: Some(ShadowCaster {
+            shadow_map: &shadow_map,
+            vtx_module: &shadow_shader_module,
+        }),
+        ..Default::default()
+    };
+    let create_cube = || {
+        cube::create_cube_with_normals(
+            &context,
+            &shader_module,
+            &shader_module,
+            scene.scene_uniforms(),
+            &options,
+        )
+    };
+    let mut ground = create_cube();
+    ground.set_scale(Vector3::new(1.0, 1.0, 0.05));
+    ground.set_translation(Vector3::new(0.0, 0.0, 0.8));
+    let mut cube = create_cube();
+    cube.set_scale(Vector3::new(0.3, 0.3, 0.3));
+    cube.set_translation(Vector3::new(0.0, 0.0, 0.35));
+
+    shadow_map.render(&context, |render_pass| {
+        ground.as_ref().render_shadow(render_pass);
+        if cube_casts_shadow {
+            cube.as_ref().render_shadow(render_pass);
+        }
+    });
+    context
+        .render_scene(|mut render_pass| {
+            ground.as_ref().render(&mut render_pass);
+            cube.as_ref().render(&mut render_pass);
+        })
+        .unwrap();
+    context.read_target_pixels().unwrap()
+}
+
+#[test]
+fn shadow_caster_darkens_the_ground() {
+    let brightness = |pixels: &[u8]| pixels.iter().map(|&value| u64::from(value)).sum::<u64>();
+    let lit = render_ground_with_shadow(false);
+    let shadowed = render_ground_with_shadow(true);
+    assert!(brightness(&shadowed) < brightness(&lit));
+}
+
+fn triangle_mesh() -> MeshData {
+    MeshData {
+        positions: vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]],
+        normals: vec![[0.0, 0.0, 1.0]; 3],
+        indices: vec![0, 1, 2],
+    }
+}
+
+#[test]
+fn merge_two_translated_triangles() {
+    let merged = mesh::merge(&[
+        (
+            Matrix4::from_translation(Vector3::new(-2.0, 0.0, 0.0)),
+            triangle_mesh(),
+        ),
+        (
+            Matrix4::from_translation(Vector3::new(2.0, 0.0, 1.0)),
+            triangle_mesh(),
+        ),
+    ]);
+    assert_eq!(merged.positions.len(), 6);
+    assert_eq!(
+        merged.positions,
+        vec![
+            [-2.0, 0.0, 0.0],
+            [-1.0, 0.0, 0.0],
+            [-2.0, 1.0, 0.0],
+            [2.0, 0.0, 1.0],
+            [3.0, 0.0, 1.0],
+            [2.0, 1.0, 1.0],
+        ]
+    );
+    assert_eq!(merged.indices, vec![0, 1, 2, 3, 4, 5]);
+    assert_eq!(merged.normals, vec![[0.0, 0.0, 1.0]; 6]);
+}
+
+#[test]
+fn merge_transforms_normals_by_inverse_transpose() {
+    let mesh = MeshData {
+        positions: vec![[0.0; 3]],
+        normals: vec![[1.0, 1.0, 0.0]],
+        indices: vec![0],
+    };
+    let merged = mesh::merge(&[(Matrix4::from_nonuniform_scale(2.0, 1.0, 1.0), mesh)]);
+    let normal = Vector3::from(merged.normals[0]);
+    let expected = Vector3::new(0.5, 1.0, 0.0).normalize();
+    assert!((normal - expected).magnitude() < 1e-6, "{normal:?}");
+}
+
+#[test]
+fn merge_accepts_singular_transforms() {
+    let merged = mesh::merge(&[(
+        Matrix4::from_nonuniform_scale(1.0, 1.0, 0.0),
+        triangle_mesh(),
+    )]);
+    assert_eq!(merged.positions.len(), 3);
+    assert!(
+        merged
+            .normals
+            .iter()
+            .flatten()
+            .all(|value| value.is_finite())
+    );
+}
+
+#[test]
+fn merge_flips_the_winding_of_mirrored_meshes() {
+    let merged = mesh::merge(&[
+        (Matrix4::from_scale(1.0), triangle_mesh()),
+        (
+            Matrix4::from_nonuniform_scale(-1.0, 1.0, 1.0),
+            triangle_mesh(),
+        ),
+    ]);
+    assert_eq!(merged.indices, vec![0, 1, 2, 3, 5, 4]);
+    // The normals are mirrored, not reversed
+    assert_eq!(merged.normals[3], [0.0, 0.0, 1.0]);
+    let mesh = MeshData {
+        positions: vec![[0.0; 3]],
+        normals: vec![[1.0, 1.0, 0.0]],
+        indices: vec![0],
+    };
+    let merged = mesh::merge(&[(Matrix4::from_nonuniform_scale(-1.0, 1.0, 1.0), mesh)]);
+    let normal = Vector3::from(merged.normals[0]);
+    let expected = Vector3::new(-1.0, 1.0, 0.0).normalize();
+    assert!((normal - expected).magnitude() < 1e-6, "{normal:?}");
+}
+
+const INSTANCES_PICK_SHADER: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/examples/cube_instances/cube_instances_pick.wgsl"
+));
+
+#[test]
+fn pick_returns_instance_under_cursor() {
+    let context = create_headless_context();
+    let shader_module = context.create_shader_module(INSTANCES_SHADER);
+    let pick_module =
+        context.create_shader_module(&[INSTANCES_PICK_SHADER, PickTarget::SHADER_SNIPPET].concat());
+    let mut scene = Scene3D::new(&context);
+    let mut cubes = cube::create_cube_with_normals_instances(
+        &context,
+        &shader_module,
+        &shader_module,
+        scene.scene_uniforms(),
+        4,
+        &CubeOptions {
+            pick_module: Some(&pick_module),
+            ..Default::default()
+        },
+    );
+    cubes.set_transform(
+        Matrix4::from_translation(Vector3::new(0.0, 0.0, 0.5)) * Matrix4::from_scale(0.4),
+    );
+    let positions = [(-1.5, 0.0), (1.5, 0.0), (0.0, 1.5), (0.0, -1.5)];
+    cubes.update_instances(move |idx, instance| {
+        let (x, y) = positions[idx];
+        instance.set_translation(Vector3::new(x, y, 0.0));
+    });
+    scene.add(cubes.into_shareable());
+    for (index, (x, y)) in positions.into_iter().enumerate() {
+        let cursor_ndc = [x * 0.4, y * 0.4];
+        assert_eq!(
+            scene.pick_instance(&context, cursor_ndc),
+            Some((0, index as u32))
+        );
+        assert_eq!(scene.pick(&context, cursor_ndc), Some(0));
+    }
+    assert_eq!(scene.pick(&context, [0.0, 0.0]), None);
+    assert_eq!(scene.pick(&context, [2.0, 0.0]), None);
+}
+
+const FLAT_SHADER: &str = r"
+@vertex
+fn vtx_main(@location(0) position: vec3<f32>) -> @builtin(position) vec4<f32> {
+    return vec4<f32>(position, 1.0);
+}
+
+@fragment
+fn frg_main() -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0, 1.0, 1.0, 1.0);
+}
+";
+
+// Clockwise in normalized device coordinates, so facing away with the default culling
+const CW_TRIANGLE: [[f32; 3]; 3] = [[-1.0, -1.0, 0.5], [0.0, 1.0, 0.5], [1.0, -1.0, 0.5]];
+
+fn render_triangle(indices: &[u32], double_sided: bool) -> Vec<u8> {
+    let mut context = create_headless_context();
+    context.set_clear_color(Some(wgpu::Color::BLACK));
+    let shader_module = context.create_shader_module(FLAT_SHADER);
+    let mut builder = DrawableBuilder::new(
+        &context,
+        &shader_module,
+        &shader_module,
+        DrawModeParams::Indexed {
+            index_data: IndexData::from_u32_auto(indices),
+        },
+    );
+    builder
+        .add_attribute(
+            0,
+            wgpu::VertexStepMode::Vertex,
+            &CW_TRIANGLE,
+            wgpu::VertexFormat::Float32x3,
+        )
+        .unwrap()
+        .set_double_sided(double_sided);
+    let triangle = builder.build();
+    context
+        .render_scene(|mut render_pass| triangle.render(&mut render_pass))
+        .unwrap();
+    context.read_target_pixels().unwrap()
+}
+
+fn center_pixel(pixels: &[u8]) -> &[u8] {
+    let offset = (250 * 500 + 250) * 4;
+    &pixels[offset..offset + 4]
+}
+
+#[test]
+fn double_sided_renders_clockwise_triangle() {
+    assert_eq!(
+        center_pixel(&render_triangle(&[0, 1, 2], false)),
+        [0, 0, 0, 255]
+    );
+    assert_eq!(
+        center_pixel(&render_triangle(&[0, 1, 2], true)),
+        [255, 255, 255, 255]
+    );
+}
+
+#[test]
+fn flip_winding_makes_clockwise_triangle_front_facing() {
+    let mut mesh = MeshData {
+        positions: CW_TRIANGLE.to_vec(),
+        normals: vec![[0.0, 0.0, 1.0]; 3],
+        indices: vec![0, 1, 2, 3, 4, 5],
+    };
+    mesh.flip_winding();
+    assert_eq!(mesh.indices, [0, 2, 1, 3, 5, 4]);
+    assert_eq!(
+        center_pixel(&render_triangle(&mesh.indices[..3], false)),
+        [255, 255, 255, 255]
+    );
+}
+
+const RED_SHADER: &str = r"
+@fragment
+fn frg_main() -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0, 0.0, 0.0, 1.0);
+}
+";
+
+#[test]
+fn triangle_edges_are_unique() {
+    let edges = mesh::triangle_edges(&[0, 1, 2, 0, 2, 3]);
+    assert_eq!(edges, [0, 1, 1, 2, 2, 0, 2, 3, 3, 0]);
+}
+
+#[test]
+fn wireframe_overlay_draws_edges_over_faces() {
+    let mut context = create_headless_context();
+    context.set_clear_color(Some(wgpu::Color::BLACK));
+    let shader_module = context.create_shader_module(FLAT_SHADER);
+    let wireframe_module = context.create_shader_module(RED_SHADER);
+    // Tilted in depth, so the shared diagonal is not at a constant depth
+    let quad = [
+        [-0.5f32, -0.5, 0.2],
+        [0.5, -0.5, 0.8],
+        [0.5, 0.5, 0.8],
+        [-0.5, 0.5, 0.2],
+    ];
+    let mut builder = DrawableBuilder::new(
+        &context,
+        &shader_module,
+        &shader_module,
+        DrawModeParams::Indexed {
+            index_data: IndexData::from_u32_auto(&[0, 1, 2, 0, 2, 3]),
+        },
+    );
+    builder
+        .add_attribute(
+            0,
+            wgpu::VertexStepMode::Vertex,
+            &quad,
+            wgpu::VertexFormat::Float32x3,
+        )
+        .unwrap()
+        .set_wireframe_overlay(&wireframe_module);
+    let drawable = builder.build();
+    assert!(drawable.has_wireframe_overlay());
+    context
+        .render_scene(|mut render_pass| drawable.render(&mut render_pass))
+        .unwrap();
+    let pixels = context.read_target_pixels().unwrap();
+    let pixel = |x: usize, y: usize| &pixels[(y * 500 + x) * 4..(y * 500 + x) * 4 + 4];
+    // Faces stay white away from the edges
+    assert_eq!(pixel(300, 250), [255, 255, 255, 255]);
+    // Along the diagonal, the lines win the depth test against the faces
+    let covered = (150..350)
+        .filter(|&x| {
+            [pixel(x, 499 - x), pixel(x, 500 - x)]
+                .iter()
+                .any(|p| p[1] < 128)
+        })
+        .count();
+    assert!(
+        covered > 190,
+        "Only {covered} pixels of the diagonal are red"
+    );
+}
+
+#[test]
+fn srgb_mid_gray_converts_to_linear() {
+    assert!((color::srgb_to_linear(0.5) - 0.214_041).abs() < 1e-5);
+    assert!((color::srgb8_to_linear_rgb([128, 128, 128])[0] - 0.215_861).abs() < 1e-5);
+    assert!((color::linear_to_srgb(0.214_041) - 0.5).abs() < 1e-5);
+    // Components at 0 and 1, used by the color constants, are the same in both spaces
+    assert_eq!(
+        color::srgb_to_linear_rgb(color::COLOR_YELLOW),
+        color::COLOR_YELLOW
+    );
+    assert_eq!(color::srgb_to_linear(0.0), 0.0);
+}
+
+#[test]
+fn srgb_shader_snippet_compiles() {
+    let context = create_headless_context();
+    let shader = [
+        color::SHADER_SNIPPET,
+        r"
+@fragment
+fn frg_main() -> @location(0) vec4<f32> {
+    return vec4<f32>(srgb_to_linear(vec3<f32>(0.5)), 1.0);
+}
+",
+    ]
+    .concat();
+    context
+        .device
+        .push_error_scope(wgpu::ErrorFilter::Validation);
+    context.create_shader_module(&shader);
+    assert!(context.device.pop_error_scope().block_on().is_none());
+}
+
+#[test]
+fn cubes_built_from_a_material_share_its_pipeline() {
+    let context = create_headless_context();
+    let shader_module = context.create_shader_module(DEFAULT_SHADER);
+    let scene = Scene3D::new(&context);
+    let mut material = Material::new(&shader_module, &shader_module);
+    material
+        .set_blend_option(cube::CONSTANT_ALPHA_BLEND)
+        .add_binding_slot(BindingSlot {
+            bind_group: DrawContext::BIND_GROUP_INDEX_CAMERA,
+            binding: Scene3DUniforms::BINDING_CAMERA_MAT,
+            resource: &scene.scene_uniforms().camera_mat,
+        });
+    let left = cube::create_cube_from_material(&context, &material).unwrap();
+    let mut right = cube::create_cube_from_material(&context, &material).unwrap();
+    assert_eq!(material.cached_pipeline_count(), 1);
+    right.set_translation(Vector3::new(1.0, 0.0, 0.0));
+    context
+        .render_scene(|mut render_pass| {
+            left.as_ref().render(&mut render_pass);
+            right.as_ref().render(&mut render_pass);
+        })
+        .unwrap();
+}
+
+const STRAIGHT_ALPHA_RED_SHADER: &str = r"
+@vertex
+fn vtx_main(@builtin(vertex_index) vertex: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32((vertex << 1u) & 2u), f32(vertex & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.5, 1.0);
+}
+
+@fragment
+fn frg_main() -> @location(0) vec4<f32> {
+    return premultiply_alpha(vec4<f32>(1.0, 0.0, 0.0, 0.5));
+}
+";
+
+#[test]
+fn premultiplied_half_red_over_white_is_pink() {
+    let mut context = create_headless_context();
+    context.set_clear_color(Some(wgpu::Color::WHITE));
+    let shader_module =
+        context.create_shader_module(&[color::SHADER_SNIPPET, STRAIGHT_ALPHA_RED_SHADER].concat());
+    let mut builder = DrawableBuilder::new(
+        &context,
+        &shader_module,
+        &shader_module,
+        DrawModeParams::Direct { vertex_count: 3 },
+    );
+    builder.set_blend_option(cube::PREMULTIPLIED_ALPHA_BLEND);
+    let drawable = builder.build();
+    context
+        .render_scene(|mut render_pass| drawable.render(&mut render_pass))
+        .unwrap();
+    let pixels = context.read_target_pixels().unwrap();
+    let center = (250 * 500 + 250) * 4;
+    // Blended in linear space, (1.0, 0.5, 0.5) encoded by the sRGB target, alpha stays opaque
+    let pixel = &pixels[center..center + 4];
+    let expected = [255u8, 188, 188, 255];
+    assert!(
+        pixel.iter().zip(expected).all(|(c, e)| c.abs_diff(e) <= 2),
+        "{pixel:?} != {expected:?}"
+    );
+}
+
+fn render_premultiplied_cube_over_white(opacity: f32) -> [u8; 4] {
+    let mut context = create_headless_context();
+    context.set_clear_color(Some(wgpu::Color::WHITE));
+    let shader_module = context.create_shader_module(cube::FLAT_PREMULTIPLIED_SHADER);
+    let mut camera =
+        Camera::new_looking_at(Point3::new(0.0, 0.0, -5.0), Point3::new(0.0, 0.0, 0.0));
+    camera.resize_screen(context.surface_dimensions());
+    let mut scene = Scene3D::new(&context);
+    let mut cube = cube::create_cube_with_colors(
+        &context,
+        &shader_module,
+        &shader_module,
+        scene.scene_uniforms(),
+        &CubeOptions {
+            with_alpha: true,
+            premultiplied_alpha: true,
+            ..Default::default()
+        },
+    );
+    cube.set_opacity(opacity);
+    scene.add(cube.into_shareable());
+    scene.update(&camera);
+    context
+        .render_scene(|mut render_pass| scene.render(&mut render_pass))
+        .unwrap();
+    let pixels = context.read_target_pixels().unwrap();
+    let center = (250 * 500 + 250) * 4;
+    pixels[center..center + 4].try_into().unwrap()
+}
+
+#[test]
+fn premultiplied_cube_blends_with_its_opacity() {
+    let opaque = render_premultiplied_cube_over_white(1.0);
+    assert_eq!(opaque[3], 255);
+    assert_ne!(
+        opaque,
+        [255, 255, 255, 255],
+        "the cube should cover the center"
+    );
+    // Half of the cube color and half of the white background, composited in linear space
+    let linear = color::srgb8_to_linear_rgb([opaque[0], opaque[1], opaque[2]]);
+    let expected = linear.map(|c| (color::linear_to_srgb(0.5 * c + 0.5) * 255.0).round() as u8);
+    let pixel = render_premultiplied_cube_over_white(0.5);
+    assert_eq!(pixel[3], 255, "alpha should stay opaque");
+    assert!(
+        pixel.iter().zip(expected).all(|(c, e)| c.abs_diff(e) <= 2),
+        "{pixel:?} != {expected:?}"
+    );
+}
+
+#[test]
+fn planar_parametric_surface_has_upward_normals() {
+    let mesh = parametric::parametric_mesh(|u, v| [u * 2.0, 0.0, v * 3.0], 4, 3);
+    assert_eq!(mesh.positions.len(), 5 * 4);
+    assert_eq!(mesh.indices.len(), 4 * 3 * 6);
+    for normal in &mesh.normals {
+        assert!(
+            (Vector3::from(*normal) - Vector3::unit_y()).magnitude() < 1e-4,
+            "{normal:?}"
+        );
+    }
+    // Same winding convention as the cube, whose top face normal is +y
+    let [a, b, c] = [0, 1, 2].map(|i| Vector3::from(mesh.positions[mesh.indices[i] as usize]));
+    assert!((b - a).cross(c - a).y < 0.0);
+}
+
+#[test]
+fn parametric_sphere_normals_at_poles_and_seam() {
+    use std::f32::consts::{PI, TAU};
+    let sphere = |u: f32, v: f32| {
+        let (theta, phi) = (u * TAU, v * PI);
+        [theta.cos() * phi.sin(), -phi.cos(), theta.sin() * phi.sin()]
+    };
+    let mesh = parametric::parametric_mesh(sphere, 16, 8);
+    for (position, normal) in mesh.positions.iter().zip(&mesh.normals) {
+        let normal = Vector3::from(*normal);
+        assert!((normal.magnitude() - 1.0).abs() < 1e-3, "{normal:?}");
+        // Radial, up to the sign fixed by the orientation of the parameters
+        assert!(
+            normal.dot(Vector3::from(*position)).abs() > 0.99,
+            "{normal:?}"
+        );
+    }
+    // Duplicated seam vertices share their normal
+    for row in mesh.normals.chunks(17) {
+        assert!((Vector3::from(row[0]) - Vector3::from(row[16])).magnitude() < 1e-3);
+    }
+}
+
+fn farthest_point(mesh: &MeshData, from: Point3<f32>) -> Point3<f32> {
+    mesh.positions
+        .iter()
+        .map(|&position| Point3::from(position))
+        .max_by(|a, b| (a - from).magnitude().total_cmp(&(b - from).magnitude()))
+        .unwrap()
+}
+
+#[test]
+fn arrow_tip_is_at_its_end_point() {
+    let from = Point3::new(0.0, 0.0, 0.0);
+    let to = Point3::new(2.0, 0.0, 0.0);
+    let mesh = arrow::arrow_mesh(from, to, 0.05, 0.3);
+    assert!((farthest_point(&mesh, from) - to).magnitude() < 1e-5);
+    // Every vertex lies between both ends along the direction
+    for position in &mesh.positions {
+        assert!((-1e-5..=2.0 + 1e-5).contains(&position[0]), "{position:?}");
+    }
+    // Antiparallel to the +y axis the mesh is built along
+    let from = Point3::new(1.0, 3.0, -1.0);
+    let to = Point3::new(1.0, 1.0, -1.0);
+    let mesh = arrow::arrow_mesh(from, to, 0.05, 0.3);
+    assert!((farthest_point(&mesh, from) - to).magnitude() < 1e-5);
+    for normal in &mesh.normals {
+        assert!((Vector3::from(*normal).magnitude() - 1.0).abs() < 1e-3);
+    }
+}
+
+#[test]
+fn straight_control_points_give_collinear_samples() {
+    let control_points = [
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(1.0, 1.0, 0.0),
+        Point3::new(1.5, 1.5, 0.0),
+        Point3::new(4.0, 4.0, 0.0),
+    ];
+    let direction = Vector3::new(1.0, 1.0, 0.0).normalize();
+    for kind in [curve::CurveKind::Bezier, curve::CurveKind::CatmullRom] {
+        let curve = curve::Curve::new(&control_points, kind).unwrap();
+        let samples = curve.samples(20);
+        assert_eq!(samples.len(), 20);
+        for sample in &samples {
+            let offset = sample - control_points[0];
+            assert!(
+                offset.cross(direction).magnitude() < 1e-4,
+                "{kind:?} {sample:?}"
+            );
+        }
+        // Both kinds end on the first and last control points
+        assert!((samples[0] - control_points[0]).magnitude() < 1e-5);
+        assert!((samples[19] - control_points[3]).magnitude() < 1e-4);
+    }
+}
+
+#[test]
+fn curve_samples_are_evenly_spaced_along_the_arc() {
+    // Unevenly spaced control points, which would cluster samples by parameter
+    let control_points = [
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(0.5, 1.0, 0.0),
+        Point3::new(4.0, 1.0, 2.0),
+        Point3::new(5.0, -2.0, 0.0),
+    ];
+    let curve = curve::Curve::new(&control_points, curve::CurveKind::CatmullRom).unwrap();
+    // The spline goes through every control point
+    for (index, control_point) in control_points.iter().enumerate() {
+        #[allow(clippy::cast_precision_loss)]
+        let point = curve.evaluate(index as f32 / 3.0);
+        assert!((point - control_point).magnitude() < 1e-5);
+    }
+    let samples = curve.samples(65);
+    // Chords, slightly shorter than the arcs in the bends
+    let spacing = curve.length() / 64.0;
+    for pair in samples.windows(2) {
+        let distance = (pair[1] - pair[0]).magnitude();
+        assert!(
+            (distance - spacing).abs() < spacing * 0.02,
+            "{distance} {spacing}"
+        );
+    }
+    assert!(curve::Curve::new(&[], curve::CurveKind::Bezier).is_err());
+}
+
+#[test]
+fn uv_sphere_has_radial_normals_and_no_degenerate_triangles() {
+    for (rings, sectors) in [(1, 0), (8, 12)] {
+        let mesh = sphere::uv_sphere_mesh(rings, sectors);
+        let (rings, sectors) = (rings.max(3), sectors.max(3));
+        assert_eq!(mesh.positions.len() as u32, 2 + (rings - 1) * sectors);
+        assert_eq!(mesh.indices.len() as u32, 6 * sectors * (rings - 1));
+        for (position, normal) in mesh.positions.iter().zip(&mesh.normals) {
+            let (position, normal) = (Vector3::from(*position), Vector3::from(*normal));
+            assert!((position.magnitude() - 0.5).abs() < 1e-5);
+            assert!((normal - position * 2.0).magnitude() < 1e-5);
+        }
+        for triangle in mesh.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| Vector3::from(mesh.positions[triangle[i] as usize]));
+            let cross = (b - a).cross(c - a);
+            assert!(cross.magnitude() > 1e-6, "{triangle:?}");
+            // Same winding convention as the cube
+            assert!(cross.dot(a + b + c) < 0.0, "{triangle:?}");
+        }
+    }
+}
+
+#[test]
+fn uv_sphere_instances_are_rendered() {
+    let context = create_headless_context();
+    let shader_module = context.create_shader_module(INSTANCES_SHADER);
+    let mut scene = Scene3D::new(&context);
+    let mut spheres = sphere::create_uv_sphere_instances(
+        &context,
+        &shader_module,
+        &shader_module,
+        scene.scene_uniforms(),
+        16,
+        24,
+        &mesh::MeshOptions {
+            instances: Some(2),
+            ..Default::default()
+        },
+    );
+    spheres.update_instances(|idx, instance| {
+        instance.set_translation(Vector3::new(idx as f32 * 1.2 - 0.6, 0.0, 0.55));
+    });
+    scene.add(spheres.into_shareable());
+    context
+        .render_scene(|mut render_pass| scene.render(&mut render_pass))
+        .unwrap();
+    let pixels = context.read_target_pixels().unwrap();
+    let pixel = |x: usize, y: usize| &pixels[(y * 500 + x) * 4..(y * 500 + x) * 4 + 4];
+    // Both spheres cover their center, the gap between them is left empty
+    assert_ne!(pixel(100, 250), pixel(250, 250));
+    assert_ne!(pixel(400, 250), pixel(250, 250));
+    assert_eq!(pixel(250, 250), pixel(10, 10));
+}
+
+#[test]
+fn capsule_vertex_count_and_continuous_normals() {
+    for (rings, segments) in [(0, 0), (4, 10)] {
+        let mesh = capsule::capsule_mesh(0.5, 2.0, rings, segments);
+        let (rings, segments) = (rings.max(2), segments.max(3));
+        assert_eq!(mesh.positions.len() as u32, 2 + 2 * rings * segments);
+        assert_eq!(mesh.indices.len() as u32, 3 * 4 * rings * segments);
+        for (position, normal) in mesh.positions.iter().zip(&mesh.normals) {
+            let (position, normal) = (Vector3::from(*position), Vector3::from(*normal));
+            // On the surface: at the radius from the axis segment, along the normal
+            let axis_point = Vector3::new(0.0, position.y.clamp(-1.0, 1.0), 0.0);
+            assert!(((position - axis_point).magnitude() - 0.5).abs() < 1e-5);
+            assert!((normal - (position - axis_point) * 2.0).magnitude() < 1e-4);
+        }
+        for triangle in mesh.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| Vector3::from(mesh.positions[triangle[i] as usize]));
+            let cross = (b - a).cross(c - a);
+            assert!(cross.magnitude() > 1e-6, "{triangle:?}");
+            assert!(cross.dot(a + b + c) < 0.0, "{triangle:?}");
+        }
+    }
+}
+
+const FLAT_COLOR_SHADER: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/src/shaders/flat.wgsl"
+));
+
+#[test]
+fn wire_cube_draws_its_twelve_edges() {
+    let half_extents = Vector3::new(1.0, 2.0, 3.0);
+    let edges = wirecube::wire_cube_edges(half_extents);
+    assert_eq!(edges.len(), 24);
+    let mut lengths_per_axis = [0; 3];
+    for edge in edges.chunks_exact(2) {
+        let delta = Vector3::from(edge[1]) - Vector3::from(edge[0]);
+        // Along a single axis, across the whole box
+        let axes: Vec<_> = (0..3).filter(|&axis| delta[axis] != 0.0).collect();
+        assert_eq!(axes.len(), 1, "{edge:?}");
+        assert_eq!(delta[axes[0]], 2.0 * half_extents[axes[0]]);
+        lengths_per_axis[axes[0]] += 1;
+    }
+    assert_eq!(lengths_per_axis, [4, 4, 4]);
+
+    let context = create_headless_context();
+    let shader_module = context.create_shader_module(FLAT_COLOR_SHADER);
+    let mut scene = Scene3D::new(&context);
+    let wire_cube = wirecube::create_wire_cube(
+        &context,
+        &shader_module,
+        &shader_module,
+        scene.scene_uniforms(),
+        Vector3::new(0.5, 0.5, 0.25),
+        color::COLOR_RED,
+    );
+    assert_eq!(wire_cube.as_ref().vertex_count(), 24);
+    scene.add(wire_cube.into_shareable());
+    context
+        .render_scene(|mut render_pass| scene.render(&mut render_pass))
+        .unwrap();
+}
+
+// Pixels brighter than half the specular color, the material having no ambient nor diffuse term
+fn count_highlight_pixels(shininess: f32) -> usize {
+    let context = create_headless_context();
+    let camera = Camera::default();
+    let mut scene = Scene3D::new(&context);
+    let shading = PhongShading::new(
+        &context,
+        &PhongMaterial {
+            ambient: [0.0; 3],
+            diffuse: [0.0; 3],
+            specular: [1.0; 3],
+            shininess,
+        },
+        // From the camera towards the sphere, so the highlight faces the camera
+        &PhongLight::Directional {
+            direction: Vector3::unit_z(),
+            color: [1.0; 3],
+        },
+    );
+    let mut sphere = phong::create_phong_mesh(
+        &context,
+        scene.scene_uniforms(),
+        &sphere::uv_sphere_mesh(32, 64),
+        &shading,
+        &mesh::MeshOptions::default(),
+    );
+    sphere.set_scale(Vector3::new(8.0, 8.0, 8.0));
+    scene.add(sphere.into_shareable());
+    scene.update(&camera);
+    context
+        .render_scene(|mut render_pass| scene.render(&mut render_pass))
+        .unwrap();
+    let pixels = context.read_target_pixels().unwrap();
+    // Linear 0.5 once encoded in sRGB
+    pixels
+        .chunks_exact(4)
+        .filter(|pixel| pixel[0] > 188)
+        .count()
+}
+
+#[test]
+fn phong_highlight_narrows_with_shininess() {
+    let wide_highlight = count_highlight_pixels(8.0);
+    let narrow_highlight = count_highlight_pixels(64.0);
+    assert!(narrow_highlight > 0);
+    assert!(
+        narrow_highlight < wide_highlight,
+        "{narrow_highlight} pixels should be fewer than {wide_highlight}"
+    );
+}
+
+// Brightest red value of a sphere lit from the camera
+fn render_pbr_sphere(
+    context: &DrawContext,
+    roughness: f32,
+    environment_map: Option<&EnvironmentMap>,
+) -> u8 {
+    let camera = Camera::default();
+    let mut scene = Scene3D::new(context);
+    // Black dielectric, only its specular reflection is visible
+    let shading = PbrShading::new(
+        context,
+        &PbrMaterial {
+            base_color: [0.0; 3],
+            metallic: 0.0,
+            roughness,
+        },
+        &PhongLight::Directional {
+            direction: Vector3::unit_z(),
+            color: [4.0; 3],
+        },
+        environment_map,
+    );
+    let mut sphere = pbr::create_pbr_mesh(
+        context,
+        scene.scene_uniforms(),
+        &sphere::uv_sphere_mesh(32, 64),
+        &shading,
+        &mesh::MeshOptions::default(),
+    );
+    sphere.set_scale(Vector3::new(8.0, 8.0, 8.0));
+    scene.add(sphere.into_shareable());
+    scene.update(&camera);
+    context
+        .render_scene(|mut render_pass| scene.render(&mut render_pass))
+        .unwrap();
+    let pixels = context.read_target_pixels().unwrap();
+    pixels.chunks_exact(4).map(|pixel| pixel[0]).max().unwrap()
+}
+
+#[test]
+fn pbr_rough_dielectric_has_no_sharp_highlight() {
+    let mut context = create_headless_context();
+    context.set_clear_color(Some(wgpu::Color::BLACK));
+    assert!(render_pbr_sphere(&context, 0.1, None) > 200);
+    assert!(render_pbr_sphere(&context, 1.0, None) < 64);
+    let sky = vec![[0.5, 0.5, 0.5, 1.0]; 8 * 4];
+    let environment_map =
+        EnvironmentMap::from_equirectangular(&context, Dimensions::new(8, 4), &sky).unwrap();
+    // The ambient term of a black material stays black
+    assert!(render_pbr_sphere(&context, 1.0, Some(&environment_map)) < 64);
+}
@@ -0,0 +1,190 @@
+use std::time::Duration;
+use wgpu_igniter::wgpu;
+use wgpu_igniter::{
+    ClickDetector, ControlFlow, DoubleClickDetector, EventState, FrameBudget, FrameStats,
+    RedrawHandle, RenderLoopHandler, TimeController, TimeInfo, schedule_redraw,
+};
+use winit::dpi::PhysicalPosition;
+use winit::event::{DeviceId, ElementState, MouseButton, WindowEvent};
+
+mod common;
+
+use common::{create_headless_context, install_warning_capture, take_warnings_containing};
+
+#[test]
+fn short_press_is_a_click_not_a_drag() {
+    let mut detector = ClickDetector::default();
+    detector.press();
+    assert!(!detector.motion((1.0, 1.0)));
+    assert!(!detector.motion((-1.0, 1.0)));
+    assert!(!detector.is_dragging());
+    assert!(detector.release());
+    assert!(!detector.release());
+
+    detector.press();
+    assert!(detector.motion((3.0, 4.0)));
+    assert!(detector.is_dragging());
+    assert!(!detector.motion((3.0, 4.0)));
+    assert!(!detector.release());
+    assert!(!detector.is_dragging());
+    // Without a press, moving the mouse neither drags nor clicks
+    assert!(!detector.motion((10.0, 0.0)));
+    assert!(!detector.release());
+}
+
+#[test]
+fn fast_clicks_are_a_double_click_slow_ones_are_not() {
+    let mut detector = DoubleClickDetector::default();
+    let start = web_time::Instant::now();
+    let at = |millis: u64| start + Duration::from_millis(millis);
+    let position = PhysicalPosition::new(100.0, 100.0);
+    assert!(!detector.click(at(0), position));
+    assert!(detector.click(at(200), PhysicalPosition::new(102.0, 101.0)));
+    // A third click starts a new pair
+    assert!(!detector.click(at(300), position));
+
+    assert!(!detector.click(at(1_000), position));
+    assert!(!detector.click(at(1_800), position));
+    // Too far from the first click
+    assert!(!detector.click(at(1_900), PhysicalPosition::new(150.0, 100.0)));
+    assert!(detector.click(at(2_000), PhysicalPosition::new(150.0, 100.0)));
+}
+
+struct StaticScene;
+
+impl RenderLoopHandler for StaticScene {
+    fn needs_continuous_redraw(&self) -> bool {
+        false
+    }
+}
+
+struct AnimatedScene;
+
+impl RenderLoopHandler for AnimatedScene {}
+
+#[test]
+fn static_handler_waits_for_events() {
+    let period = Duration::from_millis(16);
+    let schedule = schedule_redraw(&StaticScene, Duration::from_secs(1), period);
+    assert_eq!(schedule.control_flow, ControlFlow::Wait);
+    assert!(!schedule.request_redraw);
+
+    let schedule = schedule_redraw(&AnimatedScene, Duration::from_secs(1), period);
+    assert_eq!(schedule.control_flow, ControlFlow::Poll);
+    assert!(schedule.request_redraw);
+    let schedule = schedule_redraw(&AnimatedScene, Duration::ZERO, period);
+    assert!(matches!(schedule.control_flow, ControlFlow::WaitUntil(_)));
+    assert!(!schedule.request_redraw);
+}
+
+struct ClickToAnimate {
+    redraw_handle: RedrawHandle,
+}
+
+impl RenderLoopHandler for ClickToAnimate {
+    fn on_window_event(&mut self, event: &WindowEvent) -> EventState {
+        if matches!(event, WindowEvent::MouseInput { .. }) {
+            self.redraw_handle.request_redraw();
+        }
+        EventState::default()
+    }
+    fn needs_continuous_redraw(&self) -> bool {
+        false
+    }
+}
+
+#[test]
+fn window_event_can_request_a_redraw() {
+    let context = create_headless_context();
+    let mut handler = ClickToAnimate {
+        redraw_handle: context.redraw_handle(),
+    };
+    handler.on_window_event(&WindowEvent::CursorLeft {
+        device_id: DeviceId::dummy(),
+    });
+    assert!(!context.redraw_handle().is_redraw_requested());
+    handler.on_window_event(&WindowEvent::MouseInput {
+        device_id: DeviceId::dummy(),
+        state: ElementState::Pressed,
+        button: MouseButton::Left,
+    });
+    assert!(context.redraw_handle().take_redraw_request());
+    assert!(!context.redraw_handle().take_redraw_request());
+}
+
+#[test]
+fn time_info_elapsed_accumulates_frame_deltas() {
+    let mut time_controller = TimeController::default();
+    let init_start = web_time::Instant::now();
+    let frame_delta = Duration::from_millis(250);
+    let mut time_info = TimeInfo::default();
+    for frame in 0..6 {
+        time_controller.set_paused((2..4).contains(&frame));
+        time_controller.advance(frame_delta);
+        time_info = TimeInfo::with_time_controller(init_start, frame_delta, &time_controller);
+    }
+    // The pause only holds the animation clock back
+    assert_eq!(time_info.elapsed, Duration::from_millis(1_500));
+    assert_eq!(time_info.animation_time, Duration::from_millis(1_000));
+    assert_eq!(time_info.processing_delta, frame_delta);
+}
+
+#[test]
+fn slow_frame_is_logged_as_warning() {
+    install_warning_capture();
+    let mut frame_budget = FrameBudget::default();
+    frame_budget.set_slow_frame_threshold(Some(Duration::from_millis(20)));
+    frame_budget.check(Duration::from_millis(16), "TestScenario");
+    frame_budget.check(Duration::from_millis(250), "TestScenario");
+    let slow_frames = take_warnings_containing("TestScenario");
+    assert_eq!(slow_frames.len(), 1);
+    assert!(slow_frames[0].contains("250.0 ms"), "{}", slow_frames[0]);
+}
+
+#[test]
+fn idle_time_of_static_handler_is_not_a_slow_frame() {
+    install_warning_capture();
+    let mut frame_budget = FrameBudget::default();
+    frame_budget.set_slow_frame_threshold(Some(Duration::from_millis(20)));
+    // Redrawn after waiting a second for an event, the frame itself being fast
+    frame_budget.check_redraw(
+        &StaticScene,
+        Duration::from_secs(1),
+        Duration::from_millis(5),
+    );
+    frame_budget.check_redraw(
+        &AnimatedScene,
+        Duration::from_secs(1),
+        Duration::from_millis(5),
+    );
+    assert!(take_warnings_containing(StaticScene.name()).is_empty());
+    assert_eq!(take_warnings_containing(AnimatedScene.name()).len(), 1);
+}
+
+#[test]
+fn frame_stats_count_rendered_and_skipped_frames() {
+    let draw_context = create_headless_context();
+    for _ in 0..3 {
+        draw_context.render_scene(|_| {}).unwrap();
+    }
+    let frame_stats = draw_context.frame_stats();
+    assert_eq!(frame_stats.rendered_frames(), 3);
+    // Nothing is presented when drawing into a texture
+    assert_eq!(frame_stats.presented_frames(), 0);
+    assert_eq!(frame_stats.average_present_latency(), None);
+    assert_eq!(frame_stats.skipped_frames(), 0);
+
+    // Headless contexts have no surface, so the surface errors are recorded directly
+    let mut frame_stats = FrameStats::default();
+    frame_stats.record_surface_error(&wgpu::SurfaceError::Outdated);
+    frame_stats.record_surface_error(&wgpu::SurfaceError::Timeout);
+    assert_eq!(frame_stats.skipped_frames(), 2);
+    assert_eq!(frame_stats.outdated_surface_events(), 1);
+    assert_eq!(frame_stats.timeout_events(), 1);
+    frame_stats.record_presented_frame(Duration::from_millis(10));
+    frame_stats.record_presented_frame(Duration::from_millis(20));
+    assert_eq!(
+        frame_stats.average_present_latency(),
+        Some(Duration::from_millis(15))
+    );
+}
@@ -27,7 +27,6 @@ SOFTWARE.
 // File generated by build.rs, do not modify directly
 
 use assert_cmd::Command;
-use escargot;
 use std::time::Duration;
 
 const TIMEOUT_DURATION: Duration = Duration::from_secs(10);
@@ -49,11 +48,27 @@ macro_rules! generate_test_case {
     };
 }
 
+#[rustfmt::skip]
+generate_test_case!(example_arrow_axes_doesnt_panic, "arrow_axes");
 #[rustfmt::skip]
 generate_test_case!(example_canvas_plugin_doesnt_panic, "canvas_plugin");
 #[rustfmt::skip]
 generate_test_case!(example_canvas_raw_doesnt_panic, "canvas_raw");
 #[rustfmt::skip]
+generate_test_case!(example_cube_bloom_doesnt_panic, "cube_bloom");
+#[rustfmt::skip]
+generate_test_case!(example_cube_debug_draw_doesnt_panic, "cube_debug_draw");
+#[rustfmt::skip]
+generate_test_case!(example_cube_dolly_zoom_doesnt_panic, "cube_dolly_zoom");
+#[rustfmt::skip]
+generate_test_case!(example_cube_environment_doesnt_panic, "cube_environment");
+#[rustfmt::skip]
+generate_test_case!(example_cube_fly_through_doesnt_panic, "cube_fly_through");
+#[rustfmt::skip]
+generate_test_case!(example_cube_follow_doesnt_panic, "cube_follow");
+#[rustfmt::skip]
+generate_test_case!(example_cube_hdr_doesnt_panic, "cube_hdr");
+#[rustfmt::skip]
 generate_test_case!(example_cube_instances_doesnt_panic, "cube_instances");
 #[rustfmt::skip]
 generate_test_case!(example_cube_normals_doesnt_panic, "cube_normals");
@@ -62,16 +77,38 @@ generate_test_case!(example_cube_shader_transition_doesnt_panic, "cube_shader_tr
 #[rustfmt::skip]
 generate_test_case!(example_cube_simple_doesnt_panic, "cube_simple");
 #[rustfmt::skip]
+generate_test_case!(example_cube_swarm_doesnt_panic, "cube_swarm");
+#[rustfmt::skip]
 generate_test_case!(example_cube_two_doesnt_panic, "cube_two");
 #[rustfmt::skip]
+generate_test_case!(example_cube_wireframe_doesnt_panic, "cube_wireframe");
+#[rustfmt::skip]
+generate_test_case!(example_curve_camera_path_doesnt_panic, "curve_camera_path");
+#[rustfmt::skip]
 generate_test_case!(example_egui_integration_direct_doesnt_panic, "egui_integration_direct");
 #[rustfmt::skip]
 generate_test_case!(example_egui_integration_plugin_doesnt_panic, "egui_integration_plugin");
 #[rustfmt::skip]
 generate_test_case!(example_egui_integration_scene_doesnt_panic, "egui_integration_scene");
 #[rustfmt::skip]
+generate_test_case!(example_headless_png_doesnt_panic, "headless_png");
+#[rustfmt::skip]
+generate_test_case!(example_parametric_surface_doesnt_panic, "parametric_surface");
+#[rustfmt::skip]
 generate_test_case!(example_scenario_sequence_doesnt_panic, "scenario_sequence");
 #[rustfmt::skip]
+generate_test_case!(example_shadow_map_doesnt_panic, "shadow_map");
+#[rustfmt::skip]
+generate_test_case!(example_sphere_pbr_doesnt_panic, "sphere_pbr");
+#[rustfmt::skip]
+generate_test_case!(example_sphere_phong_doesnt_panic, "sphere_phong");
+#[rustfmt::skip]
+generate_test_case!(example_text_fps_doesnt_panic, "text_fps");
+#[rustfmt::skip]
+generate_test_case!(example_triangle_fxaa_doesnt_panic, "triangle_fxaa");
+#[rustfmt::skip]
+generate_test_case!(example_triangle_instances_doesnt_panic, "triangle_instances");
+#[rustfmt::skip]
 generate_test_case!(example_triangle_raw_doesnt_panic, "triangle_raw");
 #[rustfmt::skip]
 generate_test_case!(example_triangle_rotation_doesnt_panic, "triangle_rotation");
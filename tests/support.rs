@@ -0,0 +1,612 @@
+use pollster::FutureExt;
+use std::cell::RefCell;
+use std::env;
+use std::rc::Rc;
+use web_time::{Duration, Instant};
+use wgpu_igniter::plugins::PluginRegistry;
+use wgpu_igniter::support::environment::{self, EnvironmentMap};
+use wgpu_igniter::support::postprocess::{Bloom, Fxaa, FxaaQuality, Tonemap, TonemapCurve};
+use wgpu_igniter::support::render_graph::{PassOutput, RenderGraph};
+use wgpu_igniter::support::render_texture::RenderTexture;
+use wgpu_igniter::support::scene_sequence::{SceneSequence, Trigger};
+use wgpu_igniter::support::testing::{assert_frame_matches, compare_frame};
+use wgpu_igniter::support::text::TextRenderer;
+use wgpu_igniter::{
+    Dimensions, DrawContext, DrawModeParams, DrawableBuilder, LaunchContext, RenderLoopHandler,
+    TimeInfo, render_headless_frames, wgpu,
+};
+
+mod common;
+
+use common::create_headless_context;
+
+const HDR_SHADER: &str = r"
+@vertex
+fn vtx_main(@builtin(vertex_index) vertex: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32((vertex << 1u) & 2u), f32(vertex & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.5, 1.0);
+}
+
+@fragment
+fn frg_main() -> @location(0) vec4<f32> {
+    return vec4<f32>(4.0, 4.0, 4.0, 1.0);
+}
+";
+
+fn srgb_byte(linear: f32) -> u8 {
+    let srgb = if linear <= 0.003_130_8 {
+        linear * 12.92
+    } else {
+        1.055f32.mul_add(linear.powf(1.0 / 2.4), -0.055)
+    };
+    (srgb * 255.0).round() as u8
+}
+
+fn render_tonemapped(curve: TonemapCurve, exposure: f32) -> u8 {
+    let context = create_headless_context();
+    let hdr_target = RenderTexture::new(
+        &context,
+        context.surface_dimensions(),
+        RenderTexture::DEFAULT_HDR_FORMAT,
+    );
+    let shader_module = context.create_shader_module(HDR_SHADER);
+    let mut drawable_builder = DrawableBuilder::new(
+        &context,
+        &shader_module,
+        &shader_module,
+        DrawModeParams::Direct { vertex_count: 3 },
+    );
+    drawable_builder.set_color_target_format(hdr_target.format());
+    let drawable = drawable_builder.build();
+    let mut tonemap = Tonemap::new(&context, &hdr_target, curve);
+    tonemap.set_exposure(exposure);
+
+    hdr_target.render(&context, wgpu::Color::BLACK, |render_pass| {
+        drawable.render(render_pass);
+    });
+    context
+        .render_scene(|mut render_pass| tonemap.render(&mut render_pass))
+        .unwrap();
+    let pixels = context.read_target_pixels().unwrap();
+    assert!(pixels[..3].iter().all(|&channel| channel == pixels[0]));
+    pixels[0]
+}
+
+#[test]
+fn tonemap_reinhard() {
+    let expected = srgb_byte(4.0 / 5.0);
+    assert!(render_tonemapped(TonemapCurve::Reinhard, 1.0).abs_diff(expected) <= 2);
+    let expected = srgb_byte(2.0 / 3.0);
+    assert!(render_tonemapped(TonemapCurve::Reinhard, 0.5).abs_diff(expected) <= 2);
+}
+
+#[test]
+fn tonemap_aces() {
+    let x = 4.0f32;
+    let expected =
+        srgb_byte((x * 2.51f32.mul_add(x, 0.03)) / x.mul_add(2.43f32.mul_add(x, 0.59), 0.14));
+    assert!(render_tonemapped(TonemapCurve::Aces, 1.0).abs_diff(expected) <= 2);
+}
+
+const BRIGHT_SQUARE_SHADER: &str = r"
+@vertex
+fn vtx_main(@builtin(vertex_index) vertex: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32((vertex << 1u) & 2u), f32(vertex & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.5, 1.0);
+}
+
+@fragment
+fn frg_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    if (all(position.xy > vec2<f32>(200.0)) && all(position.xy < vec2<f32>(300.0))) {
+        return vec4<f32>(8.0, 8.0, 8.0, 1.0);
+    }
+    return vec4<f32>(0.0, 0.0, 0.0, 1.0);
+}
+";
+
+fn render_bright_square(with_bloom: bool) -> (Vec<u8>, u32) {
+    let context = create_headless_context();
+    let dimensions = context.surface_dimensions();
+    let hdr_target = RenderTexture::new(&context, dimensions, RenderTexture::DEFAULT_HDR_FORMAT);
+    let shader_module = context.create_shader_module(BRIGHT_SQUARE_SHADER);
+    let mut drawable_builder = DrawableBuilder::new(
+        &context,
+        &shader_module,
+        &shader_module,
+        DrawModeParams::Direct { vertex_count: 3 },
+    );
+    drawable_builder.set_color_target_format(hdr_target.format());
+    let drawable = drawable_builder.build();
+    let bloom = Bloom::new(&context, &hdr_target, 4);
+    let tonemap = Tonemap::new(&context, &hdr_target, TonemapCurve::Reinhard);
+
+    hdr_target.render(&context, wgpu::Color::BLACK, |render_pass| {
+        drawable.render(render_pass);
+    });
+    if with_bloom {
+        bloom.apply(&context);
+    }
+    context
+        .render_scene(|mut render_pass| tonemap.render(&mut render_pass))
+        .unwrap();
+    (context.read_target_pixels().unwrap(), dimensions.width)
+}
+
+#[test]
+fn bloom_adds_a_halo_around_bright_pixels() {
+    let red_at =
+        |(pixels, width): &(Vec<u8>, u32), x: u32, y: u32| pixels[((y * width + x) * 4) as usize];
+    let without_bloom = render_bright_square(false);
+    let with_bloom = render_bright_square(true);
+    assert!(red_at(&without_bloom, 250, 250) > 200);
+    assert_eq!(red_at(&without_bloom, 320, 250), 0);
+    assert!(red_at(&with_bloom, 320, 250) > 0);
+    assert_eq!(red_at(&with_bloom, 5, 5), 0);
+}
+
+const SLANTED_TRIANGLE_SHADER: &str = r"
+@vertex
+fn vtx_main(@builtin(vertex_index) vertex: u32) -> @builtin(position) vec4<f32> {
+    var positions = array<vec2<f32>, 3>(
+        vec2<f32>(-0.8, -0.7),
+        vec2<f32>(0.7, -0.3),
+        vec2<f32>(-0.2, 0.8)
+    );
+    return vec4<f32>(positions[vertex], 0.5, 1.0);
+}
+
+@fragment
+fn frg_main() -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0, 1.0, 1.0, 1.0);
+}
+";
+
+fn has_intermediate_values(pixels: &[u8]) -> bool {
+    pixels
+        .chunks_exact(4)
+        .any(|pixel| (10..=245).contains(&pixel[0]))
+}
+
+#[test]
+fn fxaa_smooths_aliased_edges() {
+    let context = create_headless_context();
+    let aliased_target = RenderTexture::with_sample_count(
+        &context,
+        context.surface_dimensions(),
+        wgpu::TextureFormat::Rgba8Unorm,
+        1,
+    );
+    let shader_module = context.create_shader_module(SLANTED_TRIANGLE_SHADER);
+    let mut drawable_builder = DrawableBuilder::new(
+        &context,
+        &shader_module,
+        &shader_module,
+        DrawModeParams::Direct { vertex_count: 3 },
+    );
+    drawable_builder
+        .set_color_target_format(aliased_target.format())
+        .set_sample_count(aliased_target.sample_count());
+    let drawable = drawable_builder.build();
+    aliased_target.render(&context, wgpu::Color::BLACK, |render_pass| {
+        drawable.render(render_pass);
+    });
+    let aliased_pixels = context
+        .read_texture_pixels(aliased_target.texture())
+        .unwrap();
+    assert!(aliased_pixels.contains(&255));
+    assert!(!has_intermediate_values(&aliased_pixels));
+
+    let fxaa = Fxaa::new(&context, &aliased_target, FxaaQuality::High);
+    context
+        .render_scene(|mut render_pass| fxaa.render(&mut render_pass))
+        .unwrap();
+    let smoothed_pixels = context.read_target_pixels().unwrap();
+    assert!(has_intermediate_values(&smoothed_pixels));
+}
+
+#[test]
+fn render_graph_orders_passes_by_dependencies() {
+    let context = create_headless_context();
+    let hdr_target = RenderTexture::new(
+        &context,
+        context.surface_dimensions(),
+        RenderTexture::DEFAULT_HDR_FORMAT,
+    );
+    let shader_module = context.create_shader_module(HDR_SHADER);
+    let mut drawable_builder = DrawableBuilder::new(
+        &context,
+        &shader_module,
+        &shader_module,
+        DrawModeParams::Direct { vertex_count: 3 },
+    );
+    drawable_builder.set_color_target_format(hdr_target.format());
+    let drawable = drawable_builder.build();
+    let tonemap = Tonemap::new(&context, &hdr_target, TonemapCurve::Reinhard);
+
+    let mut graph = RenderGraph::new();
+    graph
+        .add_texture("hdr", &hdr_target, wgpu::Color::BLACK)
+        .unwrap()
+        .add_pass("tonemap", &["hdr"], PassOutput::Target, |render_pass| {
+            tonemap.render(render_pass);
+        })
+        .unwrap()
+        .add_pass("scene", &[], PassOutput::Texture("hdr"), |render_pass| {
+            drawable.render(render_pass);
+        })
+        .unwrap()
+        .add_pass("overlay", &[], PassOutput::Texture("hdr"), |_| {})
+        .unwrap();
+    assert_eq!(
+        graph.execution_order().unwrap(),
+        ["scene", "overlay", "tonemap"]
+    );
+    assert_eq!(
+        graph.execute(&context).unwrap(),
+        ["scene", "overlay", "tonemap"]
+    );
+    let pixels = context.read_target_pixels().unwrap();
+    assert!(pixels[0].abs_diff(srgb_byte(4.0 / 5.0)) <= 2);
+}
+
+#[test]
+fn render_graph_rejects_invalid_passes() {
+    let context = create_headless_context();
+    let texture = RenderTexture::new(
+        &context,
+        context.surface_dimensions(),
+        RenderTexture::DEFAULT_HDR_FORMAT,
+    );
+    let mut graph = RenderGraph::new();
+    graph
+        .add_texture("first", &texture, wgpu::Color::BLACK)
+        .unwrap()
+        .add_texture("second", &texture, wgpu::Color::BLACK)
+        .unwrap();
+    assert!(
+        graph
+            .add_texture("first", &texture, wgpu::Color::BLACK)
+            .is_err()
+    );
+    assert!(
+        graph
+            .add_pass("unknown", &["missing"], PassOutput::Target, |_| {})
+            .is_err()
+    );
+    graph
+        .add_pass("forward", &["first"], PassOutput::Texture("second"), |_| {})
+        .unwrap()
+        .add_pass(
+            "backward",
+            &["second"],
+            PassOutput::Texture("first"),
+            |_| {},
+        )
+        .unwrap();
+    assert!(graph.execution_order().is_err());
+}
+
+struct RecordingScenario {
+    name: &'static str,
+    clear_color: wgpu::Color,
+    log: Rc<RefCell<Vec<String>>>,
+}
+
+impl RenderLoopHandler for RecordingScenario {
+    fn on_init(&mut self, _plugin_registry: &mut PluginRegistry, draw_context: &mut DrawContext) {
+        draw_context.set_clear_color(Some(self.clear_color));
+        self.log.borrow_mut().push(format!("{} init", self.name));
+    }
+    fn on_render(
+        &mut self,
+        _plugin_registry: &mut PluginRegistry,
+        _draw_context: &DrawContext,
+        _time_info: &TimeInfo,
+        _render_pass: &mut wgpu::RenderPass<'static>,
+    ) {
+        self.log.borrow_mut().push(format!("{} render", self.name));
+    }
+    fn on_update(
+        &mut self,
+        _plugin_registry: &mut PluginRegistry,
+        _draw_context: &mut DrawContext,
+        _time_info: &TimeInfo,
+    ) {
+        self.log.borrow_mut().push(format!("{} update", self.name));
+    }
+    fn on_exit(&mut self, _draw_context: &mut DrawContext) {
+        self.log.borrow_mut().push(format!("{} exit", self.name));
+    }
+}
+
+#[test]
+fn scene_sequence_advances_after_duration() {
+    let mut context = create_headless_context();
+    let mut plugin_registry = PluginRegistry::default();
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let scenario = |name| {
+        Box::new(RecordingScenario {
+            name,
+            clear_color: wgpu::Color::BLACK,
+            log: Rc::clone(&log),
+        })
+    };
+    let mut sequence = SceneSequence::new(
+        LaunchContext {
+            draw_context: &mut context,
+            plugin_registry: &mut plugin_registry,
+        },
+        vec![
+            (scenario("first"), Trigger::Duration(Duration::from_secs(2))),
+            (
+                scenario("second"),
+                Trigger::Duration(Duration::from_secs(2)),
+            ),
+        ],
+    );
+    assert_eq!(sequence.remaining_count(), 1);
+    let second_elapsed = TimeInfo::new(Instant::now(), Duration::from_secs(1));
+    for _ in 0..3 {
+        sequence.on_update(&mut plugin_registry, &mut context, &second_elapsed);
+    }
+    assert!(!sequence.is_finished());
+    assert_eq!(
+        *log.borrow(),
+        [
+            "first init",
+            "first update",
+            "first exit",
+            "second init",
+            "second update",
+            "second update",
+        ]
+    );
+    sequence.on_update(&mut plugin_registry, &mut context, &second_elapsed);
+    assert!(sequence.is_finished());
+    assert_eq!(log.borrow().last().unwrap(), "second exit");
+}
+
+#[test]
+fn scene_sequence_crossfade_renders_both_scenarios() {
+    let mut context = create_headless_context();
+    let mut plugin_registry = PluginRegistry::default();
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let scenario = |name, clear_color| {
+        Box::new(RecordingScenario {
+            name,
+            clear_color,
+            log: Rc::clone(&log),
+        })
+    };
+    let mut sequence = SceneSequence::new(
+        LaunchContext {
+            draw_context: &mut context,
+            plugin_registry: &mut plugin_registry,
+        },
+        vec![
+            (
+                scenario("first", wgpu::Color::RED),
+                Trigger::Duration(Duration::from_secs(1)),
+            ),
+            (
+                scenario("second", wgpu::Color::BLUE),
+                Trigger::Duration(Duration::from_secs(10)),
+            ),
+        ],
+    );
+    sequence.set_crossfade(Some(Duration::from_secs(2)));
+    let second_elapsed = TimeInfo::new(Instant::now(), Duration::from_secs(1));
+    sequence.on_update(&mut plugin_registry, &mut context, &second_elapsed);
+    assert!(sequence.is_transitioning());
+    sequence.on_update(&mut plugin_registry, &mut context, &second_elapsed);
+    assert!(sequence.is_transitioning());
+    log.borrow_mut().clear();
+    context
+        .render_scene(|render_pass| {
+            sequence.on_render(
+                &mut plugin_registry,
+                &context,
+                &second_elapsed,
+                &mut render_pass.forget_lifetime(),
+            );
+        })
+        .unwrap();
+    assert_eq!(*log.borrow(), ["first render", "second render"]);
+    // Halfway through, both clear colors are visible
+    let pixels = context.read_target_pixels().unwrap();
+    assert!(pixels[0] > 100 && pixels[2] > 100 && pixels[1] < 10);
+
+    log.borrow_mut().clear();
+    sequence.on_update(&mut plugin_registry, &mut context, &second_elapsed);
+    assert!(!sequence.is_transitioning());
+    assert_eq!(log.borrow()[0], "first exit");
+    log.borrow_mut().clear();
+    context
+        .render_scene(|render_pass| {
+            sequence.on_render(
+                &mut plugin_registry,
+                &context,
+                &second_elapsed,
+                &mut render_pass.forget_lifetime(),
+            );
+        })
+        .unwrap();
+    assert_eq!(*log.borrow(), ["second render"]);
+}
+
+#[test]
+fn mrt_clears_each_target_with_its_own_value() {
+    let context = create_headless_context();
+    let dimensions = Dimensions::new(8, 8);
+    let color =
+        RenderTexture::with_sample_count(&context, dimensions, wgpu::TextureFormat::Rgba8Unorm, 1);
+    let ids =
+        RenderTexture::with_sample_count(&context, dimensions, wgpu::TextureFormat::R32Uint, 1);
+    let sky = wgpu::Color {
+        r: 0.2,
+        g: 0.4,
+        b: 0.8,
+        a: 1.0,
+    };
+    let no_object = wgpu::Color {
+        r: 7.0,
+        ..wgpu::Color::TRANSPARENT
+    };
+    context
+        .render_scene_mrt(
+            &[
+                (&color, wgpu::LoadOp::Clear(sky)),
+                (&ids, wgpu::LoadOp::Clear(no_object)),
+            ],
+            |_| {},
+        )
+        .unwrap();
+
+    let color_pixels = context.read_texture_pixels(color.texture()).unwrap();
+    assert_eq!(color_pixels[0..4], [51, 102, 204, 255]);
+    let id_pixels = context.read_texture_pixels(ids.texture()).unwrap();
+    assert!(
+        bytemuck::cast_slice::<u8, u32>(&id_pixels)
+            .iter()
+            .all(|&id| id == 7)
+    );
+}
+
+#[test]
+fn mrt_rejects_mismatched_targets() {
+    let context = create_headless_context();
+    let format = wgpu::TextureFormat::Rgba8Unorm;
+    let small = RenderTexture::with_sample_count(&context, Dimensions::new(8, 8), format, 1);
+    let large = RenderTexture::with_sample_count(&context, Dimensions::new(16, 16), format, 1);
+    let load = wgpu::LoadOp::Clear(wgpu::Color::BLACK);
+    assert!(
+        context
+            .render_scene_mrt(&[(&small, load), (&large, load)], |_| {})
+            .is_err()
+    );
+    assert!(context.render_scene_mrt(&[], |_| {}).is_err());
+}
+
+#[test]
+fn text_renderer_draws_glyph_pixels() {
+    let mut draw_context = create_headless_context();
+    draw_context.set_clear_color(Some(wgpu::Color::BLACK));
+    let mut text = TextRenderer::with_default_font(&draw_context);
+    text.queue_text("A", [10.0, 10.0], 32.0, [1.0, 1.0, 1.0, 1.0]);
+    assert_eq!(text.queued_glyph_count(), 1);
+    assert!(text.text_width("AA", 32.0) > text.text_width("A", 32.0));
+    draw_context
+        .render_scene(|mut render_pass| text.render(&draw_context, &mut render_pass))
+        .unwrap();
+    assert_eq!(text.queued_glyph_count(), 0);
+    let pixels = draw_context.read_target_pixels().unwrap();
+    let width = draw_context.surface_dimensions().width as usize;
+    let lit_pixels: Vec<_> = pixels
+        .chunks(4)
+        .enumerate()
+        .filter(|(_, pixel)| pixel[0] > 0)
+        .map(|(index, _)| (index % width, index / width))
+        .collect();
+    assert!(!lit_pixels.is_empty());
+    // The glyph stays within its line, near the queued position
+    assert!(
+        lit_pixels
+            .iter()
+            .all(|&(x, y)| (10..60).contains(&x) && (10..50).contains(&y))
+    );
+}
+
+#[path = "../examples/triangle_raw/main_scenario.rs"]
+mod triangle_raw;
+
+#[test]
+fn triangle_example_matches_its_snapshot() {
+    let mut draw_context = DrawContext::new_headless(200, 150).block_on().unwrap();
+    render_headless_frames(&mut draw_context, 1, Duration::from_millis(16), |c| {
+        Box::new(triangle_raw::MainScenario::new(c))
+    })
+    .unwrap();
+    assert_frame_matches(
+        &draw_context,
+        concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/snapshots/triangle_raw.png"
+        ),
+        8,
+    );
+}
+
+#[test]
+fn triangle_example_is_read_back_with_its_dimensions() {
+    let mut draw_context = DrawContext::new_headless(200, 150).block_on().unwrap();
+    draw_context.set_clear_color(Some(wgpu::Color::BLACK));
+    render_headless_frames(&mut draw_context, 1, Duration::from_millis(16), |c| {
+        Box::new(triangle_raw::MainScenario::new(c))
+    })
+    .unwrap();
+    let (pixels, dimensions) = draw_context.read_target_image().unwrap();
+    assert_eq!((dimensions.width, dimensions.height), (200, 150));
+    assert_eq!(pixels.len(), 200 * 150 * 4);
+    // Tightly packed rows, 200 pixels not being a multiple of the copy alignment
+    let pixel = |x: usize, y: usize| &pixels[(y * 200 + x) * 4..(y * 200 + x) * 4 + 4];
+    assert_ne!(pixel(100, 75)[..3], [0, 0, 0]);
+    assert_eq!(pixel(2, 2), [0, 0, 0, 255]);
+}
+
+#[test]
+fn snapshot_mismatch_writes_actual_and_diff_images() {
+    let reference_path = env::temp_dir().join("wgpu_igniter_mismatch.png");
+    let mut draw_context = DrawContext::new_headless(16, 16).block_on().unwrap();
+    draw_context.set_clear_color(Some(wgpu::Color::BLACK));
+    draw_context.render_scene(|_| {}).unwrap();
+    image::save_buffer(
+        &reference_path,
+        &[255; 16 * 16 * 4],
+        16,
+        16,
+        image::ExtendedColorType::Rgba8,
+    )
+    .unwrap();
+    assert!(compare_frame(&draw_context, &reference_path, 8).is_err());
+    assert!(reference_path.with_extension("actual.png").exists());
+    assert!(reference_path.with_extension("diff.png").exists());
+}
+
+#[test]
+fn equirectangular_environment_is_converted_to_a_cubemap() {
+    let draw_context = create_headless_context();
+    let (width, height) = (64, 32);
+    // Bright sky above a dark ground
+    let sky = (0..width * height)
+        .map(|index| {
+            if index / width < height / 2 {
+                [1.0, 0.9, 0.7, 1.0]
+            } else {
+                [0.05, 0.05, 0.1, 1.0]
+            }
+        })
+        .collect::<Vec<_>>();
+    let dimensions = Dimensions::new(width, height);
+    assert!(EnvironmentMap::from_equirectangular(&draw_context, dimensions, &sky[1..]).is_err());
+    let environment_map =
+        EnvironmentMap::from_equirectangular(&draw_context, dimensions, &sky).unwrap();
+    let cube = environment_map.environment_texture();
+    assert_eq!(cube.depth_or_array_layers(), 6);
+    assert_eq!(cube.width(), EnvironmentMap::ENVIRONMENT_SIZE);
+    // +Y face, looking at the sky
+    let face = environment::read_cube_face(&draw_context, cube, 2).unwrap();
+    assert_eq!(
+        face.len(),
+        (EnvironmentMap::ENVIRONMENT_SIZE * EnvironmentMap::ENVIRONMENT_SIZE * 8) as usize
+    );
+    assert!(face.iter().any(|byte| *byte != 0));
+    // Red channel of the first texel, positive half floats ordering like their bits
+    let irradiance_red = |face| {
+        let pixels =
+            environment::read_cube_face(&draw_context, environment_map.irradiance_texture(), face)
+                .unwrap();
+        u16::from_le_bytes([pixels[0], pixels[1]])
+    };
+    assert!(irradiance_red(2) > irradiance_red(3));
+    assert!(environment::read_cube_face(&draw_context, cube, 6).is_err());
+}
@@ -0,0 +1,226 @@
+use pollster::FutureExt;
+use wgpu_igniter::textures::{Texture2D, TextureArrayBinding};
+use wgpu_igniter::{
+    BindingSlot, Dimensions, DrawContext, DrawModeParams, DrawableBuilder, Sampler, SamplerConfig,
+    wgpu,
+};
+
+const LOD_SHADER: &str = r"
+@group(0) @binding(0)
+var source_texture: texture_2d<f32>;
+@group(0) @binding(1)
+var source_sampler: sampler;
+
+@vertex
+fn vtx_main(@builtin(vertex_index) vertex: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32((vertex << 1u) & 2u), f32(vertex & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.5, 1.0);
+}
+
+@fragment
+fn frg_main() -> @location(0) vec4<f32> {
+    return textureSampleLevel(source_texture, source_sampler, vec2<f32>(0.5, 0.5), 2.0);
+}
+";
+
+fn checkerboard(size: u32) -> Vec<u8> {
+    (0..size * size)
+        .flat_map(|index| {
+            let value = if (index % size + index / size).is_multiple_of(2) {
+                255
+            } else {
+                0
+            };
+            [value, value, value, 255]
+        })
+        .collect()
+}
+
+#[test]
+fn full_mip_count_goes_down_to_one_texel() {
+    assert_eq!(Texture2D::full_mip_count(Dimensions::new(1, 1)), 1);
+    assert_eq!(Texture2D::full_mip_count(Dimensions::new(4, 4)), 3);
+    assert_eq!(Texture2D::full_mip_count(Dimensions::new(640, 480)), 10);
+}
+
+#[test]
+fn texture_upload_rejects_wrong_size() {
+    let context = DrawContext::new(None, None).block_on().unwrap();
+    assert!(
+        context
+            .create_texture_with_mips(Dimensions::new(4, 4), &[0; 12], true)
+            .is_err()
+    );
+}
+
+#[test]
+fn generated_mips_average_the_base_level() {
+    let context = DrawContext::new(None, None).block_on().unwrap();
+    let texture = context
+        .create_texture_with_mips(Dimensions::new(4, 4), &checkerboard(4), true)
+        .unwrap();
+    assert_eq!(texture.mip_level_count(), 3);
+    let sampler = Sampler::new(
+        &context,
+        &wgpu::SamplerDescriptor {
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        },
+    );
+    let shader_module = context.create_shader_module(LOD_SHADER);
+    let mut builder = DrawableBuilder::new(
+        &context,
+        &shader_module,
+        &shader_module,
+        DrawModeParams::Direct { vertex_count: 3 },
+    );
+    builder
+        .add_binding_slot(&BindingSlot {
+            bind_group: 0,
+            binding: 0,
+            resource: &texture,
+        })
+        .unwrap()
+        .add_binding_slot(&BindingSlot {
+            bind_group: 0,
+            binding: 1,
+            resource: &sampler,
+        })
+        .unwrap();
+    let drawable = builder.build();
+    context
+        .render_scene(|mut render_pass| drawable.render(&mut render_pass))
+        .unwrap();
+    let pixels = context.read_target_pixels().unwrap();
+    // The 1x1 level is the linear average of black and white, encoded back in sRGB
+    for &channel in &pixels[..3] {
+        assert!((185..=190).contains(&channel), "{channel}");
+    }
+}
+
+#[test]
+fn anisotropic_sampler_binds_without_validation_error() {
+    let context = DrawContext::new(None, None).block_on().unwrap();
+    context
+        .device
+        .push_error_scope(wgpu::ErrorFilter::Validation);
+    let sampler = context
+        .create_sampler(&SamplerConfig {
+            mipmap_filter: wgpu::FilterMode::Linear,
+            address_mode_u: wgpu::AddressMode::Repeat,
+            address_mode_v: wgpu::AddressMode::Repeat,
+            address_mode_w: wgpu::AddressMode::Repeat,
+            anisotropy_clamp: 16,
+            ..SamplerConfig::default()
+        })
+        .unwrap();
+    let texture = context
+        .create_texture_with_mips(Dimensions::new(4, 4), &checkerboard(4), true)
+        .unwrap();
+    let shader_module = context.create_shader_module(LOD_SHADER);
+    let mut builder = DrawableBuilder::new(
+        &context,
+        &shader_module,
+        &shader_module,
+        DrawModeParams::Direct { vertex_count: 3 },
+    );
+    builder
+        .add_binding_slot(&BindingSlot {
+            bind_group: 0,
+            binding: 0,
+            resource: &texture,
+        })
+        .unwrap()
+        .add_binding_slot(&BindingSlot {
+            bind_group: 0,
+            binding: 1,
+            resource: &sampler,
+        })
+        .unwrap();
+    let drawable = builder.build();
+    context
+        .render_scene(|mut render_pass| drawable.render(&mut render_pass))
+        .unwrap();
+    let error = context.device.pop_error_scope().block_on();
+    assert!(error.is_none(), "{error:?}");
+}
+
+#[test]
+fn sampler_config_rejects_anisotropy_without_linear_filtering() {
+    let context = DrawContext::new(None, None).block_on().unwrap();
+    let nearest = SamplerConfig {
+        mag_filter: wgpu::FilterMode::Nearest,
+        anisotropy_clamp: 4,
+        ..SamplerConfig::default()
+    };
+    assert!(context.create_sampler(&nearest).is_err());
+    let out_of_range = SamplerConfig {
+        anisotropy_clamp: 0,
+        ..SamplerConfig::default()
+    };
+    assert!(context.create_sampler(&out_of_range).is_err());
+}
+
+const TEXTURE_ARRAY_SHADER: &str = r"
+@group(0) @binding(0)
+var textures: binding_array<texture_2d<f32>>;
+@group(0) @binding(1)
+var source_sampler: sampler;
+
+@vertex
+fn vtx_main(@builtin(vertex_index) vertex: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32((vertex << 1u) & 2u), f32(vertex & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.5, 1.0);
+}
+
+@fragment
+fn frg_main() -> @location(0) vec4<f32> {
+    return textureSampleLevel(textures[1], source_sampler, vec2<f32>(0.5, 0.5), 0.0);
+}
+";
+
+#[test]
+fn texture_array_binding_samples_the_indexed_texture() {
+    let context = DrawContext::new(None, None).block_on().unwrap();
+    let red = Texture2D::new(&context, Dimensions::new(1, 1), &[255, 0, 0, 255], false).unwrap();
+    let green = Texture2D::new(&context, Dimensions::new(1, 1), &[0, 255, 0, 255], false).unwrap();
+    let binding = TextureArrayBinding::new(&context, &[&red, &green]);
+    if !context
+        .device
+        .features()
+        .contains(wgpu::Features::TEXTURE_BINDING_ARRAY)
+    {
+        assert!(binding.is_err());
+        return;
+    }
+    let binding = binding.unwrap();
+    let sampler = context.create_sampler(&SamplerConfig::default()).unwrap();
+    let shader_module = context.create_shader_module(TEXTURE_ARRAY_SHADER);
+    let mut builder = DrawableBuilder::new(
+        &context,
+        &shader_module,
+        &shader_module,
+        DrawModeParams::Direct { vertex_count: 3 },
+    );
+    builder
+        .add_binding_slot(&BindingSlot {
+            bind_group: 0,
+            binding: 0,
+            resource: &binding,
+        })
+        .unwrap()
+        .add_binding_slot(&BindingSlot {
+            bind_group: 0,
+            binding: 1,
+            resource: &sampler,
+        })
+        .unwrap();
+    let drawable = builder.build();
+    context
+        .render_scene(|mut render_pass| drawable.render(&mut render_pass))
+        .unwrap();
+    let pixels = context.read_target_pixels().unwrap();
+    assert_eq!(&pixels[..4], &[0, 255, 0, 255]);
+}